@@ -23,6 +23,14 @@ pub struct BenchEval {
     pub post_process_cmd: Option<PathBuf>,
     pub parallel_safe: bool,
 }
+/// The model that grades responses against rubrics. Unset fields fall back to the model being
+/// benchmarked.
+#[derive(Clone, Serialize, Deserialize, Debug, Default)]
+pub struct BenchJudge {
+    pub provider: Option<String>,
+    pub model: Option<String>,
+}
+
 #[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct BenchRunConfig {
     pub models: Vec<BenchModel>,
@@ -34,6 +42,11 @@ pub struct BenchRunConfig {
     pub eval_result_filename: String,
     pub run_summary_filename: String,
     pub env_file: Option<PathBuf>,
+    /// Directories of YAML evals to register alongside the built-in suites
+    #[serde(default)]
+    pub eval_dirs: Vec<PathBuf>,
+    #[serde(default)]
+    pub judge: BenchJudge,
 }
 
 impl Default for BenchRunConfig {
@@ -68,6 +81,8 @@ impl Default for BenchRunConfig {
             eval_result_filename: "eval-results.json".to_string(),
             run_summary_filename: "run-results-summary.json".to_string(),
             env_file: None,
+            eval_dirs: vec![],
+            judge: BenchJudge::default(),
         }
     }
 }
@@ -76,6 +91,7 @@ impl BenchRunConfig {
         let mut config: Self = serde_json::from_str(cfg.as_str())?;
         // update include_dirs to contain full-paths only
         config.include_dirs = BenchmarkWorkDir::canonical_dirs(config.include_dirs);
+        config.eval_dirs = BenchmarkWorkDir::canonical_dirs(config.eval_dirs);
         Self::canonicalize_eval_post_proc_cmd(&mut config);
         Ok(config)
     }
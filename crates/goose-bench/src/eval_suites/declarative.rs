@@ -0,0 +1,300 @@
+use crate::bench_config::BenchJudge;
+use crate::bench_session::BenchAgent;
+use crate::bench_work_dir::BenchmarkWorkDir;
+use crate::eval_suites::{
+    collect_baseline_metrics, metrics_hashmap_to_vec, register_eval_with, used_tool,
+    write_response_to_file, EvalMetricValue, Evaluation, ExtensionRequirements, LlmJudge, Rubric,
+};
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use goose::message::Message;
+use regex::Regex;
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// An evaluation described in a YAML file instead of Rust code
+#[derive(Debug, Clone, Deserialize)]
+pub struct EvalSpec {
+    pub name: String,
+    /// Selector prefix the eval is registered under, e.g. `custom` or `custom:docs`
+    #[serde(default = "default_suite")]
+    pub suite: String,
+    pub prompt: String,
+    #[serde(default)]
+    pub extensions: ExtensionRequirements,
+    #[serde(default)]
+    pub fixtures: Vec<Fixture>,
+    #[serde(default)]
+    pub assertions: Vec<Assertion>,
+    #[serde(default)]
+    pub rubrics: Vec<Rubric>,
+    /// Overrides the run's judge for this eval
+    #[serde(default)]
+    pub judge: Option<BenchJudge>,
+    /// Directory of the YAML file, which fixture sources are relative to
+    #[serde(skip)]
+    pub base_dir: PathBuf,
+}
+
+fn default_suite() -> String {
+    "custom".to_string()
+}
+
+/// A file or directory copied into the eval's working directory before the prompt runs
+#[derive(Debug, Clone, Deserialize)]
+pub struct Fixture {
+    pub source: PathBuf,
+    /// Destination relative to the working directory, defaulting to the source's file name
+    pub target: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Assertion {
+    /// Metric name in the results, derived from the check when not given
+    pub name: Option<String>,
+    #[serde(flatten)]
+    pub kind: AssertionKind,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AssertionKind {
+    UsedTool { tool: String },
+    FileExists { path: PathBuf },
+    FileContains { path: PathBuf, text: String },
+    ResponseContains { text: String },
+    ResponseMatches { pattern: String },
+}
+
+impl Assertion {
+    fn metric_name(&self) -> String {
+        if let Some(name) = &self.name {
+            return name.clone();
+        }
+        match &self.kind {
+            AssertionKind::UsedTool { tool } => format!("used_tool_{}", tool),
+            AssertionKind::FileExists { path } => format!("file_exists_{}", path.display()),
+            AssertionKind::FileContains { path, .. } => {
+                format!("file_contains_{}", path.display())
+            }
+            AssertionKind::ResponseContains { .. } => "response_contains".to_string(),
+            AssertionKind::ResponseMatches { .. } => "response_matches".to_string(),
+        }
+    }
+
+    /// Check the assertion against the conversation, with file paths relative to the current
+    /// working directory
+    fn check(&self, messages: &[Message], response: &str) -> bool {
+        match &self.kind {
+            AssertionKind::UsedTool { tool } => used_tool(messages, tool),
+            AssertionKind::FileExists { path } => path.exists(),
+            AssertionKind::FileContains { path, text } => fs::read_to_string(path)
+                .map(|content| content.contains(text.as_str()))
+                .unwrap_or(false),
+            AssertionKind::ResponseContains { text } => response.contains(text.as_str()),
+            AssertionKind::ResponseMatches { pattern } => Regex::new(pattern)
+                .map(|re| re.is_match(response))
+                .unwrap_or(false),
+        }
+    }
+}
+
+impl EvalSpec {
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read eval file {}", path.display()))?;
+        let mut spec: EvalSpec = serde_yaml::from_str(&content)
+            .with_context(|| format!("Failed to parse eval file {}", path.display()))?;
+        spec.base_dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        spec.validate()
+            .with_context(|| format!("Invalid eval file {}", path.display()))?;
+        Ok(spec)
+    }
+
+    pub fn selector(&self) -> String {
+        format!("{}:{}", self.suite, self.name)
+    }
+
+    fn validate(&self) -> Result<()> {
+        // Selectors are split on ':' and matched one word at a time
+        let word = Regex::new(r"^\w+$").unwrap();
+        if !self.selector().split(':').all(|part| word.is_match(part)) {
+            return Err(anyhow::anyhow!(
+                "Eval name and suite may only contain letters, digits and underscores, got '{}'",
+                self.selector()
+            ));
+        }
+        for assertion in &self.assertions {
+            if let AssertionKind::ResponseMatches { pattern } = &assertion.kind {
+                Regex::new(pattern)
+                    .with_context(|| format!("Invalid response_matches pattern '{}'", pattern))?;
+            }
+        }
+        for rubric in &self.rubrics {
+            if rubric.max_score <= 0.0 {
+                return Err(anyhow::anyhow!(
+                    "Rubric '{}' must have a positive max_score",
+                    rubric.name
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+pub struct DeclarativeEval {
+    spec: Arc<EvalSpec>,
+    judge: BenchJudge,
+}
+
+impl DeclarativeEval {
+    pub fn new(spec: Arc<EvalSpec>, judge: BenchJudge) -> Self {
+        DeclarativeEval { spec, judge }
+    }
+
+    fn copy_fixtures(&self) -> Result<()> {
+        for fixture in &self.spec.fixtures {
+            let source = self.spec.base_dir.join(&fixture.source);
+            let target = match (&fixture.target, source.file_name()) {
+                (Some(target), _) => target.clone(),
+                (None, Some(name)) => PathBuf::from(name),
+                (None, None) => {
+                    return Err(anyhow::anyhow!(
+                        "Fixture {} has no file name",
+                        source.display()
+                    ))
+                }
+            };
+            if let Some(parent) = target.parent().filter(|p| !p.as_os_str().is_empty()) {
+                fs::create_dir_all(parent)?;
+            }
+            BenchmarkWorkDir::deep_copy(&source, &target, true).with_context(|| {
+                format!(
+                    "Failed to copy fixture {} to {}",
+                    source.display(),
+                    target.display()
+                )
+            })?;
+        }
+        Ok(())
+    }
+
+    /// The eval's own judge settings take precedence over the run's
+    fn judge_config(&self) -> BenchJudge {
+        let own = self.spec.judge.clone().unwrap_or_default();
+        BenchJudge {
+            provider: own.provider.or_else(|| self.judge.provider.clone()),
+            model: own.model.or_else(|| self.judge.model.clone()),
+        }
+    }
+}
+
+#[async_trait]
+impl Evaluation for DeclarativeEval {
+    async fn run(
+        &self,
+        agent: &mut BenchAgent,
+        run_loc: &mut BenchmarkWorkDir,
+    ) -> Result<Vec<(String, EvalMetricValue)>> {
+        self.copy_fixtures()?;
+
+        let (messages, perf_metrics) =
+            collect_baseline_metrics(agent, self.spec.prompt.clone()).await;
+
+        let output_file = format!("{}_output.txt", self.spec.name);
+        let response = match write_response_to_file(&messages, run_loc, &output_file) {
+            Ok(text) => text,
+            Err(e) => {
+                println!("Warning: Failed to write {} output: {}", self.spec.name, e);
+                messages
+                    .last()
+                    .map_or_else(String::new, |msg| msg.as_concat_text())
+            }
+        };
+
+        let mut metrics = metrics_hashmap_to_vec(perf_metrics);
+        // Each assertion and rubric counts equally towards the overall score
+        let mut scores = Vec::new();
+
+        for assertion in &self.spec.assertions {
+            let passed = assertion.check(&messages, &response);
+            scores.push(if passed { 1.0 } else { 0.0 });
+            metrics.push((assertion.metric_name(), EvalMetricValue::Boolean(passed)));
+        }
+
+        if !self.spec.rubrics.is_empty() {
+            match LlmJudge::new(&self.judge_config()) {
+                Ok(judge) => {
+                    for rubric in &self.spec.rubrics {
+                        let metric_name = format!("judge_{}", rubric.name);
+                        match judge.grade(&self.spec.prompt, &response, rubric).await {
+                            Ok(verdict) => {
+                                scores.push(verdict.score / verdict.max_score);
+                                metrics.push((metric_name, EvalMetricValue::LlmJudge(verdict)));
+                            }
+                            Err(e) => {
+                                scores.push(0.0);
+                                metrics.push((
+                                    format!("{}_error", metric_name),
+                                    EvalMetricValue::String(format!("Error: {}", e)),
+                                ));
+                            }
+                        }
+                    }
+                }
+                Err(e) => {
+                    scores.extend(self.spec.rubrics.iter().map(|_| 0.0));
+                    metrics.push((
+                        "judge_error".to_string(),
+                        EvalMetricValue::String(format!("Error: {}", e)),
+                    ));
+                }
+            }
+        }
+
+        if !scores.is_empty() {
+            let score = scores.iter().sum::<f64>() / scores.len() as f64;
+            metrics.push(("score".to_string(), EvalMetricValue::Float(score)));
+        }
+
+        Ok(metrics)
+    }
+
+    fn name(&self) -> &str {
+        &self.spec.name
+    }
+
+    fn required_extensions(&self) -> ExtensionRequirements {
+        self.spec.extensions.clone()
+    }
+}
+
+/// Load the `.yaml` and `.yml` evals in `dirs` and register each under its selector, next to
+/// the built-in suites. Returns the number of evals registered.
+pub fn register_declarative_evals(dirs: &[PathBuf], judge: &BenchJudge) -> Result<usize> {
+    let mut count = 0;
+    for dir in dirs {
+        let mut files: Vec<PathBuf> = fs::read_dir(dir)
+            .with_context(|| format!("Failed to read eval directory {}", dir.display()))?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| {
+                path.extension()
+                    .is_some_and(|ext| ext == "yaml" || ext == "yml")
+            })
+            .collect();
+        files.sort();
+
+        for file in files {
+            let spec = Arc::new(EvalSpec::from_file(&file)?);
+            let selector: &'static str = Box::leak(spec.selector().into_boxed_str());
+            let judge = judge.clone();
+            register_eval_with(selector, move || {
+                Box::new(DeclarativeEval::new(spec.clone(), judge.clone()))
+            });
+            count += 1;
+        }
+    }
+    Ok(count)
+}
@@ -14,6 +14,15 @@ pub enum EvalMetricValue {
     Float(f64),
     String(String),
     Boolean(bool),
+    LlmJudge(JudgeVerdict),
+}
+
+/// A judge model's grade of a response against one rubric
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct JudgeVerdict {
+    pub score: f64,
+    pub max_score: f64,
+    pub rationale: String,
 }
 
 impl fmt::Display for EvalMetricValue {
@@ -23,6 +32,7 @@ impl fmt::Display for EvalMetricValue {
             EvalMetricValue::Float(fl) => write!(f, "{:.2}", fl),
             EvalMetricValue::String(s) => write!(f, "{}", s),
             EvalMetricValue::Boolean(b) => write!(f, "{}", b),
+            EvalMetricValue::LlmJudge(v) => write!(f, "{:.2}/{:.2}", v.score, v.max_score),
         }
     }
 }
@@ -32,7 +42,8 @@ pub struct EvalMetric {
     pub value: EvalMetricValue,
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default)]
 pub struct ExtensionRequirements {
     pub builtin: Vec<String>,
     pub external: Vec<String>,
@@ -2,9 +2,9 @@ pub use super::Evaluation;
 use regex::Regex;
 use std::borrow::Cow;
 use std::collections::HashMap;
-use std::sync::{OnceLock, RwLock};
+use std::sync::{Arc, OnceLock, RwLock};
 
-type EvaluationConstructor = fn() -> Box<dyn Evaluation>;
+type EvaluationConstructor = Arc<dyn Fn() -> Box<dyn Evaluation> + Send + Sync>;
 type Registry = &'static RwLock<HashMap<&'static str, EvaluationConstructor>>;
 
 // Use std::sync::RwLock for interior mutability
@@ -18,9 +18,17 @@ fn eval_registry() -> Registry {
 
 /// Register a new evaluation version
 pub fn register_eval(selector: &'static str, constructor: fn() -> Box<dyn Evaluation>) {
+    register_eval_with(selector, constructor);
+}
+
+/// Register an evaluation built from data loaded at runtime, such as a YAML eval file
+pub fn register_eval_with<F>(selector: &'static str, constructor: F)
+where
+    F: Fn() -> Box<dyn Evaluation> + Send + Sync + 'static,
+{
     let registry = eval_registry();
     if let Ok(mut map) = registry.write() {
-        map.insert(selector, constructor);
+        map.insert(selector, Arc::new(constructor));
     }
 }
 
@@ -0,0 +1,102 @@
+use crate::bench_config::BenchJudge;
+use crate::eval_suites::JudgeVerdict;
+use anyhow::{Context, Result};
+use goose::config::Config;
+use goose::message::Message;
+use goose::model::ModelConfig;
+use goose::providers::base::Provider;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::Arc;
+
+const JUDGE_SYSTEM_PROMPT: &str = "You grade the responses of an AI agent against a rubric. \
+Read the task, the agent's final response and the rubric, then reply with only a JSON object of \
+the form {\"score\": <number>, \"rationale\": \"<one or two sentences>\"}. The score must be \
+between 0 and the maximum score given in the rubric.";
+
+/// A criterion a judge model scores a response against
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Rubric {
+    pub name: String,
+    pub criteria: String,
+    #[serde(default = "default_max_score")]
+    pub max_score: f64,
+}
+
+fn default_max_score() -> f64 {
+    1.0
+}
+
+pub struct LlmJudge {
+    provider: Arc<dyn Provider>,
+}
+
+impl LlmJudge {
+    /// Create the judge provider, falling back to the provider and model under test
+    pub fn new(judge: &BenchJudge) -> Result<Self> {
+        let config = Config::global();
+        let provider_name = match &judge.provider {
+            Some(provider) => provider.clone(),
+            None => config
+                .get_param::<String>("GOOSE_PROVIDER")
+                .context("No judge provider configured and GOOSE_PROVIDER is not set")?,
+        };
+        let model_name = match &judge.model {
+            Some(model) => model.clone(),
+            None => config
+                .get_param::<String>("GOOSE_MODEL")
+                .context("No judge model configured and GOOSE_MODEL is not set")?,
+        };
+
+        let provider = goose::providers::create(&provider_name, ModelConfig::new(model_name))?;
+        Ok(LlmJudge { provider })
+    }
+
+    pub async fn grade(&self, task: &str, response: &str, rubric: &Rubric) -> Result<JudgeVerdict> {
+        let request = format!(
+            "## Task\n{}\n\n## Response\n{}\n\n## Rubric: {}\n{}\n\nMaximum score: {}",
+            task, response, rubric.name, rubric.criteria, rubric.max_score
+        );
+        let (reply, _usage) = self
+            .provider
+            .complete(
+                JUDGE_SYSTEM_PROMPT,
+                &[Message::user().with_text(request)],
+                &[],
+            )
+            .await?;
+
+        parse_verdict(&reply.as_concat_text(), rubric.max_score)
+    }
+}
+
+/// Read the JSON verdict out of the judge's reply, which may wrap it in prose or a code block
+fn parse_verdict(reply: &str, max_score: f64) -> Result<JudgeVerdict> {
+    let json = match (reply.find('{'), reply.rfind('}')) {
+        (Some(start), Some(end)) if start < end => &reply[start..=end],
+        _ => {
+            return Err(anyhow::anyhow!(
+                "Judge reply has no JSON verdict: {}",
+                reply
+            ))
+        }
+    };
+    let verdict: Value = serde_json::from_str(json)
+        .with_context(|| format!("Judge reply is not valid JSON: {}", json))?;
+
+    let score = verdict
+        .get("score")
+        .and_then(Value::as_f64)
+        .context("Judge verdict has no numeric score")?;
+    let rationale = verdict
+        .get("rationale")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_string();
+
+    Ok(JudgeVerdict {
+        score: score.clamp(0.0, max_score),
+        max_score,
+        rationale,
+    })
+}
@@ -1,11 +1,17 @@
 mod core;
+mod declarative;
 mod evaluation;
 mod factory;
+mod judge;
 mod metrics;
 mod utils;
 mod vibes;
 
+pub use declarative::{
+    register_declarative_evals, Assertion, AssertionKind, DeclarativeEval, EvalSpec, Fixture,
+};
 pub use evaluation::*;
-pub use factory::{register_eval, EvaluationSuite};
+pub use factory::{register_eval, register_eval_with, EvaluationSuite};
+pub use judge::{LlmJudge, Rubric};
 pub use metrics::*;
 pub use utils::*;
@@ -1,6 +1,6 @@
 use crate::bench_config::{BenchModel, BenchRunConfig};
 use crate::bench_work_dir::BenchmarkWorkDir;
-use crate::eval_suites::EvaluationSuite;
+use crate::eval_suites::{register_declarative_evals, EvaluationSuite};
 use crate::runners::model_runner::ModelRunner;
 use crate::utilities::{await_process_exits, parallel_bench_cmd};
 use anyhow::Context;
@@ -71,7 +71,11 @@ impl BenchRunner {
         Ok(())
     }
 
-    pub fn list_selectors(_config: Option<PathBuf>) -> anyhow::Result<()> {
+    pub fn list_selectors(config: Option<PathBuf>) -> anyhow::Result<()> {
+        if let Some(config) = config {
+            let config = BenchRunConfig::from(config)?;
+            register_declarative_evals(&config.eval_dirs, &config.judge)?;
+        }
         let selector_eval_counts = EvaluationSuite::available_selectors();
         let mut keys: Vec<_> = selector_eval_counts.keys().collect();
         keys.sort();
@@ -1,7 +1,7 @@
 use crate::bench_config::{BenchEval, BenchModel, BenchRunConfig};
 use crate::bench_session::BenchAgent;
 use crate::bench_work_dir::BenchmarkWorkDir;
-use crate::eval_suites::{EvaluationSuite, ExtensionRequirements};
+use crate::eval_suites::{register_declarative_evals, EvaluationSuite, ExtensionRequirements};
 use crate::reporting::EvaluationResult;
 use crate::utilities::await_process_exits;
 use anyhow::{bail, Context, Result};
@@ -22,6 +22,7 @@ impl EvalRunner {
     pub fn from(config: String) -> Result<EvalRunner> {
         let config = BenchRunConfig::from_string(config)
             .context("Failed to parse evaluation configuration")?;
+        register_declarative_evals(&config.eval_dirs, &config.judge)?;
         Ok(EvalRunner { config })
     }
 
@@ -1,5 +1,5 @@
 use crate::bench_config::{BenchEval, BenchModel, BenchRunConfig};
-use crate::eval_suites::EvaluationSuite;
+use crate::eval_suites::{register_declarative_evals, EvaluationSuite};
 use crate::reporting::{BenchmarkResults, SuiteResult};
 use crate::runners::eval_runner::EvalRunner;
 use crate::utilities::{await_process_exits, parallel_bench_cmd};
@@ -21,6 +21,7 @@ impl ModelRunner {
     pub fn from(config: String) -> Result<ModelRunner> {
         let config =
             BenchRunConfig::from_string(config).context("Failed to parse configuration")?;
+        register_declarative_evals(&config.eval_dirs, &config.judge)?;
         Ok(ModelRunner { config })
     }
 
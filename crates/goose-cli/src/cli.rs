@@ -1,22 +1,33 @@
 use anyhow::Result;
+use chrono::{DateTime, NaiveDate, Utc};
 use clap::{Args, Parser, Subcommand};
 
 use goose::config::{Config, ExtensionConfig};
+use goose::recipe::{RetryConfig, SubRecipeTemplate};
+use goose::session::info::SortOrder;
+use goose::session::SessionQuery;
 
 use crate::commands::bench::agent_generator;
 use crate::commands::configure::handle_configure;
 use crate::commands::info::handle_info;
 use crate::commands::mcp::run_server;
 use crate::commands::project::{handle_project_default, handle_projects_interactive};
-use crate::commands::recipe::{handle_deeplink, handle_validate};
+use crate::commands::recipe::{
+    handle_deeplink, handle_install, handle_list, handle_remove, handle_update, handle_validate,
+};
 // Import the new handlers from commands::schedule
 use crate::commands::schedule::{
     handle_schedule_add, handle_schedule_list, handle_schedule_remove, handle_schedule_run_now,
     handle_schedule_sessions,
 };
-use crate::commands::session::{handle_session_list, handle_session_remove};
+use crate::commands::session::{
+    handle_session_fork, handle_session_import, handle_session_list, handle_session_migrate,
+    handle_session_remove, handle_session_search,
+};
 use crate::logging::setup_logging;
-use crate::recipes::recipe::{explain_recipe_with_parameters, load_recipe_as_template};
+use crate::recipes::recipe::{
+    explain_recipe_with_parameters, load_recipe_as_template, load_sub_recipes,
+};
 use crate::session;
 use crate::session::{build_session, SessionBuilderConfig};
 use goose_bench::bench_config::BenchRunConfig;
@@ -24,6 +35,7 @@ use goose_bench::runners::bench_runner::BenchRunner;
 use goose_bench::runners::eval_runner::EvalRunner;
 use goose_bench::runners::metric_aggregator::MetricAggregator;
 use goose_bench::runners::model_runner::ModelRunner;
+use serde_json::Value;
 use std::io::Read;
 use std::path::PathBuf;
 
@@ -73,6 +85,12 @@ fn parse_key_val(s: &str) -> Result<(String, String), String> {
     }
 }
 
+fn parse_date(s: &str) -> Result<DateTime<Utc>, String> {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d")
+        .map(|date| date.and_time(chrono::NaiveTime::MIN).and_utc())
+        .map_err(|e| format!("invalid date '{}': {}", s, e))
+}
+
 #[derive(Subcommand)]
 enum SessionCommand {
     #[command(about = "List all available sessions")]
@@ -94,6 +112,78 @@ enum SessionCommand {
             long_help = "Sort sessions by date in ascending order (oldest first). Default is descending order (newest first)."
         )]
         ascending: bool,
+
+        #[arg(
+            long,
+            value_name = "DATE",
+            help = "Only sessions modified on or after this date (YYYY-MM-DD)",
+            value_parser = parse_date
+        )]
+        since: Option<DateTime<Utc>>,
+
+        #[arg(
+            long,
+            value_name = "DATE",
+            help = "Only sessions modified before this date (YYYY-MM-DD)",
+            value_parser = parse_date
+        )]
+        until: Option<DateTime<Utc>>,
+
+        #[arg(long, help = "Only sessions started in this working directory")]
+        working_dir: Option<PathBuf>,
+
+        #[arg(long, help = "Only sessions created by this schedule")]
+        schedule_id: Option<String>,
+
+        #[arg(long, help = "Only sessions that used at least this many tokens")]
+        min_tokens: Option<i32>,
+
+        #[arg(short, long, help = "Maximum number of sessions to list")]
+        limit: Option<usize>,
+    },
+    #[command(about = "Search the messages of all sessions")]
+    Search {
+        #[arg(help = "Words that must all appear in a message")]
+        query: String,
+
+        #[arg(
+            short,
+            long,
+            help = "Maximum number of messages to show",
+            default_value = "20"
+        )]
+        limit: usize,
+
+        #[arg(
+            short,
+            long,
+            help = "Output format (text, json)",
+            default_value = "text"
+        )]
+        format: String,
+    },
+    #[command(
+        about = "Import JSONL session files into the SQLite session store",
+        long_about = "Import the JSONL session files into the SQLite session database. Set GOOSE_SESSION_STORE=sqlite afterwards to use it. Running it again re-imports every file."
+    )]
+    Migrate {},
+    #[command(
+        about = "Copy the start of a session into a new session",
+        long_about = "Create a new session holding the first N messages of an existing one, to continue it from an earlier point. The original session is left unchanged."
+    )]
+    Fork {
+        #[command(flatten)]
+        identifier: Option<Identifier>,
+
+        #[arg(long, value_name = "N", help = "Number of messages to keep")]
+        from_message: usize,
+
+        #[arg(
+            long,
+            value_name = "NAME",
+            help = "Name for the new session (default: generated)"
+        )]
+        fork_name: Option<String>,
     },
     #[command(about = "Remove sessions. Runs interactively if no ID or regex is provided.")]
     Remove {
@@ -102,7 +192,7 @@ enum SessionCommand {
         #[arg(short, long, help = "Regex for removing matched sessions (optional)")]
         regex: Option<String>,
     },
-    #[command(about = "Export a session to Markdown format")]
+    #[command(about = "Export a session to Markdown, HTML, JSON or fine-tuning data")]
     Export {
         #[command(flatten)]
         identifier: Option<Identifier>,
@@ -111,9 +201,30 @@ enum SessionCommand {
             short,
             long,
             help = "Output file path (default: stdout)",
-            long_help = "Path to save the exported Markdown. If not provided, output will be sent to stdout"
+            long_help = "Path to save the exported session. If not provided, output will be sent to stdout"
         )]
         output: Option<PathBuf>,
+
+        #[arg(
+            short,
+            long,
+            help = "Output format (markdown, html, json, openai, anthropic)",
+            long_help = "Output format: markdown, a self-contained html page, a json transcript that can be imported again, or one line of openai or anthropic chat fine-tuning JSONL",
+            default_value = "markdown"
+        )]
+        format: String,
+    },
+    #[command(about = "Import a JSON transcript as a new session")]
+    Import {
+        #[arg(help = "Path to a transcript written by `goose session export --format json`")]
+        file: PathBuf,
+
+        #[arg(
+            long,
+            value_name = "NAME",
+            help = "Name for the new session (default: generated)"
+        )]
+        import_name: Option<String>,
     },
 }
 
@@ -230,6 +341,50 @@ enum RecipeCommand {
         )]
         recipe_name: String,
     },
+
+    /// Install a recipe into the local registry and pin it in the project's lockfile
+    #[command(about = "Install a recipe and pin it in goose-recipes.lock")]
+    Install {
+        /// Name of the recipe to install
+        #[arg(help = "name of the recipe to install")]
+        name: String,
+
+        /// Where to install the recipe from
+        #[arg(
+            long,
+            value_name = "SOURCE",
+            help = "Git URL, local directory or HTTP index file to install from (defaults to the source in the lockfile)"
+        )]
+        source: Option<String>,
+
+        /// Version requirement to resolve against the recipe's version field
+        #[arg(
+            long,
+            value_name = "REQUIREMENT",
+            help = "Semver requirement such as '^1.2' (defaults to the locked version, or the newest)"
+        )]
+        version: Option<String>,
+    },
+
+    /// List installed recipes
+    #[command(about = "List installed recipes")]
+    List {},
+
+    /// Update locked recipes to the newest version matching their requirement
+    #[command(about = "Update locked recipes to the newest matching version")]
+    Update {
+        /// Recipe to update, all locked recipes when not given
+        #[arg(help = "name of the recipe to update (defaults to all recipes in the lockfile)")]
+        name: Option<String>,
+    },
+
+    /// Remove an installed recipe
+    #[command(about = "Remove an installed recipe and unpin it")]
+    Remove {
+        /// Name of the recipe to remove
+        #[arg(help = "name of the recipe to remove")]
+        name: String,
+    },
 }
 
 #[derive(Subcommand)]
@@ -296,6 +451,14 @@ enum Command {
         )]
         max_tool_repetitions: Option<u32>,
 
+        /// Run shell commands in a sandbox
+        #[arg(
+            long,
+            help = "Run the developer extension's shell commands in a sandbox",
+            long_help = "Run shell commands under bubblewrap with a read-only filesystem outside the working directory and no network access. Limits are configured with the GOOSE_SANDBOX_* environment variables."
+        )]
+        sandbox: bool,
+
         /// Add stdio extensions with environment variables and commands
         #[arg(
             long = "with-extension",
@@ -391,6 +554,18 @@ enum Command {
         )]
         interactive: bool,
 
+        /// Print only the run's result on stdout
+        #[arg(
+            long = "output-format",
+            value_name = "FORMAT",
+            help = "Output format (text, json)",
+            long_help = "With json, only a JSON object is printed on stdout: the output validated against the recipe's response schema, the final response as {\"response\": ...} when the recipe has no schema, or {\"error\": ...} if the run fails.",
+            default_value = "text",
+            value_parser = ["text", "json"],
+            conflicts_with = "interactive"
+        )]
+        output_format: String,
+
         /// Run without storing a session file
         #[arg(
             long = "no-session",
@@ -416,6 +591,14 @@ enum Command {
         )]
         max_tool_repetitions: Option<u32>,
 
+        /// Run shell commands in a sandbox
+        #[arg(
+            long,
+            help = "Run the developer extension's shell commands in a sandbox",
+            long_help = "Run shell commands under bubblewrap with a read-only filesystem outside the working directory and no network access. Limits are configured with the GOOSE_SANDBOX_* environment variables."
+        )]
+        sandbox: bool,
+
         /// Identifier for this run session
         #[command(flatten)]
         identifier: Option<Identifier>,
@@ -469,8 +652,8 @@ enum Command {
         builtins: Vec<String>,
     },
 
-    /// Recipe utilities for validation and deeplinking
-    #[command(about = "Recipe utilities for validation and deeplinking")]
+    /// Recipe utilities for validation, deeplinking and installing
+    #[command(about = "Recipe utilities for validation, deeplinking and installing")]
     Recipe {
         #[command(subcommand)]
         command: RecipeCommand,
@@ -542,6 +725,45 @@ struct InputConfig {
     contents: Option<String>,
     extensions_override: Option<Vec<ExtensionConfig>>,
     additional_system_prompt: Option<String>,
+    response_schema: Option<Value>,
+    sub_recipes: Vec<SubRecipeTemplate>,
+    retry_config: Option<RetryConfig>,
+}
+
+fn exit_with_recipe_error(err: anyhow::Error, json_output: bool) -> ! {
+    if json_output {
+        println!("{}", serde_json::json!({ "error": err.to_string() }));
+    } else {
+        eprintln!("{}: {}", console::style("Error").red().bold(), err);
+    }
+    std::process::exit(1);
+}
+
+/// Print the result of a headless run as a single JSON object on stdout, exiting with an error
+/// status if the run did not produce one
+async fn print_json_result(session: &session::Session, result: Result<()>, has_schema: bool) {
+    let output = match result {
+        Err(e) => Err(e.to_string()),
+        Ok(()) if has_schema => session
+            .final_output()
+            .await
+            .ok_or_else(|| "The run finished without a valid final output".to_string()),
+        Ok(()) => session
+            .message_history()
+            .iter()
+            .rev()
+            .find(|message| message.role == mcp_core::role::Role::Assistant)
+            .map(|message| serde_json::json!({ "response": message.as_concat_text() }))
+            .ok_or_else(|| "The run finished without a response".to_string()),
+    };
+
+    match output {
+        Ok(output) => println!("{}", output),
+        Err(error) => {
+            println!("{}", serde_json::json!({ "error": error }));
+            std::process::exit(1);
+        }
+    }
 }
 
 pub async fn cli() -> Result<()> {
@@ -571,6 +793,7 @@ pub async fn cli() -> Result<()> {
             history,
             debug,
             max_tool_repetitions,
+            sandbox,
             extensions,
             remote_extensions,
             builtins,
@@ -580,15 +803,74 @@ pub async fn cli() -> Result<()> {
                     verbose,
                     format,
                     ascending,
+                    since,
+                    until,
+                    working_dir,
+                    schedule_id,
+                    min_tokens,
+                    limit,
                 }) => {
-                    handle_session_list(verbose, format, ascending)?;
+                    let query = SessionQuery {
+                        since,
+                        until,
+                        working_dir,
+                        schedule_id,
+                        min_total_tokens: min_tokens,
+                        order: if ascending {
+                            SortOrder::Ascending
+                        } else {
+                            SortOrder::Descending
+                        },
+                        limit,
+                    };
+                    handle_session_list(verbose, format, query)?;
+                    Ok(())
+                }
+                Some(SessionCommand::Search {
+                    query,
+                    limit,
+                    format,
+                }) => {
+                    handle_session_search(query, limit, format)?;
+                    Ok(())
+                }
+                Some(SessionCommand::Fork {
+                    identifier,
+                    from_message,
+                    fork_name,
+                }) => {
+                    let session_identifier = if let Some(id) = identifier {
+                        extract_identifier(id)
+                    } else {
+                        match crate::commands::session::prompt_interactive_session_selection() {
+                            Ok(id) => id,
+                            Err(e) => {
+                                eprintln!("Error: {}", e);
+                                return Ok(());
+                            }
+                        }
+                    };
+
+                    handle_session_fork(session_identifier, from_message, fork_name)?;
+                    Ok(())
+                }
+                Some(SessionCommand::Migrate {}) => {
+                    handle_session_migrate()?;
                     Ok(())
                 }
                 Some(SessionCommand::Remove { id, regex }) => {
                     handle_session_remove(id, regex)?;
                     return Ok(());
                 }
-                Some(SessionCommand::Export { identifier, output }) => {
+                Some(SessionCommand::Import { file, import_name }) => {
+                    handle_session_import(file, import_name)?;
+                    Ok(())
+                }
+                Some(SessionCommand::Export {
+                    identifier,
+                    output,
+                    format,
+                }) => {
                     let session_identifier = if let Some(id) = identifier {
                         extract_identifier(id)
                     } else {
@@ -602,7 +884,11 @@ pub async fn cli() -> Result<()> {
                         }
                     };
 
-                    crate::commands::session::handle_session_export(session_identifier, output)?;
+                    crate::commands::session::handle_session_export(
+                        session_identifier,
+                        output,
+                        format,
+                    )?;
                     Ok(())
                 }
                 None => {
@@ -618,6 +904,9 @@ pub async fn cli() -> Result<()> {
                         additional_system_prompt: None,
                         debug,
                         max_tool_repetitions,
+                        sandbox,
+                        retry_config: None,
+                        json_output: false,
                     })
                     .await;
                     setup_logging(
@@ -655,12 +944,19 @@ pub async fn cli() -> Result<()> {
             no_session,
             debug,
             max_tool_repetitions,
+            sandbox,
             extensions,
             remote_extensions,
             builtins,
             params,
             explain,
+            output_format,
         }) => {
+            let json_output = output_format == "json";
+            if json_output {
+                session::set_quiet(true);
+            }
+
             let input_config = match (instructions, input_text, recipe, explain) {
                 (Some(file), _, _, _) if file == "-" => {
                     let mut input = String::new();
@@ -672,6 +968,9 @@ pub async fn cli() -> Result<()> {
                         contents: Some(input),
                         extensions_override: None,
                         additional_system_prompt: None,
+                        response_schema: None,
+                        sub_recipes: Vec::new(),
+                        retry_config: None,
                     }
                 }
                 (Some(file), _, _, _) => {
@@ -686,27 +985,35 @@ pub async fn cli() -> Result<()> {
                         contents: Some(contents),
                         extensions_override: None,
                         additional_system_prompt: None,
+                        response_schema: None,
+                        sub_recipes: Vec::new(),
+                        retry_config: None,
                     }
                 }
                 (_, Some(text), _, _) => InputConfig {
                     contents: Some(text),
                     extensions_override: None,
                     additional_system_prompt: None,
+                    response_schema: None,
+                    sub_recipes: Vec::new(),
+                    retry_config: None,
                 },
                 (_, _, Some(recipe_name), explain) => {
                     if explain {
                         explain_recipe_with_parameters(&recipe_name, params)?;
                         return Ok(());
                     }
-                    let recipe =
-                        load_recipe_as_template(&recipe_name, params).unwrap_or_else(|err| {
-                            eprintln!("{}: {}", console::style("Error").red().bold(), err);
-                            std::process::exit(1);
-                        });
+                    let recipe = load_recipe_as_template(&recipe_name, params)
+                        .unwrap_or_else(|err| exit_with_recipe_error(err, json_output));
+                    let sub_recipes = load_sub_recipes(&recipe)
+                        .unwrap_or_else(|err| exit_with_recipe_error(err, json_output));
                     InputConfig {
                         contents: recipe.prompt,
                         extensions_override: recipe.extensions,
                         additional_system_prompt: recipe.instructions,
+                        response_schema: recipe.response.and_then(|response| response.json_schema),
+                        sub_recipes,
+                        retry_config: recipe.retry,
                     }
                 }
                 (None, None, None, _) => {
@@ -726,6 +1033,9 @@ pub async fn cli() -> Result<()> {
                 additional_system_prompt: input_config.additional_system_prompt,
                 debug,
                 max_tool_repetitions,
+                sandbox,
+                retry_config: input_config.retry_config,
+                json_output,
             })
             .await;
 
@@ -734,10 +1044,33 @@ pub async fn cli() -> Result<()> {
                 None,
             )?;
 
+            session.set_sub_recipes(input_config.sub_recipes).await;
+
+            let has_schema = input_config.response_schema.is_some();
+            if let Some(schema) = input_config.response_schema {
+                if let Err(e) = session.set_final_output_schema(schema).await {
+                    exit_with_recipe_error(e, json_output);
+                }
+            }
+
             if interactive {
                 let _ = session.interactive(input_config.contents).await;
             } else if let Some(contents) = input_config.contents {
-                let _ = session.headless(contents).await;
+                let result = session.headless(contents).await;
+                if json_output {
+                    print_json_result(&session, result, has_schema).await;
+                } else if session.exits_with_failure(&result) {
+                    // Automation relies on the exit status when success checks keep failing
+                    if let Err(e) = result {
+                        eprintln!("{}: {}", console::style("Error").red().bold(), e);
+                    }
+                    std::process::exit(1);
+                } else if has_schema {
+                    // The structured result is what the recipe was run for, so show it last
+                    if let Some(output) = session.final_output().await {
+                        println!("{}", serde_json::to_string_pretty(&output)?);
+                    }
+                }
             } else {
                 eprintln!("Error: no text provided for prompt in headless mode");
                 std::process::exit(1);
@@ -807,6 +1140,22 @@ pub async fn cli() -> Result<()> {
                 RecipeCommand::Deeplink { recipe_name } => {
                     handle_deeplink(&recipe_name)?;
                 }
+                RecipeCommand::Install {
+                    name,
+                    source,
+                    version,
+                } => {
+                    handle_install(&name, source.as_deref(), version.as_deref()).await?;
+                }
+                RecipeCommand::List {} => {
+                    handle_list()?;
+                }
+                RecipeCommand::Update { name } => {
+                    handle_update(name.as_deref()).await?;
+                }
+                RecipeCommand::Remove { name } => {
+                    handle_remove(&name)?;
+                }
             }
             return Ok(());
         }
@@ -831,6 +1180,9 @@ pub async fn cli() -> Result<()> {
                     additional_system_prompt: None,
                     debug: false,
                     max_tool_repetitions: None,
+                    sandbox: false,
+                    retry_config: None,
+                    json_output: false,
                 })
                 .await;
                 setup_logging(
@@ -42,6 +42,9 @@ pub async fn agent_generator(
         additional_system_prompt: None,
         debug: false,
         max_tool_repetitions: None,
+        sandbox: false,
+        retry_config: None,
+        json_output: false,
     })
     .await;
 
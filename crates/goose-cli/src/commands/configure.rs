@@ -373,8 +373,7 @@ pub async fn configure_provider_dialog() -> Result<bool, Box<dyn Error>> {
 
     let model_config = goose::model::ModelConfig::new(model.clone())
         .with_max_tokens(Some(50))
-        .with_toolshim(toolshim_enabled)
-        .with_toolshim_model(std::env::var("GOOSE_TOOLSHIM_OLLAMA_MODEL").ok());
+        .with_toolshim(toolshim_enabled);
 
     let provider = create(provider_name, model_config)?;
 
@@ -497,6 +496,11 @@ pub fn configure_extensions_dialog() -> Result<(), Box<dyn Error>> {
             "Remote Extension",
             "Connect to a remote extension via SSE",
         )
+        .item(
+            "streamable_http",
+            "Remote Extension (Streamable HTTP)",
+            "Connect to a remote extension via Streamable HTTP",
+        )
         .interact()?;
 
     match extension_type {
@@ -655,13 +659,14 @@ pub fn configure_extensions_dialog() -> Result<(), Box<dyn Error>> {
                     env_keys,
                     description,
                     timeout: Some(timeout),
+                    roots: Vec::new(),
                     bundled: None,
                 },
             })?;
 
             cliclack::outro(format!("Added {} extension", style(name).green()))?;
         }
-        "sse" => {
+        "sse" | "streamable_http" => {
             let extensions = ExtensionConfigManager::get_all_names()?;
             let name: String = cliclack::input("What would you like to call this extension?")
                 .placeholder("my-remote-extension")
@@ -676,8 +681,16 @@ pub fn configure_extensions_dialog() -> Result<(), Box<dyn Error>> {
                 })
                 .interact()?;
 
-            let uri: String = cliclack::input("What is the SSE endpoint URI?")
-                .placeholder("http://localhost:8000/events")
+            let (prompt, placeholder) = if extension_type == "sse" {
+                (
+                    "What is the SSE endpoint URI?",
+                    "http://localhost:8000/events",
+                )
+            } else {
+                ("What is the MCP endpoint URI?", "http://localhost:8000/mcp")
+            };
+            let uri: String = cliclack::input(prompt)
+                .placeholder(placeholder)
                 .validate(|input: &String| {
                     if input.is_empty() {
                         Err("Please enter a URI")
@@ -748,17 +761,34 @@ pub fn configure_extensions_dialog() -> Result<(), Box<dyn Error>> {
                 }
             }
 
-            ExtensionConfigManager::set(ExtensionEntry {
-                enabled: true,
-                config: ExtensionConfig::Sse {
+            let extension_config = if extension_type == "sse" {
+                ExtensionConfig::Sse {
                     name: name.clone(),
                     uri,
                     envs: Envs::new(envs),
                     env_keys,
                     description,
                     timeout: Some(timeout),
+                    roots: Vec::new(),
                     bundled: None,
-                },
+                }
+            } else {
+                ExtensionConfig::StreamableHttp {
+                    name: name.clone(),
+                    uri,
+                    envs: Envs::new(envs),
+                    env_keys,
+                    headers: HashMap::new(),
+                    description,
+                    timeout: Some(timeout),
+                    roots: Vec::new(),
+                    bundled: None,
+                }
+            };
+
+            ExtensionConfigManager::set(ExtensionEntry {
+                enabled: true,
+                config: extension_config,
             })?;
 
             cliclack::outro(format!("Added {} extension", style(name).green()))?;
@@ -2,7 +2,10 @@ use anyhow::Result;
 use base64::Engine;
 use console::style;
 
-use crate::recipes::recipe::load_recipe;
+use crate::recipes::lockfile::Lockfile;
+use crate::recipes::recipe::{load_recipe, validate_recipe};
+use crate::recipes::recipe_source::RecipeSource;
+use crate::recipes::registry::RecipeRegistry;
 
 /// Validates a recipe file
 ///
@@ -14,8 +17,8 @@ use crate::recipes::recipe::load_recipe;
 ///
 /// Result indicating success or failure
 pub fn handle_validate(recipe_name: &str) -> Result<()> {
-    // Load and validate the recipe file
-    match load_recipe(recipe_name) {
+    // Load and validate the recipe file and its sub-recipes
+    match validate_recipe(recipe_name) {
         Ok(_) => {
             println!("{} recipe file is valid", style("✓").green().bold());
             Ok(())
@@ -57,3 +60,151 @@ pub fn handle_deeplink(recipe_name: &str) -> Result<()> {
         }
     }
 }
+
+/// Installs a recipe into the local registry and pins it in the project's lockfile
+///
+/// # Arguments
+///
+/// * `name` - Name of the recipe to install
+/// * `source` - Git URL, local directory or HTTP index file, defaulting to the locked source
+/// * `requirement` - Semver requirement, defaulting to the locked version or the newest
+///
+/// # Returns
+///
+/// Result indicating success or failure
+pub async fn handle_install(
+    name: &str,
+    source: Option<&str>,
+    requirement: Option<&str>,
+) -> Result<()> {
+    let registry = RecipeRegistry::new();
+    let mut lockfile = Lockfile::find()?;
+    let previous_sha256 = lockfile
+        .recipes
+        .get(name)
+        .map(|locked| locked.sha256.clone());
+    let installed = registry
+        .install(name, source, requirement, &mut lockfile)
+        .await?;
+    lockfile.save()?;
+
+    println!(
+        "{} Installed {} {} from {}",
+        style("✓").green().bold(),
+        installed.name,
+        installed.version,
+        installed.record.source
+    );
+    println!(
+        "  {}",
+        style(format!("Locked in {}", lockfile.path().display())).dim()
+    );
+
+    // Git and directory sources list no checksums, so a new version is trusted as installed
+    let verified = previous_sha256.as_deref() == Some(installed.record.sha256.as_str())
+        || RecipeSource::parse(&installed.record.source)?.has_upstream_checksum();
+    if !verified && installed.runs_local_commands()? {
+        println!(
+            "  {}",
+            style(format!(
+                "{} starts local processes and its source lists no checksum, review {} before running it",
+                installed.name,
+                installed.dir.display()
+            ))
+            .yellow()
+        );
+    }
+    Ok(())
+}
+
+/// Lists installed recipes, marking the versions the project's lockfile pins
+///
+/// # Returns
+///
+/// Result indicating success or failure
+pub fn handle_list() -> Result<()> {
+    let installed = RecipeRegistry::new().list()?;
+    if installed.is_empty() {
+        println!("No recipes installed. Install one with `goose recipe install`.");
+        return Ok(());
+    }
+
+    let lockfile = Lockfile::find()?;
+    for recipe in installed {
+        let locked = lockfile
+            .recipes
+            .get(&recipe.name)
+            .is_some_and(|locked| locked.version == recipe.version.to_string());
+        println!(
+            "{} {} {}{}",
+            style(&recipe.name).bold(),
+            recipe.version,
+            style(&recipe.record.source).dim(),
+            if locked {
+                style(" (locked)").green().to_string()
+            } else {
+                String::new()
+            }
+        );
+    }
+    Ok(())
+}
+
+/// Updates locked recipes to the newest version matching their requirement
+///
+/// # Arguments
+///
+/// * `name` - Recipe to update, or all recipes in the lockfile
+///
+/// # Returns
+///
+/// Result indicating success or failure
+pub async fn handle_update(name: Option<&str>) -> Result<()> {
+    let registry = RecipeRegistry::new();
+    let mut lockfile = Lockfile::find()?;
+    let names: Vec<String> = match name {
+        Some(name) => vec![name.to_string()],
+        None => lockfile.recipes.keys().cloned().collect(),
+    };
+    if names.is_empty() {
+        println!("No recipes locked in {}", lockfile.path().display());
+        return Ok(());
+    }
+
+    for name in names {
+        let (previous, installed) = registry.update(&name, &mut lockfile).await?;
+        // Save after each recipe so a later failure keeps the earlier updates
+        lockfile.save()?;
+        if previous.sha256 == installed.record.sha256 {
+            println!("{} {} is up to date", style("✓").green().bold(), name);
+        } else {
+            println!(
+                "{} Updated {} {} -> {}",
+                style("✓").green().bold(),
+                name,
+                previous.version,
+                installed.version
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Removes every installed version of a recipe and unpins it from the project's lockfile
+///
+/// # Arguments
+///
+/// * `name` - Name of the recipe to remove
+///
+/// # Returns
+///
+/// Result indicating success or failure
+pub fn handle_remove(name: &str) -> Result<()> {
+    let mut lockfile = Lockfile::find()?;
+    RecipeRegistry::new().remove(name, &mut lockfile)?;
+    if lockfile.path().exists() {
+        lockfile.save()?;
+    }
+    println!("{} Removed {}", style("✓").green().bold(), name);
+    Ok(())
+}
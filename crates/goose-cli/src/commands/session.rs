@@ -1,8 +1,12 @@
-use crate::session::message_to_markdown;
+use crate::session::{
+    format_cost, message_to_markdown, session_to_finetune_jsonl, session_to_html, session_to_json,
+    transcript_from_json, FineTuneFormat,
+};
 use anyhow::{Context, Result};
 use cliclack::{confirm, multiselect, select};
-use goose::session::info::{get_session_info, SessionInfo, SortOrder};
-use goose::session::{self, Identifier};
+use goose::session::info::{get_session_info, query_sessions, SessionInfo, SortOrder};
+use goose::session::sqlite::{database_path, SqliteStore};
+use goose::session::{self, Identifier, SessionQuery};
 use regex::Regex;
 use std::fs;
 use std::path::{Path, PathBuf};
@@ -21,8 +25,8 @@ pub fn remove_sessions(sessions: Vec<SessionInfo>) -> Result<()> {
 
     if should_delete {
         for session in sessions {
-            fs::remove_file(session.path.clone())
-                .with_context(|| format!("Failed to remove session file '{}'", session.path))?;
+            session::delete_session(Path::new(&session.path))
+                .with_context(|| format!("Failed to remove session '{}'", session.id))?;
             println!("Session `{}` removed.", session.id);
         }
     } else {
@@ -118,14 +122,8 @@ pub fn handle_session_remove(id: Option<String>, regex_string: Option<String>) -
     remove_sessions(matched_sessions)
 }
 
-pub fn handle_session_list(verbose: bool, format: String, ascending: bool) -> Result<()> {
-    let sort_order = if ascending {
-        SortOrder::Ascending
-    } else {
-        SortOrder::Descending
-    };
-
-    let sessions = match get_session_info(sort_order) {
+pub fn handle_session_list(verbose: bool, format: String, query: SessionQuery) -> Result<()> {
+    let sessions = match query_sessions(&query) {
         Ok(sessions) => sessions,
         Err(e) => {
             tracing::error!("Failed to list sessions: {:?}", e);
@@ -155,7 +153,10 @@ pub fn handle_session_list(verbose: bool, format: String, ascending: bool) -> Re
                     } else {
                         &metadata.description
                     };
-                    let output = format!("{} - {} - {}", id, description, modified);
+                    let mut output = format!("{} - {} - {}", id, description, modified);
+                    if let Some(cost) = metadata.accumulated_cost {
+                        output.push_str(&format!(" - {}", format_cost(cost)));
+                    }
                     if verbose {
                         println!("  {}", output);
                         println!("    Path: {}", path);
@@ -169,15 +170,85 @@ pub fn handle_session_list(verbose: bool, format: String, ascending: bool) -> Re
     Ok(())
 }
 
-/// Export a session to Markdown without creating a full Session object
+pub fn handle_session_search(query: String, limit: usize, format: String) -> Result<()> {
+    let hits = session::search_sessions(&query, limit)?;
+
+    match format.as_str() {
+        "json" => {
+            println!("{}", serde_json::to_string(&hits)?);
+        }
+        _ => {
+            if hits.is_empty() {
+                println!("No messages found matching '{}'", query);
+            }
+            for hit in hits {
+                let description = if hit.description.is_empty() {
+                    "(none)"
+                } else {
+                    &hit.description
+                };
+                // Numbered from 1, like `goose session fork --from-message`
+                println!(
+                    "{} - {} - message {}",
+                    hit.id,
+                    description,
+                    hit.message_index + 1
+                );
+                println!("    {}", hit.snippet);
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn handle_session_fork(
+    identifier: Identifier,
+    from_message: usize,
+    fork_name: Option<String>,
+) -> Result<()> {
+    let source = session::get_path(identifier);
+    let forked = session::fork_session(&source, from_message, fork_name.map(Identifier::Name))?;
+
+    let id = forked
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+    println!(
+        "Forked the first {} messages of {} into session `{}`.",
+        from_message,
+        source.display(),
+        id
+    );
+    println!("Continue it with: goose session --resume --name {}", id);
+    Ok(())
+}
+
+/// Import the JSONL session files into the SQLite session database
+pub fn handle_session_migrate() -> Result<()> {
+    let session_dir = session::ensure_session_dir()?;
+    let database = database_path()?;
+    let store = SqliteStore::open(&database)
+        .with_context(|| format!("Failed to open session database '{}'", database.display()))?;
+
+    let imported = store.import_jsonl(&session_dir)?;
+    println!("Imported {} sessions into {}", imported, database.display());
+    println!("Set GOOSE_SESSION_STORE=sqlite to use it. The JSONL files were left in place.");
+    Ok(())
+}
+
+/// Export a session without creating a full Session object
 ///
-/// This function directly reads messages from the session file and converts them to Markdown
-/// without creating an Agent or prompting about working directories.
-pub fn handle_session_export(identifier: Identifier, output_path: Option<PathBuf>) -> Result<()> {
+/// This function directly reads messages from the session file and converts them to the requested
+/// format without creating an Agent or prompting about working directories.
+pub fn handle_session_export(
+    identifier: Identifier,
+    output_path: Option<PathBuf>,
+    format: String,
+) -> Result<()> {
     // Get the session file path
     let session_file_path = goose::session::get_path(identifier.clone());
 
-    if !session_file_path.exists() {
+    if !goose::session::session_exists(&session_file_path) {
         return Err(anyhow::anyhow!(
             "Session file not found (expected path: {})",
             session_file_path.display()
@@ -192,21 +263,69 @@ pub fn handle_session_export(identifier: Identifier, output_path: Option<PathBuf
         }
     };
 
-    // Generate the markdown content using the export functionality
-    let markdown = export_session_to_markdown(messages, &session_file_path, None);
+    let session_id = session_file_path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("Unnamed Session");
+
+    let exported = match format.as_str() {
+        "markdown" | "md" => export_session_to_markdown(messages, &session_file_path, None),
+        "html" => session_to_html(session_id, &messages),
+        "json" => {
+            let metadata = goose::session::read_metadata(&session_file_path)?;
+            session_to_json(session_id, metadata, messages)?
+        }
+        "openai" => session_to_finetune_jsonl(&messages, FineTuneFormat::OpenAi)?,
+        "anthropic" => session_to_finetune_jsonl(&messages, FineTuneFormat::Anthropic)?,
+        other => {
+            return Err(anyhow::anyhow!(
+                "Unknown export format '{}'. Use markdown, html, json, openai or anthropic",
+                other
+            ));
+        }
+    };
 
-    // Output the markdown
     if let Some(output) = output_path {
-        fs::write(&output, markdown)
+        fs::write(&output, exported)
             .with_context(|| format!("Failed to write to output file: {}", output.display()))?;
         println!("Session exported to {}", output.display());
     } else {
-        println!("{}", markdown);
+        println!("{}", exported);
     }
 
     Ok(())
 }
 
+/// Create a new session from a JSON transcript written by `goose session export --format json`
+pub fn handle_session_import(file: PathBuf, import_name: Option<String>) -> Result<()> {
+    let json = fs::read_to_string(&file)
+        .with_context(|| format!("Failed to read transcript: {}", file.display()))?;
+    let transcript = transcript_from_json(&json)
+        .with_context(|| format!("Invalid session transcript: {}", file.display()))?;
+
+    let id = import_name.unwrap_or_else(session::generate_session_id);
+    let session_file = session::get_path(Identifier::Name(id.clone()));
+    if session::session_exists(&session_file) {
+        return Err(anyhow::anyhow!(
+            "Session `{}` already exists. Choose another name with --import-name",
+            id
+        ));
+    }
+
+    let mut metadata = transcript.metadata;
+    metadata.message_count = transcript.messages.len();
+    session::storage::save_messages_with_metadata(&session_file, &metadata, &transcript.messages)?;
+
+    println!(
+        "Imported {} messages from {} into session `{}`.",
+        transcript.messages.len(),
+        file.display(),
+        id
+    );
+    println!("Continue it with: goose session --resume --name {}", id);
+    Ok(())
+}
+
 /// Convert a list of messages to markdown format for session export
 ///
 /// This function handles the formatting of a complete session including headers,
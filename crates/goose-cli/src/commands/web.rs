@@ -413,7 +413,7 @@ async fn process_message_streaming(
     sender: Arc<Mutex<futures::stream::SplitSink<WebSocket, Message>>>,
 ) -> Result<()> {
     use futures::StreamExt;
-    use goose::agents::SessionConfig;
+    use goose::agents::{ReplyBudget, SessionConfig};
     use goose::message::MessageContent;
     use goose::session;
 
@@ -454,6 +454,7 @@ async fn process_message_streaming(
         id: session::Identifier::Path(session_file.clone()),
         working_dir: std::env::current_dir()?,
         schedule_id: None,
+        budget: ReplyBudget::default(),
     };
 
     // Get response from agent
@@ -584,6 +585,19 @@ async fn process_message_streaming(
                             }
                         }
                     }
+                    Ok(AgentEvent::MessageDelta(_)) => {
+                        // The web interface renders complete messages only
+                    }
+                    Ok(AgentEvent::HistoryReplaced(messages)) => {
+                        // The agent already saved the compacted history to the session file
+                        *session_messages.lock().await = messages;
+                    }
+                    Ok(AgentEvent::Subagent(_)) => {
+                        // Only the delegate tool's final result is rendered in the web interface
+                    }
+                    Ok(AgentEvent::ToolProgress(_)) => {
+                        // Progress is not rendered in the web interface
+                    }
                     Ok(AgentEvent::McpNotification(_notification)) => {
                         // Handle MCP notifications if needed
                         // For now, we'll just log them
@@ -11,7 +11,7 @@ use tracing_subscriber::{
     Registry,
 };
 
-use goose::tracing::langfuse_layer;
+use goose::tracing::{langfuse_layer, otlp_layer};
 use goose_bench::bench_session::BenchAgentError;
 use goose_bench::error_capture::ErrorCaptureLayer;
 
@@ -145,6 +145,11 @@ fn setup_logging_internal(
                 layers.push(langfuse.with_filter(LevelFilter::DEBUG).boxed());
             }
 
+            // Add OpenTelemetry layer if an OTLP endpoint is configured
+            if let Some(otlp) = otlp_layer::create_otlp_observer() {
+                layers.push(otlp.with_filter(LevelFilter::DEBUG).boxed());
+            }
+
             // Build the subscriber
             let subscriber = Registry::default().with(layers);
 
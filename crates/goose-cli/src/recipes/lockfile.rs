@@ -0,0 +1,102 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub const LOCKFILE_NAME: &str = "goose-recipes.lock";
+
+/// The exact recipe versions a project uses, so everyone working on it runs the same recipes
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Lockfile {
+    #[serde(default)]
+    pub recipes: BTreeMap<String, LockedRecipe>,
+    #[serde(skip)]
+    path: PathBuf,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LockedRecipe {
+    pub version: String,
+    /// Version requirement `goose recipe update` resolves against
+    pub requirement: String,
+    pub source: String,
+    /// Commit the recipe was installed from, for git sources
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+    /// SHA-256 of the installed files, see [`tree_sha256`](crate::recipes::registry::tree_sha256)
+    pub sha256: String,
+}
+
+impl Lockfile {
+    /// Load the lockfile of the project containing the working directory. The nearest
+    /// directory with a lockfile is the project root; without one, a new lockfile is created
+    /// in the working directory when saved.
+    pub fn find() -> Result<Self> {
+        let cwd = env::current_dir()?;
+        match cwd
+            .ancestors()
+            .map(|dir| dir.join(LOCKFILE_NAME))
+            .find(|path| path.is_file())
+        {
+            Some(path) => Self::load(&path),
+            None => Ok(Self {
+                path: cwd.join(LOCKFILE_NAME),
+                ..Default::default()
+            }),
+        }
+    }
+
+    /// Load the lockfile at `path`, or start an empty one there if it doesn't exist
+    pub fn load(path: &Path) -> Result<Self> {
+        let mut lockfile: Lockfile = if path.exists() {
+            let content = fs::read_to_string(path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            serde_yaml::from_str(&content)
+                .with_context(|| format!("Failed to parse {}", path.display()))?
+        } else {
+            Lockfile::default()
+        };
+        lockfile.path = path.to_path_buf();
+        Ok(lockfile)
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let content = serde_yaml::to_string(self)?;
+        fs::write(&self.path, content)
+            .with_context(|| format!("Failed to write {}", self.path.display()))
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_lockfile_round_trip() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(LOCKFILE_NAME);
+
+        let mut lockfile = Lockfile::load(&path).unwrap();
+        assert!(lockfile.recipes.is_empty());
+        lockfile.recipes.insert(
+            "review".to_string(),
+            LockedRecipe {
+                version: "1.2.0".to_string(),
+                requirement: "^1".to_string(),
+                source: "https://github.com/org/recipes.git".to_string(),
+                commit: Some("0123abcd".to_string()),
+                sha256: "ff".repeat(32),
+            },
+        );
+        lockfile.save().unwrap();
+
+        assert_eq!(Lockfile::load(&path).unwrap(), lockfile);
+    }
+}
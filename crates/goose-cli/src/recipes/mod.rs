@@ -1,4 +1,7 @@
 pub mod github_recipe;
+pub mod lockfile;
 pub mod print_recipe;
 pub mod recipe;
+pub mod recipe_source;
+pub mod registry;
 pub mod search_recipe;
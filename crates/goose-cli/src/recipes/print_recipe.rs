@@ -1,7 +1,7 @@
 use std::collections::HashMap;
 
 use console::style;
-use goose::recipe::Recipe;
+use goose::recipe::{Recipe, SubRecipeTemplate};
 
 use crate::recipes::recipe::BUILT_IN_RECIPE_DIR_PARAM;
 
@@ -35,6 +35,20 @@ pub fn print_recipe_explanation(recipe: &Recipe) {
     }
 }
 
+pub fn print_sub_recipes(sub_recipes: &[SubRecipeTemplate]) {
+    if sub_recipes.is_empty() {
+        return;
+    }
+    println!("{}", style("🧩 Sub-recipes the agent can run:").bold());
+    for sub_recipe in sub_recipes {
+        println!(
+            "   - {}: {}",
+            style(&sub_recipe.name).cyan(),
+            sub_recipe.description
+        );
+    }
+}
+
 pub fn print_parameters_with_values(params: HashMap<String, String>) {
     for (key, value) in params {
         let label = if key == BUILT_IN_RECIPE_DIR_PARAM {
@@ -1,17 +1,19 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use console::style;
 
 use crate::recipes::print_recipe::{
     missing_parameters_command_line, print_parameters_with_values, print_recipe_explanation,
-    print_required_parameters_for_template,
+    print_required_parameters_for_template, print_sub_recipes,
 };
 use crate::recipes::search_recipe::retrieve_recipe_file;
-use goose::recipe::{Recipe, RecipeParameter, RecipeParameterRequirement};
+use goose::recipe::{
+    Recipe, RecipeParameter, RecipeParameterRequirement, SubRecipe, SubRecipeTemplate,
+};
 use minijinja::{Environment, Error, Template, UndefinedBehavior};
 use serde_json::Value as JsonValue;
 use serde_yaml::Value as YamlValue;
 use std::collections::{HashMap, HashSet};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 pub const BUILT_IN_RECIPE_DIR_PARAM: &str = "recipe_dir";
 pub const RECIPE_FILE_EXTENSIONS: &[&str] = &["yaml", "json"];
@@ -47,9 +49,13 @@ pub fn load_recipe_as_template(recipe_name: &str, params: Vec<(String, String)>)
 
     let rendered_content = render_content_with_params(&recipe_file_content, &params_for_template)?;
 
-    let recipe = parse_recipe_content(&rendered_content)?;
+    let mut recipe = parse_recipe_content(&rendered_content)?;
+    resolve_sub_recipe_paths(&mut recipe, &recipe_parent_dir);
 
-    // Display information about the loaded recipe
+    // Display information about the loaded recipe, unless stdout is reserved for the result
+    if crate::session::is_quiet() {
+        return Ok(recipe);
+    }
     println!(
         "{} {}",
         style("Loading recipe:").green().bold(),
@@ -89,14 +95,129 @@ pub fn load_recipe(recipe_name: &str) -> Result<Recipe> {
     validate_recipe_file_parameters(&recipe_file_content)
 }
 
+/// Loads a recipe like [`load_recipe`] and also checks the sub-recipes it declares, and theirs
+/// in turn, can be loaded
+pub fn validate_recipe(recipe_name: &str) -> Result<Recipe> {
+    let (recipe_file_content, recipe_parent_dir) = retrieve_recipe_file(recipe_name)?;
+
+    let mut recipe = validate_recipe_file_parameters(&recipe_file_content)?;
+    resolve_sub_recipe_paths(&mut recipe, &recipe_parent_dir);
+    load_sub_recipes(&recipe)?;
+    Ok(recipe)
+}
+
+/// Loads the sub-recipes a loaded recipe declares, and theirs in turn, so the agent can run
+/// them as tools. Each sub-recipe is validated but left unrendered, since the agent only
+/// provides the remaining parameters when it runs one.
+///
+/// # Errors
+///
+/// Returns an error if:
+/// - A sub-recipe file can't be read or is not a valid recipe
+/// - A sub-recipe name is invalid or used twice by the same recipe
+/// - A parent passes a value for a parameter the sub-recipe does not define
+/// - Sub-recipes refer to each other in a cycle
+pub fn load_sub_recipes(recipe: &Recipe) -> Result<Vec<SubRecipeTemplate>> {
+    load_sub_recipe_templates(
+        recipe.sub_recipes.as_deref().unwrap_or_default(),
+        &mut Vec::new(),
+    )
+}
+
+fn load_sub_recipe_templates(
+    sub_recipes: &[SubRecipe],
+    ancestors: &mut Vec<PathBuf>,
+) -> Result<Vec<SubRecipeTemplate>> {
+    let mut names = HashSet::new();
+    let mut templates = Vec::new();
+    for sub_recipe in sub_recipes {
+        let name = &sub_recipe.name;
+        if name.is_empty()
+            || !name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        {
+            return Err(anyhow::anyhow!(
+                "Sub-recipe name '{}' may only contain letters, digits, '_' and '-'",
+                name
+            ));
+        }
+        if !names.insert(name) {
+            return Err(anyhow::anyhow!("Sub-recipe '{}' is declared twice", name));
+        }
+
+        let path = Path::new(&sub_recipe.path)
+            .canonicalize()
+            .with_context(|| format!("Sub-recipe '{}' not found at {}", name, sub_recipe.path))?;
+        if ancestors.contains(&path) {
+            let chain = ancestors
+                .iter()
+                .chain(std::iter::once(&path))
+                .map(|p| p.display().to_string())
+                .collect::<Vec<_>>()
+                .join(" -> ");
+            return Err(anyhow::anyhow!("Sub-recipes form a cycle: {}", chain));
+        }
+
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read sub-recipe {}", path.display()))?;
+        let mut recipe = validate_recipe_file_parameters(&content)
+            .with_context(|| format!("Invalid sub-recipe {}", path.display()))?;
+        let parameters = recipe.parameters.take().unwrap_or_default();
+        if let Some(key) = sub_recipe
+            .values
+            .keys()
+            .find(|key| !parameters.iter().any(|p| &p.key == *key))
+        {
+            return Err(anyhow::anyhow!(
+                "Sub-recipe '{}' has no parameter '{}'",
+                name,
+                key
+            ));
+        }
+
+        let recipe_dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        resolve_sub_recipe_paths(&mut recipe, &recipe_dir);
+        ancestors.push(path);
+        let nested = load_sub_recipe_templates(
+            recipe.sub_recipes.as_deref().unwrap_or_default(),
+            ancestors,
+        )?;
+        ancestors.pop();
+
+        templates.push(SubRecipeTemplate {
+            name: name.clone(),
+            description: sub_recipe.description.clone().unwrap_or(recipe.description),
+            content,
+            recipe_dir,
+            parameters,
+            values: sub_recipe.values.clone(),
+            sub_recipes: nested,
+        });
+    }
+    Ok(templates)
+}
+
+/// Sub-recipe paths are relative to the recipe declaring them
+fn resolve_sub_recipe_paths(recipe: &mut Recipe, recipe_dir: &Path) {
+    for sub_recipe in recipe.sub_recipes.iter_mut().flatten() {
+        sub_recipe.path = recipe_dir
+            .join(&sub_recipe.path)
+            .to_string_lossy()
+            .to_string();
+    }
+}
+
 pub fn explain_recipe_with_parameters(
     recipe_name: &str,
     params: Vec<(String, String)>,
 ) -> Result<()> {
     let (recipe_file_content, recipe_parent_dir) = retrieve_recipe_file(recipe_name)?;
 
-    let raw_recipe = validate_recipe_file_parameters(&recipe_file_content)?;
+    let mut raw_recipe = validate_recipe_file_parameters(&recipe_file_content)?;
     print_recipe_explanation(&raw_recipe);
+    resolve_sub_recipe_paths(&mut raw_recipe, &recipe_parent_dir);
+    print_sub_recipes(&load_sub_recipes(&raw_recipe)?);
     let recipe_parameters = raw_recipe.parameters;
     let (params_for_template, missing_params) =
         apply_values_to_parameters(&params, recipe_parameters, recipe_parent_dir, false)?;
@@ -105,7 +226,8 @@ pub fn explain_recipe_with_parameters(
     Ok(())
 }
 
-fn validate_recipe_file_parameters(recipe_file_content: &str) -> Result<Recipe> {
+/// Parses a recipe file and checks its parameters match the template variables it uses
+pub fn validate_recipe_file_parameters(recipe_file_content: &str) -> Result<Recipe> {
     let recipe_from_recipe_file: Recipe = parse_recipe_content(recipe_file_content)?;
     validate_optional_parameters(&recipe_from_recipe_file)?;
     validate_parameters_in_template(&recipe_from_recipe_file.parameters, recipe_file_content)?;
@@ -518,6 +640,80 @@ mod tests {
             .contains("unknown variant `some_invalid_type`"));
     }
 
+    fn write_sub_recipe(dir: &std::path::Path, name: &str, sub_recipes: &str) {
+        let content = format!(
+            r#"{{
+            "title": "{name}",
+            "description": "The {name} recipe",
+            "prompt": "Work on {{{{ module }}}}",
+            "parameters": [
+                {{
+                    "key": "module",
+                    "input_type": "string",
+                    "requirement": "required",
+                    "description": "Module to work on"
+                }}
+            ],
+            "sub_recipes": [{sub_recipes}]
+        }}"#
+        );
+        std::fs::write(dir.join(format!("{}.json", name)), content).unwrap();
+    }
+
+    #[test]
+    fn test_load_sub_recipes() {
+        let instructions_and_parameters = r#"
+            "instructions": "Triage the issue",
+            "sub_recipes": [
+                {"name": "fix", "path": "fix.json", "values": {"module": "agents"}},
+                {"name": "write_tests", "path": "nested/write_tests.json", "description": "Add tests"}
+            ]"#;
+        let (temp_dir, recipe_path) = setup_recipe_file(instructions_and_parameters);
+        std::fs::create_dir(temp_dir.path().join("nested")).unwrap();
+        write_sub_recipe(temp_dir.path(), "fix", "");
+        write_sub_recipe(
+            &temp_dir.path().join("nested"),
+            "write_tests",
+            r#"{"name": "fix", "path": "../fix.json"}"#,
+        );
+
+        let recipe = load_recipe_as_template(recipe_path.to_str().unwrap(), Vec::new()).unwrap();
+        let sub_recipes = load_sub_recipes(&recipe).unwrap();
+
+        assert_eq!(sub_recipes.len(), 2);
+        assert_eq!(sub_recipes[0].name, "fix");
+        assert_eq!(sub_recipes[0].description, "The fix recipe");
+        assert_eq!(sub_recipes[0].values["module"], "agents");
+        assert!(sub_recipes[0].content.contains("{{ module }}"));
+        assert_eq!(sub_recipes[1].description, "Add tests");
+        // Nested paths are relative to the sub-recipe declaring them
+        assert_eq!(sub_recipes[1].sub_recipes.len(), 1);
+        assert_eq!(sub_recipes[1].sub_recipes[0].name, "fix");
+    }
+
+    #[test]
+    fn test_load_sub_recipes_errors() {
+        let instructions_and_parameters = r#"
+            "instructions": "Triage the issue",
+            "sub_recipes": [{"name": "a", "path": "a.json"}]"#;
+        let (temp_dir, recipe_path) = setup_recipe_file(instructions_and_parameters);
+        write_sub_recipe(temp_dir.path(), "a", r#"{"name": "b", "path": "b.json"}"#);
+        write_sub_recipe(temp_dir.path(), "b", r#"{"name": "a", "path": "a.json"}"#);
+
+        let recipe = load_recipe_as_template(recipe_path.to_str().unwrap(), Vec::new()).unwrap();
+        let err = load_sub_recipes(&recipe).unwrap_err();
+        assert!(err.to_string().contains("Sub-recipes form a cycle"));
+
+        write_sub_recipe(
+            temp_dir.path(),
+            "b",
+            r#"{"name": "c", "path": "c.json", "values": {"language": "rust"}}"#,
+        );
+        write_sub_recipe(temp_dir.path(), "c", "");
+        let err = load_sub_recipes(&recipe).unwrap_err();
+        assert!(err.to_string().contains("has no parameter 'language'"));
+    }
+
     #[test]
     fn test_load_recipe_as_template_success_without_parameters() {
         let instructions_and_parameters = r#"
@@ -0,0 +1,462 @@
+use anyhow::{anyhow, Context, Result};
+use semver::Version;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use tar::Archive;
+
+use crate::recipes::recipe::{validate_recipe_file_parameters, RECIPE_FILE_EXTENSIONS};
+
+/// Where recipes are installed from
+#[derive(Debug, Clone, PartialEq)]
+pub enum RecipeSource {
+    /// A git repository with a `<name>/recipe.yaml` directory or `<name>.yaml` file per recipe.
+    /// Its tags and default branch are the versions on offer.
+    Git(String),
+    /// A local directory laid out like a git source
+    Dir(PathBuf),
+    /// A YAML or JSON file served over HTTP listing recipe versions and where to download them
+    Index(String),
+}
+
+impl RecipeSource {
+    pub fn parse(source: &str) -> Result<Self> {
+        let is_url = ["http://", "https://"]
+            .iter()
+            .any(|p| source.starts_with(p));
+        let is_index = [".yaml", ".yml", ".json"]
+            .iter()
+            .any(|ext| source.ends_with(ext));
+        if is_url && is_index {
+            return Ok(RecipeSource::Index(source.to_string()));
+        }
+        if is_url
+            || source.ends_with(".git")
+            || ["git@", "git://", "ssh://", "file://"]
+                .iter()
+                .any(|p| source.starts_with(p))
+        {
+            return Ok(RecipeSource::Git(source.to_string()));
+        }
+
+        let path = PathBuf::from(source);
+        if path.is_dir() {
+            let path = path
+                .canonicalize()
+                .with_context(|| format!("Failed to resolve {}", path.display()))?;
+            return Ok(RecipeSource::Dir(path));
+        }
+        Err(anyhow!(
+            "Recipe source '{}' is not a git URL, an index URL or a directory",
+            source
+        ))
+    }
+
+    /// Whether the source lists a checksum for every recipe, so even a first install is
+    /// verified against it
+    pub fn has_upstream_checksum(&self) -> bool {
+        matches!(self, RecipeSource::Index(_))
+    }
+}
+
+impl fmt::Display for RecipeSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RecipeSource::Git(url) | RecipeSource::Index(url) => write!(f, "{}", url),
+            RecipeSource::Dir(path) => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+/// A version of a recipe offered by a source
+#[derive(Debug, Clone)]
+pub struct RecipeRelease {
+    pub version: Version,
+    /// Commit the recipe is read from, for git sources
+    pub commit: Option<String>,
+    location: ReleaseLocation,
+}
+
+#[derive(Debug, Clone)]
+enum ReleaseLocation {
+    /// A path in a git repository checked out under `repo`
+    Git { repo: PathBuf, path: String },
+    /// A recipe directory or file on disk
+    Path(PathBuf),
+    /// A recipe file to download, with the checksum the index lists for it
+    Url { url: String, sha256: String },
+}
+
+#[derive(Debug, Deserialize)]
+struct RecipeIndex {
+    recipes: Vec<IndexEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct IndexEntry {
+    name: String,
+    version: String,
+    /// Absolute, or relative to the index
+    url: String,
+    /// Required, it is the only way to verify the download
+    sha256: Option<String>,
+}
+
+/// The lowercase hex SHA-256 of a recipe file
+pub fn sha256_hex(content: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(content))
+}
+
+/// Where a recipe named `name` may be found in a git or directory source, relative to its root
+fn recipe_paths(name: &str) -> Vec<String> {
+    let mut paths: Vec<String> = RECIPE_FILE_EXTENSIONS
+        .iter()
+        .map(|ext| format!("{}/recipe.{}", name, ext))
+        .collect();
+    paths.extend(
+        RECIPE_FILE_EXTENSIONS
+            .iter()
+            .map(|ext| format!("{}.{}", name, ext)),
+    );
+    paths
+}
+
+/// Read the version a recipe declares, checking it is a valid recipe on the way
+fn recipe_version(content: &str) -> Result<Version> {
+    let recipe = validate_recipe_file_parameters(content)?;
+    Version::parse(&recipe.version).with_context(|| {
+        format!(
+            "Recipe version '{}' is not a semantic version",
+            recipe.version
+        )
+    })
+}
+
+/// List the versions of recipe `name` that `source` offers, newest first. Git repositories are
+/// cloned into `cache_dir`.
+pub async fn releases(
+    source: &RecipeSource,
+    name: &str,
+    cache_dir: &Path,
+) -> Result<Vec<RecipeRelease>> {
+    let mut releases = match source {
+        RecipeSource::Git(url) => git_releases(url, name, cache_dir)?,
+        RecipeSource::Dir(dir) => dir_releases(dir, name)?,
+        RecipeSource::Index(url) => index_releases(url, name).await?,
+    };
+    releases.sort_by(|a, b| b.version.cmp(&a.version));
+    Ok(releases)
+}
+
+/// The release at a commit of a git source, which may no longer be tagged or on a branch
+pub fn release_at_commit(
+    source: &RecipeSource,
+    name: &str,
+    commit: &str,
+    cache_dir: &Path,
+) -> Result<RecipeRelease> {
+    let url = match source {
+        RecipeSource::Git(url) => url,
+        _ => return Err(anyhow!("Only git sources can be installed at a commit")),
+    };
+    let repo = ensure_repo(url, cache_dir)?;
+    git_release(&repo, name, commit)?.ok_or_else(|| {
+        anyhow!(
+            "Recipe '{}' not found at commit {} of {}",
+            name,
+            commit,
+            url
+        )
+    })
+}
+
+/// Write the release's recipe, and the files next to it when the recipe is a directory, into
+/// `target`. Returns the recipe file's content.
+pub async fn fetch(release: &RecipeRelease, target: &Path) -> Result<String> {
+    fs::create_dir_all(target)?;
+    match &release.location {
+        ReleaseLocation::Git { repo, path } => {
+            let commit = release.commit.as_deref().unwrap_or("HEAD");
+            match path.rsplit_once('/') {
+                Some((dir, _)) => {
+                    let mut child = Command::new("git")
+                        .args(["archive", &format!("{}:{}", commit, dir)])
+                        .current_dir(repo)
+                        .stdout(Stdio::piped())
+                        .spawn()
+                        .context("Failed to run git archive")?;
+                    let stdout = child
+                        .stdout
+                        .take()
+                        .ok_or_else(|| anyhow!("Failed to capture stdout from git archive"))?;
+                    Archive::new(stdout).unpack(target)?;
+                    if !child.wait()?.success() {
+                        return Err(anyhow!("Failed to extract {} at {}", dir, commit));
+                    }
+                }
+                None => {
+                    let content = git(repo, &["show", &format!("{}:{}", commit, path)])?;
+                    fs::write(target.join(recipe_file_name(path)), content)?;
+                }
+            }
+        }
+        ReleaseLocation::Path(path) => {
+            if path.is_dir() {
+                copy_dir(path, target)?;
+            } else {
+                fs::copy(path, target.join(recipe_file_name(&path.to_string_lossy())))?;
+            }
+        }
+        ReleaseLocation::Url { url, sha256 } => {
+            let content = reqwest::get(url.as_str())
+                .await
+                .and_then(|response| response.error_for_status())
+                .with_context(|| format!("Failed to download {}", url))?
+                .bytes()
+                .await?;
+            let actual = sha256_hex(&content);
+            if !actual.eq_ignore_ascii_case(sha256) {
+                return Err(anyhow!(
+                    "Checksum of {} is {}, but the index lists {}",
+                    url,
+                    actual,
+                    sha256
+                ));
+            }
+            fs::write(target.join(recipe_file_name(url)), content)?;
+        }
+    }
+
+    read_recipe_in(target)
+        .map(|(content, _)| content)
+        .ok_or_else(|| anyhow!("No recipe file found in {}", target.display()))
+}
+
+/// The recipe file in an installed recipe directory and its path
+pub fn read_recipe_in(dir: &Path) -> Option<(String, PathBuf)> {
+    RECIPE_FILE_EXTENSIONS.iter().find_map(|ext| {
+        let path = dir.join(format!("recipe.{}", ext));
+        fs::read_to_string(&path)
+            .ok()
+            .map(|content| (content, path))
+    })
+}
+
+/// Installed recipe files are always named `recipe.yaml` or `recipe.json`
+fn recipe_file_name(path: &str) -> String {
+    if path.ends_with(".json") {
+        "recipe.json".to_string()
+    } else {
+        "recipe.yaml".to_string()
+    }
+}
+
+fn dir_releases(dir: &Path, name: &str) -> Result<Vec<RecipeRelease>> {
+    for relative in recipe_paths(name) {
+        let path = dir.join(&relative);
+        if let Ok(content) = fs::read_to_string(&path) {
+            let version = recipe_version(&content)
+                .with_context(|| format!("Invalid recipe {}", path.display()))?;
+            let location = if relative.contains('/') {
+                ReleaseLocation::Path(dir.join(name))
+            } else {
+                ReleaseLocation::Path(path)
+            };
+            return Ok(vec![RecipeRelease {
+                version,
+                commit: None,
+                location,
+            }]);
+        }
+    }
+    Ok(Vec::new())
+}
+
+async fn index_releases(url: &str, name: &str) -> Result<Vec<RecipeRelease>> {
+    let base = reqwest::Url::parse(url).with_context(|| format!("Invalid index URL {}", url))?;
+    let content = reqwest::get(base.clone())
+        .await
+        .and_then(|response| response.error_for_status())
+        .with_context(|| format!("Failed to download recipe index {}", url))?
+        .text()
+        .await?;
+    let index: RecipeIndex = serde_yaml::from_str(&content)
+        .with_context(|| format!("Failed to parse recipe index {}", url))?;
+
+    let mut releases = Vec::new();
+    for entry in index.recipes.into_iter().filter(|entry| entry.name == name) {
+        let version = Version::parse(&entry.version).with_context(|| {
+            format!(
+                "Version '{}' of '{}' in {} is not a semantic version",
+                entry.version, name, url
+            )
+        })?;
+        let recipe_url = base
+            .join(&entry.url)
+            .with_context(|| format!("Invalid recipe URL {}", entry.url))?;
+        let sha256 = entry.sha256.ok_or_else(|| {
+            anyhow!(
+                "Version {} of '{}' in {} has no sha256, which every index entry must list",
+                version,
+                name,
+                url
+            )
+        })?;
+        releases.push(RecipeRelease {
+            version,
+            commit: None,
+            location: ReleaseLocation::Url {
+                url: recipe_url.to_string(),
+                sha256,
+            },
+        });
+    }
+    Ok(releases)
+}
+
+fn git_releases(url: &str, name: &str, cache_dir: &Path) -> Result<Vec<RecipeRelease>> {
+    let repo = ensure_repo(url, cache_dir)?;
+    let tags = git(&repo, &["tag", "--list"])?;
+    let refs = String::from_utf8_lossy(&tags)
+        .lines()
+        .map(|tag| format!("refs/tags/{}", tag.trim()))
+        .chain(std::iter::once("HEAD".to_string()))
+        .collect::<Vec<_>>();
+
+    // Tags come first, so a version on both a tag and the default branch is installed from the tag
+    let mut releases: Vec<RecipeRelease> = Vec::new();
+    for reference in refs {
+        let commit = match git(&repo, &["rev-parse", &format!("{}^{{commit}}", reference)]) {
+            Ok(commit) => String::from_utf8_lossy(&commit).trim().to_string(),
+            Err(_) => continue,
+        };
+        let release = match git_release(&repo, name, &commit) {
+            Ok(Some(release)) => release,
+            // Older tags may predate the recipe, or hold a version of it that no longer parses
+            Ok(None) | Err(_) => continue,
+        };
+        if !releases.iter().any(|r| r.version == release.version) {
+            releases.push(release);
+        }
+    }
+    Ok(releases)
+}
+
+fn git_release(repo: &Path, name: &str, commit: &str) -> Result<Option<RecipeRelease>> {
+    for path in recipe_paths(name) {
+        let content = match git(repo, &["show", &format!("{}:{}", commit, path)]) {
+            Ok(content) => content,
+            Err(_) => continue,
+        };
+        let version = recipe_version(&String::from_utf8_lossy(&content))
+            .with_context(|| format!("Invalid recipe {} at commit {}", path, commit))?;
+        return Ok(Some(RecipeRelease {
+            version,
+            commit: Some(commit.to_string()),
+            location: ReleaseLocation::Git {
+                repo: repo.to_path_buf(),
+                path,
+            },
+        }));
+    }
+    Ok(None)
+}
+
+/// Clone `url` into the cache, or fetch its latest branches and tags if it was cloned before
+fn ensure_repo(url: &str, cache_dir: &Path) -> Result<PathBuf> {
+    let repo = cache_dir.join(&sha256_hex(url.as_bytes())[..16]);
+    if repo.join("HEAD").exists() {
+        git(
+            &repo,
+            &[
+                "fetch",
+                "--quiet",
+                "--tags",
+                "--force",
+                "--prune",
+                "origin",
+                "+refs/heads/*:refs/heads/*",
+            ],
+        )
+        .with_context(|| format!("Failed to fetch {}", url))?;
+    } else {
+        fs::create_dir_all(cache_dir)?;
+        let path = repo.to_string_lossy().to_string();
+        git(cache_dir, &["clone", "--quiet", "--bare", url, &path])
+            .with_context(|| format!("Failed to clone {}", url))?;
+    }
+    Ok(repo)
+}
+
+/// Run git in `dir`, returning its stdout
+fn git(dir: &Path, args: &[&str]) -> Result<Vec<u8>> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .stdin(Stdio::null())
+        .output()
+        .context("Failed to run git. Make sure you have `git` installed.")?;
+    if output.status.success() {
+        Ok(output.stdout)
+    } else {
+        Err(anyhow!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+fn copy_dir(source: &Path, target: &Path) -> Result<()> {
+    fs::create_dir_all(target)?;
+    for entry in fs::read_dir(source)? {
+        let entry = entry?;
+        let path = entry.path();
+        if path.is_dir() {
+            copy_dir(&path, &target.join(entry.file_name()))?;
+        } else {
+            fs::copy(&path, target.join(entry.file_name()))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_source() {
+        let dir = TempDir::new().unwrap();
+        assert!(matches!(
+            RecipeSource::parse("https://example.com/recipes/index.yaml").unwrap(),
+            RecipeSource::Index(_)
+        ));
+        assert!(matches!(
+            RecipeSource::parse("https://github.com/org/recipes").unwrap(),
+            RecipeSource::Git(_)
+        ));
+        assert!(matches!(
+            RecipeSource::parse("git@github.com:org/recipes.git").unwrap(),
+            RecipeSource::Git(_)
+        ));
+        assert!(matches!(
+            RecipeSource::parse(&dir.path().to_string_lossy()).unwrap(),
+            RecipeSource::Dir(_)
+        ));
+        assert!(RecipeSource::parse("no/such/dir").is_err());
+
+        assert!(
+            RecipeSource::parse("https://example.com/recipes/index.yaml")
+                .unwrap()
+                .has_upstream_checksum()
+        );
+        assert!(!RecipeSource::parse("https://github.com/org/recipes")
+            .unwrap()
+            .has_upstream_checksum());
+    }
+}
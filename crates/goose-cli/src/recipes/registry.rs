@@ -0,0 +1,716 @@
+use anyhow::{anyhow, Context, Result};
+use etcetera::{choose_app_strategy, AppStrategy};
+use goose::agents::ExtensionConfig;
+use goose::config::APP_STRATEGY;
+use semver::{Version, VersionReq};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::recipes::lockfile::{LockedRecipe, Lockfile};
+use crate::recipes::recipe::validate_recipe_file_parameters;
+use crate::recipes::recipe_source::{
+    fetch, read_recipe_in, release_at_commit, releases, sha256_hex, RecipeRelease, RecipeSource,
+};
+
+/// Where an installed version came from, kept next to its files
+const INSTALL_RECORD_FILE: &str = ".install.json";
+/// Clones of git sources, under the registry directory
+const SOURCE_CACHE_DIR: &str = ".sources";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InstallRecord {
+    pub source: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+    /// SHA-256 of the installed files, see [`tree_sha256`]
+    pub sha256: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct InstalledRecipe {
+    pub name: String,
+    pub version: Version,
+    pub dir: PathBuf,
+    pub record: InstallRecord,
+}
+
+impl InstalledRecipe {
+    /// Whether running the recipe would start local processes, directly or through its
+    /// sub-recipes
+    pub fn runs_local_commands(&self) -> Result<bool> {
+        let (content, _) = read_recipe_in(&self.dir)
+            .ok_or_else(|| anyhow!("No recipe file found in {}", self.dir.display()))?;
+        runs_local_commands(&self.dir, &content)
+    }
+}
+
+/// Recipes installed with `goose recipe install`, one directory per name and version
+pub struct RecipeRegistry {
+    root: PathBuf,
+}
+
+impl RecipeRegistry {
+    pub fn new() -> Self {
+        let root = choose_app_strategy(APP_STRATEGY.clone())
+            .expect("goose requires a home dir")
+            .data_dir()
+            .join("recipes");
+        Self::with_root(root)
+    }
+
+    pub fn with_root(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    fn version_dir(&self, name: &str, version: &Version) -> PathBuf {
+        self.root.join(name).join(version.to_string())
+    }
+
+    fn cache_dir(&self) -> PathBuf {
+        self.root.join(SOURCE_CACHE_DIR)
+    }
+
+    fn read_installed(&self, name: &str, dir: &Path) -> Option<InstalledRecipe> {
+        let version = Version::parse(&dir.file_name()?.to_string_lossy()).ok()?;
+        let record = fs::read_to_string(dir.join(INSTALL_RECORD_FILE)).ok()?;
+        Some(InstalledRecipe {
+            name: name.to_string(),
+            version,
+            dir: dir.to_path_buf(),
+            record: serde_json::from_str(&record).ok()?,
+        })
+    }
+
+    /// The installed versions of a recipe, newest first
+    pub fn installed(&self, name: &str) -> Result<Vec<InstalledRecipe>> {
+        let dir = self.root.join(name);
+        if !dir.is_dir() {
+            return Ok(Vec::new());
+        }
+        let mut installed: Vec<InstalledRecipe> = fs::read_dir(&dir)?
+            .filter_map(|entry| entry.ok())
+            .filter_map(|entry| self.read_installed(name, &entry.path()))
+            .collect();
+        installed.sort_by(|a, b| b.version.cmp(&a.version));
+        Ok(installed)
+    }
+
+    /// Every installed version of every recipe, by name
+    pub fn list(&self) -> Result<Vec<InstalledRecipe>> {
+        if !self.root.is_dir() {
+            return Ok(Vec::new());
+        }
+        let mut names: Vec<String> = fs::read_dir(&self.root)?
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().is_dir())
+            .map(|entry| entry.file_name().to_string_lossy().to_string())
+            .filter(|name| !name.starts_with('.'))
+            .collect();
+        names.sort();
+
+        let mut installed = Vec::new();
+        for name in names {
+            installed.extend(self.installed(&name)?);
+        }
+        Ok(installed)
+    }
+
+    /// Install a recipe and record it in the lockfile. Without a `requirement`, a recipe the
+    /// lockfile already pins is installed at exactly the locked version; otherwise the newest
+    /// version matching the requirement is installed.
+    pub async fn install(
+        &self,
+        name: &str,
+        source: Option<&str>,
+        requirement: Option<&str>,
+        lockfile: &mut Lockfile,
+    ) -> Result<InstalledRecipe> {
+        validate_name(name)?;
+        let locked = lockfile.recipes.get(name).cloned();
+        let source = match (source, &locked) {
+            (Some(source), _) => RecipeSource::parse(source)?,
+            (None, Some(locked)) => RecipeSource::parse(&locked.source)?,
+            (None, None) => {
+                return Err(anyhow!(
+                    "Recipe '{}' is not in {}, use --source to say where to install it from",
+                    name,
+                    lockfile.path().display()
+                ))
+            }
+        };
+
+        let pinned = locked.filter(|locked| {
+            requirement.is_none()
+                && RecipeSource::parse(&locked.source).ok() == Some(source.clone())
+        });
+        let installed = match &pinned {
+            Some(locked) => {
+                let release = self.locked_release(&source, name, locked).await?;
+                self.install_release(name, &source, &release, Some(&locked.sha256))
+                    .await?
+            }
+            None => {
+                let release = self
+                    .resolve(&source, name, requirement.unwrap_or("*"))
+                    .await?;
+                self.install_release(name, &source, &release, None).await?
+            }
+        };
+
+        let requirement = match (requirement, &pinned) {
+            (Some(requirement), _) => requirement.to_string(),
+            (None, Some(locked)) => locked.requirement.clone(),
+            (None, None) => "*".to_string(),
+        };
+        lock(lockfile, &installed, requirement);
+        Ok(installed)
+    }
+
+    /// Install the newest version of a locked recipe that matches its requirement and record it
+    /// in the lockfile. Returns the version locked before, and the one installed.
+    pub async fn update(
+        &self,
+        name: &str,
+        lockfile: &mut Lockfile,
+    ) -> Result<(LockedRecipe, InstalledRecipe)> {
+        let locked = lockfile.recipes.get(name).cloned().ok_or_else(|| {
+            anyhow!(
+                "Recipe '{}' is not in {}, install it with `goose recipe install` first",
+                name,
+                lockfile.path().display()
+            )
+        })?;
+        let source = RecipeSource::parse(&locked.source)?;
+        let release = self.resolve(&source, name, &locked.requirement).await?;
+        let installed = self.install_release(name, &source, &release, None).await?;
+        lock(lockfile, &installed, locked.requirement.clone());
+        Ok((locked, installed))
+    }
+
+    /// Delete every installed version of a recipe and drop it from the lockfile
+    pub fn remove(&self, name: &str, lockfile: &mut Lockfile) -> Result<()> {
+        validate_name(name)?;
+        let dir = self.root.join(name);
+        let was_installed = dir.is_dir();
+        if was_installed {
+            fs::remove_dir_all(&dir)
+                .with_context(|| format!("Failed to remove {}", dir.display()))?;
+        }
+        if lockfile.recipes.remove(name).is_none() && !was_installed {
+            return Err(anyhow!("Recipe '{}' is not installed", name));
+        }
+        Ok(())
+    }
+
+    /// Find an installed recipe to run: the version the lockfile pins, or else the newest one
+    /// installed. Returns its content and directory, like the other recipe lookups.
+    ///
+    /// Recipes that start local processes, through their own stdio extensions or their
+    /// sub-recipes', only run when the lockfile pins them and the installed files still match
+    /// the checksum it records.
+    pub fn find(&self, name: &str, lockfile: &Lockfile) -> Result<Option<(String, PathBuf)>> {
+        if validate_name(name).is_err() {
+            return Ok(None);
+        }
+        let locked = lockfile.recipes.get(name);
+        let installed = match locked {
+            Some(locked) => {
+                let version = Version::parse(&locked.version)?;
+                match self.read_installed(name, &self.version_dir(name, &version)) {
+                    Some(installed) => installed,
+                    None => {
+                        return Err(anyhow!(
+                            "Recipe '{}' is locked to version {} in {}, but that version is not installed. Run `goose recipe install {}` first.",
+                            name,
+                            locked.version,
+                            lockfile.path().display(),
+                            name
+                        ))
+                    }
+                }
+            }
+            None => match self.installed(name)?.into_iter().next() {
+                Some(installed) => installed,
+                None => return Ok(None),
+            },
+        };
+
+        let (content, _) = read_recipe_in(&installed.dir)
+            .ok_or_else(|| anyhow!("No recipe file found in {}", installed.dir.display()))?;
+        if runs_local_commands(&installed.dir, &content)? {
+            let locked = locked.ok_or_else(|| {
+                anyhow!(
+                    "Recipe '{}' {} runs stdio extensions, but {} does not pin it. Run `goose recipe install {}` to lock it first.",
+                    name,
+                    installed.version,
+                    lockfile.path().display(),
+                    name
+                )
+            })?;
+            if tree_sha256(&installed.dir)? != locked.sha256 {
+                return Err(anyhow!(
+                    "Recipe '{}' {} runs stdio extensions, but the files in {} do not match the checksum in {}. Run `goose recipe install {}` to reinstall it.",
+                    name,
+                    installed.version,
+                    installed.dir.display(),
+                    lockfile.path().display(),
+                    name
+                ));
+            }
+        }
+        Ok(Some((content, installed.dir)))
+    }
+
+    async fn resolve(
+        &self,
+        source: &RecipeSource,
+        name: &str,
+        requirement: &str,
+    ) -> Result<RecipeRelease> {
+        let requirement = VersionReq::parse(requirement)
+            .with_context(|| format!("Invalid version requirement '{}'", requirement))?;
+        let releases = releases(source, name, &self.cache_dir()).await?;
+        if releases.is_empty() {
+            return Err(anyhow!("Recipe '{}' not found in {}", name, source));
+        }
+
+        let available = releases
+            .iter()
+            .map(|release| release.version.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        releases
+            .into_iter()
+            .find(|release| requirement.matches(&release.version))
+            .ok_or_else(|| {
+                anyhow!(
+                    "No version of '{}' in {} matches {}, available versions: {}",
+                    name,
+                    source,
+                    requirement,
+                    available
+                )
+            })
+    }
+
+    async fn locked_release(
+        &self,
+        source: &RecipeSource,
+        name: &str,
+        locked: &LockedRecipe,
+    ) -> Result<RecipeRelease> {
+        let version = Version::parse(&locked.version)?;
+        let release = match &locked.commit {
+            Some(commit) => release_at_commit(source, name, commit, &self.cache_dir())?,
+            None => releases(source, name, &self.cache_dir())
+                .await?
+                .into_iter()
+                .find(|release| release.version == version)
+                .ok_or_else(|| {
+                    anyhow!(
+                        "Locked version {} of '{}' is no longer available from {}",
+                        version,
+                        name,
+                        source
+                    )
+                })?,
+        };
+        if release.version != version {
+            return Err(anyhow!(
+                "Recipe '{}' is locked to version {}, but the locked commit has version {}",
+                name,
+                version,
+                release.version
+            ));
+        }
+        Ok(release)
+    }
+
+    /// Fetch a release next to its final directory, then move it into place once it is
+    /// complete and matches `expected_sha256`, if given
+    async fn install_release(
+        &self,
+        name: &str,
+        source: &RecipeSource,
+        release: &RecipeRelease,
+        expected_sha256: Option<&str>,
+    ) -> Result<InstalledRecipe> {
+        let dir = self.version_dir(name, &release.version);
+        let staging = self.root.join(name).join(format!(".{}", release.version));
+        if staging.exists() {
+            fs::remove_dir_all(&staging)?;
+        }
+
+        let result = async {
+            fetch(release, &staging).await?;
+            let sha256 = tree_sha256(&staging)?;
+            if let Some(expected) = expected_sha256 {
+                if sha256 != expected {
+                    return Err(anyhow!(
+                        "Recipe '{}' {} has checksum {}, but the lockfile records {}",
+                        name,
+                        release.version,
+                        sha256,
+                        expected
+                    ));
+                }
+            }
+            let record = InstallRecord {
+                source: source.to_string(),
+                commit: release.commit.clone(),
+                sha256,
+            };
+            fs::write(
+                staging.join(INSTALL_RECORD_FILE),
+                serde_json::to_string_pretty(&record)?,
+            )?;
+            if dir.exists() {
+                fs::remove_dir_all(&dir)?;
+            }
+            fs::rename(&staging, &dir)?;
+            Ok(record)
+        }
+        .await;
+
+        match result {
+            Ok(record) => Ok(InstalledRecipe {
+                name: name.to_string(),
+                version: release.version.clone(),
+                dir,
+                record,
+            }),
+            Err(e) => {
+                let _ = fs::remove_dir_all(&staging);
+                Err(e)
+            }
+        }
+    }
+}
+
+impl Default for RecipeRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn lock(lockfile: &mut Lockfile, installed: &InstalledRecipe, requirement: String) {
+    lockfile.recipes.insert(
+        installed.name.clone(),
+        LockedRecipe {
+            version: installed.version.to_string(),
+            requirement,
+            source: installed.record.source.clone(),
+            commit: installed.record.commit.clone(),
+            sha256: installed.record.sha256.clone(),
+        },
+    );
+}
+
+/// Recipe names become directory names, so they are limited to a safe set of characters
+fn validate_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if valid {
+        Ok(())
+    } else {
+        Err(anyhow!(
+            "Invalid recipe name '{}', use letters, digits, '-', '_' and '.'",
+            name
+        ))
+    }
+}
+
+/// SHA-256 of an installed recipe's files: a manifest of each file's checksum and path, sorted
+/// by path, so a change to the recipe or to any file next to it is caught. The install record
+/// is left out, since it holds the checksum.
+pub fn tree_sha256(dir: &Path) -> Result<String> {
+    let mut files = Vec::new();
+    collect_file_hashes(dir, dir, &mut files)?;
+    files.sort();
+    let manifest: String = files
+        .iter()
+        .map(|(path, sha256)| format!("{}  {}\n", sha256, path))
+        .collect();
+    Ok(sha256_hex(manifest.as_bytes()))
+}
+
+fn collect_file_hashes(root: &Path, dir: &Path, files: &mut Vec<(String, String)>) -> Result<()> {
+    for entry in fs::read_dir(dir).with_context(|| format!("Failed to read {}", dir.display()))? {
+        let entry = entry?;
+        let path = entry.path();
+        let relative = path
+            .strip_prefix(root)?
+            .to_string_lossy()
+            .replace('\\', "/");
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            collect_file_hashes(root, &path, files)?;
+        } else if file_type.is_symlink() {
+            let target = fs::read_link(&path)?;
+            let link = format!("symlink:{}", target.to_string_lossy());
+            files.push((relative, sha256_hex(link.as_bytes())));
+        } else if relative != INSTALL_RECORD_FILE {
+            files.push((relative, sha256_hex(&fs::read(&path)?)));
+        }
+    }
+    Ok(())
+}
+
+/// Whether running the recipe installed in `dir` would start local processes, through its own
+/// stdio extensions or those of its sub-recipes. Recipes that fail to parse or can't be read
+/// are treated as if they would, so they are verified too. Sub-recipes outside `dir` are
+/// refused, since the checksum can't cover them.
+fn runs_local_commands(dir: &Path, content: &str) -> Result<bool> {
+    let root = dir
+        .canonicalize()
+        .with_context(|| format!("Failed to resolve {}", dir.display()))?;
+    recipe_runs_local_commands(&root, &root, content, &mut HashSet::new())
+}
+
+fn recipe_runs_local_commands(
+    root: &Path,
+    recipe_dir: &Path,
+    content: &str,
+    visited: &mut HashSet<PathBuf>,
+) -> Result<bool> {
+    let recipe = match validate_recipe_file_parameters(content) {
+        Ok(recipe) => recipe,
+        Err(_) => return Ok(true),
+    };
+    if recipe
+        .extensions
+        .unwrap_or_default()
+        .iter()
+        .any(|extension| matches!(extension, ExtensionConfig::Stdio { .. }))
+    {
+        return Ok(true);
+    }
+
+    for sub_recipe in recipe.sub_recipes.unwrap_or_default() {
+        let path = match recipe_dir.join(&sub_recipe.path).canonicalize() {
+            Ok(path) => path,
+            Err(_) => return Ok(true),
+        };
+        if !path.starts_with(root) {
+            return Err(anyhow!(
+                "Sub-recipe '{}' at {} is outside the installed recipe in {}",
+                sub_recipe.name,
+                sub_recipe.path,
+                root.display()
+            ));
+        }
+        if !visited.insert(path.clone()) {
+            continue;
+        }
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(_) => return Ok(true),
+        };
+        let sub_recipe_dir = path.parent().unwrap_or(root);
+        if recipe_runs_local_commands(root, sub_recipe_dir, &content, visited)? {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recipes::lockfile::LOCKFILE_NAME;
+    use std::process::Command;
+    use tempfile::TempDir;
+
+    fn recipe(version: &str) -> String {
+        format!(
+            r#"version: {}
+title: Review
+description: Review a change
+instructions: Review the change
+extensions:
+- type: stdio
+  name: linter
+  cmd: lint
+  args: []
+"#,
+            version
+        )
+    }
+
+    fn setup() -> (TempDir, RecipeRegistry, Lockfile) {
+        let dir = TempDir::new().unwrap();
+        let registry = RecipeRegistry::with_root(dir.path().join("registry"));
+        let lockfile = Lockfile::load(&dir.path().join(LOCKFILE_NAME)).unwrap();
+        (dir, registry, lockfile)
+    }
+
+    #[tokio::test]
+    async fn test_install_find_and_remove() {
+        let (dir, registry, mut lockfile) = setup();
+        let source = dir.path().join("source");
+        fs::create_dir_all(source.join("review")).unwrap();
+        fs::write(source.join("review/recipe.yaml"), recipe("1.0.0")).unwrap();
+        fs::write(source.join("review/notes.md"), "notes").unwrap();
+        let source = source.to_string_lossy().to_string();
+
+        assert!(registry.find("review", &lockfile).unwrap().is_none());
+        assert!(registry
+            .install("review", Some(&source), Some("^2"), &mut lockfile)
+            .await
+            .is_err());
+
+        let installed = registry
+            .install("review", Some(&source), None, &mut lockfile)
+            .await
+            .unwrap();
+        assert_eq!(installed.version, Version::new(1, 0, 0));
+        assert!(installed.dir.join("notes.md").exists());
+        let locked = &lockfile.recipes["review"];
+        assert_eq!(locked.version, "1.0.0");
+        assert_eq!(locked.requirement, "*");
+        assert_eq!(locked.sha256, tree_sha256(&installed.dir).unwrap());
+
+        let (content, recipe_dir) = registry.find("review", &lockfile).unwrap().unwrap();
+        assert_eq!(content, recipe("1.0.0"));
+        assert_eq!(recipe_dir, installed.dir);
+
+        // The recipe runs a stdio extension, so it only runs when locked
+        let unlocked = Lockfile::load(&dir.path().join("other.lock")).unwrap();
+        let err = registry.find("review", &unlocked).unwrap_err();
+        assert!(err.to_string().contains("does not pin it"));
+
+        // and any change to its files is refused
+        fs::write(installed.dir.join("notes.md"), "changed").unwrap();
+        assert!(registry.find("review", &lockfile).is_err());
+        fs::write(installed.dir.join("notes.md"), "notes").unwrap();
+        fs::write(
+            installed.dir.join("recipe.yaml"),
+            recipe("1.0.0").replace("lint", "curl"),
+        )
+        .unwrap();
+        assert!(registry.find("review", &lockfile).is_err());
+
+        registry.remove("review", &mut lockfile).unwrap();
+        assert!(lockfile.recipes.is_empty());
+        assert!(registry.list().unwrap().is_empty());
+        assert!(registry.remove("review", &mut lockfile).is_err());
+    }
+
+    #[test]
+    fn test_find_requires_locked_version() {
+        let (_dir, registry, mut lockfile) = setup();
+        lockfile.recipes.insert(
+            "review".to_string(),
+            LockedRecipe {
+                version: "1.0.0".to_string(),
+                requirement: "*".to_string(),
+                source: "https://example.com/recipes.git".to_string(),
+                commit: None,
+                sha256: sha256_hex(recipe("1.0.0").as_bytes()),
+            },
+        );
+        let err = registry.find("review", &lockfile).unwrap_err();
+        assert!(err.to_string().contains("not installed"));
+
+        assert!(registry.find("../review", &lockfile).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_runs_local_commands_through_sub_recipes() {
+        let dir = TempDir::new().unwrap();
+        let parent = r#"version: 1.0.0
+title: Fix
+description: Fix an issue
+instructions: Fix the issue
+sub_recipes:
+- name: check
+  path: check/recipe.yaml
+"#;
+        let check = r#"version: 1.0.0
+title: Check
+description: Check the fix
+instructions: Check the fix
+"#;
+        fs::create_dir_all(dir.path().join("check")).unwrap();
+        fs::write(dir.path().join("check/recipe.yaml"), check).unwrap();
+        assert!(!runs_local_commands(dir.path(), parent).unwrap());
+
+        // A stdio extension in a sub-recipe counts too
+        fs::write(dir.path().join("check/recipe.yaml"), recipe("1.0.0")).unwrap();
+        assert!(runs_local_commands(dir.path(), parent).unwrap());
+
+        // Sub-recipes outside the installed directory aren't covered by its checksum
+        let outside = parent.replace("check/recipe.yaml", "../outside.yaml");
+        let nested = dir.path().join("nested");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(dir.path().join("outside.yaml"), check).unwrap();
+        let err = runs_local_commands(&nested, &outside).unwrap_err();
+        assert!(err.to_string().contains("outside the installed recipe"));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_install_and_update_from_git() {
+        let (dir, registry, mut lockfile) = setup();
+        let repo = dir.path().join("repo");
+        fs::create_dir_all(repo.join("review")).unwrap();
+        let git = |args: &[&str]| {
+            let status = Command::new("git")
+                .args([
+                    "-c",
+                    "user.name=goose",
+                    "-c",
+                    "user.email=goose@example.com",
+                ])
+                .args(args)
+                .current_dir(&repo)
+                .output()
+                .unwrap()
+                .status;
+            assert!(status.success(), "git {:?} failed", args);
+        };
+        git(&["init", "--quiet"]);
+        for (version, tag) in [
+            ("1.0.0", Some("v1.0.0")),
+            ("1.1.0", Some("v1.1.0")),
+            ("2.0.0", None),
+        ] {
+            fs::write(repo.join("review/recipe.yaml"), recipe(version)).unwrap();
+            git(&["add", "."]);
+            git(&["commit", "--quiet", "-m", version]);
+            if let Some(tag) = tag {
+                git(&["tag", tag]);
+            }
+        }
+        let source = format!("file://{}", repo.display());
+
+        let installed = registry
+            .install("review", Some(&source), Some("^1"), &mut lockfile)
+            .await
+            .unwrap();
+        assert_eq!(installed.version, Version::new(1, 1, 0));
+        let locked = lockfile.recipes["review"].clone();
+        assert!(locked.commit.is_some());
+
+        // Reinstalling from the lockfile gives the same commit and content
+        registry.remove("review", &mut lockfile.clone()).unwrap();
+        let reinstalled = registry
+            .install("review", None, None, &mut lockfile)
+            .await
+            .unwrap();
+        assert_eq!(reinstalled.record.commit, locked.commit);
+        assert_eq!(lockfile.recipes["review"], locked);
+
+        lockfile.recipes.get_mut("review").unwrap().requirement = "*".to_string();
+        let (previous, updated) = registry.update("review", &mut lockfile).await.unwrap();
+        assert_eq!(previous.version, "1.1.0");
+        assert_eq!(updated.version, Version::new(2, 0, 0));
+        assert_eq!(lockfile.recipes["review"].version, "2.0.0");
+        assert_eq!(registry.installed("review").unwrap().len(), 2);
+    }
+}
@@ -3,7 +3,9 @@ use goose::config::Config;
 use std::path::{Path, PathBuf};
 use std::{env, fs};
 
+use crate::recipes::lockfile::Lockfile;
 use crate::recipes::recipe::RECIPE_FILE_EXTENSIONS;
+use crate::recipes::registry::RecipeRegistry;
 
 use super::github_recipe::{retrieve_recipe_from_github, GOOSE_RECIPE_GITHUB_REPO_CONFIG_KEY};
 
@@ -19,6 +21,15 @@ pub fn retrieve_recipe_file(recipe_name: &str) -> Result<(String, PathBuf)> {
         return read_recipe_file(path);
     }
     retrieve_recipe_from_local_path(recipe_name).or_else(|e| {
+        // Installed recipes, at the version the project's lockfile pins if it has one
+        match Lockfile::find() {
+            Ok(lockfile) => {
+                if let Some(result) = RecipeRegistry::new().find(recipe_name, &lockfile)? {
+                    return Ok(result);
+                }
+            }
+            Err(err) => tracing::warn!("Skipping installed recipes: {:#}", err),
+        }
         if let Some(recipe_repo_full_name) = configured_github_recipe_repo() {
             retrieve_recipe_from_github(recipe_name, &recipe_repo_full_name)
         } else {
@@ -3,6 +3,7 @@ use goose::agents::extension::ExtensionError;
 use goose::agents::Agent;
 use goose::config::{Config, ExtensionConfig, ExtensionConfigManager};
 use goose::providers::create;
+use goose::recipe::RetryConfig;
 use goose::session;
 use goose::session::Identifier;
 use mcp_client::transport::Error as McpClientError;
@@ -38,24 +39,50 @@ pub struct SessionBuilderConfig {
     pub debug: bool,
     /// Maximum number of consecutive identical tool calls allowed
     pub max_tool_repetitions: Option<u32>,
+    /// Run the developer extension's shell commands in a sandbox
+    pub sandbox: bool,
+    /// Success checks and retry policy for headless runs
+    pub retry_config: Option<RetryConfig>,
+    /// Report failures as a JSON object on stdout, for `--output-format json`
+    pub json_output: bool,
+}
+
+/// Report a failure to start the session and exit
+fn exit_with_error(message: &str, json_output: bool) -> ! {
+    if json_output {
+        println!("{}", serde_json::json!({ "error": message }));
+    } else {
+        output::render_error(message);
+    }
+    process::exit(1);
 }
 
 pub async fn build_session(session_config: SessionBuilderConfig) -> Session {
+    let json_output = session_config.json_output;
+
     // Load config and get provider/model
     let config = Config::global();
 
-    let provider_name: String = config
-        .get_param("GOOSE_PROVIDER")
-        .expect("No provider configured. Run 'goose configure' first");
+    let provider_name: String = config.get_param("GOOSE_PROVIDER").unwrap_or_else(|_| {
+        exit_with_error(
+            "No provider configured. Run 'goose configure' first",
+            json_output,
+        )
+    });
 
-    let model: String = config
-        .get_param("GOOSE_MODEL")
-        .expect("No model configured. Run 'goose configure' first");
+    let model: String = config.get_param("GOOSE_MODEL").unwrap_or_else(|_| {
+        exit_with_error(
+            "No model configured. Run 'goose configure' first",
+            json_output,
+        )
+    });
     let model_config = goose::model::ModelConfig::new(model.clone());
 
     // Create the agent
     let agent: Agent = Agent::new();
-    let new_provider = create(&provider_name, model_config).unwrap();
+    let new_provider = create(&provider_name, model_config).unwrap_or_else(|e| {
+        exit_with_error(&format!("Failed to create provider: {}", e), json_output)
+    });
 
     // Keep a reference to the provider for display_session_info
     let provider_for_display = Arc::clone(&new_provider);
@@ -76,8 +103,7 @@ pub async fn build_session(session_config: SessionBuilderConfig) -> Session {
         .update_provider(new_provider)
         .await
         .unwrap_or_else(|e| {
-            output::render_error(&format!("Failed to initialize agent: {}", e));
-            process::exit(1);
+            exit_with_error(&format!("Failed to initialize agent: {}", e), json_output)
         });
 
     // Configure tool monitoring if max_tool_repetitions is set
@@ -85,6 +111,12 @@ pub async fn build_session(session_config: SessionBuilderConfig) -> Session {
         agent.configure_tool_monitor(Some(max_repetitions)).await;
     }
 
+    // Builtin extensions run as child processes and read the sandbox settings from the
+    // environment, so this has to be set before any of them start
+    if session_config.sandbox {
+        std::env::set_var("GOOSE_SANDBOX", "true");
+    }
+
     // Handle session file resolution and resuming
     let session_file = if session_config.no_session {
         // Use a temporary path that won't be written to
@@ -99,12 +131,14 @@ pub async fn build_session(session_config: SessionBuilderConfig) -> Session {
     } else if session_config.resume {
         if let Some(identifier) = session_config.identifier {
             let session_file = session::get_path(identifier);
-            if !session_file.exists() {
-                output::render_error(&format!(
-                    "Cannot resume session {} - no such session exists",
-                    style(session_file.display()).cyan()
-                ));
-                process::exit(1);
+            if !session::session_exists(&session_file) {
+                exit_with_error(
+                    &format!(
+                        "Cannot resume session {} - no such session exists",
+                        style(session_file.display()).cyan()
+                    ),
+                    json_output,
+                );
             }
 
             session_file
@@ -113,8 +147,7 @@ pub async fn build_session(session_config: SessionBuilderConfig) -> Session {
             match session::get_most_recent_session() {
                 Ok(file) => file,
                 Err(_) => {
-                    output::render_error("Cannot resume - no previous sessions found");
-                    process::exit(1);
+                    exit_with_error("Cannot resume - no previous sessions found", json_output)
                 }
             }
         }
@@ -132,8 +165,10 @@ pub async fn build_session(session_config: SessionBuilderConfig) -> Session {
     if session_config.resume && !session_config.no_session {
         // Read the session metadata
         let metadata = session::read_metadata(&session_file).unwrap_or_else(|e| {
-            output::render_error(&format!("Failed to read session metadata: {}", e));
-            process::exit(1);
+            exit_with_error(
+                &format!("Failed to read session metadata: {}", e),
+                json_output,
+            )
         });
 
         let current_workdir =
@@ -167,7 +202,9 @@ pub async fn build_session(session_config: SessionBuilderConfig) -> Session {
         extensions.into_iter().collect()
     } else {
         ExtensionConfigManager::get_all()
-            .expect("should load extensions")
+            .unwrap_or_else(|e| {
+                exit_with_error(&format!("Failed to load extensions: {}", e), json_output)
+            })
             .into_iter()
             .filter(|ext| ext.enabled)
             .map(|ext| ext.config)
@@ -180,39 +217,43 @@ pub async fn build_session(session_config: SessionBuilderConfig) -> Session {
                 ExtensionError::Transport(McpClientError::StdioProcessError(inner)) => inner,
                 _ => e.to_string(),
             };
-            eprintln!("Failed to start extension: {}, {:?}", extension.name(), err);
-            eprintln!(
-                "Please check extension configuration for {}.",
-                extension.name()
+            exit_with_error(
+                &format!(
+                    "Failed to start extension: {}, {:?}\nPlease check extension configuration for {}.",
+                    extension.name(),
+                    err,
+                    extension.name()
+                ),
+                json_output,
             );
-            process::exit(1);
         }
     }
 
     // Create new session
     let mut session = Session::new(agent, session_file.clone(), session_config.debug);
+    session.retry_config = session_config.retry_config;
 
     // Add extensions if provided
     for extension_str in session_config.extensions {
         if let Err(e) = session.add_extension(extension_str).await {
-            eprintln!("Failed to start extension: {}", e);
-            process::exit(1);
+            exit_with_error(&format!("Failed to start extension: {}", e), json_output);
         }
     }
 
     // Add remote extensions if provided
     for extension_str in session_config.remote_extensions {
         if let Err(e) = session.add_remote_extension(extension_str).await {
-            eprintln!("Failed to start extension: {}", e);
-            process::exit(1);
+            exit_with_error(&format!("Failed to start extension: {}", e), json_output);
         }
     }
 
     // Add builtin extensions
     for builtin in session_config.builtins {
         if let Err(e) = session.add_builtin(builtin).await {
-            eprintln!("Failed to start builtin extension: {}", e);
-            process::exit(1);
+            exit_with_error(
+                &format!("Failed to start builtin extension: {}", e),
+                json_output,
+            );
         }
     }
 
@@ -229,8 +270,12 @@ pub async fn build_session(session_config: SessionBuilderConfig) -> Session {
     // Only override system prompt if a system override exists
     let system_prompt_file: Option<String> = config.get_param("GOOSE_SYSTEM_PROMPT_FILE_PATH").ok();
     if let Some(ref path) = system_prompt_file {
-        let override_prompt =
-            std::fs::read_to_string(path).expect("Failed to read system prompt file");
+        let override_prompt = std::fs::read_to_string(path).unwrap_or_else(|e| {
+            exit_with_error(
+                &format!("Failed to read system prompt file {}: {}", path, e),
+                json_output,
+            )
+        });
         session.agent.override_system_prompt(override_prompt).await;
     }
 
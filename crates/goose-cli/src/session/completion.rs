@@ -130,6 +130,7 @@ impl GooseCompleter {
             "/prompt",
             "/mode",
             "/recipe",
+            "/rewind",
         ];
 
         // Find commands that match the prefix
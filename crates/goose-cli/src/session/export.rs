@@ -1,8 +1,14 @@
+use anyhow::Result;
 use goose::message::{Message, MessageContent, ToolRequest, ToolResponse};
+use goose::providers::formats::{anthropic, openai};
+use goose::providers::utils::ImageFormat;
+use goose::session::fork::truncate_history;
+use goose::session::SessionMetadata;
 use mcp_core::content::Content as McpContent;
 use mcp_core::resource::ResourceContents;
 use mcp_core::role::Role;
-use serde_json::Value;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
 
 const MAX_STRING_LENGTH_MD_EXPORT: usize = 4096; // Generous limit for export
 const REDACTED_PREFIX_LENGTH: usize = 100; // Show first 100 chars before trimming
@@ -345,6 +351,9 @@ pub fn message_to_markdown(message: &Message, export_all_content: bool) -> Strin
                 md.push_str("**Thinking:**\n");
                 md.push_str("> *Thinking was redacted*\n\n");
             }
+            MessageContent::BudgetExceeded(exceeded) => {
+                md.push_str(&format!("**Budget exceeded:** {}\n\n", exceeded.msg));
+            }
             _ => {
                 md.push_str(
                     "`WARNING: Message content type could not be rendered to Markdown`\n\n",
@@ -355,6 +364,249 @@ pub fn message_to_markdown(message: &Message, export_all_content: bool) -> Strin
     md.trim_end_matches("\n").to_string()
 }
 
+/// Layout version written into JSON transcripts, checked on import
+pub const TRANSCRIPT_VERSION: u32 = 1;
+
+/// A whole session in one JSON document, as written by `goose session export --format json`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SessionTranscript {
+    pub version: u32,
+    pub id: String,
+    pub metadata: SessionMetadata,
+    pub messages: Vec<Message>,
+}
+
+pub fn session_to_json(
+    id: &str,
+    metadata: SessionMetadata,
+    messages: Vec<Message>,
+) -> Result<String> {
+    let transcript = SessionTranscript {
+        version: TRANSCRIPT_VERSION,
+        id: id.to_string(),
+        metadata,
+        messages,
+    };
+    Ok(serde_json::to_string_pretty(&transcript)?)
+}
+
+pub fn transcript_from_json(json: &str) -> Result<SessionTranscript> {
+    let transcript: SessionTranscript = serde_json::from_str(json)?;
+    if transcript.version > TRANSCRIPT_VERSION {
+        return Err(anyhow::anyhow!(
+            "Transcript version {} is newer than the supported version {}",
+            transcript.version,
+            TRANSCRIPT_VERSION
+        ));
+    }
+    Ok(transcript)
+}
+
+/// Chat fine-tuning schemas a session can be exported to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FineTuneFormat {
+    OpenAi,
+    Anthropic,
+}
+
+/// Render a session as one JSONL line of fine-tuning data, with tool calls in the provider's
+/// schema. Tool calls that never got a response are dropped since the APIs reject them.
+pub fn session_to_finetune_jsonl(messages: &[Message], format: FineTuneFormat) -> Result<String> {
+    let history = truncate_history(messages, messages.len());
+    if history.is_empty() {
+        return Err(anyhow::anyhow!("The session has no messages to export"));
+    }
+
+    let formatted = match format {
+        FineTuneFormat::OpenAi => openai::format_messages(&history, &ImageFormat::OpenAi),
+        FineTuneFormat::Anthropic => {
+            let mut formatted = anthropic::format_messages(&history);
+            // Prompt caching breakpoints are request options, not part of the conversation
+            for block in formatted
+                .iter_mut()
+                .filter_map(|m| m.get_mut("content").and_then(|c| c.as_array_mut()))
+                .flatten()
+            {
+                if let Some(block) = block.as_object_mut() {
+                    block.remove("cache_control");
+                }
+            }
+            formatted
+        }
+    };
+
+    Ok(serde_json::to_string(&json!({ "messages": formatted }))?)
+}
+
+const HTML_STYLE: &str = "body{font-family:-apple-system,BlinkMacSystemFont,'Segoe UI',sans-serif;max-width:56rem;margin:2rem auto;padding:0 1rem;color:#1f2328;line-height:1.5}\
+.message{border:1px solid #d0d7de;border-radius:8px;margin:1rem 0;padding:.75rem 1rem}\
+.user{background:#f6f8fa}\
+.role{font-weight:600;margin-bottom:.5rem}\
+.text{white-space:pre-wrap}\
+pre{background:#eff1f3;border-radius:6px;padding:.5rem;overflow-x:auto;white-space:pre-wrap}\
+details{margin:.5rem 0}\
+summary{cursor:pointer;font-family:monospace}\
+.error{color:#cf222e}\
+.note{color:#59636e;font-style:italic}\
+img{max-width:100%}";
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn image_to_html(data: &str, mime_type: &str) -> String {
+    format!(
+        "<img src=\"data:{};base64,{}\" alt=\"image\">\n",
+        escape_html(mime_type),
+        escape_html(data)
+    )
+}
+
+fn tool_request_to_html(req: &ToolRequest) -> String {
+    match &req.tool_call {
+        Ok(call) => format!(
+            "<details class=\"tool-call\"><summary>Tool call: {}</summary><pre>{}</pre></details>\n",
+            escape_html(&call.name),
+            escape_html(&serde_json::to_string_pretty(&call.arguments).unwrap_or_default())
+        ),
+        Err(e) => format!(
+            "<details class=\"tool-call\"><summary class=\"error\">Invalid tool call</summary><pre>{}</pre></details>\n",
+            escape_html(&e.to_string())
+        ),
+    }
+}
+
+fn tool_response_to_html(resp: &ToolResponse) -> String {
+    let contents = match &resp.tool_result {
+        Ok(contents) => contents,
+        Err(e) => {
+            return format!(
+                "<details class=\"tool-result\"><summary class=\"error\">Tool error</summary><pre>{}</pre></details>\n",
+                escape_html(&e.to_string())
+            );
+        }
+    };
+
+    let mut html = String::from("<details class=\"tool-result\"><summary>Tool result</summary>\n");
+    if contents.is_empty() {
+        html.push_str("<p class=\"note\">No output</p>\n");
+    }
+    for content in contents {
+        match content {
+            McpContent::Text(text) => {
+                html.push_str(&format!("<pre>{}</pre>\n", escape_html(&text.text)));
+            }
+            McpContent::Image(image) if image.mime_type.starts_with("image/") => {
+                html.push_str(&image_to_html(&image.data, &image.mime_type));
+            }
+            McpContent::Image(image) => {
+                html.push_str(&format!(
+                    "<p class=\"note\">Binary content ({}, {} bytes)</p>\n",
+                    escape_html(&image.mime_type),
+                    image.data.len()
+                ));
+            }
+            McpContent::Resource(resource) => match &resource.resource {
+                ResourceContents::TextResourceContents { uri, text, .. } => {
+                    html.push_str(&format!(
+                        "<p>File: <code>{}</code></p><pre>{}</pre>\n",
+                        escape_html(uri),
+                        escape_html(text)
+                    ));
+                }
+                ResourceContents::BlobResourceContents { uri, blob, .. } => {
+                    html.push_str(&format!(
+                        "<p class=\"note\">Binary file <code>{}</code> ({} bytes)</p>\n",
+                        escape_html(uri),
+                        blob.len()
+                    ));
+                }
+            },
+        }
+    }
+    html.push_str("</details>\n");
+    html
+}
+
+fn message_to_html(message: &Message) -> String {
+    let mut html = String::new();
+    for content in &message.content {
+        match content {
+            MessageContent::Text(text) => {
+                html.push_str(&format!(
+                    "<div class=\"text\">{}</div>\n",
+                    escape_html(&text.text)
+                ));
+            }
+            MessageContent::Image(image) => {
+                html.push_str(&image_to_html(&image.data, &image.mime_type));
+            }
+            MessageContent::ToolRequest(req) => html.push_str(&tool_request_to_html(req)),
+            MessageContent::ToolResponse(resp) => html.push_str(&tool_response_to_html(resp)),
+            MessageContent::Thinking(thinking) => {
+                html.push_str(&format!(
+                    "<details class=\"thinking\"><summary>Thinking</summary><div class=\"text\">{}</div></details>\n",
+                    escape_html(&thinking.thinking)
+                ));
+            }
+            MessageContent::RedactedThinking(_) => {
+                html.push_str("<p class=\"note\">Thinking was redacted</p>\n");
+            }
+            MessageContent::BudgetExceeded(exceeded) => {
+                html.push_str(&format!(
+                    "<p class=\"note\">Budget exceeded: {}</p>\n",
+                    escape_html(&exceeded.msg)
+                ));
+            }
+            _ => {}
+        }
+    }
+    html
+}
+
+/// Render a session as a self-contained HTML page, with tool calls collapsed and images inlined
+pub fn session_to_html(session_name: &str, messages: &[Message]) -> String {
+    let title = escape_html(session_name);
+    let mut html = format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>Session Export: {}</title>\n<style>{}</style>\n</head>\n<body>\n<h1>Session Export: {}</h1>\n",
+        title, HTML_STYLE, title
+    );
+
+    if messages.is_empty() {
+        html.push_str("<p class=\"note\">This session has no messages</p>\n");
+    }
+
+    for message in messages {
+        let body = message_to_html(message);
+        if body.is_empty() {
+            continue;
+        }
+        let (class, role) = match message.role {
+            Role::User if message.is_tool_response() => ("user", "Tool output"),
+            Role::User => ("user", "User"),
+            Role::Assistant => ("assistant", "Assistant"),
+        };
+        html.push_str(&format!(
+            "<div class=\"message {}\">\n<div class=\"role\">{}</div>\n{}</div>\n",
+            class, role, body
+        ));
+    }
+
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1092,4 +1344,78 @@ found 0 vulnerabilities"#;
         assert!(response_result.contains("added 57 packages"));
         assert!(response_result.contains("found 0 vulnerabilities"));
     }
+
+    fn tool_session() -> Vec<Message> {
+        vec![
+            Message::user().with_text("List the <src> files"),
+            Message::assistant().with_text("Sure").with_tool_request(
+                "call_1",
+                Ok(ToolCall::new(
+                    "developer__shell",
+                    json!({"command": "ls src"}),
+                )),
+            ),
+            Message::user().with_tool_response("call_1", Ok(vec![McpContent::text("main.rs")])),
+            Message::assistant()
+                .with_text("There is one file")
+                .with_tool_request("call_2", Ok(ToolCall::new("developer__shell", json!({})))),
+        ]
+    }
+
+    #[test]
+    fn test_session_to_html() {
+        let mut messages = tool_session();
+        messages.push(Message::user().with_image("aGVsbG8=", "image/png"));
+        let html = session_to_html("demo & test", &messages);
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<title>Session Export: demo &amp; test</title>"));
+        assert!(html.contains("List the &lt;src&gt; files"));
+        assert!(html.contains("<summary>Tool call: developer__shell</summary>"));
+        assert!(html.contains("<summary>Tool result</summary>\n<pre>main.rs</pre>"));
+        assert!(html.contains("<img src=\"data:image/png;base64,aGVsbG8=\""));
+        assert!(!html.contains("<src>"));
+    }
+
+    #[test]
+    fn test_session_json_round_trip() -> Result<()> {
+        let metadata = SessionMetadata {
+            description: "Listing files".to_string(),
+            ..SessionMetadata::default()
+        };
+        let json = session_to_json("20250101_120000", metadata, tool_session())?;
+        let transcript = transcript_from_json(&json)?;
+
+        assert_eq!(transcript.version, TRANSCRIPT_VERSION);
+        assert_eq!(transcript.id, "20250101_120000");
+        assert_eq!(transcript.metadata.description, "Listing files");
+        assert_eq!(transcript.messages, tool_session());
+
+        let future = json.replacen("\"version\": 1", "\"version\": 99", 1);
+        assert!(transcript_from_json(&future).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_session_to_finetune_jsonl() -> Result<()> {
+        let line = session_to_finetune_jsonl(&tool_session(), FineTuneFormat::OpenAi)?;
+        assert!(!line.contains('\n'));
+        let messages = serde_json::from_str::<Value>(&line)?["messages"].clone();
+        assert_eq!(messages[1]["tool_calls"][0]["id"], "call_1");
+        assert_eq!(messages[2]["role"], "tool");
+        assert_eq!(messages[2]["tool_call_id"], "call_1");
+        // The unanswered call_2 is dropped but its text is kept
+        assert_eq!(messages[3]["content"], "There is one file");
+        assert!(messages[3].get("tool_calls").is_none());
+
+        let line = session_to_finetune_jsonl(&tool_session(), FineTuneFormat::Anthropic)?;
+        assert!(!line.contains("cache_control"));
+        let messages = serde_json::from_str::<Value>(&line)?["messages"].clone();
+        assert_eq!(messages[1]["content"][1]["type"], "tool_use");
+        assert_eq!(messages[2]["content"][0]["type"], "tool_result");
+        assert_eq!(messages[2]["content"][0]["tool_use_id"], "call_1");
+
+        assert!(session_to_finetune_jsonl(&[], FineTuneFormat::OpenAi).is_err());
+        Ok(())
+    }
 }
@@ -19,6 +19,7 @@ pub enum InputResult {
     EndPlan,
     Recipe(Option<String>),
     Summarize,
+    Rewind(Option<usize>),
 }
 
 #[derive(Debug)]
@@ -93,6 +94,7 @@ fn handle_slash_command(input: &str) -> Option<InputResult> {
     const CMD_ENDPLAN: &str = "/endplan";
     const CMD_RECIPE: &str = "/recipe";
     const CMD_SUMMARIZE: &str = "/summarize";
+    const CMD_REWIND: &str = "/rewind";
 
     match input {
         "/exit" | "/quit" => Some(InputResult::Exit),
@@ -136,6 +138,8 @@ fn handle_slash_command(input: &str) -> Option<InputResult> {
         s if s == CMD_ENDPLAN => Some(InputResult::EndPlan),
         s if s.starts_with(CMD_RECIPE) => parse_recipe_command(s),
         s if s == CMD_SUMMARIZE => Some(InputResult::Summarize),
+        s if s == CMD_REWIND => Some(InputResult::Rewind(None)),
+        s if s.starts_with("/rewind ") => parse_rewind_command(s[CMD_REWIND.len()..].trim()),
         _ => None,
     }
 }
@@ -165,6 +169,19 @@ fn parse_recipe_command(s: &str) -> Option<InputResult> {
     Some(InputResult::Recipe(Some(filepath.to_string())))
 }
 
+fn parse_rewind_command(args: &str) -> Option<InputResult> {
+    match args.parse::<usize>() {
+        Ok(count) => Some(InputResult::Rewind(Some(count))),
+        Err(_) => {
+            println!(
+                "{}",
+                console::style("Usage: /rewind <number of messages to keep>").red()
+            );
+            Some(InputResult::Retry)
+        }
+    }
+}
+
 fn parse_prompts_command(args: &str) -> Option<InputResult> {
     let parts: Vec<String> = shlex::split(args).unwrap_or_default();
 
@@ -245,6 +262,8 @@ fn print_help() {
 /recipe [filepath] - Generate a recipe from the current conversation and save it to the specified filepath (must end with .yaml).
                        If no filepath is provided, it will be saved to ./recipe.yaml.
 /summarize - Summarize the current conversation to reduce context length while preserving key information.
+/rewind [n] - Continue in a new session that keeps only the first n messages, optionally reverting later file edits.
+              Without n, lists the points you can rewind to.
 /? or /help - Display this help message
 
 Navigation:
@@ -489,4 +508,21 @@ mod tests {
         let result = handle_slash_command("  /summarize  ");
         assert!(matches!(result, Some(InputResult::Summarize)));
     }
+
+    #[test]
+    fn test_rewind_command() {
+        assert!(matches!(
+            handle_slash_command("/rewind"),
+            Some(InputResult::Rewind(None))
+        ));
+        assert!(matches!(
+            handle_slash_command("/rewind 12"),
+            Some(InputResult::Rewind(Some(12)))
+        ));
+        assert!(matches!(
+            handle_slash_command("/rewind twelve"),
+            Some(InputResult::Retry)
+        ));
+        assert!(handle_slash_command("/rewinder").is_none());
+    }
 }
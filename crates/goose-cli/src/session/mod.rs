@@ -4,23 +4,30 @@ mod export;
 mod input;
 mod output;
 mod prompt;
+mod retry;
 mod thinking;
 
-pub use self::export::message_to_markdown;
+pub use self::export::{
+    message_to_markdown, session_to_finetune_jsonl, session_to_html, session_to_json,
+    transcript_from_json, FineTuneFormat,
+};
+pub use self::output::{format_cost, is_quiet, set_quiet};
 pub use builder::{build_session, SessionBuilderConfig};
 use console::Color;
+use goose::agents::sampling::is_sampling_request_id;
 use goose::agents::AgentEvent;
 use goose::permission::permission_confirmation::PrincipalType;
 use goose::permission::Permission;
 use goose::permission::PermissionConfirmation;
 use goose::providers::base::Provider;
+use goose::recipe::{RetryConfig, SubRecipeTemplate};
 pub use goose::session::Identifier;
 
 use anyhow::{Context, Result};
 use completion::GooseCompleter;
 use etcetera::{choose_app_strategy, AppStrategy};
 use goose::agents::extension::{Envs, ExtensionConfig};
-use goose::agents::{Agent, SessionConfig};
+use goose::agents::{Agent, ReplyBudget, SessionConfig};
 use goose::config::Config;
 use goose::message::{Message, MessageContent};
 use goose::session;
@@ -51,6 +58,8 @@ pub struct Session {
     completion_cache: Arc<std::sync::RwLock<CompletionCache>>,
     debug: bool, // New field for debug mode
     run_mode: RunMode,
+    /// Success checks to run after headless messages
+    retry_config: Option<RetryConfig>,
 }
 
 // Cache structure for completion data
@@ -123,6 +132,7 @@ impl Session {
             completion_cache: Arc::new(std::sync::RwLock::new(CompletionCache::new())),
             debug,
             run_mode: RunMode::Normal,
+            retry_config: None,
         }
     }
 
@@ -181,6 +191,7 @@ impl Session {
             description: Some(goose::config::DEFAULT_EXTENSION_DESCRIPTION.to_string()),
             // TODO: should set timeout
             timeout: Some(goose::config::DEFAULT_EXTENSION_TIMEOUT),
+            roots: Vec::new(),
             bundled: None,
         };
 
@@ -214,6 +225,7 @@ impl Session {
             description: Some(goose::config::DEFAULT_EXTENSION_DESCRIPTION.to_string()),
             // TODO: should set timeout
             timeout: Some(goose::config::DEFAULT_EXTENSION_TIMEOUT),
+            roots: Vec::new(),
             bundled: None,
         };
 
@@ -614,6 +626,15 @@ impl Session {
 
                     continue;
                 }
+                InputResult::Rewind(count) => {
+                    save_history(&mut editor);
+
+                    match count {
+                        Some(count) => self.rewind(count).await?,
+                        None => self.list_rewind_points(),
+                    }
+                    continue;
+                }
             }
         }
 
@@ -703,7 +724,10 @@ impl Session {
 
     /// Process a single message and exit
     pub async fn headless(&mut self, message: String) -> Result<()> {
-        self.process_message(message).await
+        match self.retry_config.clone() {
+            Some(retry) => self.run_with_retries(message, &retry).await,
+            None => self.process_message(message).await,
+        }
     }
 
     async fn process_agent_response(&mut self, interactive: bool) -> Result<()> {
@@ -717,11 +741,14 @@ impl Session {
                     working_dir: std::env::current_dir()
                         .expect("failed to get current session working directory"),
                     schedule_id: None,
+                    budget: ReplyBudget::default(),
                 }),
             )
             .await?;
 
         let mut progress_bars = output::McpSpinners::new();
+        // Whether deltas of the current assistant message have already been printed
+        let mut streaming = false;
 
         use futures::StreamExt;
         loop {
@@ -734,7 +761,13 @@ impl Session {
                                 output::hide_thinking();
 
                                 // Format the confirmation prompt
-                                let prompt = "Goose would like to call the above tool, do you allow?".to_string();
+                                let is_sampling = is_sampling_request_id(&confirmation.id);
+                                let prompt = if is_sampling {
+                                    let extension = confirmation.tool_name.trim_end_matches("__sampling");
+                                    format!("The {} extension would like to use your model, do you allow?", extension)
+                                } else {
+                                    "Goose would like to call the above tool, do you allow?".to_string()
+                                };
 
                                 // Get confirmation from user
                                 let permission_result = cliclack::select(prompt)
@@ -756,7 +789,7 @@ impl Session {
                                     }
                                 };
 
-                                if permission == Permission::Cancel {
+                                if permission == Permission::Cancel && !is_sampling {
                                     output::render_text("Tool call cancelled. Returning to chat...", Some(Color::Yellow), true);
 
                                     let mut response_message = Message::user();
@@ -770,6 +803,12 @@ impl Session {
                                     drop(stream);
                                     break;
                                 } else {
+                                    // Sampling has no tool response to cancel, treat it as a denial
+                                    let permission = if permission == Permission::Cancel {
+                                        Permission::DenyOnce
+                                    } else {
+                                        permission
+                                    };
                                     self.agent.handle_confirmation(confirmation.id.clone(), PermissionConfirmation {
                                         principal_type: PrincipalType::Tool,
                                         permission,
@@ -852,6 +891,7 @@ impl Session {
                                             working_dir: std::env::current_dir()
                                                 .expect("failed to get current session working directory"),
                                             schedule_id: None,
+                                            budget: ReplyBudget::default(),
                                         }),
                                     )
                                     .await?;
@@ -865,10 +905,48 @@ impl Session {
 
                                 if interactive {output::hide_thinking()};
                                 let _ = progress_bars.hide();
-                                output::render_message(&message, self.debug);
+                                if streaming {
+                                    output::render_streamed_message(&message, self.debug);
+                                    streaming = false;
+                                } else {
+                                    output::render_message(&message, self.debug);
+                                }
                                 if interactive {output::show_thinking()};
                             }
                         }
+                        Some(Ok(AgentEvent::MessageDelta(delta))) => {
+                            if !streaming {
+                                if interactive {output::hide_thinking()};
+                                let _ = progress_bars.hide();
+                                streaming = true;
+                            }
+                            output::render_message_delta(&delta);
+                        }
+                        Some(Ok(AgentEvent::ToolProgress(progress))) => {
+                            progress_bars.update(
+                                &progress.request_id,
+                                progress.progress,
+                                progress.total,
+                                progress.message.as_deref(),
+                            );
+                        }
+                        Some(Ok(AgentEvent::Subagent(event))) => {
+                            if interactive {output::hide_thinking()};
+                            let _ = progress_bars.hide();
+                            output::render_subagent_event(&event);
+                            if interactive {output::show_thinking()};
+                        }
+                        Some(Ok(AgentEvent::HistoryReplaced(messages))) => {
+                            // The agent compacted the conversation and already saved it
+                            self.messages = messages;
+                            if interactive {output::hide_thinking()};
+                            output::render_text(
+                                "Summarized older messages to stay within the context limit",
+                                Some(Color::Yellow),
+                                true,
+                            );
+                            if interactive {output::show_thinking()};
+                        }
                         Some(Ok(AgentEvent::McpNotification((_id, message)))) => {
                                 if let JsonRpcMessage::Notification(JsonRpcNotification{
                                     method,
@@ -893,22 +971,6 @@ impl Session {
                                         };
                                         progress_bars.log(&message);
                                     },
-                                    "notifications/progress" => {
-                                        let progress = o.get("progress").and_then(|v| v.as_f64());
-                                        let token = o.get("progressToken").map(|v| v.to_string());
-                                        let message = o.get("message").and_then(|v| v.as_str());
-                                        let total = o
-                                            .get("total")
-                                            .and_then(|v| v.as_f64());
-                                        if let (Some(progress), Some(token)) = (progress, token) {
-                                            progress_bars.update(
-                                                token.as_str(),
-                                                progress,
-                                                total,
-                                                message,
-                                            );
-                                        }
-                                    },
                                     _ => (),
                                 }
                             }
@@ -931,6 +993,8 @@ impl Session {
                     }
                 }
                 _ = tokio::signal::ctrl_c() => {
+                    // Tell extensions to stop the tool calls that are still running
+                    self.agent.cancel();
                     drop(stream);
                     if let Err(e) = self.handle_interrupted_messages(true).await {
                         eprintln!("Error handling interruption: {}", e);
@@ -1033,10 +1097,128 @@ impl Session {
         Ok(())
     }
 
+    /// Show the user messages the session can be rewound to just before
+    fn list_rewind_points(&self) {
+        const MAX_REWIND_POINTS: usize = 10;
+
+        let points = self
+            .messages
+            .iter()
+            .enumerate()
+            .filter(|(_, m)| m.role == mcp_core::role::Role::User)
+            .filter_map(|(i, m)| {
+                let text = m.as_concat_text();
+                let first_line = text.lines().find(|line| !line.trim().is_empty())?;
+                Some((i, first_line.chars().take(80).collect::<String>()))
+            })
+            .collect::<Vec<_>>();
+
+        if points.is_empty() {
+            println!(
+                "{}",
+                console::style("There is nothing to rewind to yet.").yellow()
+            );
+            return;
+        }
+
+        println!("Use /rewind <n> to go back to just before one of your messages:");
+        for (count, text) in points.iter().rev().take(MAX_REWIND_POINTS).rev() {
+            println!("  {:>4}  {}", count, text);
+        }
+    }
+
+    /// Continue in a new session holding only the first `count` messages, leaving the current
+    /// session as it was, and offer to undo the file edits made after that point
+    async fn rewind(&mut self, count: usize) -> Result<()> {
+        if count >= self.messages.len() {
+            println!(
+                "{}",
+                console::style(format!(
+                    "The session has {} messages, so there is nothing to rewind.",
+                    self.messages.len()
+                ))
+                .yellow()
+            );
+            return Ok(());
+        }
+
+        let edits = session::fork::edits_after(&self.messages, count);
+        let forked = match session::fork_session(&self.session_file, count, None) {
+            Ok(forked) => forked,
+            Err(e) => {
+                output::render_error(&format!("Failed to rewind: {}", e));
+                return Ok(());
+            }
+        };
+
+        if !edits.is_empty() {
+            println!("Files edited after message {}:", count);
+            for (path, _) in &edits {
+                println!("  {}", path.display());
+            }
+            let revert = cliclack::confirm("Revert these edits?")
+                .initial_value(true)
+                .interact()
+                .unwrap_or(false);
+            if revert {
+                self.revert_edits(&edits).await;
+            }
+        }
+
+        let previous = std::mem::replace(&mut self.session_file, forked);
+        self.messages = session::read_messages(&self.session_file)?;
+        println!(
+            "{}",
+            console::style(format!(
+                "Rewound to message {}. Continuing in {}, {} is unchanged.",
+                count,
+                self.session_file.display(),
+                previous.display()
+            ))
+            .green()
+        );
+        Ok(())
+    }
+
+    /// Undo edits with the developer extension, which only remembers the edits it made since
+    /// it started. Returns whether every edit was undone.
+    async fn revert_edits(&self, edits: &[(PathBuf, usize)]) -> bool {
+        let mut reverted = true;
+        for (path, undos) in edits {
+            for _ in 0..*undos {
+                let undo = mcp_core::tool::ToolCall::new(
+                    "developer__text_editor",
+                    serde_json::json!({"command": "undo_edit", "path": path}),
+                );
+                if let Err(e) = self.agent.call_tool(undo).await {
+                    output::render_error(&format!("Could not revert {}: {}", path.display(), e));
+                    reverted = false;
+                    break;
+                }
+            }
+        }
+        reverted
+    }
+
     pub fn session_file(&self) -> PathBuf {
         self.session_file.clone()
     }
 
+    /// Require the agent to end each reply with an output matching `schema`
+    pub async fn set_final_output_schema(&self, schema: Value) -> Result<()> {
+        self.agent.set_final_output_schema(schema).await
+    }
+
+    /// Let the agent run the recipe's sub-recipes as tools
+    pub async fn set_sub_recipes(&self, sub_recipes: Vec<SubRecipeTemplate>) {
+        self.agent.set_sub_recipes(sub_recipes).await;
+    }
+
+    /// The validated output of the last reply, when a schema was set
+    pub async fn final_output(&self) -> Option<Value> {
+        self.agent.final_output().await
+    }
+
     /// Update the completion cache with fresh data
     /// This should be called before the interactive session starts
     pub async fn update_completion_cache(&mut self) -> Result<()> {
@@ -1109,7 +1291,7 @@ impl Session {
 
     /// Get the session metadata
     pub fn get_metadata(&self) -> Result<session::SessionMetadata> {
-        if !self.session_file.exists() {
+        if !session::session_exists(&self.session_file) {
             return Err(anyhow::anyhow!("Session file does not exist"));
         }
 
@@ -1132,10 +1314,14 @@ impl Session {
             Ok(metadata) => {
                 let total_tokens = metadata.total_tokens.unwrap_or(0) as usize;
 
-                output::display_context_usage(total_tokens, context_limit);
+                output::display_context_usage(
+                    total_tokens,
+                    context_limit,
+                    metadata.accumulated_cost,
+                );
             }
             Err(_) => {
-                output::display_context_usage(0, context_limit);
+                output::display_context_usage(0, context_limit, None);
             }
         }
 
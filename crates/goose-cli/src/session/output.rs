@@ -1,15 +1,17 @@
 use bat::WrappingMode;
 use console::{style, Color};
+use goose::agents::{SubagentEvent, SubagentUpdate};
 use goose::config::Config;
-use goose::message::{Message, MessageContent, ToolRequest, ToolResponse};
+use goose::message::{Message, MessageContent, MessageDelta, ToolRequest, ToolResponse};
 use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
 use mcp_core::prompt::PromptArgument;
 use mcp_core::tool::ToolCall;
 use serde_json::Value;
 use std::cell::RefCell;
 use std::collections::HashMap;
-use std::io::Error;
+use std::io::{Error, Write};
 use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -73,6 +75,18 @@ pub fn get_theme() -> Theme {
     CURRENT_THEME.with(|t| *t.borrow())
 }
 
+// Set when stdout is reserved for machine readable output
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+/// Stop printing the conversation and session details to stdout, errors go to stderr instead
+pub fn set_quiet(quiet: bool) {
+    QUIET.store(quiet, Ordering::Relaxed);
+}
+
+pub fn is_quiet() -> bool {
+    QUIET.load(Ordering::Relaxed)
+}
+
 // Simple wrapper around spinner to manage its state
 #[derive(Default)]
 pub struct ThinkingIndicator {
@@ -118,33 +132,120 @@ pub fn hide_thinking() {
 }
 
 pub fn render_message(message: &Message, debug: bool) {
+    if is_quiet() {
+        return;
+    }
     let theme = get_theme();
 
+    for content in &message.content {
+        render_content(content, theme, debug);
+    }
+    println!();
+}
+
+/// Render a message whose text and thinking were already printed as deltas while streaming,
+/// so only the remaining content such as tool requests is shown
+pub fn render_streamed_message(message: &Message, debug: bool) {
+    if is_quiet() {
+        return;
+    }
+    let theme = get_theme();
+
+    // Finish the line the streamed text was written on
+    println!();
     for content in &message.content {
         match content {
-            MessageContent::Text(text) => print_markdown(&text.text, theme),
-            MessageContent::ToolRequest(req) => render_tool_request(req, theme, debug),
-            MessageContent::ToolResponse(resp) => render_tool_response(resp, theme, debug),
-            MessageContent::Image(image) => {
-                println!("Image: [data: {}, type: {}]", image.data, image.mime_type);
-            }
-            MessageContent::Thinking(thinking) => {
-                if std::env::var("GOOSE_CLI_SHOW_THINKING").is_ok() {
-                    println!("\n{}", style("Thinking:").dim().italic());
-                    print_markdown(&thinking.thinking, theme);
-                }
+            MessageContent::Text(_) | MessageContent::Thinking(_) => {}
+            _ => render_content(content, theme, debug),
+        }
+    }
+    println!();
+}
+
+/// Print a fragment of the assistant's reply as soon as it arrives
+pub fn render_message_delta(delta: &MessageDelta) {
+    if is_quiet() {
+        return;
+    }
+    match delta {
+        MessageDelta::Text { text } => print!("{}", text),
+        MessageDelta::Thinking { thinking } => {
+            if std::env::var("GOOSE_CLI_SHOW_THINKING").is_ok() {
+                print!("{}", style(thinking).dim());
             }
-            MessageContent::RedactedThinking(_) => {
-                // For redacted thinking, print thinking was redacted
+        }
+    }
+    let _ = std::io::stdout().flush();
+}
+
+fn render_content(content: &MessageContent, theme: Theme, debug: bool) {
+    match content {
+        MessageContent::Text(text) => print_markdown(&text.text, theme),
+        MessageContent::ToolRequest(req) => render_tool_request(req, theme, debug),
+        MessageContent::ToolResponse(resp) => render_tool_response(resp, theme, debug),
+        MessageContent::Image(image) => {
+            println!("Image: [data: {}, type: {}]", image.data, image.mime_type);
+        }
+        MessageContent::Thinking(thinking) => {
+            if std::env::var("GOOSE_CLI_SHOW_THINKING").is_ok() {
                 println!("\n{}", style("Thinking:").dim().italic());
-                print_markdown("Thinking was redacted", theme);
+                print_markdown(&thinking.thinking, theme);
             }
-            _ => {
-                println!("WARNING: Message content type could not be rendered");
+        }
+        MessageContent::RedactedThinking(_) => {
+            // For redacted thinking, print thinking was redacted
+            println!("\n{}", style("Thinking:").dim().italic());
+            print_markdown("Thinking was redacted", theme);
+        }
+        MessageContent::BudgetExceeded(exceeded) => {
+            println!("\n{}", style(&exceeded.msg).yellow());
+        }
+        _ => {
+            println!("WARNING: Message content type could not be rendered");
+        }
+    }
+}
+
+/// Print a one line update on what a sub-agent of the delegate tool is doing
+pub fn render_subagent_event(event: &SubagentEvent) {
+    if is_quiet() {
+        return;
+    }
+    let label = style(format!("sub-agent {}", event.subagent + 1))
+        .magenta()
+        .dim();
+    match &event.update {
+        SubagentUpdate::Started { instructions } => {
+            let first_line = instructions.lines().next().unwrap_or_default();
+            let summary = if first_line.chars().count() > SUBAGENT_SUMMARY_MAX_LENGTH {
+                let short: String = first_line
+                    .chars()
+                    .take(SUBAGENT_SUMMARY_MAX_LENGTH)
+                    .collect();
+                format!("{}...", short)
+            } else {
+                first_line.to_string()
+            };
+            println!("{} {} {}", label, style("started:").dim(), summary);
+        }
+        SubagentUpdate::Message { message } => {
+            for content in &message.content {
+                if let MessageContent::ToolRequest(ToolRequest {
+                    tool_call: Ok(call),
+                    ..
+                }) = content
+                {
+                    println!("{} {} {}", label, style("->").dim(), call.name);
+                }
             }
         }
+        SubagentUpdate::Finished { .. } => {
+            println!("{} {}", label, style("finished").green());
+        }
+        SubagentUpdate::Failed { error } => {
+            println!("{} {} {}", label, style("failed:").red(), error);
+        }
     }
-    println!();
 }
 
 pub fn render_text(text: &str, color: Option<Color>, dim: bool) {
@@ -152,6 +253,9 @@ pub fn render_text(text: &str, color: Option<Color>, dim: bool) {
 }
 
 pub fn render_text_no_newlines(text: &str, color: Option<Color>, dim: bool) {
+    if is_quiet() {
+        return;
+    }
     let mut styled_text = style(text);
     if dim {
         styled_text = styled_text.dim();
@@ -239,7 +343,11 @@ fn render_tool_response(resp: &ToolResponse, theme: Theme, debug: bool) {
 }
 
 pub fn render_error(message: &str) {
-    println!("\n  {} {}\n", style("error:").red().bold(), message);
+    if is_quiet() {
+        eprintln!("\n  {} {}\n", style("error:").red().bold(), message);
+    } else {
+        println!("\n  {} {}\n", style("error:").red().bold(), message);
+    }
 }
 
 pub fn render_prompts(prompts: &HashMap<String, Vec<String>>) {
@@ -288,6 +396,9 @@ pub fn render_prompt_info(info: &PromptInfo) {
 }
 
 pub fn render_extension_success(name: &str) {
+    if is_quiet() {
+        return;
+    }
     println!();
     println!(
         "  {} extension `{}`",
@@ -310,6 +421,9 @@ pub fn render_extension_error(name: &str, error: &str) {
 }
 
 pub fn render_builtin_success(names: &str) {
+    if is_quiet() {
+        return;
+    }
     println!();
     println!(
         "  {} builtin{}: {}",
@@ -414,6 +528,9 @@ fn print_markdown(content: &str, theme: Theme) {
 
 const INDENT: &str = "    ";
 
+/// Longest part of a sub-agent's instructions shown when it starts
+const SUBAGENT_SUMMARY_MAX_LENGTH: usize = 80;
+
 fn get_tool_params_max_length() -> usize {
     Config::global()
         .get_param::<usize>("GOOSE_CLI_TOOL_PARAMS_TRUNCATION_MAX_LENGTH")
@@ -544,6 +661,9 @@ pub fn display_session_info(
     session_file: &Path,
     provider_instance: Option<&Arc<dyn goose::providers::base::Provider>>,
 ) {
+    if is_quiet() {
+        return;
+    }
     let start_session_msg = if resume {
         "resuming session |"
     } else if session_file.to_str() == Some("/dev/null") || session_file.to_str() == Some("NUL") {
@@ -610,9 +730,13 @@ pub fn display_greeting() {
 }
 
 /// Display context window usage with both current and session totals
-pub fn display_context_usage(total_tokens: usize, context_limit: usize) {
+pub fn display_context_usage(total_tokens: usize, context_limit: usize, cost: Option<f64>) {
     use console::style;
 
+    if is_quiet() {
+        return;
+    }
+
     // Calculate percentage used
     let percentage = (total_tokens as f64 / context_limit as f64 * 100.0).round() as usize;
 
@@ -634,13 +758,25 @@ pub fn display_context_usage(total_tokens: usize, context_limit: usize) {
         style(dots).red()
     };
 
-    // Print the status line
+    // Print the status line, with what the session has cost so far if the model's price is known
+    let cost = cost
+        .map(|cost| format!(" · Cost: {}", format_cost(cost)))
+        .unwrap_or_default();
     println!(
-        "Context: {} {}% ({}/{} tokens)",
-        colored_dots, percentage, total_tokens, context_limit
+        "Context: {} {}% ({}/{} tokens){}",
+        colored_dots, percentage, total_tokens, context_limit, cost
     );
 }
 
+/// Format a cost in USD, with more precision for amounts under a cent
+pub fn format_cost(cost: f64) -> String {
+    if cost > 0.0 && cost < 0.01 {
+        format!("${:.4}", cost)
+    } else {
+        format!("${:.2}", cost)
+    }
+}
+
 pub struct McpSpinners {
     bars: HashMap<String, ProgressBar>,
     log_spinner: Option<ProgressBar>,
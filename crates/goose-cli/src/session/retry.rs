@@ -0,0 +1,262 @@
+use anyhow::{anyhow, Result};
+use console::Color;
+use goose::recipe::{RetryConfig, SuccessCheck};
+use goose::session::{self, RetryAttempt};
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::process::Command;
+
+use super::{output, Session};
+
+/// How long each check or cleanup command may run, unless the recipe says otherwise
+const DEFAULT_TIMEOUT_SECONDS: u64 = 600;
+
+/// How much of a failing check's output is sent back to the model, from the end
+const MAX_FAILURE_OUTPUT_CHARS: usize = 4000;
+
+/// Run `command` in a shell in the working directory, returning whether it exited with status
+/// 0 and its combined output
+async fn run_shell(command: &str, timeout: Duration) -> Result<(bool, String)> {
+    let mut cmd = if cfg!(windows) {
+        let mut cmd = Command::new("cmd");
+        cmd.arg("/C").arg(command);
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c").arg(command);
+        cmd
+    };
+    cmd.stdin(Stdio::null()).kill_on_drop(true);
+
+    let output = match tokio::time::timeout(timeout, cmd.output()).await {
+        Ok(output) => output?,
+        Err(_) => {
+            return Ok((
+                false,
+                format!("Timed out after {} seconds", timeout.as_secs()),
+            ))
+        }
+    };
+    let mut text = String::from_utf8_lossy(&output.stdout).to_string();
+    text.push_str(&String::from_utf8_lossy(&output.stderr));
+    Ok((output.status.success(), text))
+}
+
+/// The end of a command's output, where test runners and compilers put their summary
+fn tail(output: &str) -> String {
+    let output = output.trim();
+    let count = output.chars().count();
+    if count <= MAX_FAILURE_OUTPUT_CHARS {
+        return output.to_string();
+    }
+    let tail: String = output
+        .chars()
+        .skip(count - MAX_FAILURE_OUTPUT_CHARS)
+        .collect();
+    format!("...\n{}", tail)
+}
+
+/// Run the checks in order, describing the first one that fails
+async fn first_failure(checks: &[SuccessCheck], timeout: Duration) -> Option<String> {
+    for check in checks {
+        match check {
+            SuccessCheck::Shell { command } => {
+                let failure = match run_shell(command, timeout).await {
+                    Ok((true, _)) => continue,
+                    Ok((false, output)) => format!("`{}` failed:\n{}", command, tail(&output)),
+                    Err(e) => format!("`{}` could not be run: {}", command, e),
+                };
+                return Some(failure);
+            }
+        }
+    }
+    None
+}
+
+/// The message sent back to the agent after a failed attempt. `reverted` says whether its
+/// file edits were undone, when the retry policy resets them.
+fn retry_prompt(failure: &str, reverted: Option<bool>) -> String {
+    let reset = match reverted {
+        Some(true) => " Your file edits have been reverted.",
+        Some(false) => " Reverting your file edits failed, so check the current content of the files before changing them.",
+        None => "",
+    };
+    format!(
+        "The success checks failed after your changes.{}\n\n{}\n\nFix the problem and finish the task again.",
+        reset, failure
+    )
+}
+
+impl Session {
+    /// Process the message, then run the recipe's success checks. While they fail and retries
+    /// are left, clean up as configured and send the failure back to the agent. Each attempt
+    /// is recorded in the session metadata.
+    pub(super) async fn run_with_retries(
+        &mut self,
+        message: String,
+        retry: &RetryConfig,
+    ) -> Result<()> {
+        let timeout = Duration::from_secs(retry.timeout_seconds.unwrap_or(DEFAULT_TIMEOUT_SECONDS));
+        let attempts = retry.max_retries + 1;
+        let mut history = Vec::new();
+        let mut prompt = message;
+
+        for attempt in 1..=attempts {
+            let message_index = self.messages.len();
+            self.process_message(prompt).await?;
+
+            let failure = first_failure(&retry.checks, timeout).await;
+            history.push(RetryAttempt {
+                attempt,
+                message_index,
+                passed: failure.is_none(),
+                failure: failure.clone(),
+            });
+            self.record_attempts(&history).await;
+
+            let failure = match failure {
+                Some(failure) => failure,
+                None => {
+                    output::render_text("Success checks passed", Some(Color::Green), true);
+                    return Ok(());
+                }
+            };
+            if attempt == attempts {
+                break;
+            }
+
+            output::render_text(
+                &format!(
+                    "Success checks failed, retrying (attempt {} of {})",
+                    attempt + 1,
+                    attempts
+                ),
+                Some(Color::Yellow),
+                true,
+            );
+            let reverted = if retry.reset_files {
+                let edits = session::fork::edits_after(&self.messages, message_index);
+                Some(self.revert_edits(&edits).await)
+            } else {
+                None
+            };
+            if let Some(cleanup) = &retry.on_failure {
+                match run_shell(cleanup, timeout).await {
+                    Ok((true, _)) => {}
+                    Ok((false, output)) => output::render_error(&format!(
+                        "Cleanup command `{}` failed:\n{}",
+                        cleanup,
+                        tail(&output)
+                    )),
+                    Err(e) => output::render_error(&format!(
+                        "Cleanup command `{}` could not be run: {}",
+                        cleanup, e
+                    )),
+                }
+            }
+
+            prompt = retry_prompt(&failure, reverted);
+        }
+
+        Err(anyhow!(
+            "Success checks still failed after {} attempts",
+            attempts
+        ))
+    }
+
+    /// Whether a headless run that ended with `result` should make goose exit with status 1.
+    /// Only runs with a retry policy do, so automation can tell the success checks kept
+    /// failing; other runs exit with status 0 as before.
+    pub fn exits_with_failure(&self, result: &Result<()>) -> bool {
+        self.retry_config.is_some() && result.is_err()
+    }
+
+    async fn record_attempts(&self, attempts: &[RetryAttempt]) {
+        let result = async {
+            let mut metadata = session::read_metadata(&self.session_file)?;
+            metadata.retry_attempts = Some(attempts.to_vec());
+            session::update_metadata(&self.session_file, &metadata).await
+        }
+        .await;
+        if let Err(e) = result {
+            tracing::warn!("Failed to record retry attempts: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use goose::agents::Agent;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_tail_keeps_the_end() {
+        assert_eq!(tail("  short  "), "short");
+
+        let long = format!("{}END", "x".repeat(MAX_FAILURE_OUTPUT_CHARS));
+        let tailed = tail(&long);
+        assert!(tailed.starts_with("...\n"));
+        assert!(tailed.ends_with("END"));
+        assert_eq!(tailed.chars().count(), MAX_FAILURE_OUTPUT_CHARS + 4);
+    }
+
+    #[test]
+    fn test_retry_prompt_reports_the_reset() {
+        let failure = "`cargo test` failed";
+        assert!(retry_prompt(failure, Some(true)).contains("have been reverted"));
+        let prompt = retry_prompt(failure, Some(false));
+        assert!(prompt.contains("Reverting your file edits failed"));
+        assert!(!prompt.contains("have been reverted"));
+        assert!(!retry_prompt(failure, None).contains("revert"));
+        assert!(retry_prompt(failure, None).contains(failure));
+    }
+
+    #[tokio::test]
+    async fn test_only_retry_runs_exit_with_failure() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut session = Session::new(Agent::new(), temp_dir.path().join("run.jsonl"), false);
+        let failed: Result<()> = Err(anyhow!("The provider is unavailable"));
+
+        assert!(!session.exits_with_failure(&failed));
+
+        session.retry_config = Some(RetryConfig {
+            max_retries: 1,
+            checks: vec![],
+            reset_files: false,
+            on_failure: None,
+            timeout_seconds: None,
+        });
+        assert!(session.exits_with_failure(&failed));
+        assert!(!session.exits_with_failure(&Ok(())));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_first_failure() {
+        let timeout = Duration::from_secs(10);
+        let shell = |command: &str| SuccessCheck::Shell {
+            command: command.to_string(),
+        };
+
+        assert!(first_failure(&[shell("true")], timeout).await.is_none());
+
+        let failure = first_failure(
+            &[
+                shell("true"),
+                shell("echo 3 tests failed; exit 1"),
+                shell("false"),
+            ],
+            timeout,
+        )
+        .await
+        .unwrap();
+        assert!(failure.starts_with("`echo 3 tests failed; exit 1` failed"));
+        assert!(failure.contains("3 tests failed"));
+
+        let failure = first_failure(&[shell("sleep 5")], Duration::from_millis(100))
+            .await
+            .unwrap();
+        assert!(failure.contains("Timed out"));
+    }
+}
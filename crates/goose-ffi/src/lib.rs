@@ -263,6 +263,18 @@ pub unsafe extern "C" fn goose_agent_send_message(
                         full_response.push_str(&json);
                     }
                 }
+                Ok(AgentEvent::MessageDelta(_)) => {
+                    // The full message follows the deltas, so there is nothing to add here
+                }
+                Ok(AgentEvent::HistoryReplaced(_)) => {
+                    // Only the reply text is returned, the history is not kept here
+                }
+                Ok(AgentEvent::Subagent(_)) => {
+                    // Sub-agents report back through the delegate tool's result
+                }
+                Ok(AgentEvent::ToolProgress(_)) => {
+                    // Progress is not reported through the FFI
+                }
                 Ok(AgentEvent::McpNotification(_)) => {
                     // TODO: Handle MCP notifications.
                 }
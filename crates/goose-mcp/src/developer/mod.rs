@@ -1,4 +1,5 @@
 mod lang;
+mod sandbox;
 mod shell;
 
 use anyhow::Result;
@@ -37,6 +38,7 @@ use mcp_server::Router;
 
 use mcp_core::role::Role;
 
+use self::sandbox::{HiddenPaths, SandboxConfig};
 use self::shell::{
     expand_path, format_command_for_platform, get_shell_config, is_absolute_path,
     normalize_line_endings,
@@ -94,12 +96,84 @@ pub fn load_prompt_files() -> HashMap<String, Prompt> {
     prompts
 }
 
+/// The global .gooseignore
+/// - macOS/Linux: ~/.config/goose/
+/// - Windows:     ~\AppData\Roaming\Block\goose\config\
+fn global_ignore_path() -> PathBuf {
+    choose_app_strategy(crate::APP_STRATEGY.clone())
+        .map(|strategy| strategy.in_config_dir(".gooseignore"))
+        .unwrap_or_else(|_| {
+            PathBuf::from(shellexpand::tilde("~/.config/goose/.gooseignore").to_string())
+        })
+}
+
+/// The files the ignore patterns for `cwd` can be read from, whether or not they exist
+fn ignore_files(cwd: &Path) -> Vec<PathBuf> {
+    vec![
+        global_ignore_path(),
+        cwd.join(".gooseignore"),
+        cwd.join(".gitignore"),
+    ]
+}
+
+/// The ignore patterns for `cwd`: the global and local .gooseignore, the .gitignore when there
+/// is no local .gooseignore, and some defaults when none of them exist
+fn load_ignore_patterns(cwd: &Path) -> Gitignore {
+    let mut builder = GitignoreBuilder::new(cwd);
+    let mut has_ignore_file = false;
+    let global_ignore_path = global_ignore_path();
+
+    // Create the directory if it doesn't exist
+    if let Some(parent) = global_ignore_path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+
+    // Read global ignores if they exist
+    if global_ignore_path.is_file() {
+        let _ = builder.add(global_ignore_path);
+        has_ignore_file = true;
+    }
+
+    // Check for local ignores in current directory
+    let local_ignore_path = cwd.join(".gooseignore");
+
+    // Read local ignores if they exist
+    if local_ignore_path.is_file() {
+        let _ = builder.add(local_ignore_path);
+        has_ignore_file = true;
+    } else {
+        // If no .gooseignore exists, check for .gitignore as fallback
+        let gitignore_path = cwd.join(".gitignore");
+        if gitignore_path.is_file() {
+            tracing::debug!(
+                "No .gooseignore found, using .gitignore as fallback for ignore patterns"
+            );
+            let _ = builder.add(gitignore_path);
+            has_ignore_file = true;
+        }
+    }
+
+    // Only use default patterns if no .gooseignore files were found
+    // AND no .gitignore was used as fallback
+    if !has_ignore_file {
+        // Add some sensible defaults
+        let _ = builder.add_line(None, "**/.env");
+        let _ = builder.add_line(None, "**/.env.*");
+        let _ = builder.add_line(None, "**/secrets.*");
+    }
+
+    builder.build().expect("Failed to build ignore patterns")
+}
+
 pub struct DeveloperRouter {
     tools: Vec<Tool>,
     prompts: Arc<HashMap<String, Prompt>>,
     instructions: String,
-    file_history: Arc<Mutex<HashMap<PathBuf, Vec<String>>>>,
+    /// Content of each file before every edit, `None` where an edit created the file
+    file_history: Arc<Mutex<HashMap<PathBuf, Vec<Option<String>>>>>,
     ignore_patterns: Arc<Gitignore>,
+    sandbox: SandboxConfig,
+    hidden_paths: Arc<HiddenPaths>,
 }
 
 impl Default for DeveloperRouter {
@@ -158,9 +232,17 @@ impl DeveloperRouter {
             "#},
         };
 
+        // Tell the model what the sandbox allows, if commands run in one
+        let sandbox = SandboxConfig::from_env();
+        let cwd = std::env::current_dir().expect("should have a current working dir");
+        let shell_tool_desc = match sandbox.describe(&cwd) {
+            Some(sandbox_desc) => format!("{}\n{}", shell_tool_desc, sandbox_desc),
+            None => shell_tool_desc.to_string(),
+        };
+
         let bash_tool = Tool::new(
             "shell".to_string(),
-            shell_tool_desc.to_string(),
+            shell_tool_desc,
             json!({
                 "type": "object",
                 "required": ["command"],
@@ -296,7 +378,6 @@ impl DeveloperRouter {
         );
 
         // Get base instructions and working directory
-        let cwd = std::env::current_dir().expect("should have a current working dir");
         let os = std::env::consts::OS;
 
         let base_instructions = match os {
@@ -381,55 +462,7 @@ impl DeveloperRouter {
             format!("{base_instructions}\n{hints}")
         };
 
-        let mut builder = GitignoreBuilder::new(cwd.clone());
-        let mut has_ignore_file = false;
-        // Initialize ignore patterns
-        // - macOS/Linux: ~/.config/goose/
-        // - Windows:     ~\AppData\Roaming\Block\goose\config\
-        let global_ignore_path = choose_app_strategy(crate::APP_STRATEGY.clone())
-            .map(|strategy| strategy.in_config_dir(".gooseignore"))
-            .unwrap_or_else(|_| {
-                PathBuf::from(shellexpand::tilde("~/.config/goose/.gooseignore").to_string())
-            });
-
-        // Create the directory if it doesn't exist
-        let _ = std::fs::create_dir_all(global_ignore_path.parent().unwrap());
-
-        // Read global ignores if they exist
-        if global_ignore_path.is_file() {
-            let _ = builder.add(global_ignore_path);
-            has_ignore_file = true;
-        }
-
-        // Check for local ignores in current directory
-        let local_ignore_path = cwd.join(".gooseignore");
-
-        // Read local ignores if they exist
-        if local_ignore_path.is_file() {
-            let _ = builder.add(local_ignore_path);
-            has_ignore_file = true;
-        } else {
-            // If no .gooseignore exists, check for .gitignore as fallback
-            let gitignore_path = cwd.join(".gitignore");
-            if gitignore_path.is_file() {
-                tracing::debug!(
-                    "No .gooseignore found, using .gitignore as fallback for ignore patterns"
-                );
-                let _ = builder.add(gitignore_path);
-                has_ignore_file = true;
-            }
-        }
-
-        // Only use default patterns if no .gooseignore files were found
-        // AND no .gitignore was used as fallback
-        if !has_ignore_file {
-            // Add some sensible defaults
-            let _ = builder.add_line(None, "**/.env");
-            let _ = builder.add_line(None, "**/.env.*");
-            let _ = builder.add_line(None, "**/secrets.*");
-        }
-
-        let ignore_patterns = builder.build().expect("Failed to build ignore patterns");
+        let ignore_patterns = load_ignore_patterns(&cwd);
 
         Self {
             tools: vec![
@@ -443,6 +476,8 @@ impl DeveloperRouter {
             instructions,
             file_history: Arc::new(Mutex::new(HashMap::new())),
             ignore_patterns: Arc::new(ignore_patterns),
+            sandbox,
+            hidden_paths: Arc::new(HiddenPaths::default()),
         }
     }
 
@@ -508,14 +543,16 @@ impl DeveloperRouter {
         let shell_config = get_shell_config();
         let cmd_str = format_command_for_platform(command);
 
-        // Execute the command using platform-specific shell
-        let mut child = Command::new(&shell_config.executable)
+        // Execute the command using platform-specific shell, inside the sandbox if enabled
+        let cwd = std::env::current_dir().expect("should have a current working dir");
+        let mut child = self
+            .sandbox
+            .command(&shell_config, &cmd_str, &cwd, &self.hidden_paths)
+            .map_err(ToolError::ExecutionError)?
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
             .stdin(Stdio::null())
             .kill_on_drop(true)
-            .arg(&shell_config.arg)
-            .arg(cmd_str)
             .spawn()
             .map_err(|e| ToolError::ExecutionError(e.to_string()))?;
 
@@ -593,10 +630,27 @@ impl DeveloperRouter {
         });
 
         // Wait for the command to complete and get output
-        child
-            .wait()
-            .await
-            .map_err(|e| ToolError::ExecutionError(e.to_string()))?;
+        match self.sandbox.timeout() {
+            Some(limit) => match tokio::time::timeout(limit, child.wait()).await {
+                Ok(status) => {
+                    status.map_err(|e| ToolError::ExecutionError(e.to_string()))?;
+                }
+                Err(_) => {
+                    let _ = child.kill().await;
+                    return Err(ToolError::ExecutionError(format!(
+                        "The command '{}' was stopped after running for {} seconds",
+                        command,
+                        limit.as_secs()
+                    )));
+                }
+            },
+            None => {
+                child
+                    .wait()
+                    .await
+                    .map_err(|e| ToolError::ExecutionError(e.to_string()))?;
+            }
+        }
 
         let output_str = match output_task.await {
             Ok(result) => result.map_err(|e| ToolError::ExecutionError(e.to_string()))?,
@@ -755,6 +809,9 @@ impl DeveloperRouter {
         // Normalize line endings based on platform
         let normalized_text = normalize_line_endings(file_text);
 
+        // Save history for undo
+        self.save_file_history(path)?;
+
         // Write to the file
         std::fs::write(path, normalized_text)
             .map_err(|e| ToolError::ExecutionError(format!("Failed to write file: {}", e)))?;
@@ -880,10 +937,18 @@ impl DeveloperRouter {
         let mut history = self.file_history.lock().unwrap();
         if let Some(contents) = history.get_mut(path) {
             if let Some(previous_content) = contents.pop() {
-                // Write previous content back to file
-                std::fs::write(path, previous_content).map_err(|e| {
-                    ToolError::ExecutionError(format!("Failed to write file: {}", e))
-                })?;
+                match previous_content {
+                    // Write previous content back to file
+                    Some(previous_content) => {
+                        std::fs::write(path, previous_content).map_err(|e| {
+                            ToolError::ExecutionError(format!("Failed to write file: {}", e))
+                        })?
+                    }
+                    // The edit created the file
+                    None => std::fs::remove_file(path).map_err(|e| {
+                        ToolError::ExecutionError(format!("Failed to remove file: {}", e))
+                    })?,
+                }
                 Ok(vec![Content::text("Undid the last edit")])
             } else {
                 Err(ToolError::InvalidParameters(
@@ -899,12 +964,14 @@ impl DeveloperRouter {
 
     fn save_file_history(&self, path: &PathBuf) -> Result<(), ToolError> {
         let mut history = self.file_history.lock().unwrap();
-        let content = if path.exists() {
-            std::fs::read_to_string(path)
-                .map_err(|e| ToolError::ExecutionError(format!("Failed to read file: {}", e)))?
-        } else {
-            String::new()
-        };
+        let content =
+            if path.exists() {
+                Some(std::fs::read_to_string(path).map_err(|e| {
+                    ToolError::ExecutionError(format!("Failed to read file: {}", e))
+                })?)
+            } else {
+                None
+            };
         history.entry(path.clone()).or_default().push(content);
         Ok(())
     }
@@ -1209,6 +1276,8 @@ impl Clone for DeveloperRouter {
             instructions: self.instructions.clone(),
             file_history: Arc::clone(&self.file_history),
             ignore_patterns: Arc::clone(&self.ignore_patterns),
+            sandbox: self.sandbox.clone(),
+            hidden_paths: Arc::clone(&self.hidden_paths),
         }
     }
 }
@@ -1612,6 +1681,52 @@ mod tests {
         temp_dir.close().unwrap();
     }
 
+    #[tokio::test]
+    #[serial]
+    async fn test_text_editor_undo_write() {
+        let router = get_router().await;
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let file_path = temp_dir.path().join("test.txt");
+        let file_path_str = file_path.to_str().unwrap();
+        std::env::set_current_dir(&temp_dir).unwrap();
+
+        let call = |arguments: Value| router.call_tool("text_editor", arguments, dummy_sender());
+        let write =
+            |text: &str| json!({"command": "write", "path": file_path_str, "file_text": text});
+        let undo = json!({"command": "undo_edit", "path": file_path_str});
+
+        call(write("First version")).await.unwrap();
+        call(json!({
+            "command": "str_replace",
+            "path": file_path_str,
+            "old_str": "First",
+            "new_str": "Second"
+        }))
+        .await
+        .unwrap();
+        call(write("Third version")).await.unwrap();
+
+        // Each undo restores the content from before one edit, newest first
+        call(undo.clone()).await.unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&file_path).unwrap(),
+            "Second version"
+        );
+        call(undo.clone()).await.unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&file_path).unwrap(),
+            "First version"
+        );
+
+        // Undoing the write that created the file removes it again
+        call(undo.clone()).await.unwrap();
+        assert!(!file_path.exists());
+        assert!(call(undo).await.is_err());
+
+        temp_dir.close().unwrap();
+    }
+
     // Test GooseIgnore pattern matching
     #[tokio::test]
     #[serial]
@@ -1631,6 +1746,8 @@ mod tests {
             instructions: String::new(),
             file_history: Arc::new(Mutex::new(HashMap::new())),
             ignore_patterns: Arc::new(ignore_patterns),
+            sandbox: SandboxConfig::default(),
+            hidden_paths: Arc::new(HiddenPaths::default()),
         };
 
         // Test basic file matching
@@ -1681,6 +1798,8 @@ mod tests {
             instructions: String::new(),
             file_history: Arc::new(Mutex::new(HashMap::new())),
             ignore_patterns: Arc::new(ignore_patterns),
+            sandbox: SandboxConfig::default(),
+            hidden_paths: Arc::new(HiddenPaths::default()),
         };
 
         // Try to write to an ignored file
@@ -1740,6 +1859,8 @@ mod tests {
             instructions: String::new(),
             file_history: Arc::new(Mutex::new(HashMap::new())),
             ignore_patterns: Arc::new(ignore_patterns),
+            sandbox: SandboxConfig::default(),
+            hidden_paths: Arc::new(HiddenPaths::default()),
         };
 
         // Create an ignored file
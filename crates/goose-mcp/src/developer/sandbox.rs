@@ -0,0 +1,442 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use ignore::gitignore::Gitignore;
+use tokio::process::Command;
+
+use super::shell::ShellConfig;
+use super::{ignore_files, load_ignore_patterns};
+
+/// Restrictions on the commands run by the shell tool, read from the extension's environment.
+///
+/// Sandboxed commands run under bubblewrap on Linux: the filesystem is read-only apart from
+/// the working directory, the configured writable paths and a private /tmp, files matched by
+/// .gooseignore are hidden, and the network is unavailable unless allowed. CPU and memory
+/// limits are applied with `ulimit`.
+///
+/// Without bubblewrap commands are refused, unless GOOSE_SANDBOX_ALLOW_UNSANDBOXED opts in to
+/// running them with only the resource limits applied.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SandboxConfig {
+    pub enabled: bool,
+    /// The bubblewrap executable, if it is available
+    pub bwrap: Option<PathBuf>,
+    /// Run commands with only the resource limits when bubblewrap is unavailable
+    pub allow_unsandboxed: bool,
+    /// Directories commands may write to besides the working directory
+    pub writable_paths: Vec<PathBuf>,
+    pub allow_network: bool,
+    pub cpu_seconds: Option<u64>,
+    pub memory_mb: Option<u64>,
+    /// Wall-clock limit, after which the command is killed
+    pub timeout_seconds: Option<u64>,
+}
+
+fn env_flag(name: &str) -> bool {
+    env::var(name)
+        .map(|value| matches!(value.to_lowercase().as_str(), "1" | "true" | "yes"))
+        .unwrap_or(false)
+}
+
+fn env_number(name: &str) -> Option<u64> {
+    env::var(name)
+        .ok()
+        .and_then(|value| value.trim().parse().ok())
+}
+
+impl SandboxConfig {
+    /// Read GOOSE_SANDBOX and the GOOSE_SANDBOX_* settings
+    pub fn from_env() -> Self {
+        Self {
+            enabled: env_flag("GOOSE_SANDBOX"),
+            bwrap: find_bwrap(),
+            allow_unsandboxed: env_flag("GOOSE_SANDBOX_ALLOW_UNSANDBOXED"),
+            writable_paths: env::var_os("GOOSE_SANDBOX_WRITABLE_PATHS")
+                .map(|paths| env::split_paths(&paths).collect())
+                .unwrap_or_default(),
+            allow_network: env_flag("GOOSE_SANDBOX_ALLOW_NETWORK"),
+            cpu_seconds: env_number("GOOSE_SANDBOX_CPU_SECONDS"),
+            memory_mb: env_number("GOOSE_SANDBOX_MEMORY_MB"),
+            timeout_seconds: env_number("GOOSE_SANDBOX_TIMEOUT_SECONDS"),
+        }
+    }
+
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout_seconds
+            .filter(|_| self.enabled)
+            .map(Duration::from_secs)
+    }
+
+    /// The constraints, written for the model, appended to the shell tool's description
+    pub fn describe(&self, working_dir: &Path) -> Option<String> {
+        if !self.enabled {
+            return None;
+        }
+
+        let mut lines = Vec::new();
+        if self.bwrap.is_some() {
+            let mut writable = vec![working_dir.display().to_string()];
+            writable.extend(self.writable_paths.iter().map(|p| p.display().to_string()));
+            lines.push(format!(
+                "Commands can only write to {} and a private /tmp that is discarded after each command, everything else is read-only.",
+                writable.join(", ")
+            ));
+            lines.push("Files restricted by .gooseignore are hidden.".to_string());
+            lines.push(
+                "Processes started in the background are stopped when the command finishes."
+                    .to_string(),
+            );
+            if !self.allow_network {
+                lines.push("There is no network access.".to_string());
+            }
+        }
+        if let Some(seconds) = self.cpu_seconds {
+            lines.push(format!(
+                "Each command may use {} seconds of CPU time.",
+                seconds
+            ));
+        }
+        if let Some(mb) = self.memory_mb {
+            lines.push(format!("Each command may use {} MB of memory.", mb));
+        }
+        if let Some(seconds) = self.timeout_seconds {
+            lines.push(format!(
+                "Commands are stopped after running for {} seconds.",
+                seconds
+            ));
+        }
+        if lines.is_empty() {
+            return None;
+        }
+
+        let summary = if self.bwrap.is_some() {
+            "Commands run in a sandbox."
+        } else {
+            "Commands run with resource limits."
+        };
+        Some(format!(
+            "**Sandbox**: {}\n{}",
+            summary,
+            lines
+                .iter()
+                .map(|line| format!("  - {}", line))
+                .collect::<Vec<_>>()
+                .join("\n")
+        ))
+    }
+
+    /// The `ulimit` calls that apply the CPU and memory limits to the command and its children
+    fn limits_prefix(&self) -> String {
+        let mut prefix = String::new();
+        if let Some(seconds) = self.cpu_seconds {
+            prefix.push_str(&format!("ulimit -t {} && ", seconds));
+        }
+        if let Some(mb) = self.memory_mb {
+            prefix.push_str(&format!("ulimit -v {} && ", mb * 1024));
+        }
+        prefix
+    }
+
+    /// The bubblewrap arguments that confine a command to `working_dir`, with `hidden` files
+    /// and directories masked
+    fn bwrap_args(&self, working_dir: &Path, hidden: &[PathBuf]) -> Vec<String> {
+        let mut args: Vec<String> = [
+            "--ro-bind",
+            "/",
+            "/",
+            "--dev",
+            "/dev",
+            "--proc",
+            "/proc",
+            "--tmpfs",
+            "/tmp",
+            "--unshare-pid",
+            "--die-with-parent",
+            "--new-session",
+        ]
+        .iter()
+        .map(|arg| arg.to_string())
+        .collect();
+        if !self.allow_network {
+            args.push("--unshare-net".to_string());
+        }
+
+        for path in
+            std::iter::once(working_dir).chain(self.writable_paths.iter().map(|p| p.as_path()))
+        {
+            // bubblewrap refuses to bind paths that don't exist
+            if path.exists() {
+                let path = path.display().to_string();
+                args.extend(["--bind".to_string(), path.clone(), path]);
+            }
+        }
+        // Masks come after the binds so they cover the writable paths as well
+        for path in hidden {
+            let mask = if path.is_dir() {
+                vec!["--tmpfs".to_string()]
+            } else {
+                vec!["--ro-bind".to_string(), "/dev/null".to_string()]
+            };
+            args.extend(mask);
+            args.push(path.display().to_string());
+        }
+        args.extend(["--chdir".to_string(), working_dir.display().to_string()]);
+        args
+    }
+
+    /// The process that runs `command`, inside the sandbox if it is enabled
+    pub fn command(
+        &self,
+        shell: &ShellConfig,
+        command: &str,
+        working_dir: &Path,
+        hidden_paths: &HiddenPaths,
+    ) -> Result<Command, String> {
+        if !self.enabled {
+            let mut process = Command::new(&shell.executable);
+            process.arg(&shell.arg).arg(command);
+            return Ok(process);
+        }
+
+        let bwrap = match &self.bwrap {
+            Some(bwrap) => bwrap,
+            None if self.allow_unsandboxed => {
+                tracing::warn!("bubblewrap (bwrap) was not found, running the command with only the sandbox's resource limits");
+                let mut process = Command::new(&shell.executable);
+                process
+                    .arg(&shell.arg)
+                    .arg(format!("{}{}", self.limits_prefix(), command));
+                return Ok(process);
+            }
+            None => {
+                return Err(
+                    "The shell sandbox is enabled but bubblewrap (bwrap) was not found, so the command was not run. \
+                    Install bubblewrap, or set GOOSE_SANDBOX_ALLOW_UNSANDBOXED to run commands with only the resource limits."
+                        .to_string(),
+                )
+            }
+        };
+        let hidden = hidden_paths.get(working_dir);
+        let mut process = Command::new(bwrap);
+        process
+            .args(self.bwrap_args(working_dir, &hidden))
+            .arg("--")
+            .arg(&shell.executable)
+            .arg(&shell.arg)
+            .arg(format!("{}{}", self.limits_prefix(), command));
+        Ok(process)
+    }
+}
+
+/// The files and directories hidden from sandboxed commands. The working directory is walked
+/// for them once and again only when it changes or one of its ignore files does, rather than
+/// before every command.
+#[derive(Debug, Default)]
+pub struct HiddenPaths {
+    cached: Mutex<Option<(IgnoreState, Arc<Vec<PathBuf>>)>>,
+}
+
+/// A working directory and when each of its ignore files was last modified
+#[derive(Debug, PartialEq)]
+struct IgnoreState {
+    working_dir: PathBuf,
+    ignore_files: Vec<Option<(SystemTime, u64)>>,
+}
+
+impl IgnoreState {
+    fn read(working_dir: &Path) -> Self {
+        let ignore_files = ignore_files(working_dir)
+            .iter()
+            .map(|path| {
+                let metadata = fs::metadata(path).ok()?;
+                Some((metadata.modified().ok()?, metadata.len()))
+            })
+            .collect();
+        Self {
+            working_dir: working_dir.to_path_buf(),
+            ignore_files,
+        }
+    }
+}
+
+impl HiddenPaths {
+    fn get(&self, working_dir: &Path) -> Arc<Vec<PathBuf>> {
+        let state = IgnoreState::read(working_dir);
+        let mut cached = self.cached.lock().unwrap();
+        match cached.as_ref() {
+            Some((cached_state, hidden)) if *cached_state == state => Arc::clone(hidden),
+            _ => {
+                let hidden = Arc::new(ignored_paths(
+                    working_dir,
+                    &load_ignore_patterns(working_dir),
+                ));
+                *cached = Some((state, Arc::clone(&hidden)));
+                hidden
+            }
+        }
+    }
+}
+
+/// Files and directories under `root` matched by the ignore patterns. Matched directories are
+/// hidden whole so their contents aren't walked, and symlinks aren't followed.
+fn ignored_paths(root: &Path, ignore_patterns: &Gitignore) -> Vec<PathBuf> {
+    let mut ignored = Vec::new();
+    let mut pending = vec![root.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(_) => continue,
+        };
+        for entry in entries.flatten() {
+            let file_type = match entry.file_type() {
+                Ok(file_type) if !file_type.is_symlink() => file_type,
+                _ => continue,
+            };
+            let path = entry.path();
+            if ignore_patterns
+                .matched(&path, file_type.is_dir())
+                .is_ignore()
+            {
+                ignored.push(path);
+            } else if file_type.is_dir() {
+                pending.push(path);
+            }
+        }
+    }
+    ignored
+}
+
+/// The bubblewrap executable on the PATH, only supported on Linux
+fn find_bwrap() -> Option<PathBuf> {
+    if !cfg!(target_os = "linux") {
+        return None;
+    }
+    env::var_os("PATH").and_then(|paths| {
+        env::split_paths(&paths)
+            .map(|dir| dir.join("bwrap"))
+            .find(|path| path.is_file())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sandbox() -> SandboxConfig {
+        SandboxConfig {
+            enabled: true,
+            bwrap: Some(PathBuf::from("/usr/bin/bwrap")),
+            allow_unsandboxed: false,
+            writable_paths: vec![PathBuf::from("/")],
+            allow_network: false,
+            cpu_seconds: Some(30),
+            memory_mb: Some(512),
+            timeout_seconds: Some(60),
+        }
+    }
+
+    #[test]
+    fn test_bwrap_args_bind_writable_paths_and_disable_network() {
+        let args = sandbox().bwrap_args(Path::new("/"), &[]);
+        let joined = args.join(" ");
+        assert!(joined.starts_with("--ro-bind / /"));
+        assert!(joined.contains("--unshare-net"));
+        assert!(joined.contains("--bind / /"));
+        assert!(joined.ends_with("--chdir /"));
+
+        let with_network = SandboxConfig {
+            allow_network: true,
+            ..sandbox()
+        };
+        assert!(!with_network
+            .bwrap_args(Path::new("/"), &[])
+            .contains(&"--unshare-net".to_string()));
+    }
+
+    #[test]
+    fn test_ignored_files_are_hidden() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::create_dir_all(root.join("secrets/keys")).unwrap();
+        fs::write(root.join("src/main.rs"), "").unwrap();
+        fs::write(root.join("src/.env"), "").unwrap();
+        fs::write(root.join("secrets/keys/id_rsa"), "").unwrap();
+
+        let mut builder = ignore::gitignore::GitignoreBuilder::new(root);
+        builder.add_line(None, "**/.env").unwrap();
+        builder.add_line(None, "secrets/").unwrap();
+        let ignore_patterns = builder.build().unwrap();
+
+        let mut hidden = ignored_paths(root, &ignore_patterns);
+        hidden.sort();
+        assert_eq!(hidden, vec![root.join("secrets"), root.join("src/.env")]);
+
+        let joined = sandbox().bwrap_args(root, &hidden).join(" ");
+        let bind = format!("--bind {0} {0}", root.display());
+        let mask_dir = format!("--tmpfs {}", root.join("secrets").display());
+        let mask_file = format!("--ro-bind /dev/null {}", root.join("src/.env").display());
+        assert!(joined.contains(&mask_dir));
+        assert!(joined.contains(&mask_file));
+        // Masks only work on top of the writable bind
+        assert!(joined.find(&bind).unwrap() < joined.find(&mask_dir).unwrap());
+    }
+
+    #[test]
+    fn test_hidden_paths_are_walked_again_when_gooseignore_changes() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("secrets")).unwrap();
+        fs::create_dir_all(root.join("notes")).unwrap();
+        fs::write(root.join(".gooseignore"), "secrets/\n").unwrap();
+
+        let hidden_paths = HiddenPaths::default();
+        let first = hidden_paths.get(root);
+        assert!(first.contains(&root.join("secrets")));
+        assert!(!first.contains(&root.join("notes")));
+        assert!(Arc::ptr_eq(&first, &hidden_paths.get(root)));
+
+        fs::write(root.join(".gooseignore"), "secrets/\nnotes/\n").unwrap();
+        let refreshed = hidden_paths.get(root);
+        assert!(refreshed.contains(&root.join("secrets")));
+        assert!(refreshed.contains(&root.join("notes")));
+        assert!(Arc::ptr_eq(&refreshed, &hidden_paths.get(root)));
+    }
+
+    #[test]
+    fn test_limits_prefix() {
+        assert_eq!(
+            sandbox().limits_prefix(),
+            "ulimit -t 30 && ulimit -v 524288 && "
+        );
+        assert_eq!(SandboxConfig::default().limits_prefix(), "");
+    }
+
+    #[test]
+    fn test_describe_only_when_enabled() {
+        let description = sandbox().describe(Path::new("/work")).unwrap();
+        assert!(description.contains("/work"));
+        assert!(description.contains("no network access"));
+        assert!(description.contains("30 seconds of CPU time"));
+        assert!(description.contains("stopped after running for 60 seconds"));
+
+        assert!(SandboxConfig::default()
+            .describe(Path::new("/work"))
+            .is_none());
+
+        // Without bubblewrap only the limits can be promised
+        let unsandboxed = SandboxConfig {
+            bwrap: None,
+            allow_unsandboxed: true,
+            ..sandbox()
+        }
+        .describe(Path::new("/work"))
+        .unwrap();
+        assert!(!unsandboxed.contains("/work"));
+        assert!(unsandboxed.contains("30 seconds of CPU time"));
+        assert!(SandboxConfig::default().timeout().is_none());
+    }
+}
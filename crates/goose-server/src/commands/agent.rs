@@ -1,15 +1,19 @@
 use std::sync::Arc;
+use std::time::Duration;
 
 use crate::configuration;
 use crate::state;
 use anyhow::Result;
 use etcetera::{choose_app_strategy, AppStrategy};
 use goose::agents::Agent;
-use goose::config::APP_STRATEGY;
+use goose::config::{Config, APP_STRATEGY};
 use goose::scheduler::Scheduler as GooseScheduler;
 use tower_http::cors::{Any, CorsLayer};
 use tracing::info;
 
+/// How long an agent session may go unused before it is removed, unless configured
+const DEFAULT_SESSION_IDLE_TIMEOUT_SECS: u64 = 60 * 60;
+
 pub async fn run() -> Result<()> {
     // Initialize logging
     crate::logging::setup_logging(Some("goosed"))?;
@@ -24,6 +28,13 @@ pub async fn run() -> Result<()> {
 
     let app_state = state::AppState::new(agent_ref.clone(), secret_key.clone()).await;
 
+    let idle_timeout = Config::global()
+        .get_param::<u64>("GOOSE_SERVER_SESSION_IDLE_TIMEOUT")
+        .unwrap_or(DEFAULT_SESSION_IDLE_TIMEOUT_SECS);
+    if idle_timeout > 0 {
+        app_state.spawn_session_eviction(Duration::from_secs(idle_timeout));
+    }
+
     let schedule_file_path = choose_app_strategy(APP_STRATEGY.clone())?
         .data_dir()
         .join("schedules.json");
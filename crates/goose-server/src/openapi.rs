@@ -4,13 +4,14 @@ use goose::agents::ExtensionConfig;
 use goose::config::permission::PermissionLevel;
 use goose::config::ExtensionEntry;
 use goose::message::{
-    ContextLengthExceeded, FrontendToolRequest, Message, MessageContent, RedactedThinkingContent,
-    SummarizationRequested, ThinkingContent, ToolConfirmationRequest, ToolRequest, ToolResponse,
+    BudgetExceeded, BudgetKind, ContextLengthExceeded, FrontendToolRequest, Message,
+    MessageContent, RedactedThinkingContent, SummarizationRequested, ThinkingContent,
+    ToolConfirmationRequest, ToolRequest, ToolResponse,
 };
 use goose::permission::permission_confirmation::PrincipalType;
 use goose::providers::base::{ConfigKey, ModelInfo, ProviderMetadata};
 use goose::session::info::SessionInfo;
-use goose::session::SessionMetadata;
+use goose::session::{RetryAttempt, SessionMetadata, SessionSearchHit};
 use mcp_core::content::{Annotations, Content, EmbeddedResource, ImageContent, TextContent};
 use mcp_core::handler::ToolResultSchema;
 use mcp_core::resource::ResourceContents;
@@ -34,9 +35,13 @@ use utoipa::OpenApi;
         super::routes::config_management::providers,
         super::routes::config_management::upsert_permissions,
         super::routes::agent::get_tools,
+        super::routes::agent::create_agent_session,
+        super::routes::agent::list_agent_sessions,
+        super::routes::agent::delete_agent_session,
         super::routes::reply::confirm_permission,
         super::routes::context::manage_context,
         super::routes::session::list_sessions,
+        super::routes::session::search_sessions,
         super::routes::session::get_session_history,
         super::routes::schedule::create_schedule,
         super::routes::schedule::list_schedules,
@@ -59,11 +64,15 @@ use utoipa::OpenApi;
         super::routes::config_management::ExtensionQuery,
         super::routes::config_management::ToolPermission,
         super::routes::config_management::UpsertPermissionsQuery,
+        super::routes::agent::CreateAgentSessionRequest,
+        super::routes::agent::AgentSessionInfo,
         super::routes::reply::PermissionConfirmationRequest,
         super::routes::context::ContextManageRequest,
         super::routes::context::ContextManageResponse,
         super::routes::session::SessionListResponse,
         super::routes::session::SessionHistoryResponse,
+        super::routes::session::SessionSearchResponse,
+        SessionSearchHit,
         Message,
         MessageContent,
         Content,
@@ -81,6 +90,8 @@ use utoipa::OpenApi;
         ResourceContents,
         ContextLengthExceeded,
         SummarizationRequested,
+        BudgetExceeded,
+        BudgetKind,
         Role,
         ProviderMetadata,
         ExtensionEntry,
@@ -95,6 +106,7 @@ use utoipa::OpenApi;
         ModelInfo,
         SessionInfo,
         SessionMetadata,
+        RetryAttempt,
         super::routes::schedule::CreateScheduleRequest,
         super::routes::schedule::UpdateScheduleRequest,
         super::routes::schedule::KillJobResponse,
@@ -1,22 +1,25 @@
-use super::utils::verify_secret_key;
+use super::utils::{get_request_agent, verify_secret_key};
+use crate::state::AgentSessionSummary;
 use crate::state::AppState;
 use axum::{
-    extract::{Query, State},
+    extract::{Path, Query, State},
     http::{HeaderMap, StatusCode},
-    routing::{get, post},
+    routing::{delete, get, post},
     Json, Router,
 };
 use goose::config::Config;
-use goose::config::PermissionManager;
+use goose::config::{ExtensionConfigManager, PermissionManager};
 use goose::model::ModelConfig;
 use goose::providers::create;
+use goose::session;
 use goose::{
-    agents::{extension::ToolInfo, extension_manager::get_parameter_names},
+    agents::{extension::ToolInfo, extension_manager::get_parameter_names, Agent, ExtensionConfig},
     config::permission::PermissionLevel,
 };
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
+use utoipa::ToSchema;
 
 #[derive(Serialize)]
 struct VersionsResponse {
@@ -67,6 +70,43 @@ pub struct GetToolsQuery {
     extension_name: Option<String>,
 }
 
+#[derive(Deserialize, ToSchema)]
+pub struct CreateAgentSessionRequest {
+    /// Id for the new session, generated if not given
+    session_id: Option<String>,
+    /// Provider for the session's agent, the configured provider if not given
+    provider: Option<String>,
+    /// Model for the session's agent, the configured model if not given
+    model: Option<String>,
+    /// Extensions to start, the extensions enabled in the config if not given
+    extensions: Option<Vec<ExtensionConfig>>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct AgentSessionInfo {
+    session_id: String,
+    model: Option<String>,
+    extensions: Vec<String>,
+    created_at: String,
+    idle_seconds: u64,
+}
+
+impl AgentSessionInfo {
+    async fn new(summary: AgentSessionSummary) -> Self {
+        let model = match summary.agent.provider().await {
+            Ok(provider) => Some(provider.get_model_config().model_name),
+            Err(_) => None,
+        };
+        Self {
+            session_id: summary.session_id,
+            model,
+            extensions: summary.agent.list_extensions().await,
+            created_at: summary.created_at.to_rfc3339(),
+            idle_seconds: summary.idle.as_secs(),
+        }
+    }
+}
+
 async fn get_versions() -> Json<VersionsResponse> {
     let versions = ["goose".to_string()];
     let default_version = "goose".to_string();
@@ -84,10 +124,7 @@ async fn extend_prompt(
 ) -> Result<Json<ExtendPromptResponse>, StatusCode> {
     verify_secret_key(&headers, &state)?;
 
-    let agent = state
-        .get_agent()
-        .await
-        .map_err(|_| StatusCode::PRECONDITION_FAILED)?;
+    let agent = get_request_agent(&state, &headers).await?;
     agent.extend_system_prompt(payload.extension.clone()).await;
     Ok(Json(ExtendPromptResponse { success: true }))
 }
@@ -125,6 +162,7 @@ async fn list_providers() -> Json<Vec<ProviderList>> {
         (status = 200, description = "Tools retrieved successfully", body = Vec<ToolInfo>),
         (status = 401, description = "Unauthorized - invalid secret key"),
         (status = 424, description = "Agent not initialized"),
+        (status = 404, description = "No agent session with the id in the X-Session-Id header"),
         (status = 500, description = "Internal server error")
     )
 )]
@@ -137,10 +175,7 @@ async fn get_tools(
 
     let config = Config::global();
     let goose_mode = config.get_param("GOOSE_MODE").unwrap_or("auto".to_string());
-    let agent = state
-        .get_agent()
-        .await
-        .map_err(|_| StatusCode::PRECONDITION_FAILED)?;
+    let agent = get_request_agent(&state, &headers).await?;
     let permission_manager = PermissionManager::default();
 
     let mut tools: Vec<ToolInfo> = agent
@@ -196,10 +231,7 @@ async fn update_agent_provider(
         return Err(StatusCode::UNAUTHORIZED);
     }
 
-    let agent = state
-        .get_agent()
-        .await
-        .map_err(|_| StatusCode::PRECONDITION_FAILED)?;
+    let agent = get_request_agent(&state, &headers).await?;
 
     let config = Config::global();
     let model = payload.model.unwrap_or_else(|| {
@@ -217,6 +249,129 @@ async fn update_agent_provider(
     Ok(StatusCode::OK)
 }
 
+#[utoipa::path(
+    post,
+    path = "/agent/sessions",
+    request_body = CreateAgentSessionRequest,
+    responses(
+        (status = 200, description = "Agent session created", body = AgentSessionInfo),
+        (status = 400, description = "No provider or model given or configured"),
+        (status = 401, description = "Unauthorized - invalid secret key"),
+        (status = 409, description = "An agent session with this id already exists"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+async fn create_agent_session(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(payload): Json<CreateAgentSessionRequest>,
+) -> Result<Json<AgentSessionInfo>, StatusCode> {
+    verify_secret_key(&headers, &state)?;
+
+    let config = Config::global();
+    let provider_name = payload
+        .provider
+        .or_else(|| config.get_param("GOOSE_PROVIDER").ok())
+        .ok_or(StatusCode::BAD_REQUEST)?;
+    let model = payload
+        .model
+        .or_else(|| config.get_param("GOOSE_MODEL").ok())
+        .ok_or(StatusCode::BAD_REQUEST)?;
+    let provider =
+        create(&provider_name, ModelConfig::new(model)).map_err(|_| StatusCode::BAD_REQUEST)?;
+
+    let agent = Agent::new();
+    agent
+        .update_provider(provider)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let extensions = match payload.extensions {
+        Some(extensions) => extensions,
+        None => ExtensionConfigManager::get_all()
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+            .into_iter()
+            .filter(|entry| entry.enabled)
+            .map(|entry| entry.config)
+            .collect(),
+    };
+    // Like the CLI, a session still starts if some of its extensions fail to
+    for extension in extensions {
+        let name = extension.name();
+        if let Err(e) = agent.add_extension(extension).await {
+            tracing::warn!(
+                "Failed to start extension {} for agent session: {}",
+                name,
+                e
+            );
+        }
+    }
+
+    let session_id = payload
+        .session_id
+        .unwrap_or_else(session::generate_session_id);
+    if !state.add_session(session_id.clone(), Arc::new(agent)).await {
+        return Err(StatusCode::CONFLICT);
+    }
+
+    let summary = state
+        .list_sessions()
+        .await
+        .into_iter()
+        .find(|summary| summary.session_id == session_id)
+        .ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(Json(AgentSessionInfo::new(summary).await))
+}
+
+#[utoipa::path(
+    get,
+    path = "/agent/sessions",
+    responses(
+        (status = 200, description = "Agent sessions listed", body = Vec<AgentSessionInfo>),
+        (status = 401, description = "Unauthorized - invalid secret key")
+    )
+)]
+async fn list_agent_sessions(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<AgentSessionInfo>>, StatusCode> {
+    verify_secret_key(&headers, &state)?;
+
+    let mut sessions = Vec::new();
+    for summary in state.list_sessions().await {
+        sessions.push(AgentSessionInfo::new(summary).await);
+    }
+    Ok(Json(sessions))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/agent/sessions/{session_id}",
+    params(
+        ("session_id" = String, Path, description = "Id of the agent session to delete")
+    ),
+    responses(
+        (status = 200, description = "Agent session deleted"),
+        (status = 401, description = "Unauthorized - invalid secret key"),
+        (status = 404, description = "No agent session with this id")
+    )
+)]
+async fn delete_agent_session(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(session_id): Path<String>,
+) -> Result<StatusCode, StatusCode> {
+    verify_secret_key(&headers, &state)?;
+
+    let agent = state
+        .remove_session(&session_id)
+        .await
+        .ok_or(StatusCode::NOT_FOUND)?;
+    // Stop any reply still running so its extensions can shut down
+    agent.cancel();
+    Ok(StatusCode::OK)
+}
+
 pub fn routes(state: Arc<AppState>) -> Router {
     Router::new()
         .route("/agent/versions", get(get_versions))
@@ -224,5 +379,10 @@ pub fn routes(state: Arc<AppState>) -> Router {
         .route("/agent/prompt", post(extend_prompt))
         .route("/agent/tools", get(get_tools))
         .route("/agent/update_provider", post(update_agent_provider))
+        .route(
+            "/agent/sessions",
+            get(list_agent_sessions).post(create_agent_session),
+        )
+        .route("/agent/sessions/{session_id}", delete(delete_agent_session))
         .with_state(state)
 }
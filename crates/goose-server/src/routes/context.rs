@@ -1,4 +1,4 @@
-use super::utils::verify_secret_key;
+use super::utils::{get_request_agent, verify_secret_key};
 use crate::state::AppState;
 use axum::{
     extract::State,
@@ -39,6 +39,7 @@ pub struct ContextManageResponse {
         (status = 200, description = "Context managed successfully", body = ContextManageResponse),
         (status = 401, description = "Unauthorized - Invalid or missing API key"),
         (status = 412, description = "Precondition failed - Agent not available"),
+        (status = 404, description = "No agent session with the id in the X-Session-Id header"),
         (status = 500, description = "Internal server error")
     ),
     security(
@@ -53,10 +54,7 @@ async fn manage_context(
 ) -> Result<Json<ContextManageResponse>, StatusCode> {
     verify_secret_key(&headers, &state)?;
 
-    let agent = state
-        .get_agent()
-        .await
-        .map_err(|_| StatusCode::PRECONDITION_FAILED)?;
+    let agent = get_request_agent(&state, &headers).await?;
 
     let mut processed_messages: Vec<Message> = vec![];
     let mut token_counts: Vec<usize> = vec![];
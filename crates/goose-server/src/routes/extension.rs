@@ -1,9 +1,10 @@
+use std::collections::HashMap;
 use std::env;
 use std::path::Path;
 use std::sync::Arc;
 use std::sync::OnceLock;
 
-use super::utils::verify_secret_key;
+use super::utils::{get_request_agent, verify_secret_key};
 use crate::state::AppState;
 use axum::{extract::State, routing::post, Json, Router};
 use goose::agents::{extension::Envs, ExtensionConfig};
@@ -29,6 +30,30 @@ enum ExtensionConfigRequest {
         #[serde(default)]
         env_keys: Vec<String>,
         timeout: Option<u64>,
+        /// Extra directories exposed to the extension as MCP roots.
+        #[serde(default)]
+        roots: Vec<String>,
+    },
+    /// Streamable HTTP extension.
+    #[serde(rename = "streamable_http")]
+    StreamableHttp {
+        /// The name to identify this extension
+        name: String,
+        /// The URI of the MCP endpoint.
+        uri: String,
+        #[serde(default)]
+        /// Map of environment variable key to values.
+        envs: Envs,
+        /// List of environment variable keys. The server will fetch their values from the keyring.
+        #[serde(default)]
+        env_keys: Vec<String>,
+        /// Extra HTTP headers sent with every request.
+        #[serde(default)]
+        headers: HashMap<String, String>,
+        timeout: Option<u64>,
+        /// Extra directories exposed to the extension as MCP roots.
+        #[serde(default)]
+        roots: Vec<String>,
     },
     /// Standard I/O (stdio) extension.
     #[serde(rename = "stdio")]
@@ -47,6 +72,9 @@ enum ExtensionConfigRequest {
         #[serde(default)]
         env_keys: Vec<String>,
         timeout: Option<u64>,
+        /// Extra directories exposed to the extension as MCP roots.
+        #[serde(default)]
+        roots: Vec<String>,
     },
     /// Built-in extension that is part of the goose binary.
     #[serde(rename = "builtin")]
@@ -167,6 +195,7 @@ async fn add_extension(
             envs,
             env_keys,
             timeout,
+            roots,
         } => ExtensionConfig::Sse {
             name,
             uri,
@@ -174,6 +203,26 @@ async fn add_extension(
             env_keys,
             description: None,
             timeout,
+            roots,
+            bundled: None,
+        },
+        ExtensionConfigRequest::StreamableHttp {
+            name,
+            uri,
+            envs,
+            env_keys,
+            headers,
+            timeout,
+            roots,
+        } => ExtensionConfig::StreamableHttp {
+            name,
+            uri,
+            envs,
+            env_keys,
+            headers,
+            description: None,
+            timeout,
+            roots,
             bundled: None,
         },
         ExtensionConfigRequest::Stdio {
@@ -183,6 +232,7 @@ async fn add_extension(
             envs,
             env_keys,
             timeout,
+            roots,
         } => {
             // TODO: We can uncomment once bugs are fixed. Check allowlist for Stdio extensions
             // if !is_command_allowed(&cmd, &args) {
@@ -204,6 +254,7 @@ async fn add_extension(
                 envs,
                 env_keys,
                 timeout,
+                roots,
                 bundled: None,
             }
         }
@@ -230,10 +281,7 @@ async fn add_extension(
     };
 
     // Get a reference to the agent
-    let agent = state
-        .get_agent()
-        .await
-        .map_err(|_| StatusCode::PRECONDITION_FAILED)?;
+    let agent = get_request_agent(&state, &headers).await?;
     let response = agent.add_extension(extension_config).await;
 
     // Respond with the result.
@@ -264,10 +312,7 @@ async fn remove_extension(
     verify_secret_key(&headers, &state)?;
 
     // Get a reference to the agent
-    let agent = state
-        .get_agent()
-        .await
-        .map_err(|_| StatusCode::PRECONDITION_FAILED)?;
+    let agent = get_request_agent(&state, &headers).await?;
     match agent.remove_extension(&name).await {
         Ok(_) => Ok(Json(ExtensionResponse {
             error: false,
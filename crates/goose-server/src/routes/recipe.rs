@@ -1,10 +1,16 @@
 use std::sync::Arc;
 
-use axum::{extract::State, http::StatusCode, routing::post, Json, Router};
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    routing::post,
+    Json, Router,
+};
 use goose::message::Message;
 use goose::recipe::Recipe;
 use serde::{Deserialize, Serialize};
 
+use super::utils::get_request_agent;
 use crate::state::AppState;
 
 #[derive(Debug, Deserialize)]
@@ -37,16 +43,16 @@ pub struct CreateRecipeResponse {
 /// Create a Recipe configuration from the current state of an agent
 async fn create_recipe(
     State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
     Json(request): Json<CreateRecipeRequest>,
 ) -> Result<Json<CreateRecipeResponse>, (StatusCode, Json<CreateRecipeResponse>)> {
     let error_response = CreateRecipeResponse {
         recipe: None,
         error: Some("Missing agent".to_string()),
     };
-    let agent = state
-        .get_agent()
+    let agent = get_request_agent(&state, &headers)
         .await
-        .map_err(|_| (StatusCode::PRECONDITION_FAILED, Json(error_response)))?;
+        .map_err(|status| (status, Json(error_response)))?;
 
     // Create base recipe from agent state and messages
     let recipe_result = agent.create_recipe(request.messages).await;
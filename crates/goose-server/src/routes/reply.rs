@@ -1,4 +1,4 @@
-use super::utils::verify_secret_key;
+use super::utils::{get_request_agent, verify_secret_key};
 use crate::state::AppState;
 use axum::{
     extract::State,
@@ -10,8 +10,8 @@ use axum::{
 use bytes::Bytes;
 use futures::{stream::StreamExt, Stream};
 use goose::{
-    agents::{AgentEvent, SessionConfig},
-    message::{Message, MessageContent},
+    agents::{AgentEvent, ReplyBudget, SessionConfig, SubagentEvent, ToolProgress},
+    message::{Message, MessageContent, MessageDelta},
     permission::permission_confirmation::PrincipalType,
 };
 use goose::{
@@ -40,6 +40,9 @@ struct ChatRequest {
     messages: Vec<Message>,
     session_id: Option<String>,
     session_working_dir: String,
+    /// Limits for this reply, overriding the ones in the config
+    #[serde(default)]
+    budget: ReplyBudget,
 }
 
 pub struct SseResponse {
@@ -82,12 +85,26 @@ enum MessageEvent {
     Message {
         message: Message,
     },
+    MessageDelta {
+        delta: MessageDelta,
+    },
     Error {
         error: String,
     },
     Finish {
         reason: String,
     },
+    ToolProgress {
+        #[serde(flatten)]
+        progress: ToolProgress,
+    },
+    HistoryReplaced {
+        messages: Vec<Message>,
+    },
+    Subagent {
+        #[serde(flatten)]
+        event: SubagentEvent,
+    },
     Notification {
         request_id: String,
         message: JsonRpcMessage,
@@ -119,13 +136,20 @@ async fn handler(
 
     let messages = request.messages;
     let session_working_dir = request.session_working_dir;
+    let budget = request.budget;
 
     let session_id = request
         .session_id
         .unwrap_or_else(session::generate_session_id);
 
+    // An unknown agent session is an error for the request itself, a missing default agent
+    // is reported on the stream
+    let agent = match get_request_agent(&state, &headers).await {
+        Err(StatusCode::NOT_FOUND) => return Err(StatusCode::NOT_FOUND),
+        agent => agent,
+    };
+
     tokio::spawn(async move {
-        let agent = state.get_agent().await;
         let agent = match agent {
             Ok(agent) => {
                 let provider = agent.provider().await;
@@ -178,6 +202,7 @@ async fn handler(
                     id: session::Identifier::Name(session_id.clone()),
                     working_dir: PathBuf::from(session_working_dir),
                     schedule_id: None,
+                    budget,
                 }),
             )
             .await
@@ -233,6 +258,34 @@ async fn handler(
                                 }
                             });
                         }
+                        Ok(Some(Ok(AgentEvent::MessageDelta(delta)))) => {
+                            // Deltas are forwarded for live rendering only, the complete
+                            // message that follows is the one that gets persisted
+                            if let Err(e) = stream_event(MessageEvent::MessageDelta { delta }, &tx).await {
+                                tracing::error!("Error sending message through channel: {}", e);
+                                break;
+                            }
+                        }
+                        Ok(Some(Ok(AgentEvent::ToolProgress(progress)))) => {
+                            if let Err(e) = stream_event(MessageEvent::ToolProgress { progress }, &tx).await {
+                                tracing::error!("Error sending message through channel: {}", e);
+                                break;
+                            }
+                        }
+                        Ok(Some(Ok(AgentEvent::Subagent(event)))) => {
+                            if let Err(e) = stream_event(MessageEvent::Subagent { event }, &tx).await {
+                                tracing::error!("Error sending message through channel: {}", e);
+                                break;
+                            }
+                        }
+                        Ok(Some(Ok(AgentEvent::HistoryReplaced(messages)))) => {
+                            // The agent already saved the compacted history to the session
+                            all_messages = messages.clone();
+                            if let Err(e) = stream_event(MessageEvent::HistoryReplaced { messages }, &tx).await {
+                                tracing::error!("Error sending message through channel: {}", e);
+                                break;
+                            }
+                        }
                         Ok(Some(Ok(AgentEvent::McpNotification((request_id, n))))) => {
                             if let Err(e) = stream_event(MessageEvent::Notification{
                                 request_id: request_id.clone(),
@@ -308,10 +361,7 @@ async fn ask_handler(
         .session_id
         .unwrap_or_else(session::generate_session_id);
 
-    let agent = state
-        .get_agent()
-        .await
-        .map_err(|_| StatusCode::PRECONDITION_FAILED)?;
+    let agent = get_request_agent(&state, &headers).await?;
 
     let provider = agent.provider().await;
 
@@ -325,6 +375,7 @@ async fn ask_handler(
                 id: session::Identifier::Name(session_id.clone()),
                 working_dir: PathBuf::from(session_working_dir),
                 schedule_id: None,
+                budget: ReplyBudget::default(),
             }),
         )
         .await
@@ -352,6 +403,18 @@ async fn ask_handler(
                     }
                 }
             }
+            Ok(AgentEvent::MessageDelta(_)) => {
+                // The complete message follows, which is what we collect
+            }
+            Ok(AgentEvent::ToolProgress(_)) => {
+                // Only the final answer is returned
+            }
+            Ok(AgentEvent::HistoryReplaced(messages)) => {
+                all_messages = messages;
+            }
+            Ok(AgentEvent::Subagent(_)) => {
+                // Only the final answer is returned
+            }
             Ok(AgentEvent::McpNotification(n)) => {
                 // Handle notifications if needed
                 tracing::info!("Received notification: {:?}", n);
@@ -402,6 +465,7 @@ fn default_principal_type() -> PrincipalType {
     responses(
         (status = 200, description = "Permission action is confirmed", body = Value),
         (status = 401, description = "Unauthorized - invalid secret key"),
+        (status = 404, description = "No agent session with the id in the X-Session-Id header"),
         (status = 500, description = "Internal server error")
     )
 )]
@@ -412,10 +476,7 @@ pub async fn confirm_permission(
 ) -> Result<Json<Value>, StatusCode> {
     verify_secret_key(&headers, &state)?;
 
-    let agent = state
-        .get_agent()
-        .await
-        .map_err(|_| StatusCode::PRECONDITION_FAILED)?;
+    let agent = get_request_agent(&state, &headers).await?;
 
     let permission = match request.action.as_str() {
         "always_allow" => Permission::AlwaysAllow,
@@ -466,20 +527,30 @@ async fn submit_tool_result(
         }
     };
 
-    let agent = state
-        .get_agent()
-        .await
-        .map_err(|_| StatusCode::PRECONDITION_FAILED)?;
+    let agent = get_request_agent(&state, &headers).await?;
     agent.handle_tool_result(payload.id, payload.result).await;
     Ok(Json(json!({"status": "ok"})))
 }
 
+/// Cancel the reply in progress, stopping any tool calls it is waiting on
+async fn cancel_reply(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, StatusCode> {
+    verify_secret_key(&headers, &state)?;
+
+    let agent = get_request_agent(&state, &headers).await?;
+    agent.cancel();
+    Ok(Json(json!({"status": "ok"})))
+}
+
 pub fn routes(state: Arc<AppState>) -> Router {
     Router::new()
         .route("/reply", post(handler))
         .route("/ask", post(ask_handler))
         .route("/confirm", post(confirm_permission))
         .route("/tool_result", post(submit_tool_result))
+        .route("/reply/cancel", post(cancel_reply))
         .with_state(state)
 }
 
@@ -567,5 +638,26 @@ mod tests {
 
             assert_eq!(response.status(), StatusCode::OK);
         }
+
+        #[tokio::test]
+        async fn test_unknown_agent_session_is_not_found() {
+            let state = AppState::new(Arc::new(Agent::new()), "test-secret".to_string()).await;
+            let app = routes(state);
+
+            let request = Request::builder()
+                .uri("/confirm")
+                .method("POST")
+                .header("content-type", "application/json")
+                .header("x-secret-key", "test-secret")
+                .header("x-session-id", "no-such-session")
+                .body(Body::from(
+                    json!({ "id": "tool_1", "action": "allow_once" }).to_string(),
+                ))
+                .unwrap();
+
+            let response = app.oneshot(request).await.unwrap();
+
+            assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        }
     }
 }
@@ -79,6 +79,7 @@ pub struct SessionDisplayInfo {
     accumulated_total_tokens: Option<i32>,
     accumulated_input_tokens: Option<i32>,
     accumulated_output_tokens: Option<i32>,
+    accumulated_cost: Option<f64>,
 }
 
 fn parse_session_name_to_iso(session_name: &str) -> String {
@@ -280,6 +281,7 @@ async fn sessions_handler(
                     accumulated_total_tokens: metadata.accumulated_total_tokens,
                     accumulated_input_tokens: metadata.accumulated_input_tokens,
                     accumulated_output_tokens: metadata.accumulated_output_tokens,
+                    accumulated_cost: metadata.accumulated_cost,
                 })
                 .collect();
             Ok(Json(display_infos))
@@ -3,7 +3,7 @@ use std::sync::Arc;
 
 use crate::state::AppState;
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::{HeaderMap, StatusCode},
     routing::get,
     Json, Router,
@@ -11,8 +11,8 @@ use axum::{
 use goose::message::Message;
 use goose::session;
 use goose::session::info::{get_session_info, SessionInfo, SortOrder};
-use goose::session::SessionMetadata;
-use serde::Serialize;
+use goose::session::{SessionMetadata, SessionSearchHit};
+use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
 #[derive(Serialize, ToSchema)]
@@ -59,6 +59,56 @@ async fn list_sessions(
     Ok(Json(SessionListResponse { sessions }))
 }
 
+#[derive(Deserialize, utoipa::IntoParams)]
+pub struct SessionSearchQuery {
+    /// Words that must all appear in a message
+    query: String,
+    /// Maximum number of messages to return
+    #[serde(default = "default_search_limit")]
+    limit: usize,
+}
+
+fn default_search_limit() -> usize {
+    50
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionSearchResponse {
+    /// Matching messages, most recent sessions first
+    results: Vec<SessionSearchHit>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/sessions/search",
+    params(SessionSearchQuery),
+    responses(
+        (status = 200, description = "Messages matching the search", body = SessionSearchResponse),
+        (status = 401, description = "Unauthorized - Invalid or missing API key"),
+        (status = 500, description = "Internal server error")
+    ),
+    security(
+        ("api_key" = [])
+    ),
+    tag = "Session Management"
+)]
+// Search the messages of all sessions
+async fn search_sessions(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Query(params): Query<SessionSearchQuery>,
+) -> Result<Json<SessionSearchResponse>, StatusCode> {
+    verify_secret_key(&headers, &state)?;
+
+    let results = session::search_sessions(&params.query, params.limit).map_err(|e| {
+        tracing::error!("Failed to search sessions: {:?}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })?;
+
+    Ok(Json(SessionSearchResponse { results }))
+}
+
 #[utoipa::path(
     get,
     path = "/sessions/{session_id}",
@@ -108,6 +158,7 @@ async fn get_session_history(
 pub fn routes(state: Arc<AppState>) -> Router {
     Router::new()
         .route("/sessions", get(list_sessions))
+        .route("/sessions/search", get(search_sessions))
         .route("/sessions/{session_id}", get(get_session_history))
         .with_state(state)
 }
@@ -1,4 +1,4 @@
-use crate::state::AppState;
+use crate::state::{AgentRef, AppState};
 use goose::config::Config;
 use goose::providers::base::{ConfigKey, ProviderMetadata};
 use http::{HeaderMap, StatusCode};
@@ -37,6 +37,30 @@ pub fn verify_secret_key(headers: &HeaderMap, state: &AppState) -> Result<Status
     }
 }
 
+/// Header naming the agent session a request is for
+pub const SESSION_ID_HEADER: &str = "X-Session-Id";
+
+/// The agent a request is for: the agent session named by the X-Session-Id header, or the
+/// default agent for requests without one
+pub async fn get_request_agent(
+    state: &AppState,
+    headers: &HeaderMap,
+) -> Result<AgentRef, StatusCode> {
+    match headers
+        .get(SESSION_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+    {
+        Some(session_id) => state
+            .get_session_agent(session_id)
+            .await
+            .ok_or(StatusCode::NOT_FOUND),
+        None => state
+            .get_agent()
+            .await
+            .map_err(|_| StatusCode::PRECONDITION_FAILED),
+    }
+}
+
 /// Inspects a configuration key to determine if it's set, its location, and value (for non-secret keys)
 #[allow(dead_code)]
 pub fn inspect_key(key_name: &str, is_secret: bool) -> Result<KeyInfo, Box<dyn Error>> {
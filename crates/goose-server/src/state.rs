@@ -1,13 +1,35 @@
+use chrono::{DateTime, Utc};
 use goose::agents::Agent;
 use goose::scheduler::Scheduler;
+use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::Mutex;
 
 pub type AgentRef = Arc<Agent>;
 
+/// How often idle agent sessions are looked for
+const EVICTION_INTERVAL: Duration = Duration::from_secs(60);
+
+/// An agent created for one client session, with its own provider and extensions
+struct AgentSession {
+    agent: AgentRef,
+    created_at: DateTime<Utc>,
+    last_active: Instant,
+}
+
+/// A snapshot of an agent session, for listing
+pub struct AgentSessionSummary {
+    pub session_id: String,
+    pub agent: AgentRef,
+    pub created_at: DateTime<Utc>,
+    pub idle: Duration,
+}
+
 #[derive(Clone)]
 pub struct AppState {
     agent: Option<AgentRef>,
+    sessions: Arc<Mutex<HashMap<String, AgentSession>>>,
     pub secret_key: String,
     pub scheduler: Arc<Mutex<Option<Arc<Scheduler>>>>,
 }
@@ -16,17 +38,101 @@ impl AppState {
     pub async fn new(agent: AgentRef, secret_key: String) -> Arc<AppState> {
         Arc::new(Self {
             agent: Some(agent.clone()),
+            sessions: Arc::new(Mutex::new(HashMap::new())),
             secret_key,
             scheduler: Arc::new(Mutex::new(None)),
         })
     }
 
+    /// The default agent, used by requests that don't name an agent session
     pub async fn get_agent(&self) -> Result<Arc<Agent>, anyhow::Error> {
         self.agent
             .clone()
             .ok_or_else(|| anyhow::anyhow!("Agent needs to be created first."))
     }
 
+    /// The agent of `session_id`, which counts as activity for idle eviction
+    pub async fn get_session_agent(&self, session_id: &str) -> Option<AgentRef> {
+        let mut sessions = self.sessions.lock().await;
+        sessions.get_mut(session_id).map(|session| {
+            session.last_active = Instant::now();
+            session.agent.clone()
+        })
+    }
+
+    /// Register `agent` under `session_id`, returning false if the id is already taken
+    pub async fn add_session(&self, session_id: String, agent: AgentRef) -> bool {
+        let mut sessions = self.sessions.lock().await;
+        if sessions.contains_key(&session_id) {
+            return false;
+        }
+        sessions.insert(
+            session_id,
+            AgentSession {
+                agent,
+                created_at: Utc::now(),
+                last_active: Instant::now(),
+            },
+        );
+        true
+    }
+
+    /// Remove an agent session. Its extensions shut down once in-flight requests finish.
+    pub async fn remove_session(&self, session_id: &str) -> Option<AgentRef> {
+        self.sessions
+            .lock()
+            .await
+            .remove(session_id)
+            .map(|session| session.agent)
+    }
+
+    pub async fn list_sessions(&self) -> Vec<AgentSessionSummary> {
+        let sessions = self.sessions.lock().await;
+        let mut summaries: Vec<AgentSessionSummary> = sessions
+            .iter()
+            .map(|(session_id, session)| AgentSessionSummary {
+                session_id: session_id.clone(),
+                agent: session.agent.clone(),
+                created_at: session.created_at,
+                idle: session.last_active.elapsed(),
+            })
+            .collect();
+        summaries.sort_by_key(|summary| summary.created_at);
+        summaries
+    }
+
+    /// Remove the sessions that have been idle for longer than `idle_timeout`, skipping any
+    /// whose agent is still in use by a request
+    pub async fn evict_idle_sessions(&self, idle_timeout: Duration) -> Vec<String> {
+        let mut sessions = self.sessions.lock().await;
+        let idle: Vec<String> = sessions
+            .iter()
+            .filter(|(_, session)| {
+                session.last_active.elapsed() > idle_timeout
+                    && Arc::strong_count(&session.agent) == 1
+            })
+            .map(|(session_id, _)| session_id.clone())
+            .collect();
+        for session_id in &idle {
+            sessions.remove(session_id);
+        }
+        idle
+    }
+
+    /// Periodically evict agent sessions idle for longer than `idle_timeout`
+    pub fn spawn_session_eviction(self: &Arc<Self>, idle_timeout: Duration) {
+        let state = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(EVICTION_INTERVAL);
+            loop {
+                interval.tick().await;
+                for session_id in state.evict_idle_sessions(idle_timeout).await {
+                    tracing::info!("Evicted idle agent session {}", session_id);
+                }
+            }
+        });
+    }
+
     pub async fn set_scheduler(&self, sched: Arc<Scheduler>) {
         let mut guard = self.scheduler.lock().await;
         *guard = Some(sched);
@@ -40,3 +146,43 @@ impl AppState {
             .ok_or_else(|| anyhow::anyhow!("Scheduler not initialized"))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_sessions_are_isolated_and_evicted_when_idle() {
+        let state = AppState::new(Arc::new(Agent::new()), "secret".to_string()).await;
+        assert!(
+            state
+                .add_session("a".to_string(), Arc::new(Agent::new()))
+                .await
+        );
+        assert!(
+            state
+                .add_session("b".to_string(), Arc::new(Agent::new()))
+                .await
+        );
+        assert!(
+            !state
+                .add_session("a".to_string(), Arc::new(Agent::new()))
+                .await
+        );
+
+        let a = state.get_session_agent("a").await.unwrap();
+        let b = state.get_session_agent("b").await.unwrap();
+        assert!(!Arc::ptr_eq(&a, &b));
+        assert!(state.get_session_agent("missing").await.is_none());
+
+        // A session whose agent is in use by a request is kept
+        drop(b);
+        let evicted = state.evict_idle_sessions(Duration::ZERO).await;
+        assert_eq!(evicted, vec!["b".to_string()]);
+        assert_eq!(state.list_sessions().await.len(), 1);
+
+        drop(a);
+        assert!(state.remove_session("a").await.is_some());
+        assert!(state.list_sessions().await.is_empty());
+    }
+}
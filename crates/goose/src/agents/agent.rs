@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::future::Future;
 use std::pin::Pin;
 use std::sync::Arc;
@@ -11,21 +11,28 @@ use futures_util::stream::StreamExt;
 use mcp_core::protocol::JsonRpcMessage;
 
 use crate::config::{Config, ExtensionConfigManager, PermissionManager};
-use crate::message::Message;
+use crate::context_mgmt::compact::AutoCompactConfig;
+use crate::message::{Message, MessageContent, MessageDelta};
 use crate::permission::permission_judge::check_tool_permissions;
 use crate::permission::PermissionConfirmation;
-use crate::providers::base::Provider;
+use crate::providers::base::{Provider, ProviderStreamItem};
 use crate::providers::errors::ProviderError;
-use crate::recipe::{Author, Recipe};
+use crate::providers::pricing;
+use crate::recipe::{Author, Recipe, SubRecipeTemplate};
+use crate::session;
 use crate::tool_monitor::{ToolCall, ToolMonitor};
 use regex::Regex;
+use serde::Serialize;
 use serde_json::Value;
-use tokio::sync::{mpsc, Mutex};
+use tokio::sync::{mpsc, watch, Mutex};
 use tracing::{debug, error, instrument};
 
+use crate::agents::budget::{BudgetTracker, ReplyBudget};
 use crate::agents::extension::{ExtensionConfig, ExtensionError, ExtensionResult, ToolInfo};
 use crate::agents::extension_manager::{get_parameter_names, ExtensionManager};
+use crate::agents::final_output::{FinalOutput, MAX_FINAL_OUTPUT_REMINDERS};
 use crate::agents::platform_tools::{
+    PLATFORM_DELEGATE_TOOL_NAME, PLATFORM_FINAL_OUTPUT_TOOL_NAME,
     PLATFORM_LIST_RESOURCES_TOOL_NAME, PLATFORM_MANAGE_EXTENSIONS_TOOL_NAME,
     PLATFORM_READ_RESOURCE_TOOL_NAME, PLATFORM_SEARCH_AVAILABLE_EXTENSIONS_TOOL_NAME,
 };
@@ -34,6 +41,10 @@ use crate::agents::router_tool_selector::{
     create_tool_selector, RouterToolSelectionStrategy, RouterToolSelector,
 };
 use crate::agents::router_tools::ROUTER_VECTOR_SEARCH_TOOL_NAME;
+use crate::agents::sampling::{
+    SamplingContext, SamplingReceiver, SamplingRequests, SharedProvider,
+};
+use crate::agents::subagent::SubagentEvent;
 use crate::agents::tool_router_index_manager::ToolRouterIndexManager;
 use crate::agents::tool_vectordb::generate_table_id;
 use crate::agents::types::SessionConfig;
@@ -48,7 +59,7 @@ use super::tool_execution::{ToolCallResult, CHAT_MODE_TOOL_SKIPPED_RESPONSE, DEC
 
 /// The main goose Agent
 pub struct Agent {
-    pub(super) provider: Mutex<Option<Arc<dyn Provider>>>,
+    pub(super) provider: SharedProvider,
     pub(super) extension_manager: Mutex<ExtensionManager>,
     pub(super) frontend_tools: Mutex<HashMap<String, FrontendTool>>,
     pub(super) frontend_instructions: Mutex<Option<String>>,
@@ -59,23 +70,106 @@ pub struct Agent {
     pub(super) tool_result_rx: ToolResultReceiver,
     pub(super) tool_monitor: Mutex<Option<ToolMonitor>>,
     pub(super) router_tool_selector: Mutex<Option<Arc<Box<dyn RouterToolSelector>>>>,
+    pub(super) sampling_rx: SamplingReceiver,
+    pub(super) cancel_tx: watch::Sender<bool>,
+    /// Set on sub-agents to the limits each of their replies runs under
+    pub(super) subagent_budget: Option<ReplyBudget>,
+    /// The structured result replies must report, when the caller asked for one
+    pub(super) final_output: Mutex<Option<FinalOutput>>,
+    /// Recipes the agent can run as tools in child agents
+    pub(super) sub_recipes: Mutex<Vec<SubRecipeTemplate>>,
+    /// The session of the latest reply, which sub-recipe sessions are recorded as children of
+    pub(super) session: Mutex<Option<SessionConfig>>,
 }
 
 #[derive(Clone, Debug)]
 pub enum AgentEvent {
     Message(Message),
+    /// Partial assistant output for incremental rendering, always followed by the full `Message`
+    MessageDelta(MessageDelta),
+    /// Progress an extension reported for a running tool call
+    ToolProgress(ToolProgress),
+    /// What a sub-agent started by the delegate tool is doing
+    Subagent(SubagentEvent),
+    /// The conversation was compacted. These messages replace the history the reply started
+    /// from along with everything it has yielded so far.
+    HistoryReplaced(Vec<Message>),
     McpNotification((String, JsonRpcMessage)),
 }
 
+/// A `notifications/progress` update for the tool request `request_id`
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ToolProgress {
+    pub request_id: String,
+    pub progress: f64,
+    pub total: Option<f64>,
+    pub message: Option<String>,
+}
+
+impl ToolProgress {
+    /// Parse a progress notification, returning None for any other message
+    pub fn from_notification(request_id: &str, message: &JsonRpcMessage) -> Option<Self> {
+        let params = match message {
+            JsonRpcMessage::Notification(notification)
+                if notification.method == "notifications/progress" =>
+            {
+                notification.params.as_ref()?
+            }
+            _ => return None,
+        };
+
+        Some(Self {
+            request_id: request_id.to_string(),
+            progress: params.get("progress")?.as_f64()?,
+            total: params.get("total").and_then(Value::as_f64),
+            message: params
+                .get("message")
+                .and_then(Value::as_str)
+                .map(str::to_string),
+        })
+    }
+}
+
+/// The response recorded for tool calls that were still running when the reply was cancelled
+const CANCELLED_RESPONSE: &str = "The user cancelled this tool call before it finished.";
+
+/// The response recorded for tool calls that were still running when the reply ran out of time
+const OUT_OF_TIME_RESPONSE: &str = "This tool call was stopped because the reply ran out of time.";
+
+/// Answer every tool call that has not finished yet with an error explaining why it stopped
+fn with_unfinished_tool_responses(
+    mut message: Message,
+    pending: &mut HashSet<String>,
+    reason: &str,
+) -> Message {
+    for request_id in pending.drain() {
+        message = message.with_tool_response(
+            request_id,
+            Err(ToolError::ExecutionError(reason.to_string())),
+        );
+    }
+    message
+}
+
 impl Agent {
     pub fn new() -> Self {
         // Create channels with buffer size 32 (adjust if needed)
         let (confirm_tx, confirm_rx) = mpsc::channel(32);
         let (tool_tx, tool_rx) = mpsc::channel(32);
+        let (sampling_tx, sampling_rx) = mpsc::channel(32);
+        let sampling_rx: SamplingReceiver = Arc::new(Mutex::new(sampling_rx));
+
+        let provider: SharedProvider = Arc::new(Mutex::new(None));
+        let mut extension_manager = ExtensionManager::new();
+        extension_manager.set_sampling_context(SamplingContext::new(
+            provider.clone(),
+            sampling_tx,
+            sampling_rx.clone(),
+        ));
 
         Self {
-            provider: Mutex::new(None),
-            extension_manager: Mutex::new(ExtensionManager::new()),
+            provider,
+            extension_manager: Mutex::new(extension_manager),
             frontend_tools: Mutex::new(HashMap::new()),
             frontend_instructions: Mutex::new(None),
             prompt_manager: Mutex::new(PromptManager::new()),
@@ -85,6 +179,12 @@ impl Agent {
             tool_result_rx: Arc::new(Mutex::new(tool_rx)),
             tool_monitor: Mutex::new(None),
             router_tool_selector: Mutex::new(None),
+            sampling_rx,
+            cancel_tx: watch::channel(false).0,
+            subagent_budget: None,
+            final_output: Mutex::new(None),
+            sub_recipes: Mutex::new(Vec::new()),
+            session: Mutex::new(None),
         }
     }
 
@@ -132,7 +232,9 @@ where
         let mut rx = rx;
 
         loop {
+            // Pass on notifications that are already waiting before the result
             tokio::select! {
+                biased;
                 Some(msg) = rx.next() => {
                     yield ToolStreamItem::Message(msg);
                 }
@@ -183,7 +285,10 @@ impl Agent {
     }
 
     /// Dispatch a single tool call to the appropriate client
-    #[instrument(skip(self, tool_call, request_id), fields(input, output))]
+    #[instrument(
+        skip(self, tool_call, request_id),
+        fields(input, output, tool_name = %tool_call.name, tool_call_id = %request_id)
+    )]
     pub(super) async fn dispatch_tool_call(
         &self,
         tool_call: mcp_core::tool::ToolCall,
@@ -203,6 +308,17 @@ impl Agent {
             }
         }
 
+        if tool_call.name == PLATFORM_DELEGATE_TOOL_NAME {
+            return (request_id, self.delegate(tool_call.arguments).await);
+        }
+
+        if let Some(template) = self.sub_recipe_for_tool(&tool_call.name).await {
+            return (
+                request_id,
+                self.run_sub_recipe(template, tool_call.arguments).await,
+            );
+        }
+
         if tool_call.name == PLATFORM_MANAGE_EXTENSIONS_TOOL_NAME {
             let extension_name = tool_call
                 .arguments
@@ -433,6 +549,11 @@ impl Agent {
             prefixed_tools.push(platform_tools::search_available_extensions_tool());
             prefixed_tools.push(platform_tools::manage_extensions_tool());
 
+            // Sub-agents do not delegate further
+            if self.subagent_budget.is_none() {
+                prefixed_tools.push(platform_tools::delegate_tool());
+            }
+
             // Add resource tools if supported
             if extension_manager.supports_resources() {
                 prefixed_tools.push(platform_tools::read_resource_tool());
@@ -500,6 +621,15 @@ impl Agent {
         Ok(())
     }
 
+    /// Update the working directory extensions see as their root
+    pub async fn set_working_dir(&self, working_dir: std::path::PathBuf) {
+        self.extension_manager
+            .lock()
+            .await
+            .set_working_dir(working_dir)
+            .await;
+    }
+
     pub async fn list_extensions(&self) -> Vec<String> {
         let extension_manager = self.extension_manager.lock().await;
         extension_manager
@@ -519,6 +649,12 @@ impl Agent {
         }
     }
 
+    /// Cancel the reply in progress. Running tool calls are cancelled on their extensions and
+    /// recorded as cancelled, then the reply stream ends.
+    pub fn cancel(&self) {
+        self.cancel_tx.send_replace(true);
+    }
+
     #[instrument(skip(self, messages, session), fields(user_message))]
     pub async fn reply(
         &self,
@@ -528,6 +664,25 @@ impl Agent {
         let mut messages = messages.to_vec();
         let reply_span = tracing::Span::current();
 
+        // A cancel only applies to the reply that was running when it was requested
+        self.cancel_tx.send_replace(false);
+        let mut cancel_rx = self.cancel_tx.subscribe();
+
+        // Keep the roots extensions see in step with the session
+        if let Some(session_config) = &session {
+            self.set_working_dir(session_config.working_dir.clone())
+                .await;
+        }
+        *self.session.lock().await = session.clone();
+
+        // Tool permission policies resolve relative paths against this
+        let working_dir = self
+            .extension_manager
+            .lock()
+            .await
+            .working_dir()
+            .to_path_buf();
+
         // Load settings from config
         let config = Config::global();
 
@@ -537,6 +692,34 @@ impl Agent {
 
         let goose_mode = config.get_param("GOOSE_MODE").unwrap_or("auto".to_string());
 
+        let budget = session
+            .as_ref()
+            .map(|session_config| session_config.budget.clone())
+            .unwrap_or_default()
+            .or(self.subagent_budget.clone().unwrap_or_default())
+            .or(ReplyBudget::from_config(config));
+        // The spending cap covers the whole session, so start from what it has spent already
+        let session_cost = session
+            .as_ref()
+            .and_then(|session_config| {
+                session::storage::read_metadata(&session::storage::get_path(
+                    session_config.id.clone(),
+                ))
+                .ok()
+            })
+            .and_then(|metadata| metadata.accumulated_cost)
+            .unwrap_or(0.0);
+        let mut budget = BudgetTracker::new(budget, session_cost);
+        let auto_compact = AutoCompactConfig::from_config(config);
+        // Compacting only helps once per turn, if the provider still refuses we give up
+        let mut compacted_this_turn = false;
+
+        // Each reply has to report its own final output
+        if let Some(final_output) = self.final_output.lock().await.as_mut() {
+            final_output.clear();
+        }
+        let mut final_output_reminders = 0;
+
         let (tools_with_readonly_annotation, tools_without_annotation) =
             Self::categorize_tools_by_annotation(&tools);
 
@@ -550,18 +733,87 @@ impl Agent {
 
         Ok(Box::pin(async_stream::try_stream! {
             let _ = reply_span.enter();
+            // Extensions may ask to sample from the model at any point while we reply
+            let mut sampling_requests = SamplingRequests::acquire(&self.sampling_rx);
             loop {
-                match Self::generate_response_from_provider(
+                if *cancel_rx.borrow() {
+                    break;
+                }
+                if let Some(exceeded) = budget.exhausted() {
+                    yield AgentEvent::Message(
+                        Message::assistant().with_content(MessageContent::BudgetExceeded(exceeded)),
+                    );
+                    break;
+                }
+
+                // Summarize older turns before the conversation outgrows the context window
+                match self.auto_compact_context(&messages, &auto_compact, false).await {
+                    Ok(Some(compacted)) => {
+                        messages = compacted;
+                        compacted_this_turn = true;
+                        Self::persist_compacted_context(&session, &messages).await?;
+                        yield AgentEvent::HistoryReplaced(messages.clone());
+                    }
+                    Ok(None) => {}
+                    Err(e) => error!("Failed to compact the conversation: {}", e),
+                }
+
+                // Forward partial output as it streams in, holding on to the final
+                // message and usage for the tool handling below
+                let mut completion = Err(ProviderError::ExecutionError(
+                    "Provider stream ended without a response".to_string(),
+                ));
+                match Self::stream_response_from_provider(
                     self.provider().await?,
                     &system_prompt,
                     &messages,
                     &tools,
                     &toolshim_tools,
                 ).await {
+                    Ok(mut response_stream) => loop {
+                        tokio::select! {
+                            item = response_stream.next() => {
+                                match item {
+                                    Some(Ok(ProviderStreamItem::Delta(delta))) => {
+                                        yield AgentEvent::MessageDelta(delta);
+                                    }
+                                    Some(Ok(ProviderStreamItem::Complete(response, usage))) => {
+                                        completion = Ok((response, usage));
+                                    }
+                                    Some(Err(e)) => {
+                                        completion = Err(e);
+                                        break;
+                                    }
+                                    None => break,
+                                }
+                            }
+                            Some(request) = sampling_requests.next() => {
+                                if request.approval_tx.is_closed() {
+                                    continue;
+                                }
+                                let mut permission_manager = PermissionManager::default();
+                                let approved = if request.is_pre_approved(&goose_mode, &permission_manager) {
+                                    true
+                                } else {
+                                    yield AgentEvent::Message(request.confirmation_message());
+                                    self.wait_for_sampling_confirmation(&request, &mut permission_manager).await
+                                };
+                                let _ = request.approval_tx.send(approved);
+                            }
+                        }
+                    },
+                    Err(e) => completion = Err(e),
+                }
+
+                match completion {
                     Ok((response, usage)) => {
+                        let cost = pricing::cost_of(&usage);
+                        budget.record_turn(&usage.usage, cost);
+                        compacted_this_turn = false;
+
                         // record usage for the session in the session file
                         if let Some(session_config) = session.clone() {
-                            Self::update_session_metrics(session_config, &usage, messages.len()).await?;
+                            Self::update_session_metrics(session_config, &usage, cost, messages.len()).await?;
                         }
 
                         // categorize the type of requests we need to handle
@@ -597,12 +849,43 @@ impl Agent {
 
                         let num_tool_requests = frontend_requests.len() + remaining_requests.len();
                         if num_tool_requests == 0 {
+                            // Send the model back to work if it stopped without reporting its result
+                            let reminder = match self.final_output.lock().await.as_ref() {
+                                Some(final_output)
+                                    if final_output.output().is_none()
+                                        && final_output_reminders < MAX_FINAL_OUTPUT_REMINDERS =>
+                                {
+                                    Some(final_output.reminder())
+                                }
+                                _ => None,
+                            };
+                            if let Some(reminder) = reminder {
+                                final_output_reminders += 1;
+                                let reminder = Message::user().with_text(reminder);
+                                yield AgentEvent::Message(reminder.clone());
+                                messages.push(response);
+                                messages.push(reminder);
+                                continue;
+                            }
                             break;
                         }
 
                         // Process tool requests depending on frontend tools and then goose_mode
                         let message_tool_response = Arc::new(Mutex::new(Message::user()));
 
+                        // Reporting the final output has no side effects, so it never needs approval
+                        let (final_output_requests, remaining_requests): (Vec<_>, Vec<_>) =
+                            remaining_requests.into_iter().partition(|request| {
+                                matches!(&request.tool_call, Ok(tool_call) if tool_call.name == PLATFORM_FINAL_OUTPUT_TOOL_NAME)
+                            });
+                        for request in &final_output_requests {
+                            if let Ok(tool_call) = &request.tool_call {
+                                let result = self.record_final_output(&tool_call.arguments).await;
+                                let mut response = message_tool_response.lock().await;
+                                *response = response.clone().with_tool_response(request.id.clone(), result);
+                            }
+                        }
+
                         // First handle any frontend tool requests
                         let mut frontend_tool_stream = self.handle_frontend_tool_requests(
                             &frontend_requests,
@@ -618,6 +901,7 @@ impl Agent {
 
                         // Clone goose_mode once before the match to avoid move issues
                         let mode = goose_mode.clone();
+                        let mut cancelled = false;
                         if mode.as_str() == "chat" {
                             // Skip all tool calls in chat mode
                             for request in remaining_requests {
@@ -638,7 +922,8 @@ impl Agent {
                                 tools_with_readonly_annotation.clone(),
                                 tools_without_annotation.clone(),
                                 &mut permission_manager,
-                                self.provider().await?).await;
+                                self.provider().await?,
+                                &working_dir).await;
 
                             // Handle pre-approved and read-only tools in parallel
                             let mut tool_futures: Vec<(String, ToolStream)> = Vec::new();
@@ -695,6 +980,9 @@ impl Agent {
                                 futures_lock.drain(..).collect::<Vec<_>>()
                             };
 
+                            let mut pending: HashSet<String> =
+                                tool_futures.iter().map(|(request_id, _)| request_id.clone()).collect();
+
                             let with_id = tool_futures
                                 .into_iter()
                                 .map(|(request_id, stream)| {
@@ -706,23 +994,68 @@ impl Agent {
 
                             let mut all_install_successful = true;
 
-                            while let Some((request_id, item)) = combined.next().await {
-                                match item {
-                                    ToolStreamItem::Result(output) => {
-                                        if enable_extension_request_ids.contains(&request_id) && output.is_err(){
-                                            all_install_successful = false;
+                            loop {
+                                tokio::select! {
+                                    next = combined.next() => {
+                                        let (request_id, item) = match next {
+                                            Some(next) => next,
+                                            None => break,
+                                        };
+                                        match item {
+                                            ToolStreamItem::Result(output) => {
+                                                if enable_extension_request_ids.contains(&request_id) && output.is_err(){
+                                                    all_install_successful = false;
+                                                }
+                                                pending.remove(&request_id);
+                                                let mut response = message_tool_response.lock().await;
+                                                *response = response.clone().with_tool_response(request_id, output);
+                                            },
+                                            ToolStreamItem::Message(msg) => {
+                                                if let Some(progress) = ToolProgress::from_notification(&request_id, &msg) {
+                                                    yield AgentEvent::ToolProgress(progress);
+                                                } else if let Some(event) = SubagentEvent::from_notification(&request_id, &msg) {
+                                                    yield AgentEvent::Subagent(event);
+                                                } else {
+                                                    yield AgentEvent::McpNotification((request_id, msg));
+                                                }
+                                            }
+                                        }
+                                    }
+                                    Ok(()) = cancel_rx.changed() => {
+                                        if !*cancel_rx.borrow() {
+                                            continue;
                                         }
+                                        // Dropping the tool streams below cancels the calls on their extensions
+                                        cancelled = true;
+                                        let mut response = message_tool_response.lock().await;
+                                        *response = with_unfinished_tool_responses(response.clone(), &mut pending, CANCELLED_RESPONSE);
+                                        break;
+                                    }
+                                    _ = budget.deadline() => {
+                                        // The budget check at the top of the loop ends the reply
                                         let mut response = message_tool_response.lock().await;
-                                        *response = response.clone().with_tool_response(request_id, output);
-                                    },
-                                    ToolStreamItem::Message(msg) => {
-                                        yield AgentEvent::McpNotification((request_id, msg))
+                                        *response = with_unfinished_tool_responses(response.clone(), &mut pending, OUT_OF_TIME_RESPONSE);
+                                        break;
+                                    }
+                                    Some(request) = sampling_requests.next() => {
+                                        // The extension gave up waiting on this one already
+                                        if request.approval_tx.is_closed() {
+                                            continue;
+                                        }
+                                        let approved = if request.is_pre_approved(&mode, &permission_manager) {
+                                            true
+                                        } else {
+                                            yield AgentEvent::Message(request.confirmation_message());
+                                            self.wait_for_sampling_confirmation(&request, &mut permission_manager).await
+                                        };
+                                        let _ = request.approval_tx.send(approved);
                                     }
                                 }
                             }
+                            drop(combined);
 
                             // Update system prompt and tools if installations were successful
-                            if all_install_successful {
+                            if all_install_successful && !cancelled {
                                 (tools, toolshim_tools, system_prompt) = self.prepare_tools_and_prompt().await?;
                             }
                         }
@@ -732,8 +1065,27 @@ impl Agent {
 
                         messages.push(response);
                         messages.push(final_message_tool_resp);
+
+                        if cancelled || self.final_output().await.is_some() {
+                            break;
+                        }
                     },
                     Err(ProviderError::ContextLengthExceeded(_)) => {
+                        if auto_compact.enabled && !compacted_this_turn {
+                            // Compact regardless of our token estimate since the provider disagrees, then retry
+                            compacted_this_turn = true;
+                            match self.auto_compact_context(&messages, &auto_compact, true).await {
+                                Ok(Some(compacted)) => {
+                                    messages = compacted;
+                                    Self::persist_compacted_context(&session, &messages).await?;
+                                    yield AgentEvent::HistoryReplaced(messages.clone());
+                                    continue;
+                                }
+                                Ok(None) => {}
+                                Err(e) => error!("Failed to compact the conversation: {}", e),
+                            }
+                        }
+
                         // At this point, the last message should be a user message
                         // because call to provider led to context length exceeded error
                         // Immediately yield a special message and break
@@ -756,6 +1108,31 @@ impl Agent {
         }))
     }
 
+    /// Require replies to end by reporting a result matching `schema` through the final output
+    /// tool. Outputs that do not match are rejected so the model can correct them.
+    pub async fn set_final_output_schema(&self, schema: Value) -> Result<()> {
+        *self.final_output.lock().await = Some(FinalOutput::new(schema)?);
+        Ok(())
+    }
+
+    /// The validated output reported by the last reply, if any
+    pub async fn final_output(&self) -> Option<Value> {
+        self.final_output
+            .lock()
+            .await
+            .as_ref()
+            .and_then(|final_output| final_output.output().cloned())
+    }
+
+    async fn record_final_output(&self, arguments: &Value) -> ToolResult<Vec<Content>> {
+        match self.final_output.lock().await.as_mut() {
+            Some(final_output) => final_output.record(arguments),
+            None => Err(ToolError::NotFound(
+                PLATFORM_FINAL_OUTPUT_TOOL_NAME.to_string(),
+            )),
+        }
+    }
+
     /// Extend the system prompt with one line of additional instruction
     pub async fn extend_system_prompt(&self, instruction: String) {
         let mut prompt_manager = self.prompt_manager.lock().await;
@@ -859,6 +1236,19 @@ impl Agent {
         }
     }
 
+    /// Call an extension tool directly rather than from a reply, e.g. to undo file edits when
+    /// rewinding a session
+    pub async fn call_tool(&self, tool_call: mcp_core::tool::ToolCall) -> ToolResult<Vec<Content>> {
+        let call_result = self
+            .extension_manager
+            .lock()
+            .await
+            .dispatch_tool_call(tool_call)
+            .await
+            .map_err(|e| ToolError::ExecutionError(e.to_string()))?;
+        call_result.result.await
+    }
+
     pub async fn create_recipe(&self, mut messages: Vec<Message>) -> Result<Recipe> {
         let extension_manager = self.extension_manager.lock().await;
         let extensions_info = extension_manager.get_extensions_info().await;
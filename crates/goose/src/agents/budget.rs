@@ -0,0 +1,236 @@
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::Config;
+use crate::message::{BudgetExceeded, BudgetKind};
+use crate::providers::base::Usage;
+
+/// Hard limits on a single call to `Agent::reply`, limits that are not set are not enforced
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ReplyBudget {
+    /// Maximum number of calls to the provider
+    pub max_turns: Option<u64>,
+    /// Maximum input tokens summed over all provider calls
+    pub max_input_tokens: Option<u64>,
+    /// Maximum output tokens summed over all provider calls
+    pub max_output_tokens: Option<u64>,
+    /// Maximum wall-clock time for the whole reply, in seconds
+    pub max_seconds: Option<u64>,
+    /// Maximum estimated cost of the whole session in USD, including earlier replies
+    pub max_session_cost: Option<f64>,
+}
+
+impl ReplyBudget {
+    /// Read the limits from the GOOSE_MAX_TURNS, GOOSE_MAX_INPUT_TOKENS, GOOSE_MAX_OUTPUT_TOKENS,
+    /// GOOSE_MAX_REPLY_SECONDS and GOOSE_MAX_SESSION_COST config keys
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            max_turns: config.get_param("GOOSE_MAX_TURNS").ok(),
+            max_input_tokens: config.get_param("GOOSE_MAX_INPUT_TOKENS").ok(),
+            max_output_tokens: config.get_param("GOOSE_MAX_OUTPUT_TOKENS").ok(),
+            max_seconds: config.get_param("GOOSE_MAX_REPLY_SECONDS").ok(),
+            max_session_cost: config.get_param("GOOSE_MAX_SESSION_COST").ok(),
+        }
+    }
+
+    /// Use the limits set here, falling back to `other` for the ones that are not
+    pub fn or(self, other: ReplyBudget) -> Self {
+        Self {
+            max_turns: self.max_turns.or(other.max_turns),
+            max_input_tokens: self.max_input_tokens.or(other.max_input_tokens),
+            max_output_tokens: self.max_output_tokens.or(other.max_output_tokens),
+            max_seconds: self.max_seconds.or(other.max_seconds),
+            max_session_cost: self.max_session_cost.or(other.max_session_cost),
+        }
+    }
+}
+
+/// Tracks what a reply has used against its budget
+pub(crate) struct BudgetTracker {
+    budget: ReplyBudget,
+    deadline: Option<Instant>,
+    turns: u64,
+    input_tokens: u64,
+    output_tokens: u64,
+    /// Spent by the session so far, including before this reply
+    session_cost: f64,
+}
+
+impl BudgetTracker {
+    /// Start tracking a reply of a session that has already spent `session_cost` USD
+    pub(crate) fn new(budget: ReplyBudget, session_cost: f64) -> Self {
+        let deadline = budget
+            .max_seconds
+            .map(|seconds| Instant::now() + Duration::from_secs(seconds));
+        Self {
+            budget,
+            deadline,
+            turns: 0,
+            input_tokens: 0,
+            output_tokens: 0,
+            session_cost,
+        }
+    }
+
+    /// Count a completed provider call, the tokens it used and its cost if known
+    pub(crate) fn record_turn(&mut self, usage: &Usage, cost: Option<f64>) {
+        self.turns += 1;
+        self.input_tokens += usage.input_tokens.unwrap_or(0).max(0) as u64;
+        self.output_tokens += usage.output_tokens.unwrap_or(0).max(0) as u64;
+        self.session_cost += cost.unwrap_or(0.0);
+    }
+
+    /// The first budget that has run out, checked before each provider call
+    pub(crate) fn exhausted(&self) -> Option<BudgetExceeded> {
+        let over = |limit: Option<u64>, used: u64| limit.filter(|limit| used >= *limit);
+
+        if let Some(limit) = over(self.budget.max_turns, self.turns) {
+            return Some(exceeded(
+                BudgetKind::Turns,
+                limit,
+                format!("Stopped after reaching the limit of {} turns.", limit),
+            ));
+        }
+        if let Some(limit) = over(self.budget.max_input_tokens, self.input_tokens) {
+            return Some(exceeded(
+                BudgetKind::InputTokens,
+                limit,
+                format!(
+                    "Stopped after using {} of the {} input tokens allowed.",
+                    self.input_tokens, limit
+                ),
+            ));
+        }
+        if let Some(limit) = over(self.budget.max_output_tokens, self.output_tokens) {
+            return Some(exceeded(
+                BudgetKind::OutputTokens,
+                limit,
+                format!(
+                    "Stopped after using {} of the {} output tokens allowed.",
+                    self.output_tokens, limit
+                ),
+            ));
+        }
+        if let Some(limit) = self
+            .budget
+            .max_session_cost
+            .filter(|limit| self.session_cost >= *limit)
+        {
+            return Some(BudgetExceeded {
+                budget: BudgetKind::SessionCost,
+                limit: None,
+                cost_limit: Some(limit),
+                msg: format!(
+                    "Stopped after spending ${:.2} of the ${:.2} allowed for this session.",
+                    self.session_cost, limit
+                ),
+            });
+        }
+        match (self.budget.max_seconds, self.deadline) {
+            (Some(limit), Some(deadline)) if Instant::now() >= deadline => Some(exceeded(
+                BudgetKind::Time,
+                limit,
+                format!(
+                    "Stopped after reaching the time limit of {} seconds.",
+                    limit
+                ),
+            )),
+            _ => None,
+        }
+    }
+
+    /// Resolves once the time budget runs out, never if there is none
+    pub(crate) async fn deadline(&self) {
+        match self.deadline {
+            Some(deadline) => tokio::time::sleep_until(deadline.into()).await,
+            None => std::future::pending().await,
+        }
+    }
+}
+
+fn exceeded(budget: BudgetKind, limit: u64, msg: String) -> BudgetExceeded {
+    BudgetExceeded {
+        budget,
+        limit: Some(limit),
+        cost_limit: None,
+        msg,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_budget_overrides_config() {
+        let session = ReplyBudget {
+            max_turns: Some(3),
+            ..Default::default()
+        };
+        let config = ReplyBudget {
+            max_turns: Some(10),
+            max_seconds: Some(60),
+            ..Default::default()
+        };
+
+        let budget = session.or(config);
+        assert_eq!(budget.max_turns, Some(3));
+        assert_eq!(budget.max_seconds, Some(60));
+        assert_eq!(budget.max_input_tokens, None);
+    }
+
+    #[test]
+    fn test_exhausted_reports_first_budget_used_up() {
+        let mut tracker = BudgetTracker::new(
+            ReplyBudget {
+                max_turns: Some(2),
+                max_output_tokens: Some(100),
+                ..Default::default()
+            },
+            0.0,
+        );
+        assert!(tracker.exhausted().is_none());
+
+        tracker.record_turn(&Usage::new(Some(50), Some(120), Some(170)), None);
+        let exceeded = tracker.exhausted().unwrap();
+        assert_eq!(exceeded.budget, BudgetKind::OutputTokens);
+        assert_eq!(exceeded.limit, Some(100));
+
+        tracker.record_turn(&Usage::new(Some(50), Some(10), Some(60)), None);
+        assert_eq!(tracker.exhausted().unwrap().budget, BudgetKind::Turns);
+    }
+
+    #[test]
+    fn test_unlimited_budget_never_runs_out() {
+        let mut tracker = BudgetTracker::new(ReplyBudget::default(), 0.0);
+        for _ in 0..100 {
+            tracker.record_turn(&Usage::new(Some(1000), Some(1000), Some(2000)), Some(1.0));
+        }
+        assert!(tracker.exhausted().is_none());
+    }
+
+    #[test]
+    fn test_session_cost_counts_earlier_replies() {
+        let budget = ReplyBudget {
+            max_session_cost: Some(1.5),
+            ..Default::default()
+        };
+        let mut tracker = BudgetTracker::new(budget.clone(), 1.0);
+        assert!(tracker.exhausted().is_none());
+        tracker.record_turn(&Usage::default(), Some(0.25));
+        assert!(tracker.exhausted().is_none());
+        // Calls of unknown cost don't count towards the limit
+        tracker.record_turn(&Usage::default(), None);
+        assert!(tracker.exhausted().is_none());
+        tracker.record_turn(&Usage::default(), Some(0.25));
+
+        let exceeded = tracker.exhausted().unwrap();
+        assert_eq!(exceeded.budget, BudgetKind::SessionCost);
+        assert_eq!(exceeded.limit, None);
+        assert_eq!(exceeded.cost_limit, Some(1.5));
+        assert!(exceeded.msg.contains("$1.50 of the $1.50"));
+
+        assert!(BudgetTracker::new(budget, 2.0).exhausted().is_some());
+    }
+}
@@ -3,9 +3,12 @@ use anyhow::Ok;
 use crate::message::Message;
 use crate::token_counter::TokenCounter;
 
+use crate::agents::types::SessionConfig;
+use crate::context_mgmt::compact::{compact_messages, AutoCompactConfig};
 use crate::context_mgmt::summarize::summarize_messages;
 use crate::context_mgmt::truncate::{truncate_messages, OldestFirstTruncation};
 use crate::context_mgmt::{estimate_target_context_limit, get_messages_token_counts};
+use crate::session;
 
 use super::super::agents::Agent;
 
@@ -60,4 +63,45 @@ impl Agent {
 
         Ok((new_messages, new_token_counts))
     }
+
+    /// Summarize the older turns of the conversation when auto compaction is on and it is
+    /// close to the context limit, or regardless of size once the provider has rejected it.
+    /// Returns None when there was nothing to compact.
+    pub(crate) async fn auto_compact_context(
+        &self,
+        messages: &[Message],
+        config: &AutoCompactConfig,
+        force: bool,
+    ) -> Result<Option<Vec<Message>>, anyhow::Error> {
+        if !config.enabled {
+            return Ok(None);
+        }
+
+        let provider = self.provider().await?;
+        let token_counter = TokenCounter::new(provider.get_model_config().tokenizer_name());
+        let target_context_limit = estimate_target_context_limit(provider.clone());
+
+        if !force {
+            let tokens: usize = get_messages_token_counts(&token_counter, messages)
+                .iter()
+                .sum();
+            if !config.should_compact(tokens, target_context_limit) {
+                return Ok(None);
+            }
+        }
+
+        compact_messages(provider, messages, &token_counter, target_context_limit).await
+    }
+
+    /// Write the compacted conversation to the session file, replacing the full history
+    pub(crate) async fn persist_compacted_context(
+        session: &Option<SessionConfig>,
+        messages: &[Message],
+    ) -> Result<(), anyhow::Error> {
+        if let Some(session_config) = session {
+            let session_file = session::storage::get_path(session_config.id.clone());
+            session::storage::persist_messages(&session_file, messages, None).await?;
+        }
+        Ok(())
+    }
 }
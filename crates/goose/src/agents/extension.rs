@@ -136,6 +136,31 @@ pub enum ExtensionConfig {
         // NOTE: set timeout to be optional for compatibility.
         // However, new configurations should include this field.
         timeout: Option<u64>,
+        /// Extra directories exposed to the extension as MCP roots, besides the working directory
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        roots: Vec<String>,
+        /// Whether this extension is bundled with Goose
+        #[serde(default)]
+        bundled: Option<bool>,
+    },
+    /// Streamable HTTP client with a single MCP endpoint URI
+    #[serde(rename = "streamable_http")]
+    StreamableHttp {
+        /// The name used to identify this extension
+        name: String,
+        uri: String,
+        #[serde(default)]
+        envs: Envs,
+        #[serde(default)]
+        env_keys: Vec<String>,
+        /// Extra HTTP headers sent with every request, e.g. for authentication
+        #[serde(default)]
+        headers: HashMap<String, String>,
+        description: Option<String>,
+        timeout: Option<u64>,
+        /// Extra directories exposed to the extension as MCP roots, besides the working directory
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        roots: Vec<String>,
         /// Whether this extension is bundled with Goose
         #[serde(default)]
         bundled: Option<bool>,
@@ -153,6 +178,9 @@ pub enum ExtensionConfig {
         env_keys: Vec<String>,
         timeout: Option<u64>,
         description: Option<String>,
+        /// Extra directories exposed to the extension as MCP roots, besides the working directory
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        roots: Vec<String>,
         /// Whether this extension is bundled with Goose
         #[serde(default)]
         bundled: Option<bool>,
@@ -203,6 +231,26 @@ impl ExtensionConfig {
             env_keys: Vec::new(),
             description: Some(description.into()),
             timeout: Some(timeout.into()),
+            roots: Vec::new(),
+            bundled: None,
+        }
+    }
+
+    pub fn streamable_http<S: Into<String>, T: Into<u64>>(
+        name: S,
+        uri: S,
+        description: S,
+        timeout: T,
+    ) -> Self {
+        Self::StreamableHttp {
+            name: name.into(),
+            uri: uri.into(),
+            envs: Envs::default(),
+            env_keys: Vec::new(),
+            headers: HashMap::new(),
+            description: Some(description.into()),
+            timeout: Some(timeout.into()),
+            roots: Vec::new(),
             bundled: None,
         }
     }
@@ -221,6 +269,7 @@ impl ExtensionConfig {
             env_keys: Vec::new(),
             description: Some(description.into()),
             timeout: Some(timeout.into()),
+            roots: Vec::new(),
             bundled: None,
         }
     }
@@ -238,6 +287,7 @@ impl ExtensionConfig {
                 env_keys,
                 timeout,
                 description,
+                roots,
                 bundled,
                 ..
             } => Self::Stdio {
@@ -248,6 +298,7 @@ impl ExtensionConfig {
                 args: args.into_iter().map(Into::into).collect(),
                 description,
                 timeout,
+                roots,
                 bundled,
             },
             other => other,
@@ -259,10 +310,21 @@ impl ExtensionConfig {
         name_to_key(&name)
     }
 
+    /// Extra roots configured for this extension
+    pub fn roots(&self) -> &[String] {
+        match self {
+            Self::Sse { roots, .. }
+            | Self::StreamableHttp { roots, .. }
+            | Self::Stdio { roots, .. } => roots,
+            Self::Builtin { .. } | Self::Frontend { .. } => &[],
+        }
+    }
+
     /// Get the extension name regardless of variant
     pub fn name(&self) -> String {
         match self {
             Self::Sse { name, .. } => name,
+            Self::StreamableHttp { name, .. } => name,
             Self::Stdio { name, .. } => name,
             Self::Builtin { name, .. } => name,
             Self::Frontend { name, .. } => name,
@@ -275,6 +337,9 @@ impl std::fmt::Display for ExtensionConfig {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             ExtensionConfig::Sse { name, uri, .. } => write!(f, "SSE({}: {})", name, uri),
+            ExtensionConfig::StreamableHttp { name, uri, .. } => {
+                write!(f, "StreamableHttp({}: {})", name, uri)
+            }
             ExtensionConfig::Stdio {
                 name, cmd, args, ..
             } => {
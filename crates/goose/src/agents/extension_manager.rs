@@ -4,21 +4,25 @@ use futures::stream::{FuturesUnordered, StreamExt};
 use futures::{future, FutureExt};
 use mcp_core::protocol::GetPromptResult;
 use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::sync::LazyLock;
 use std::time::Duration;
-use tokio::sync::Mutex;
 use tokio::task;
 use tokio_stream::wrappers::ReceiverStream;
 use tracing::{error, warn};
 
 use super::extension::{ExtensionConfig, ExtensionError, ExtensionInfo, ExtensionResult, ToolInfo};
+use super::sampling::SamplingContext;
 use super::tool_execution::ToolCallResult;
 use crate::agents::extension::Envs;
 use crate::config::{Config, ExtensionConfigManager};
 use crate::prompt_template;
-use mcp_client::client::{ClientCapabilities, ClientInfo, McpClient, McpClientTrait};
-use mcp_client::transport::{SseTransport, StdioTransport, Transport};
+use mcp_client::client::{
+    ClientCapabilities, ClientInfo, McpClient, McpClientTrait, RootsCapability, SamplingCapability,
+};
+use mcp_client::transport::{SseTransport, StdioTransport, StreamableHttpTransport, Transport};
+use mcp_core::protocol::{JsonRpcMessage, Root};
 use mcp_core::{prompt::Prompt, Content, Tool, ToolCall, ToolError};
 use serde_json::Value;
 
@@ -27,13 +31,35 @@ use serde_json::Value;
 static DEFAULT_TIMESTAMP: LazyLock<DateTime<Utc>> =
     LazyLock::new(|| Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap());
 
-type McpClientBox = Arc<Mutex<Box<dyn McpClientTrait>>>;
+type McpClientBox = Arc<dyn McpClientTrait>;
+
+/// Whether a notification belongs to the call with `progress_token`, other calls' progress is dropped
+fn is_for_progress_token(message: &JsonRpcMessage, progress_token: &str) -> bool {
+    match message {
+        JsonRpcMessage::Notification(notification)
+            if notification.method == "notifications/progress" =>
+        {
+            notification
+                .params
+                .as_ref()
+                .and_then(|params| params.get("progressToken"))
+                .and_then(|token| token.as_str())
+                == Some(progress_token)
+        }
+        _ => true,
+    }
+}
 
 /// Manages Goose extensions / MCP clients and their interactions
 pub struct ExtensionManager {
     clients: HashMap<String, McpClientBox>,
     instructions: HashMap<String, String>,
     resource_capable_extensions: HashSet<String>,
+    sampling: Option<SamplingContext>,
+    /// The session working directory, always exposed to extensions as the first root
+    working_dir: PathBuf,
+    /// Extra roots configured per extension
+    extension_roots: HashMap<String, Vec<String>>,
 }
 
 /// A flattened representation of a resource used by the agent to prepare inference
@@ -83,6 +109,27 @@ fn normalize(input: String) -> String {
     result.to_lowercase()
 }
 
+/// Build the MCP root for a directory given as a path or a `file://` URI.
+/// Relative paths are resolved against the working directory.
+fn to_root(dir: &str, working_dir: &Path) -> Root {
+    if dir.starts_with("file://") {
+        return Root {
+            uri: dir.to_string(),
+            name: None,
+        };
+    }
+
+    let path = working_dir.join(dir);
+    Root {
+        uri: url::Url::from_file_path(&path)
+            .map(|url| url.to_string())
+            .unwrap_or_else(|_| format!("file://{}", path.display())),
+        name: path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string()),
+    }
+}
+
 pub fn get_parameter_names(tool: &Tool) -> Vec<String> {
     tool.input_schema
         .get("properties")
@@ -104,15 +151,96 @@ impl ExtensionManager {
             clients: HashMap::new(),
             instructions: HashMap::new(),
             resource_capable_extensions: HashSet::new(),
+            sampling: None,
+            working_dir: std::env::current_dir().unwrap_or_default(),
+            extension_roots: HashMap::new(),
+        }
+    }
+
+    /// Let extensions added from now on request completions from the agent's model
+    pub fn set_sampling_context(&mut self, sampling: SamplingContext) {
+        self.sampling = Some(sampling);
+    }
+
+    /// The roots exposed to an extension: the working directory, then its configured extras
+    fn roots_for(&self, name: &str) -> Vec<Root> {
+        let mut roots = vec![to_root(
+            &self.working_dir.to_string_lossy(),
+            &self.working_dir,
+        )];
+        for dir in self.extension_roots.get(name).into_iter().flatten() {
+            let root = to_root(dir, &self.working_dir);
+            if !roots.iter().any(|r| r.uri == root.uri) {
+                roots.push(root);
+            }
+        }
+        roots
+    }
+
+    /// The session working directory
+    pub fn working_dir(&self) -> &Path {
+        &self.working_dir
+    }
+
+    /// Change the working directory exposed as a root, notifying extensions if it moved
+    pub async fn set_working_dir(&mut self, working_dir: PathBuf) {
+        if self.working_dir == working_dir {
+            return;
+        }
+        self.working_dir = working_dir;
+
+        for (name, client) in &self.clients {
+            if let Err(e) = client.set_roots(self.roots_for(name)).await {
+                warn!(extension = %name, error = %e, "Failed to update roots");
+            }
         }
     }
 
+    /// A manager sharing the running clients of the named extensions, or of all of them if
+    /// `names` is None. Used to give sub-agents a subset of the session's extensions.
+    pub fn scoped(&self, names: Option<&[String]>) -> ExtensionResult<Self> {
+        let names: Vec<String> = match names {
+            Some(names) => names.iter().map(|name| normalize(name.clone())).collect(),
+            None => self.clients.keys().cloned().collect(),
+        };
+
+        let mut scoped = Self {
+            sampling: self.sampling.clone(),
+            working_dir: self.working_dir.clone(),
+            ..Self::new()
+        };
+        for name in names {
+            let client = self.clients.get(&name).ok_or_else(|| {
+                ExtensionError::SetupError(format!("Extension '{}' is not enabled", name))
+            })?;
+            scoped.clients.insert(name.clone(), Arc::clone(client));
+            if let Some(instructions) = self.instructions.get(&name) {
+                scoped
+                    .instructions
+                    .insert(name.clone(), instructions.clone());
+            }
+            if self.resource_capable_extensions.contains(&name) {
+                scoped.resource_capable_extensions.insert(name.clone());
+            }
+            if let Some(roots) = self.extension_roots.get(&name) {
+                scoped.extension_roots.insert(name.clone(), roots.clone());
+            }
+        }
+        Ok(scoped)
+    }
+
+    /// Whether an extension with this name is running
+    pub fn is_enabled(&self, name: &str) -> bool {
+        self.clients.contains_key(&normalize(name.to_string()))
+    }
+
     pub fn supports_resources(&self) -> bool {
         !self.resource_capable_extensions.is_empty()
     }
 
     /// Add a new MCP extension based on the provided client type
     // TODO IMPORTANT need to ensure this times out if the extension command is broken!
+    #[tracing::instrument(skip(self, config), fields(extension = %config.key()))]
     pub async fn add_extension(&mut self, config: ExtensionConfig) -> ExtensionResult<()> {
         let config_name = config.key().to_string();
         let sanitized_name = normalize(config_name.clone());
@@ -174,6 +302,11 @@ impl ExtensionManager {
             Ok(all_envs)
         }
 
+        let sampling_handler = self
+            .sampling
+            .as_ref()
+            .map(|sampling| sampling.handler(&sanitized_name));
+
         let mut client: Box<dyn McpClientTrait> = match &config {
             ExtensionConfig::Sse {
                 uri,
@@ -186,11 +319,34 @@ impl ExtensionManager {
                 let transport = SseTransport::new(uri, all_envs);
                 let handle = transport.start().await?;
                 Box::new(
-                    McpClient::connect(
+                    McpClient::connect_with_sampling(
+                        handle,
+                        Duration::from_secs(
+                            timeout.unwrap_or(crate::config::DEFAULT_EXTENSION_TIMEOUT),
+                        ),
+                        sampling_handler.clone(),
+                    )
+                    .await?,
+                )
+            }
+            ExtensionConfig::StreamableHttp {
+                uri,
+                envs,
+                env_keys,
+                headers,
+                timeout,
+                ..
+            } => {
+                let all_envs = merge_environments(envs, env_keys, &sanitized_name).await?;
+                let transport = StreamableHttpTransport::new(uri, all_envs, headers.clone());
+                let handle = transport.start().await?;
+                Box::new(
+                    McpClient::connect_with_sampling(
                         handle,
                         Duration::from_secs(
                             timeout.unwrap_or(crate::config::DEFAULT_EXTENSION_TIMEOUT),
                         ),
+                        sampling_handler.clone(),
                     )
                     .await?,
                 )
@@ -207,11 +363,12 @@ impl ExtensionManager {
                 let transport = StdioTransport::new(cmd, args.to_vec(), all_envs);
                 let handle = transport.start().await?;
                 Box::new(
-                    McpClient::connect(
+                    McpClient::connect_with_sampling(
                         handle,
                         Duration::from_secs(
                             timeout.unwrap_or(crate::config::DEFAULT_EXTENSION_TIMEOUT),
                         ),
+                        sampling_handler.clone(),
                     )
                     .await?,
                 )
@@ -234,11 +391,12 @@ impl ExtensionManager {
                 );
                 let handle = transport.start().await?;
                 Box::new(
-                    McpClient::connect(
+                    McpClient::connect_with_sampling(
                         handle,
                         Duration::from_secs(
                             timeout.unwrap_or(crate::config::DEFAULT_EXTENSION_TIMEOUT),
                         ),
+                        sampling_handler.clone(),
                     )
                     .await?,
                 )
@@ -251,7 +409,20 @@ impl ExtensionManager {
             name: "goose".to_string(),
             version: env!("CARGO_PKG_VERSION").to_string(),
         };
-        let capabilities = ClientCapabilities::default();
+        let capabilities = ClientCapabilities {
+            roots: Some(RootsCapability {
+                list_changed: Some(true),
+            }),
+            sampling: self
+                .sampling
+                .as_ref()
+                .map(|_| SamplingCapability::default()),
+        };
+
+        // Roots must be in place before initialize, the server may ask for them right away
+        self.extension_roots
+            .insert(sanitized_name.clone(), config.roots().to_vec());
+        client.set_roots(self.roots_for(&sanitized_name)).await?;
 
         let init_result = client
             .initialize(info, capabilities)
@@ -269,7 +440,7 @@ impl ExtensionManager {
         }
 
         self.clients
-            .insert(sanitized_name.clone(), Arc::new(Mutex::new(client)));
+            .insert(sanitized_name.clone(), Arc::from(client));
 
         Ok(())
     }
@@ -293,6 +464,7 @@ impl ExtensionManager {
         self.clients.remove(&sanitized_name);
         self.instructions.remove(&sanitized_name);
         self.resource_capable_extensions.remove(&sanitized_name);
+        self.extension_roots.remove(&sanitized_name);
         Ok(())
     }
 
@@ -352,8 +524,7 @@ impl ExtensionManager {
 
             task::spawn(async move {
                 let mut tools = Vec::new();
-                let client_guard = client.lock().await;
-                let mut client_tools = client_guard.list_tools(None).await?;
+                let mut client_tools = client.list_tools(None).await?;
 
                 loop {
                     for tool in client_tools.tools {
@@ -370,7 +541,7 @@ impl ExtensionManager {
                         break;
                     }
 
-                    client_tools = client_guard.list_tools(client_tools.next_cursor).await?;
+                    client_tools = client.list_tools(client_tools.next_cursor).await?;
                 }
 
                 Ok::<Vec<Tool>, ExtensionError>(tools)
@@ -398,8 +569,7 @@ impl ExtensionManager {
         let mut result: Vec<ResourceItem> = Vec::new();
 
         for (name, client) in &self.clients {
-            let client_guard = client.lock().await;
-            let resources = client_guard.list_resources(None).await?;
+            let resources = client.list_resources(None).await?;
 
             for resource in resources.resources {
                 // Skip reading the resource if it's not marked active
@@ -408,7 +578,7 @@ impl ExtensionManager {
                     continue;
                 }
 
-                if let Ok(contents) = client_guard.read_resource(&resource.uri).await {
+                if let Ok(contents) = client.read_resource(&resource.uri).await {
                     for content in contents.contents {
                         let (uri, content_str) = match content {
                             mcp_core::resource::ResourceContents::TextResourceContents {
@@ -519,8 +689,7 @@ impl ExtensionManager {
             .get(extension_name)
             .ok_or(ToolError::InvalidParameters(error_msg))?;
 
-        let client_guard = client.lock().await;
-        let read_result = client_guard.read_resource(uri).await.map_err(|_| {
+        let read_result = client.read_resource(uri).await.map_err(|_| {
             ToolError::ExecutionError(format!("Could not read resource with uri: {}", uri))
         })?;
 
@@ -545,8 +714,7 @@ impl ExtensionManager {
             ToolError::InvalidParameters(format!("Extension {} is not valid", extension_name))
         })?;
 
-        let client_guard = client.lock().await;
-        client_guard
+        client
             .list_resources(None)
             .await
             .map_err(|e| {
@@ -633,12 +801,18 @@ impl ExtensionManager {
 
         let arguments = tool_call.arguments.clone();
         let client = client.clone();
-        let notifications_receiver = client.lock().await.subscribe().await;
+        let notifications_receiver = client.subscribe().await;
+
+        // Progress is tagged with a token unique to this call, so concurrent calls to the same
+        // extension only see their own progress. Dropping the future cancels the call on the server.
+        let progress_token = uuid::Uuid::new_v4().to_string();
+        let stream_token = progress_token.clone();
+        let notification_stream = ReceiverStream::new(notifications_receiver)
+            .filter(move |message| future::ready(is_for_progress_token(message, &stream_token)));
 
         let fut = async move {
-            let client_guard = client.lock().await;
-            client_guard
-                .call_tool(&tool_name, arguments)
+            client
+                .call_tool_with_progress(&tool_name, arguments, &progress_token)
                 .await
                 .map(|call| call.content)
                 .map_err(|e| ToolError::ExecutionError(e.to_string()))
@@ -646,7 +820,7 @@ impl ExtensionManager {
 
         Ok(ToolCallResult {
             result: Box::new(fut.boxed()),
-            notification_stream: Some(Box::new(ReceiverStream::new(notifications_receiver))),
+            notification_stream: Some(Box::new(notification_stream)),
         })
     }
 
@@ -658,8 +832,7 @@ impl ExtensionManager {
             ToolError::InvalidParameters(format!("Extension {} is not valid", extension_name))
         })?;
 
-        let client_guard = client.lock().await;
-        client_guard
+        client
             .list_prompts(None)
             .await
             .map_err(|e| {
@@ -724,8 +897,7 @@ impl ExtensionManager {
             .get(extension_name)
             .ok_or_else(|| anyhow::anyhow!("Extension {} not found", extension_name))?;
 
-        let client_guard = client.lock().await;
-        client_guard
+        client
             .get_prompt(name, arguments)
             .await
             .map_err(|e| anyhow::anyhow!("Failed to get prompt: {}", e))
@@ -752,10 +924,13 @@ impl ExtensionManager {
                     ExtensionConfig::Sse {
                         description, name, ..
                     }
+                    | ExtensionConfig::StreamableHttp {
+                        description, name, ..
+                    }
                     | ExtensionConfig::Stdio {
                         description, name, ..
                     } => {
-                        // For SSE/Stdio, use description if available
+                        // For SSE/Streamable HTTP/Stdio, use description if available
                         description
                             .as_ref()
                             .map(|s| s.to_string())
@@ -809,7 +984,7 @@ mod tests {
         ListResourcesResult, ListToolsResult, ReadResourceResult,
     };
     use serde_json::json;
-    use tokio::sync::mpsc;
+    use tokio::sync::{mpsc, Barrier};
 
     struct MockClient {}
 
@@ -866,6 +1041,128 @@ mod tests {
         async fn subscribe(&self) -> mpsc::Receiver<JsonRpcMessage> {
             mpsc::channel(1).1
         }
+
+        async fn set_roots(&self, _roots: Vec<Root>) -> Result<(), Error> {
+            Ok(())
+        }
+    }
+
+    /// Only answers a tool call once as many calls as the barrier waits for are in progress
+    struct BarrierClient {
+        barrier: Arc<Barrier>,
+    }
+
+    #[async_trait::async_trait]
+    impl McpClientTrait for BarrierClient {
+        async fn initialize(
+            &mut self,
+            _info: ClientInfo,
+            _capabilities: ClientCapabilities,
+        ) -> Result<InitializeResult, Error> {
+            Err(Error::NotInitialized)
+        }
+
+        async fn list_resources(
+            &self,
+            _next_cursor: Option<String>,
+        ) -> Result<ListResourcesResult, Error> {
+            Err(Error::NotInitialized)
+        }
+
+        async fn read_resource(&self, _uri: &str) -> Result<ReadResourceResult, Error> {
+            Err(Error::NotInitialized)
+        }
+
+        async fn list_tools(&self, _next_cursor: Option<String>) -> Result<ListToolsResult, Error> {
+            Err(Error::NotInitialized)
+        }
+
+        async fn call_tool(&self, _name: &str, _arguments: Value) -> Result<CallToolResult, Error> {
+            self.barrier.wait().await;
+            Ok(CallToolResult {
+                content: vec![],
+                is_error: None,
+            })
+        }
+
+        async fn list_prompts(
+            &self,
+            _next_cursor: Option<String>,
+        ) -> Result<ListPromptsResult, Error> {
+            Err(Error::NotInitialized)
+        }
+
+        async fn get_prompt(
+            &self,
+            _name: &str,
+            _arguments: Value,
+        ) -> Result<GetPromptResult, Error> {
+            Err(Error::NotInitialized)
+        }
+
+        async fn subscribe(&self) -> mpsc::Receiver<JsonRpcMessage> {
+            mpsc::channel(1).1
+        }
+
+        async fn set_roots(&self, _roots: Vec<Root>) -> Result<(), Error> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_roots_for_extension() {
+        let mut extension_manager = ExtensionManager::new();
+        extension_manager.working_dir = PathBuf::from("/home/user/project");
+        extension_manager.extension_roots.insert(
+            "indexer".to_string(),
+            vec![
+                "docs".to_string(),
+                "/home/user/project".to_string(),
+                "file:///srv/shared".to_string(),
+            ],
+        );
+
+        let roots = extension_manager.roots_for("indexer");
+        assert_eq!(
+            roots,
+            vec![
+                Root {
+                    uri: "file:///home/user/project".to_string(),
+                    name: Some("project".to_string()),
+                },
+                Root {
+                    uri: "file:///home/user/project/docs".to_string(),
+                    name: Some("docs".to_string()),
+                },
+                Root {
+                    uri: "file:///srv/shared".to_string(),
+                    name: None,
+                },
+            ]
+        );
+
+        // Extensions without extra roots only see the working directory
+        assert_eq!(extension_manager.roots_for("other").len(), 1);
+    }
+
+    #[test]
+    fn test_progress_notifications_filtered_by_token() {
+        let progress = |token: &str| {
+            JsonRpcMessage::Notification(mcp_core::protocol::JsonRpcNotification {
+                jsonrpc: "2.0".to_string(),
+                method: "notifications/progress".to_string(),
+                params: Some(json!({ "progressToken": token, "progress": 1 })),
+            })
+        };
+        let log = JsonRpcMessage::Notification(mcp_core::protocol::JsonRpcNotification {
+            jsonrpc: "2.0".to_string(),
+            method: "notifications/message".to_string(),
+            params: Some(json!({ "level": "info", "data": "hello" })),
+        });
+
+        assert!(is_for_progress_token(&progress("mine"), "mine"));
+        assert!(!is_for_progress_token(&progress("theirs"), "mine"));
+        assert!(is_for_progress_token(&log, "mine"));
     }
 
     #[test]
@@ -875,23 +1172,21 @@ mod tests {
         // Add some mock clients
         extension_manager.clients.insert(
             normalize("test_client".to_string()),
-            Arc::new(Mutex::new(Box::new(MockClient {}))),
+            Arc::new(MockClient {}),
         );
 
-        extension_manager.clients.insert(
-            normalize("__client".to_string()),
-            Arc::new(Mutex::new(Box::new(MockClient {}))),
-        );
+        extension_manager
+            .clients
+            .insert(normalize("__client".to_string()), Arc::new(MockClient {}));
 
         extension_manager.clients.insert(
             normalize("__cli__ent__".to_string()),
-            Arc::new(Mutex::new(Box::new(MockClient {}))),
+            Arc::new(MockClient {}),
         );
 
-        extension_manager.clients.insert(
-            normalize("client 🚀".to_string()),
-            Arc::new(Mutex::new(Box::new(MockClient {}))),
-        );
+        extension_manager
+            .clients
+            .insert(normalize("client 🚀".to_string()), Arc::new(MockClient {}));
 
         // Test basic case
         assert!(extension_manager
@@ -914,6 +1209,35 @@ mod tests {
             .is_some());
     }
 
+    #[test]
+    fn test_scoped_shares_only_named_clients() {
+        let mut extension_manager = ExtensionManager::new();
+        for name in ["developer", "memory"] {
+            extension_manager
+                .clients
+                .insert(name.to_string(), Arc::new(MockClient {}));
+        }
+        extension_manager
+            .resource_capable_extensions
+            .insert("memory".to_string());
+
+        let scoped = extension_manager
+            .scoped(Some(&["developer".to_string()]))
+            .unwrap();
+        assert!(scoped.get_client_for_tool("developer__shell").is_some());
+        assert!(scoped.get_client_for_tool("memory__remember").is_none());
+        assert!(!scoped.supports_resources());
+        assert!(Arc::ptr_eq(
+            &scoped.clients["developer"],
+            &extension_manager.clients["developer"]
+        ));
+
+        assert_eq!(extension_manager.scoped(None).unwrap().clients.len(), 2);
+        assert!(extension_manager
+            .scoped(Some(&["missing".to_string()]))
+            .is_err());
+    }
+
     #[tokio::test]
     async fn test_dispatch_tool_call() {
         // test that dispatch_tool_call parses out the sanitized name correctly, and extracts
@@ -923,18 +1247,17 @@ mod tests {
         // Add some mock clients
         extension_manager.clients.insert(
             normalize("test_client".to_string()),
-            Arc::new(Mutex::new(Box::new(MockClient {}))),
+            Arc::new(MockClient {}),
         );
 
         extension_manager.clients.insert(
             normalize("__cli__ent__".to_string()),
-            Arc::new(Mutex::new(Box::new(MockClient {}))),
+            Arc::new(MockClient {}),
         );
 
-        extension_manager.clients.insert(
-            normalize("client 🚀".to_string()),
-            Arc::new(Mutex::new(Box::new(MockClient {}))),
-        );
+        extension_manager
+            .clients
+            .insert(normalize("client 🚀".to_string()), Arc::new(MockClient {}));
 
         // verify a normal tool call
         let tool_call = ToolCall {
@@ -1013,4 +1336,35 @@ mod tests {
             panic!("Expected ToolError::NotFound");
         }
     }
+
+    #[tokio::test]
+    async fn test_delegated_tool_calls_overlap() {
+        let mut extension_manager = ExtensionManager::new();
+        extension_manager.clients.insert(
+            "shared".to_string(),
+            Arc::new(BarrierClient {
+                barrier: Arc::new(Barrier::new(2)),
+            }),
+        );
+
+        // Sub-agents get scoped managers that share the session's running clients
+        let first = extension_manager.scoped(None).unwrap();
+        let second = extension_manager.scoped(None).unwrap();
+        let call = || ToolCall {
+            name: "shared__wait".to_string(),
+            arguments: json!({}),
+        };
+        let first_call = first.dispatch_tool_call(call()).await.unwrap();
+        let second_call = second.dispatch_tool_call(call()).await.unwrap();
+
+        // Neither call returns until both are running in the extension at the same time
+        let (first_result, second_result) = tokio::time::timeout(
+            Duration::from_secs(5),
+            future::join(first_call.result, second_call.result),
+        )
+        .await
+        .expect("the tool calls ran one after the other");
+        assert!(first_result.is_ok());
+        assert!(second_result.is_ok());
+    }
 }
@@ -0,0 +1,140 @@
+use anyhow::{anyhow, Result};
+use mcp_core::{Content, Tool, ToolError};
+use serde_json::Value;
+
+use super::platform_tools::{final_output_tool, PLATFORM_FINAL_OUTPUT_TOOL_NAME};
+
+/// How many times a reply that ends without a final output is sent back to the model
+pub(super) const MAX_FINAL_OUTPUT_REMINDERS: usize = 3;
+
+/// A structured result the caller asked the agent to report, and the output once reported
+pub struct FinalOutput {
+    schema: Value,
+    validator: jsonschema::Validator,
+    output: Option<Value>,
+}
+
+impl FinalOutput {
+    pub fn new(schema: Value) -> Result<Self> {
+        let validator = jsonschema::validator_for(&schema)
+            .map_err(|e| anyhow!("Invalid response JSON schema: {}", e))?;
+        Ok(Self {
+            schema,
+            validator,
+            output: None,
+        })
+    }
+
+    pub fn tool(&self) -> Tool {
+        final_output_tool(&self.schema)
+    }
+
+    pub fn system_prompt(&self) -> String {
+        format!(
+            "When you have finished the task, you must call the {} tool with the result. \
+            The user only sees what you pass to that tool, so include everything they asked for.",
+            PLATFORM_FINAL_OUTPUT_TOOL_NAME
+        )
+    }
+
+    pub fn reminder(&self) -> String {
+        format!(
+            "You have not reported a result yet. Call the {} tool with the final output of the task.",
+            PLATFORM_FINAL_OUTPUT_TOOL_NAME
+        )
+    }
+
+    pub fn output(&self) -> Option<&Value> {
+        self.output.as_ref()
+    }
+
+    pub fn clear(&mut self) {
+        self.output = None;
+    }
+
+    /// Validate the arguments of a final output tool call and keep the output if it matches
+    pub fn record(&mut self, arguments: &Value) -> Result<Vec<Content>, ToolError> {
+        let output = arguments.get("output").ok_or_else(|| {
+            ToolError::InvalidParameters("Missing the 'output' argument".to_string())
+        })?;
+
+        let errors: Vec<String> = self
+            .validator
+            .iter_errors(output)
+            .map(|error| {
+                let path = error.instance_path.to_string();
+                if path.is_empty() {
+                    error.to_string()
+                } else {
+                    format!("{}: {}", path, error)
+                }
+            })
+            .collect();
+        if !errors.is_empty() {
+            return Err(ToolError::InvalidParameters(format!(
+                "The output does not match the required schema:\n- {}\nCall the tool again with a corrected output.",
+                errors.join("\n- ")
+            )));
+        }
+
+        self.output = Some(output.clone());
+        Ok(vec![Content::text("The final output was recorded.")])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn schema() -> Value {
+        json!({
+            "type": "object",
+            "required": ["status", "count"],
+            "properties": {
+                "status": {"type": "string", "enum": ["ok", "failed"]},
+                "count": {"type": "integer", "minimum": 0}
+            }
+        })
+    }
+
+    #[test]
+    fn test_record_valid_output() {
+        let mut final_output = FinalOutput::new(schema()).unwrap();
+        assert!(final_output.output().is_none());
+
+        let output = json!({"status": "ok", "count": 3});
+        assert!(final_output.record(&json!({ "output": output })).is_ok());
+        assert_eq!(final_output.output(), Some(&output));
+
+        final_output.clear();
+        assert!(final_output.output().is_none());
+    }
+
+    #[test]
+    fn test_record_invalid_output() {
+        let mut final_output = FinalOutput::new(schema()).unwrap();
+
+        let err = final_output
+            .record(&json!({"output": {"status": "unknown", "count": -1}}))
+            .unwrap_err();
+        let message = err.to_string();
+        assert!(message.contains("/status"));
+        assert!(message.contains("/count"));
+        assert!(final_output.output().is_none());
+
+        assert!(final_output.record(&json!({"result": {}})).is_err());
+    }
+
+    #[test]
+    fn test_invalid_schema() {
+        assert!(FinalOutput::new(json!({"type": "no-such-type"})).is_err());
+    }
+
+    #[test]
+    fn test_tool_wraps_schema() {
+        let tool = FinalOutput::new(schema()).unwrap().tool();
+        assert_eq!(tool.name, PLATFORM_FINAL_OUTPUT_TOOL_NAME);
+        assert_eq!(tool.input_schema["properties"]["output"], schema());
+    }
+}
@@ -1,20 +1,28 @@
 mod agent;
+mod budget;
 mod context;
 pub mod extension;
 pub mod extension_manager;
+mod final_output;
 mod large_response_handler;
 pub mod platform_tools;
 pub mod prompt_manager;
 mod reply_parts;
 mod router_tool_selector;
 mod router_tools;
+pub mod sampling;
+mod sub_recipes;
+mod subagent;
 mod tool_execution;
 mod tool_router_index_manager;
 pub(crate) mod tool_vectordb;
 mod types;
 
-pub use agent::{Agent, AgentEvent};
+pub use agent::{Agent, AgentEvent, ToolProgress};
+pub use budget::ReplyBudget;
 pub use extension::ExtensionConfig;
 pub use extension_manager::ExtensionManager;
 pub use prompt_manager::PromptManager;
+pub use sub_recipes::SUB_RECIPE_TOOL_PREFIX;
+pub use subagent::{SubagentEvent, SubagentUpdate};
 pub use types::{FrontendTool, SessionConfig};
@@ -1,12 +1,14 @@
 use indoc::indoc;
 use mcp_core::tool::{Tool, ToolAnnotations};
-use serde_json::json;
+use serde_json::{json, Value};
 
 pub const PLATFORM_READ_RESOURCE_TOOL_NAME: &str = "platform__read_resource";
 pub const PLATFORM_LIST_RESOURCES_TOOL_NAME: &str = "platform__list_resources";
 pub const PLATFORM_SEARCH_AVAILABLE_EXTENSIONS_TOOL_NAME: &str =
     "platform__search_available_extensions";
 pub const PLATFORM_MANAGE_EXTENSIONS_TOOL_NAME: &str = "platform__manage_extensions";
+pub const PLATFORM_DELEGATE_TOOL_NAME: &str = "platform__delegate";
+pub const PLATFORM_FINAL_OUTPUT_TOOL_NAME: &str = "platform__final_output";
 
 pub fn read_resource_tool() -> Tool {
     Tool::new(
@@ -112,3 +114,76 @@ pub fn manage_extensions_tool() -> Tool {
         }),
     )
 }
+
+pub fn delegate_tool() -> Tool {
+    Tool::new(
+        PLATFORM_DELEGATE_TOOL_NAME.to_string(),
+        indoc! {r#"
+            Delegate independent pieces of work to sub-agents that run in parallel.
+
+            Each task is handled by a fresh agent with its own context window, which sees only
+            the instructions you give it, so include every detail it needs. A sub-agent can use
+            the extensions you list for it, or all enabled extensions if none are listed. It
+            cannot ask the user for approval, so tools that need approval are declined.
+            You receive each sub-agent's final response, in the order of the tasks.
+            Only delegate work that does not depend on the result of another task.
+        "#}
+        .to_string(),
+        json!({
+            "type": "object",
+            "required": ["tasks"],
+            "properties": {
+                "tasks": {
+                    "type": "array",
+                    "description": "The tasks to run, one sub-agent each",
+                    "items": {
+                        "type": "object",
+                        "required": ["instructions"],
+                        "properties": {
+                            "instructions": {"type": "string", "description": "Everything the sub-agent needs to know to do the task and what to report back"},
+                            "extensions": {"type": "array", "items": {"type": "string"}, "description": "Optional names of the extensions the sub-agent may use"}
+                        }
+                    }
+                },
+                "max_turns": {"type": "integer", "description": "Optional limit on the model calls each sub-agent may make"}
+            }
+        }),
+        Some(ToolAnnotations {
+            title: Some("Delegate to sub-agents".to_string()),
+            read_only_hint: false,
+            destructive_hint: false,
+            idempotent_hint: false,
+            open_world_hint: false,
+        }),
+    )
+}
+
+/// The tool the agent reports its result with when the caller asked for structured output.
+/// The result is wrapped in an `output` argument since tool inputs must be objects.
+pub fn final_output_tool(schema: &Value) -> Tool {
+    Tool::new(
+        PLATFORM_FINAL_OUTPUT_TOOL_NAME.to_string(),
+        indoc! {r#"
+            Report the final result of the task as structured data.
+
+            Call this once, after finishing the task, with the result in `output`. The output is
+            checked against the required JSON schema; if it does not match, the errors are returned
+            and you should call the tool again with a corrected output.
+        "#}
+        .to_string(),
+        json!({
+            "type": "object",
+            "required": ["output"],
+            "properties": {
+                "output": schema
+            }
+        }),
+        Some(ToolAnnotations {
+            title: Some("Report the final output".to_string()),
+            read_only_hint: true,
+            destructive_hint: false,
+            idempotent_hint: true,
+            open_world_hint: false,
+        }),
+    )
+}
@@ -1,15 +1,18 @@
 use anyhow::Result;
+use async_stream::try_stream;
+use futures::StreamExt;
 use std::collections::HashSet;
 use std::sync::Arc;
 
 use crate::agents::router_tool_selector::RouterToolSelectionStrategy;
+use crate::agents::sub_recipes::sub_recipe_tool;
 use crate::config::Config;
 use crate::message::{Message, MessageContent, ToolRequest};
-use crate::providers::base::{Provider, ProviderUsage};
+use crate::providers::base::{MessageStream, Provider, ProviderStreamItem};
 use crate::providers::errors::ProviderError;
 use crate::providers::toolshim::{
-    augment_message_with_tool_calls, convert_tool_messages_to_text,
-    modify_system_prompt_for_tool_json, OllamaInterpreter,
+    augment_message_with_tool_calls, convert_tool_messages_to_text, create_interpreter,
+    modify_system_prompt_for_tool_json,
 };
 use crate::session;
 use mcp_core::tool::Tool;
@@ -45,6 +48,14 @@ impl Agent {
         for frontend_tool in frontend_tools.values() {
             tools.push(frontend_tool.tool.clone());
         }
+        // Sub-recipe and final output tools are needed whichever tools the router selects
+        for template in self.sub_recipes.lock().await.iter() {
+            tools.push(sub_recipe_tool(template));
+        }
+        let final_output = self.final_output.lock().await;
+        if let Some(final_output) = final_output.as_ref() {
+            tools.push(final_output.tool());
+        }
 
         // Prepare system prompt
         let extension_manager = self.extension_manager.lock().await;
@@ -63,6 +74,10 @@ impl Agent {
             Some(model_name),
             tool_selection_strategy,
         );
+        if let Some(final_output) = final_output.as_ref() {
+            system_prompt.push_str("\n\n");
+            system_prompt.push_str(&final_output.system_prompt());
+        }
 
         // Handle toolshim if enabled
         let mut toolshim_tools = vec![];
@@ -100,15 +115,15 @@ impl Agent {
             })
     }
 
-    /// Generate a response from the LLM provider
+    /// Stream a response from the LLM provider
     /// Handles toolshim transformations if needed
-    pub(crate) async fn generate_response_from_provider(
+    pub(crate) async fn stream_response_from_provider(
         provider: Arc<dyn Provider>,
         system_prompt: &str,
         messages: &[Message],
         tools: &[Tool],
         toolshim_tools: &[Tool],
-    ) -> Result<(Message, ProviderUsage), ProviderError> {
+    ) -> Result<MessageStream, ProviderError> {
         let config = provider.get_model_config();
 
         // Convert tool messages to text if toolshim is enabled
@@ -118,28 +133,38 @@ impl Agent {
             messages.to_vec()
         };
 
-        // Call the provider to get a response
-        let (mut response, usage) = provider
-            .complete(system_prompt, &messages_for_provider, tools)
+        // Call the provider to get a response stream
+        let mut stream = provider
+            .stream(system_prompt, &messages_for_provider, tools)
             .await?;
-
-        // Store the model information in the global store
-        crate::providers::base::set_current_model(&usage.model);
-
-        // Post-process / structure the response only if tool interpretation is enabled
-        if config.toolshim {
-            let interpreter = OllamaInterpreter::new().map_err(|e| {
-                ProviderError::ExecutionError(format!("Failed to create OllamaInterpreter: {}", e))
-            })?;
-
-            response = augment_message_with_tool_calls(&interpreter, response, toolshim_tools)
-                .await
-                .map_err(|e| {
-                    ProviderError::ExecutionError(format!("Failed to augment message: {}", e))
-                })?;
-        }
-
-        Ok((response, usage))
+        let toolshim_tools = toolshim_tools.to_vec();
+
+        Ok(Box::pin(try_stream! {
+            while let Some(item) = stream.next().await {
+                match item? {
+                    ProviderStreamItem::Complete(mut response, usage) => {
+                        // Store the model information in the global store
+                        crate::providers::base::set_current_model(&usage.model);
+
+                        // Post-process / structure the response only if tool interpretation is enabled
+                        if config.toolshim {
+                            let interpreter = create_interpreter(&config).map_err(|e| {
+                                ProviderError::ExecutionError(format!("Failed to create tool interpreter: {}", e))
+                            })?;
+
+                            response = augment_message_with_tool_calls(interpreter.as_ref(), response, &toolshim_tools)
+                                .await
+                                .map_err(|e| {
+                                    ProviderError::ExecutionError(format!("Failed to augment message: {}", e))
+                                })?;
+                        }
+
+                        yield ProviderStreamItem::Complete(response, usage);
+                    }
+                    delta => yield delta,
+                }
+            }
+        }))
     }
 
     /// Categorize tool requests from the response into different types
@@ -215,6 +240,7 @@ impl Agent {
     pub(crate) async fn update_session_metrics(
         session_config: crate::agents::types::SessionConfig,
         usage: &crate::providers::base::ProviderUsage,
+        cost: Option<f64>,
         messages_length: usize,
     ) -> Result<()> {
         let session_file_path = session::storage::get_path(session_config.id.clone());
@@ -242,6 +268,10 @@ impl Agent {
             metadata.accumulated_output_tokens,
             usage.usage.output_tokens,
         );
+        metadata.accumulated_cost = match (metadata.accumulated_cost, cost) {
+            (Some(total), Some(cost)) => Some(total + cost),
+            (total, cost) => total.or(cost),
+        };
 
         session::storage::update_metadata(&session_file_path, &metadata).await?;
 
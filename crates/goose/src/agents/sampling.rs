@@ -0,0 +1,349 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use mcp_client::client::SamplingHandler;
+use mcp_core::protocol::{CreateMessageParams, CreateMessageResult, ErrorData, INTERNAL_ERROR};
+use mcp_core::{Content, Role};
+use tokio::sync::{mpsc, oneshot, Mutex, MutexGuard};
+
+use crate::config::permission::PermissionLevel;
+use crate::config::{Config, PermissionManager};
+use crate::message::{Message, MessageContent};
+use crate::providers::base::Provider;
+
+/// Error code MCP uses when the user rejects a sampling request
+const USER_REJECTED: i32 = -1;
+
+/// Prefix of the confirmation ids used for sampling requests
+const SAMPLING_REQUEST_PREFIX: &str = "sampling_";
+
+/// How long a sampling request waits for the user before it is rejected
+const APPROVAL_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Whether a tool confirmation request is actually asking to approve sampling
+pub fn is_sampling_request_id(id: &str) -> bool {
+    id.starts_with(SAMPLING_REQUEST_PREFIX)
+}
+
+pub(crate) type SharedProvider = Arc<Mutex<Option<Arc<dyn Provider>>>>;
+pub(crate) type SamplingReceiver = Arc<Mutex<mpsc::Receiver<SamplingRequest>>>;
+
+/// A sampling request from an extension, waiting on the user's approval
+pub struct SamplingRequest {
+    pub id: String,
+    pub extension_name: String,
+    pub params: CreateMessageParams,
+    pub approval_tx: oneshot::Sender<bool>,
+}
+
+impl SamplingRequest {
+    /// The name the approval is recorded under in the permission manager
+    pub fn permission_name(&self) -> String {
+        format!("{}__sampling", self.extension_name)
+    }
+
+    /// Whether the user doesn't need to be asked, in auto mode or after they chose "always allow"
+    pub fn is_pre_approved(&self, mode: &str, permission_manager: &PermissionManager) -> bool {
+        mode == "auto"
+            || permission_manager.get_user_permission(&self.permission_name())
+                == Some(PermissionLevel::AlwaysAllow)
+    }
+
+    /// The confirmation shown to the user before the model is called
+    pub fn confirmation_message(&self) -> Message {
+        Message::user().with_tool_confirmation_request(
+            self.id.clone(),
+            self.permission_name(),
+            serde_json::to_value(&self.params).unwrap_or_default(),
+            Some(format!(
+                "The {} extension would like to use your model to generate a response. Allow? (y/n):",
+                self.extension_name
+            )),
+        )
+    }
+}
+
+/// A reply's hold on the sampling requests, only one reply answers them at a time.
+/// Requests still queued when the reply ends are rejected rather than left waiting.
+pub(crate) struct SamplingRequests<'a> {
+    rx: Option<MutexGuard<'a, mpsc::Receiver<SamplingRequest>>>,
+}
+
+impl<'a> SamplingRequests<'a> {
+    pub(crate) fn acquire(rx: &'a Mutex<mpsc::Receiver<SamplingRequest>>) -> Self {
+        Self {
+            rx: rx.try_lock().ok(),
+        }
+    }
+
+    /// The next request, never resolving when another reply is answering them
+    pub(crate) async fn next(&mut self) -> Option<SamplingRequest> {
+        match self.rx.as_mut() {
+            Some(rx) => rx.recv().await,
+            None => std::future::pending().await,
+        }
+    }
+}
+
+impl Drop for SamplingRequests<'_> {
+    fn drop(&mut self) {
+        if let Some(rx) = self.rx.as_mut() {
+            while let Ok(request) = rx.try_recv() {
+                let _ = request.approval_tx.send(false);
+            }
+        }
+    }
+}
+
+/// Shared state needed to build a sampling handler for each extension
+#[derive(Clone)]
+pub struct SamplingContext {
+    provider: SharedProvider,
+    request_tx: mpsc::Sender<SamplingRequest>,
+    request_rx: SamplingReceiver,
+}
+
+impl SamplingContext {
+    pub(crate) fn new(
+        provider: SharedProvider,
+        request_tx: mpsc::Sender<SamplingRequest>,
+        request_rx: SamplingReceiver,
+    ) -> Self {
+        Self {
+            provider,
+            request_tx,
+            request_rx,
+        }
+    }
+
+    pub fn handler(&self, extension_name: &str) -> Arc<dyn SamplingHandler> {
+        Arc::new(AgentSamplingHandler {
+            extension_name: extension_name.to_string(),
+            provider: Arc::clone(&self.provider),
+            request_tx: self.request_tx.clone(),
+            request_rx: Arc::clone(&self.request_rx),
+        })
+    }
+}
+
+/// Answers an extension's sampling requests with the agent's provider, once the user approves
+struct AgentSamplingHandler {
+    extension_name: String,
+    provider: SharedProvider,
+    request_tx: mpsc::Sender<SamplingRequest>,
+    request_rx: SamplingReceiver,
+}
+
+impl AgentSamplingHandler {
+    /// Ask the running reply to get the user's approval. Without a reply there is nobody to
+    /// ask, so only requests the user allowed up front go ahead.
+    async fn approve(
+        &self,
+        request: SamplingRequest,
+        approval_rx: oneshot::Receiver<bool>,
+    ) -> bool {
+        let idle = self.request_rx.try_lock().is_ok();
+        if idle {
+            let mode = Config::global()
+                .get_param("GOOSE_MODE")
+                .unwrap_or("auto".to_string());
+            return request.is_pre_approved(&mode, &PermissionManager::default());
+        }
+
+        if self.request_tx.send(request).await.is_err() {
+            return false;
+        }
+        matches!(
+            tokio::time::timeout(APPROVAL_TIMEOUT, approval_rx).await,
+            Ok(Ok(true))
+        )
+    }
+}
+
+fn sampling_error(code: i32, message: impl Into<String>) -> ErrorData {
+    ErrorData {
+        code,
+        message: message.into(),
+        data: None,
+    }
+}
+
+/// Convert the request into the system prompt and messages goose sends to the provider
+fn to_messages(params: &CreateMessageParams) -> (String, Vec<Message>) {
+    let messages = params
+        .messages
+        .iter()
+        .map(|message| {
+            let base = match message.role {
+                Role::User => Message::user(),
+                Role::Assistant => Message::assistant(),
+            };
+            base.with_content(MessageContent::from(message.content.clone()))
+        })
+        .collect();
+
+    (params.system_prompt.clone().unwrap_or_default(), messages)
+}
+
+#[async_trait]
+impl SamplingHandler for AgentSamplingHandler {
+    async fn create_message(
+        &self,
+        params: CreateMessageParams,
+    ) -> Result<CreateMessageResult, ErrorData> {
+        let (approval_tx, approval_rx) = oneshot::channel();
+        let request = SamplingRequest {
+            id: format!("{}{}", SAMPLING_REQUEST_PREFIX, uuid::Uuid::new_v4()),
+            extension_name: self.extension_name.clone(),
+            params: params.clone(),
+            approval_tx,
+        };
+        if !self.approve(request, approval_rx).await {
+            return Err(sampling_error(
+                USER_REJECTED,
+                "User rejected the sampling request",
+            ));
+        }
+
+        let provider = match self.provider.lock().await.as_ref() {
+            Some(provider) => Arc::clone(provider),
+            None => return Err(sampling_error(INTERNAL_ERROR, "Provider not set")),
+        };
+
+        let (system, messages) = to_messages(&params);
+        let (response, usage) = provider
+            .complete(&system, &messages, &[])
+            .await
+            .map_err(|e| sampling_error(INTERNAL_ERROR, e.to_string()))?;
+
+        Ok(CreateMessageResult {
+            role: Role::Assistant,
+            content: Content::text(response.as_concat_text()),
+            model: usage.model,
+            stop_reason: Some("endTurn".to_string()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::ModelConfig;
+    use crate::providers::base::{ProviderMetadata, ProviderUsage, Usage};
+    use crate::providers::errors::ProviderError;
+    use mcp_core::protocol::SamplingMessage;
+    use mcp_core::tool::Tool;
+
+    struct EchoProvider;
+
+    #[async_trait]
+    impl Provider for EchoProvider {
+        fn metadata() -> ProviderMetadata {
+            ProviderMetadata::empty()
+        }
+
+        async fn complete(
+            &self,
+            system: &str,
+            messages: &[Message],
+            _tools: &[Tool],
+        ) -> Result<(Message, ProviderUsage), ProviderError> {
+            Ok((
+                Message::assistant().with_text(format!(
+                    "{}: {}",
+                    system,
+                    messages[0].as_concat_text()
+                )),
+                ProviderUsage::new("echo-model".to_string(), Usage::default()),
+            ))
+        }
+
+        fn get_model_config(&self) -> ModelConfig {
+            ModelConfig::new("echo-model".to_string())
+        }
+    }
+
+    fn params() -> CreateMessageParams {
+        CreateMessageParams {
+            messages: vec![SamplingMessage {
+                role: Role::User,
+                content: Content::text("hello"),
+            }],
+            model_preferences: None,
+            system_prompt: Some("echo".to_string()),
+            include_context: None,
+            temperature: None,
+            max_tokens: 100,
+            stop_sequences: None,
+            metadata: None,
+        }
+    }
+
+    fn context() -> (SamplingContext, SamplingReceiver) {
+        let provider: Arc<dyn Provider> = Arc::new(EchoProvider);
+        let (tx, rx) = mpsc::channel(1);
+        let rx = Arc::new(Mutex::new(rx));
+        (
+            SamplingContext::new(Arc::new(Mutex::new(Some(provider))), tx, Arc::clone(&rx)),
+            rx,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_approved_sampling_uses_provider() {
+        let (context, rx) = context();
+        let handler = context.handler("notes");
+
+        // Stand in for a running reply
+        let mut rx = rx.lock_owned().await;
+        tokio::spawn(async move {
+            let request = rx.recv().await.unwrap();
+            assert_eq!(request.permission_name(), "notes__sampling");
+            assert!(is_sampling_request_id(&request.id));
+            request.approval_tx.send(true).unwrap();
+        });
+
+        let result = handler.create_message(params()).await.unwrap();
+        assert_eq!(result.content, Content::text("echo: hello"));
+        assert_eq!(result.model, "echo-model");
+        assert_eq!(result.role, Role::Assistant);
+    }
+
+    #[tokio::test]
+    async fn test_rejected_sampling_returns_error() {
+        let (context, rx) = context();
+        let handler = context.handler("notes");
+
+        let mut rx = rx.lock_owned().await;
+        tokio::spawn(async move {
+            let request = rx.recv().await.unwrap();
+            request.approval_tx.send(false).unwrap();
+        });
+
+        let err = handler.create_message(params()).await.unwrap_err();
+        assert_eq!(err.code, USER_REJECTED);
+    }
+
+    #[tokio::test]
+    async fn test_requests_left_at_the_end_of_a_reply_are_rejected() {
+        let (tx, rx) = mpsc::channel(1);
+        let rx = Mutex::new(rx);
+        let requests = SamplingRequests::acquire(&rx);
+        // Only one reply answers sampling requests at a time
+        assert!(SamplingRequests::acquire(&rx).rx.is_none());
+
+        let (approval_tx, approval_rx) = oneshot::channel();
+        tx.send(SamplingRequest {
+            id: format!("{}1", SAMPLING_REQUEST_PREFIX),
+            extension_name: "notes".to_string(),
+            params: params(),
+            approval_tx,
+        })
+        .await
+        .unwrap();
+        drop(requests);
+
+        assert_eq!(approval_rx.await, Ok(false));
+    }
+}
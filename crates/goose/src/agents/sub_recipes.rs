@@ -0,0 +1,284 @@
+use futures::FutureExt;
+use mcp_core::tool::{Tool, ToolAnnotations};
+use mcp_core::{Content, ToolError};
+use serde_json::Value;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+
+use crate::agents::agent::Agent;
+use crate::agents::budget::ReplyBudget;
+use crate::agents::subagent::{configured_max_turns, run_subagent};
+use crate::agents::tool_execution::ToolCallResult;
+use crate::agents::types::SessionConfig;
+use crate::recipe::SubRecipeTemplate;
+use crate::session::storage::{self, Identifier, SessionMetadata};
+
+/// Sub-recipe tools are named after the sub-recipe with this prefix
+pub const SUB_RECIPE_TOOL_PREFIX: &str = "subrecipe__";
+
+pub(super) fn sub_recipe_tool(template: &SubRecipeTemplate) -> Tool {
+    Tool::new(
+        format!("{}{}", SUB_RECIPE_TOOL_PREFIX, template.name),
+        format!(
+            "{}\n\nRuns the '{}' recipe in a separate session and returns its result.",
+            template.description, template.name
+        ),
+        template.input_schema(),
+        Some(ToolAnnotations {
+            title: Some(format!("Run the {} recipe", template.name)),
+            read_only_hint: false,
+            destructive_hint: true,
+            idempotent_hint: false,
+            open_world_hint: true,
+        }),
+    )
+}
+
+impl Agent {
+    /// Let the agent run `sub_recipes` as tools, replacing any it had before
+    pub async fn set_sub_recipes(&self, sub_recipes: Vec<SubRecipeTemplate>) {
+        *self.sub_recipes.lock().await = sub_recipes;
+    }
+
+    /// The sub-recipe a tool call is for, if it is for one
+    pub(super) async fn sub_recipe_for_tool(&self, tool_name: &str) -> Option<SubRecipeTemplate> {
+        let name = tool_name.strip_prefix(SUB_RECIPE_TOOL_PREFIX)?;
+        self.sub_recipes
+            .lock()
+            .await
+            .iter()
+            .find(|template| template.name == name)
+            .cloned()
+    }
+
+    /// Render the sub-recipe with the tool call's arguments and run it in a child agent with
+    /// its own conversation, returning the child's final output or response
+    pub(super) async fn run_sub_recipe(
+        &self,
+        template: SubRecipeTemplate,
+        arguments: Value,
+    ) -> Result<ToolCallResult, ToolError> {
+        let recipe = template
+            .render(&arguments)
+            .map_err(|e| ToolError::InvalidParameters(e.to_string()))?;
+
+        // The child shares the extensions the parent is running and starts the others itself
+        let (shared, own) = match recipe.extensions {
+            Some(extensions) => {
+                let extension_manager = self.extension_manager.lock().await;
+                let (shared, own): (Vec<_>, Vec<_>) = extensions
+                    .into_iter()
+                    .partition(|extension| extension_manager.is_enabled(&extension.key()));
+                let shared: Vec<String> = shared.iter().map(|e| e.key()).collect();
+                (Some(shared), own)
+            }
+            None => (None, Vec::new()),
+        };
+        let child = self
+            .subagent(shared.as_deref(), configured_max_turns())
+            .await?;
+        for extension in own {
+            let name = extension.name();
+            child.add_extension(extension).await.map_err(|e| {
+                ToolError::ExecutionError(format!(
+                    "Failed to start extension '{}' for sub-recipe '{}': {}",
+                    name, template.name, e
+                ))
+            })?;
+        }
+
+        if let Some(schema) = recipe.response.and_then(|response| response.json_schema) {
+            child
+                .set_final_output_schema(schema)
+                .await
+                .map_err(|e| ToolError::ExecutionError(e.to_string()))?;
+        }
+
+        // The values the parent passed on are already rendered into the recipe's own entries
+        let mut sub_recipes = template.sub_recipes;
+        for declared in recipe.sub_recipes.unwrap_or_default() {
+            if let Some(sub_recipe) = sub_recipes.iter_mut().find(|t| t.name == declared.name) {
+                sub_recipe.values = declared.values;
+            }
+        }
+        child.set_sub_recipes(sub_recipes).await;
+
+        let task = match (recipe.instructions, recipe.prompt) {
+            (Some(instructions), Some(prompt)) => {
+                child.extend_system_prompt(instructions).await;
+                prompt
+            }
+            (Some(task), None) | (None, Some(task)) => task,
+            (None, None) => {
+                return Err(ToolError::ExecutionError(format!(
+                    "Sub-recipe '{}' has neither instructions nor a prompt",
+                    template.name
+                )))
+            }
+        };
+
+        let session = self.sub_recipe_session(&template.name).await?;
+        let (tx, rx) = mpsc::channel(32);
+        let result = async move {
+            run_subagent(0, child, task, session, tx)
+                .await
+                .map(|response| vec![Content::text(response)])
+                .map_err(ToolError::ExecutionError)
+        };
+
+        Ok(ToolCallResult {
+            result: Box::new(result.boxed()),
+            notification_stream: Some(Box::new(ReceiverStream::new(rx))),
+        })
+    }
+
+    /// Start a session for a run of the sub-recipe `name`, recorded as a child of the session
+    /// of the reply in progress. Replies without a session run their sub-recipes without one.
+    async fn sub_recipe_session(&self, name: &str) -> Result<Option<SessionConfig>, ToolError> {
+        let parent = match self.session.lock().await.clone() {
+            Some(parent) => parent,
+            None => return Ok(None),
+        };
+        let parent_file = storage::get_path(parent.id.clone());
+        let parent_id = parent_file
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        let mut session_file = parent_file.with_file_name(format!("{}_{}.jsonl", parent_id, name));
+        let mut suffix = 1;
+        while storage::session_exists(&session_file) {
+            session_file =
+                parent_file.with_file_name(format!("{}_{}_{}.jsonl", parent_id, name, suffix));
+            suffix += 1;
+        }
+
+        let mut metadata = SessionMetadata::new(parent.working_dir.clone());
+        metadata.description = format!("Sub-recipe {}", name);
+        metadata.schedule_id = parent.schedule_id.clone();
+        metadata.parent_session = Some(parent_id);
+        storage::save_messages_with_metadata(&session_file, &metadata, &[]).map_err(|e| {
+            ToolError::ExecutionError(format!("Failed to create the sub-recipe session: {}", e))
+        })?;
+
+        Ok(Some(SessionConfig {
+            id: Identifier::Path(session_file),
+            working_dir: parent.working_dir,
+            schedule_id: parent.schedule_id,
+            budget: ReplyBudget::default(),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::message::Message;
+    use crate::model::ModelConfig;
+    use crate::providers::base::{Provider, ProviderMetadata, ProviderUsage, Usage};
+    use crate::providers::errors::ProviderError;
+    use async_trait::async_trait;
+    use serde_json::json;
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    struct DoneProvider;
+
+    #[async_trait]
+    impl Provider for DoneProvider {
+        fn metadata() -> ProviderMetadata {
+            ProviderMetadata::empty()
+        }
+
+        async fn complete(
+            &self,
+            _system: &str,
+            _messages: &[Message],
+            _tools: &[Tool],
+        ) -> Result<(Message, ProviderUsage), ProviderError> {
+            Ok((
+                Message::assistant().with_text("Notes written."),
+                ProviderUsage::new("mock".to_string(), Usage::default()),
+            ))
+        }
+
+        fn get_model_config(&self) -> ModelConfig {
+            ModelConfig::new("mock".to_string())
+        }
+    }
+
+    fn template(content: &str) -> SubRecipeTemplate {
+        SubRecipeTemplate {
+            name: "notes".to_string(),
+            description: "Take notes".to_string(),
+            content: content.to_string(),
+            recipe_dir: std::env::temp_dir(),
+            parameters: Vec::new(),
+            values: HashMap::new(),
+            sub_recipes: Vec::new(),
+        }
+    }
+
+    const NOTES_RECIPE: &str = r#"
+version: 1.0.0
+title: Notes
+description: Take notes
+prompt: Write the notes
+"#;
+
+    #[tokio::test]
+    async fn test_sub_recipe_session_is_a_child_of_the_reply_session() {
+        let dir = tempfile::tempdir().unwrap();
+        let agent = Agent::new();
+        *agent.provider.lock().await = Some(Arc::new(DoneProvider));
+        *agent.session.lock().await = Some(SessionConfig {
+            id: Identifier::Path(dir.path().join("parent.jsonl")),
+            working_dir: dir.path().to_path_buf(),
+            schedule_id: None,
+            budget: ReplyBudget::default(),
+        });
+
+        for _ in 0..2 {
+            let call = agent
+                .run_sub_recipe(template(NOTES_RECIPE), json!({}))
+                .await
+                .unwrap();
+            call.result.await.unwrap();
+        }
+
+        for file in ["parent_notes.jsonl", "parent_notes_1.jsonl"] {
+            let session_file = dir.path().join(file);
+            let metadata = storage::read_metadata(&session_file).unwrap();
+            assert_eq!(metadata.parent_session.as_deref(), Some("parent"));
+            assert_eq!(metadata.description, "Sub-recipe notes");
+
+            let messages = storage::read_messages(&session_file).unwrap();
+            assert_eq!(messages.len(), 2);
+            assert_eq!(messages[0].as_concat_text(), "Write the notes");
+            assert_eq!(messages[1].as_concat_text(), "Notes written.");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sub_recipe_starts_extensions_the_parent_lacks() {
+        let agent = Agent::new();
+        *agent.provider.lock().await = Some(Arc::new(DoneProvider));
+
+        let content = r#"
+version: 1.0.0
+title: Notes
+description: Take notes
+prompt: Write the notes
+extensions:
+  - type: frontend
+    name: notepad
+    tools: []
+"#;
+        let call = agent
+            .run_sub_recipe(template(content), json!({}))
+            .await
+            .unwrap();
+        let result = call.result.await.unwrap();
+        assert_eq!(result[0].as_text(), Some("Notes written."));
+    }
+}
@@ -0,0 +1,353 @@
+use futures::stream::{self, StreamExt};
+use futures::FutureExt;
+use mcp_core::protocol::{JsonRpcMessage, JsonRpcNotification};
+use mcp_core::{Content, Role, ToolError};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tracing::warn;
+
+use crate::agents::agent::{Agent, AgentEvent};
+use crate::agents::budget::ReplyBudget;
+use crate::agents::tool_execution::ToolCallResult;
+use crate::agents::types::SessionConfig;
+use crate::config::Config;
+use crate::message::{Message, MessageContent};
+use crate::permission::permission_confirmation::PrincipalType;
+use crate::permission::{Permission, PermissionConfirmation};
+use crate::session;
+
+/// How many sub-agents run at once, unless configured
+const DEFAULT_MAX_CONCURRENCY: usize = 3;
+
+/// How many provider calls each sub-agent may make, unless configured
+const DEFAULT_MAX_TURNS: u64 = 20;
+
+/// The notification method sub-agent updates travel under on the delegate tool's stream
+const SUBAGENT_NOTIFICATION_METHOD: &str = "notifications/subagent";
+
+#[derive(Debug, Deserialize)]
+struct DelegateArgs {
+    tasks: Vec<SubagentTask>,
+    max_turns: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SubagentTask {
+    instructions: String,
+    extensions: Option<Vec<String>>,
+}
+
+/// What one of the sub-agents started by the delegate tool request `request_id` is doing
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct SubagentEvent {
+    pub request_id: String,
+    /// Position of the sub-agent's task in the delegate call, starting at 0
+    pub subagent: usize,
+    pub update: SubagentUpdate,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum SubagentUpdate {
+    Started { instructions: String },
+    Message { message: Message },
+    Finished { response: String },
+    Failed { error: String },
+}
+
+impl SubagentEvent {
+    /// Parse a sub-agent notification, returning None for any other message
+    pub fn from_notification(request_id: &str, message: &JsonRpcMessage) -> Option<Self> {
+        let params = match message {
+            JsonRpcMessage::Notification(notification)
+                if notification.method == SUBAGENT_NOTIFICATION_METHOD =>
+            {
+                notification.params.as_ref()?
+            }
+            _ => return None,
+        };
+
+        Some(Self {
+            request_id: request_id.to_string(),
+            subagent: params.get("subagent")?.as_u64()? as usize,
+            update: serde_json::from_value(params.get("update")?.clone()).ok()?,
+        })
+    }
+}
+
+fn to_notification(subagent: usize, update: &SubagentUpdate) -> JsonRpcMessage {
+    JsonRpcMessage::Notification(JsonRpcNotification {
+        jsonrpc: "2.0".to_string(),
+        method: SUBAGENT_NOTIFICATION_METHOD.to_string(),
+        params: Some(json!({ "subagent": subagent, "update": update })),
+    })
+}
+
+/// The final text of a sub-agent's reply, noting why it stopped early if it did
+fn final_response(messages: &[Message]) -> String {
+    let mut response = messages
+        .iter()
+        .rev()
+        .filter(|message| message.role == Role::Assistant)
+        .map(|message| message.as_concat_text())
+        .find(|text| !text.trim().is_empty())
+        .unwrap_or_default();
+
+    let stopped = messages.last().and_then(|message| {
+        message.content.iter().find_map(|content| match content {
+            MessageContent::BudgetExceeded(exceeded) => Some(exceeded.msg.clone()),
+            MessageContent::ContextLengthExceeded(exceeded) => Some(exceeded.msg.clone()),
+            _ => None,
+        })
+    });
+    if let Some(stopped) = stopped {
+        response = format!(
+            "{}\n\n(The sub-agent did not finish: {})",
+            response, stopped
+        );
+    }
+    response.trim().to_string()
+}
+
+/// How many provider calls each sub-agent may make
+pub(super) fn configured_max_turns() -> u64 {
+    Config::global()
+        .get_param::<u64>("GOOSE_SUBAGENT_MAX_TURNS")
+        .unwrap_or(DEFAULT_MAX_TURNS)
+}
+
+/// Run one task to completion in `child`, reporting its progress on `tx` and saving the
+/// conversation to `session` if there is one. The result is the child's final output when it
+/// was given a schema, and its final response otherwise.
+pub(super) async fn run_subagent(
+    index: usize,
+    child: Agent,
+    instructions: String,
+    session: Option<SessionConfig>,
+    tx: mpsc::Sender<JsonRpcMessage>,
+) -> Result<String, String> {
+    let notify = |update: SubagentUpdate| {
+        let tx = tx.clone();
+        async move {
+            let _ = tx.send(to_notification(index, &update)).await;
+        }
+    };
+    notify(SubagentUpdate::Started {
+        instructions: instructions.clone(),
+    })
+    .await;
+
+    let mut messages = vec![Message::user().with_text(instructions)];
+    let result: Result<String, String> = async {
+        let mut stream = child
+            .reply(&messages, session.clone())
+            .await
+            .map_err(|e| e.to_string())?;
+
+        while let Some(event) = stream.next().await {
+            match event {
+                Ok(AgentEvent::Message(message)) => {
+                    // Nobody can answer a sub-agent's confirmation requests, so decline them
+                    for content in &message.content {
+                        if let MessageContent::ToolConfirmationRequest(request) = content {
+                            child
+                                .handle_confirmation(
+                                    request.id.clone(),
+                                    PermissionConfirmation {
+                                        principal_type: PrincipalType::Tool,
+                                        permission: Permission::DenyOnce,
+                                    },
+                                )
+                                .await;
+                        }
+                    }
+                    notify(SubagentUpdate::Message {
+                        message: message.clone(),
+                    })
+                    .await;
+                    messages.push(message);
+                }
+                Ok(_) => {}
+                Err(e) => return Err(e.to_string()),
+            }
+        }
+        match child.final_output().await {
+            Some(output) => Ok(output.to_string()),
+            None => Ok(final_response(&messages)),
+        }
+    }
+    .await;
+
+    if let Some(session) = session {
+        let session_file = session::storage::get_path(session.id);
+        if let Err(e) = session::storage::persist_messages(&session_file, &messages, None).await {
+            warn!("Failed to save the sub-agent session: {}", e);
+        }
+    }
+
+    match &result {
+        Ok(response) => {
+            notify(SubagentUpdate::Finished {
+                response: response.clone(),
+            })
+            .await
+        }
+        Err(error) => {
+            notify(SubagentUpdate::Failed {
+                error: error.clone(),
+            })
+            .await
+        }
+    }
+    result
+}
+
+impl Agent {
+    /// A child agent sharing this agent's provider and the running clients of `extensions`
+    pub(super) async fn subagent(
+        &self,
+        extensions: Option<&[String]>,
+        max_turns: u64,
+    ) -> Result<Agent, ToolError> {
+        let provider = self
+            .provider()
+            .await
+            .map_err(|e| ToolError::ExecutionError(e.to_string()))?;
+        let extension_manager = self
+            .extension_manager
+            .lock()
+            .await
+            .scoped(extensions)
+            .map_err(|e| ToolError::InvalidParameters(e.to_string()))?;
+
+        let mut child = Agent::new();
+        child.subagent_budget = Some(ReplyBudget {
+            max_turns: Some(max_turns),
+            ..Default::default()
+        });
+        *child.provider.lock().await = Some(provider);
+        *child.extension_manager.lock().await = extension_manager;
+        *child.router_tool_selector.lock().await = self.router_tool_selector.lock().await.clone();
+        Ok(child)
+    }
+
+    /// Run each task of a delegate tool call in its own sub-agent, at most a configured number
+    /// at a time, and return their final responses as the tool result
+    pub(super) async fn delegate(&self, arguments: Value) -> Result<ToolCallResult, ToolError> {
+        if self.subagent_budget.is_some() {
+            return Err(ToolError::ExecutionError(
+                "Sub-agents cannot delegate to further sub-agents".to_string(),
+            ));
+        }
+        let args: DelegateArgs = serde_json::from_value(arguments)
+            .map_err(|e| ToolError::InvalidParameters(e.to_string()))?;
+        if args.tasks.is_empty() {
+            return Err(ToolError::InvalidParameters(
+                "At least one task is required".to_string(),
+            ));
+        }
+
+        let config = Config::global();
+        let max_concurrency = config
+            .get_param::<usize>("GOOSE_SUBAGENT_MAX_CONCURRENCY")
+            .unwrap_or(DEFAULT_MAX_CONCURRENCY)
+            .max(1);
+        let config_max_turns = configured_max_turns();
+        // The model may ask for fewer turns than configured, never more
+        let max_turns = args
+            .max_turns
+            .map_or(config_max_turns, |turns| turns.min(config_max_turns))
+            .max(1);
+
+        let mut children = Vec::with_capacity(args.tasks.len());
+        for task in args.tasks {
+            let child = self.subagent(task.extensions.as_deref(), max_turns).await?;
+            children.push((child, task.instructions));
+        }
+
+        let (tx, rx) = mpsc::channel(32);
+        let result = async move {
+            let results: Vec<Result<String, String>> = stream::iter(children)
+                .enumerate()
+                .map(|(index, (child, instructions))| {
+                    run_subagent(index, child, instructions, None, tx.clone())
+                })
+                .buffered(max_concurrency)
+                .collect()
+                .await;
+
+            Ok::<_, ToolError>(
+                results
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, result)| {
+                        let text = match result {
+                            Ok(response) => {
+                                format!("Sub-agent {} finished:\n{}", index + 1, response)
+                            }
+                            Err(error) => format!("Sub-agent {} failed: {}", index + 1, error),
+                        };
+                        Content::text(text)
+                    })
+                    .collect(),
+            )
+        };
+
+        Ok(ToolCallResult {
+            result: Box::new(result.boxed()),
+            notification_stream: Some(Box::new(ReceiverStream::new(rx))),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::message::BudgetKind;
+
+    #[test]
+    fn test_subagent_event_round_trips_through_notification() {
+        let update = SubagentUpdate::Message {
+            message: Message::assistant().with_text("looking at the tests"),
+        };
+        let notification = to_notification(2, &update);
+
+        let event = SubagentEvent::from_notification("req_1", &notification).unwrap();
+        assert_eq!(event.request_id, "req_1");
+        assert_eq!(event.subagent, 2);
+        assert_eq!(event.update, update);
+    }
+
+    #[test]
+    fn test_other_notifications_are_not_subagent_events() {
+        let progress = JsonRpcMessage::Notification(JsonRpcNotification {
+            jsonrpc: "2.0".to_string(),
+            method: "notifications/progress".to_string(),
+            params: Some(json!({ "progressToken": "req_1", "progress": 1 })),
+        });
+        assert!(SubagentEvent::from_notification("req_1", &progress).is_none());
+    }
+
+    #[test]
+    fn test_final_response_notes_early_stop() {
+        let messages = vec![
+            Message::assistant().with_text("Found three callers."),
+            Message::user().with_text("tool output"),
+            Message::assistant().with_budget_exceeded(
+                BudgetKind::Turns,
+                5,
+                "Stopped after reaching the limit of 5 turns.",
+            ),
+        ];
+        assert_eq!(
+            final_response(&messages),
+            "Found three callers.\n\n(The sub-agent did not finish: Stopped after reaching the limit of 5 turns.)"
+        );
+        assert_eq!(
+            final_response(&messages[..1]),
+            "Found three callers.".to_string()
+        );
+    }
+}
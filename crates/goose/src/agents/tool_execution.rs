@@ -30,6 +30,7 @@ impl From<ToolResult<Vec<Content>>> for ToolCallResult {
 }
 
 use super::agent::{tool_stream, ToolStream};
+use super::sampling::SamplingRequest;
 use crate::agents::Agent;
 
 pub const DECLINED_RESPONSE: &str = "The user has declined to run this tool. \
@@ -101,6 +102,28 @@ impl Agent {
         }.boxed()
     }
 
+    /// Wait for the user to answer a sampling confirmation, remembering "always allow"
+    pub(crate) async fn wait_for_sampling_confirmation(
+        &self,
+        request: &SamplingRequest,
+        permission_manager: &mut PermissionManager,
+    ) -> bool {
+        let mut rx = self.confirmation_rx.lock().await;
+        while let Some((req_id, confirmation)) = rx.recv().await {
+            if req_id == request.id {
+                if confirmation.permission == Permission::AlwaysAllow {
+                    permission_manager.update_user_permission(
+                        &request.permission_name(),
+                        PermissionLevel::AlwaysAllow,
+                    );
+                }
+                return confirmation.permission == Permission::AllowOnce
+                    || confirmation.permission == Permission::AlwaysAllow;
+            }
+        }
+        false
+    }
+
     pub(crate) fn handle_frontend_tool_requests<'a>(
         &'a self,
         tool_requests: &'a [ToolRequest],
@@ -78,6 +78,7 @@ impl ToolRouterIndexManager {
         // Add the standard platform tools
         tools.push(platform_tools::search_available_extensions_tool());
         tools.push(platform_tools::manage_extensions_tool());
+        tools.push(platform_tools::delegate_tool());
 
         // Add resource tools if supported
         if extension_manager.supports_resources() {
@@ -1,3 +1,4 @@
+use crate::agents::budget::ReplyBudget;
 use crate::session;
 use mcp_core::{Content, Tool, ToolResult};
 use serde::{Deserialize, Serialize};
@@ -24,4 +25,7 @@ pub struct SessionConfig {
     pub working_dir: PathBuf,
     /// ID of the schedule that triggered this session, if any
     pub schedule_id: Option<String>, // NEW
+    /// Limits for each reply, falling back to the config for any that are not set
+    #[serde(default)]
+    pub budget: ReplyBudget,
 }
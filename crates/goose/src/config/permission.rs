@@ -1,5 +1,7 @@
-use super::APP_STRATEGY;
+use super::{Config, APP_STRATEGY};
+use crate::permission::permission_policy::ToolPolicy;
 use etcetera::{choose_app_strategy, AppStrategy};
+use mcp_core::ToolCall;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
@@ -28,6 +30,7 @@ pub struct PermissionConfig {
 pub struct PermissionManager {
     config_path: PathBuf, // Path to the permission configuration file
     permission_map: HashMap<String, PermissionConfig>, // Mapping of permission names to configurations
+    policy: ToolPolicy, // Rules from the policy file, which take precedence over everything else
 }
 
 // Constants representing specific permission categories
@@ -46,6 +49,20 @@ impl Default for PermissionManager {
         std::fs::create_dir_all(&config_dir).expect("Failed to create config directory");
         let config_path = config_dir.join("permission.yaml");
 
+        // The policy file can live elsewhere, for example when it is managed centrally
+        let policy_path = Config::global()
+            .get_param::<String>("GOOSE_PERMISSION_POLICY")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| config_dir.join("policy.yaml"));
+        let policy = ToolPolicy::load(&policy_path).unwrap_or_else(|e| {
+            // Don't let a broken policy silently allow everything it was meant to stop
+            tracing::error!(
+                "Failed to load the permission policy, asking before every tool call: {:#}",
+                e
+            );
+            ToolPolicy::ask_always()
+        });
+
         // Load the existing configuration file or create an empty map if the file doesn't exist
         let permission_map = if config_path.exists() {
             // Load the configuration file
@@ -59,6 +76,7 @@ impl Default for PermissionManager {
         PermissionManager {
             config_path,
             permission_map,
+            policy,
         }
     }
 }
@@ -81,9 +99,26 @@ impl PermissionManager {
         PermissionManager {
             config_path,
             permission_map,
+            policy: ToolPolicy::default(),
         }
     }
 
+    /// Replaces the policy rules checked before any other permission.
+    pub fn set_policy(&mut self, policy: ToolPolicy) {
+        self.policy = policy;
+    }
+
+    /// Retrieves the permission level the policy gives a specific tool call, if any rule matches it.
+    pub fn get_policy_permission(
+        &self,
+        tool_call: &ToolCall,
+        working_dir: &Path,
+    ) -> Option<PermissionLevel> {
+        self.policy
+            .evaluate(tool_call, working_dir)
+            .map(PermissionLevel::from)
+    }
+
     /// Returns a list of all the names (keys) in the permission map.
     pub fn get_permission_names(&self) -> Vec<String> {
         self.permission_map.keys().cloned().collect()
@@ -0,0 +1,171 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use mcp_core::Role;
+
+use super::common::get_messages_token_counts;
+use super::summarize::summarize_messages;
+use crate::config::Config;
+use crate::message::Message;
+use crate::providers::base::Provider;
+use crate::token_counter::TokenCounter;
+
+/// Fraction of the target context limit at which compaction starts, unless configured
+const DEFAULT_THRESHOLD: f64 = 0.8;
+
+/// Fraction of the target context limit kept verbatim at the end of the conversation
+const KEEP_RECENT_FRACTION: f64 = 0.25;
+
+/// Opt-in compaction of the conversation before it outgrows the context window
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AutoCompactConfig {
+    pub enabled: bool,
+    /// Fraction of `estimate_target_context_limit` the conversation may use before compacting
+    pub threshold: f64,
+}
+
+impl Default for AutoCompactConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            threshold: DEFAULT_THRESHOLD,
+        }
+    }
+}
+
+impl AutoCompactConfig {
+    /// Read GOOSE_AUTO_COMPACT and GOOSE_AUTO_COMPACT_THRESHOLD from the config
+    pub fn from_config(config: &Config) -> Self {
+        let threshold = config
+            .get_param::<f64>("GOOSE_AUTO_COMPACT_THRESHOLD")
+            .ok()
+            .filter(|threshold| *threshold > 0.0 && *threshold <= 1.0)
+            .unwrap_or(DEFAULT_THRESHOLD);
+        Self {
+            enabled: config.get_param("GOOSE_AUTO_COMPACT").unwrap_or(false),
+            threshold,
+        }
+    }
+
+    /// Whether messages totalling `tokens` should be compacted to fit within `context_limit`
+    pub fn should_compact(&self, tokens: usize, context_limit: usize) -> bool {
+        self.enabled && tokens as f64 >= context_limit as f64 * self.threshold
+    }
+}
+
+/// Where the recent messages that are kept verbatim begin.
+///
+/// The kept messages always start with an assistant message, so every tool response they
+/// contain has its request alongside it and the summary, sent as a user message, is followed
+/// by the assistant. Returns None if there is nothing older to summarize.
+fn split_point(messages: &[Message], token_counts: &[usize], keep_tokens: usize) -> Option<usize> {
+    let mut split = None;
+    let mut tail_tokens = 0;
+    for index in (1..messages.len()).rev() {
+        tail_tokens += token_counts[index];
+        if messages[index].role != Role::Assistant {
+            continue;
+        }
+        // Always keep the latest turn, even if it alone is over the budget
+        if split.is_some() && tail_tokens > keep_tokens {
+            break;
+        }
+        split = Some(index);
+    }
+    split
+}
+
+/// Summarize the older turns of the conversation, keeping the most recent ones as they are.
+/// Returns None if the conversation is too short to compact.
+pub async fn compact_messages(
+    provider: Arc<dyn Provider>,
+    messages: &[Message],
+    token_counter: &TokenCounter,
+    context_limit: usize,
+) -> Result<Option<Vec<Message>>> {
+    let token_counts = get_messages_token_counts(token_counter, messages);
+    let keep_tokens = (context_limit as f64 * KEEP_RECENT_FRACTION) as usize;
+    let split = match split_point(messages, &token_counts, keep_tokens) {
+        Some(split) => split,
+        None => return Ok(None),
+    };
+
+    let (mut compacted, _) =
+        summarize_messages(provider, &messages[..split], token_counter, context_limit).await?;
+    compacted.extend_from_slice(&messages[split..]);
+    Ok(Some(compacted))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::message::MessageContent;
+    use mcp_core::{Content, ToolCall};
+    use serde_json::json;
+
+    fn text(role: Role, text: &str) -> Message {
+        let message = match role {
+            Role::User => Message::user(),
+            Role::Assistant => Message::assistant(),
+        };
+        message.with_text(text)
+    }
+
+    fn tool_turn(id: &str) -> Vec<Message> {
+        vec![
+            Message::assistant().with_tool_request(
+                id,
+                Ok(ToolCall::new("developer__shell", json!({"command": "ls"}))),
+            ),
+            Message::user().with_tool_response(id, Ok(vec![Content::text("file.txt")])),
+        ]
+    }
+
+    #[test]
+    fn test_should_compact_respects_threshold_and_opt_in() {
+        let config = AutoCompactConfig {
+            enabled: true,
+            threshold: 0.5,
+        };
+        assert!(!config.should_compact(49, 100));
+        assert!(config.should_compact(50, 100));
+        assert!(!AutoCompactConfig::default().should_compact(1000, 100));
+    }
+
+    #[test]
+    fn test_split_point_keeps_tool_pairs_together() {
+        let mut messages = vec![text(Role::User, "list the files")];
+        messages.extend(tool_turn("1"));
+        messages.extend(tool_turn("2"));
+        messages.push(text(Role::Assistant, "done"));
+        messages.push(text(Role::User, "thanks"));
+        let token_counts = vec![10; messages.len()];
+
+        // Room for four messages, which starts the kept messages at the second tool request
+        // rather than the first tool response
+        let split = split_point(&messages, &token_counts, 45).unwrap();
+        assert_eq!(split, 3);
+        assert_eq!(messages[split].role, Role::Assistant);
+
+        // Every kept tool response has its request kept too
+        for message in &messages[split..] {
+            for content in &message.content {
+                if let MessageContent::ToolResponse(response) = content {
+                    assert!(messages[split..].iter().any(|m| m
+                        .content
+                        .iter()
+                        .any(|c| c.as_tool_request().map(|r| &r.id) == Some(&response.id))));
+                }
+            }
+        }
+
+        // A tight budget still keeps the latest turn
+        assert_eq!(split_point(&messages, &token_counts, 0), Some(5));
+    }
+
+    #[test]
+    fn test_split_point_nothing_to_compact() {
+        let messages = vec![text(Role::User, "hello")];
+        assert_eq!(split_point(&messages, &[10], 100), None);
+    }
+}
@@ -1,4 +1,5 @@
 mod common;
+pub mod compact;
 pub mod summarize;
 pub mod truncate;
 
@@ -96,6 +96,31 @@ pub struct SummarizationRequested {
     pub msg: String,
 }
 
+/// A per-reply limit on how much work the agent may do
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub enum BudgetKind {
+    Turns,
+    InputTokens,
+    OutputTokens,
+    Time,
+    /// Spending across the whole session rather than the reply
+    SessionCost,
+}
+
+/// Ends a reply that used up one of its budgets
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct BudgetExceeded {
+    pub budget: BudgetKind,
+    /// The configured limit, in turns, tokens or seconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u64>,
+    /// The configured spending limit in USD, for the session cost budget
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost_limit: Option<f64>,
+    pub msg: String,
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
 /// Content passed inside a message, which can be both simple content and tool content
 #[serde(tag = "type", rename_all = "camelCase")]
@@ -110,6 +135,7 @@ pub enum MessageContent {
     RedactedThinking(RedactedThinkingContent),
     ContextLengthExceeded(ContextLengthExceeded),
     SummarizationRequested(SummarizationRequested),
+    BudgetExceeded(BudgetExceeded),
 }
 
 impl MessageContent {
@@ -182,6 +208,15 @@ impl MessageContent {
         MessageContent::SummarizationRequested(SummarizationRequested { msg: msg.into() })
     }
 
+    pub fn budget_exceeded<S: Into<String>>(budget: BudgetKind, limit: u64, msg: S) -> Self {
+        MessageContent::BudgetExceeded(BudgetExceeded {
+            budget,
+            limit: Some(limit),
+            cost_limit: None,
+            msg: msg.into(),
+        })
+    }
+
     // Add this new method to check for summarization requested content
     pub fn as_summarization_requested(&self) -> Option<&SummarizationRequested> {
         if let MessageContent::SummarizationRequested(ref summarization_requested) = self {
@@ -268,6 +303,29 @@ impl From<Content> for MessageContent {
     }
 }
 
+/// A partial piece of assistant output, emitted while a response is still streaming
+///
+/// Deltas are only for display; the complete message is always delivered afterwards
+/// and is what gets stored in the conversation history.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum MessageDelta {
+    Text { text: String },
+    Thinking { thinking: String },
+}
+
+impl MessageDelta {
+    pub fn text<S: Into<String>>(text: S) -> Self {
+        MessageDelta::Text { text: text.into() }
+    }
+
+    pub fn thinking<S: Into<String>>(thinking: S) -> Self {
+        MessageDelta::Thinking {
+            thinking: thinking.into(),
+        }
+    }
+}
+
 impl From<PromptMessage> for Message {
     fn from(prompt_message: PromptMessage) -> Self {
         // Create a new message with the appropriate role
@@ -401,6 +459,16 @@ impl Message {
         self.with_content(MessageContent::context_length_exceeded(msg))
     }
 
+    /// Add budget exceeded content to the message
+    pub fn with_budget_exceeded<S: Into<String>>(
+        self,
+        budget: BudgetKind,
+        limit: u64,
+        msg: S,
+    ) -> Self {
+        self.with_content(MessageContent::budget_exceeded(budget, limit, msg))
+    }
+
     /// Get the concatenated text content of the message, separated by newlines
     pub fn as_concat_text(&self) -> String {
         self.content
@@ -547,6 +615,24 @@ mod tests {
         assert_eq!(tool_call["error"], "Execution failed: Something went wrong");
     }
 
+    #[test]
+    fn test_budget_exceeded_serialization() {
+        let message = Message::assistant().with_budget_exceeded(
+            BudgetKind::OutputTokens,
+            1000,
+            "Stopped after using 1200 of the 1000 output tokens allowed.",
+        );
+
+        let value = serde_json::to_value(&message).unwrap();
+        assert_eq!(value["content"][0]["type"], "budgetExceeded");
+        assert_eq!(value["content"][0]["budget"], "outputTokens");
+        assert_eq!(value["content"][0]["limit"], 1000);
+        assert!(value["content"][0].get("cost_limit").is_none());
+
+        let roundtrip: Message = serde_json::from_value(value).unwrap();
+        assert_eq!(roundtrip, message);
+    }
+
     #[test]
     fn test_deserialization() {
         // Create a JSON string with our new format
@@ -53,6 +53,9 @@ pub struct ModelConfig {
     pub toolshim: bool,
     /// Model to use for toolshim (optional as a default exists)
     pub toolshim_model: Option<String>,
+    /// Backend that interprets tool calls for toolshim, e.g. "ollama" (the default), "openai",
+    /// "grammar" or "extract"
+    pub toolshim_provider: Option<String>,
 }
 
 /// Struct to represent model pattern matches and their limits
@@ -77,7 +80,11 @@ impl ModelConfig {
             .map(|val| val == "1" || val.to_lowercase() == "true")
             .unwrap_or(false);
 
-        let toolshim_model = std::env::var("GOOSE_TOOLSHIM_OLLAMA_MODEL").ok();
+        let toolshim_model = std::env::var("GOOSE_TOOLSHIM_MODEL")
+            .or_else(|_| std::env::var("GOOSE_TOOLSHIM_OLLAMA_MODEL"))
+            .ok();
+
+        let toolshim_provider = std::env::var("GOOSE_TOOLSHIM_PROVIDER").ok();
 
         let temperature = std::env::var("GOOSE_TEMPERATURE")
             .ok()
@@ -91,6 +98,7 @@ impl ModelConfig {
             max_tokens: None,
             toolshim,
             toolshim_model,
+            toolshim_provider,
         }
     }
 
@@ -159,6 +167,12 @@ impl ModelConfig {
         self
     }
 
+    /// Set the backend that interprets tool calls
+    pub fn with_toolshim_provider(mut self, provider: Option<String>) -> Self {
+        self.toolshim_provider = provider;
+        self
+    }
+
     /// Get the tokenizer name
     pub fn tokenizer_name(&self) -> &str {
         &self.tokenizer_name
@@ -220,6 +234,11 @@ mod tests {
         let config = ModelConfig::new("test-model".to_string())
             .with_toolshim_model(Some("mistral-nemo".to_string()));
         assert_eq!(config.toolshim_model, Some("mistral-nemo".to_string()));
+
+        // Test tool interpreter provider
+        let config = ModelConfig::new("test-model".to_string())
+            .with_toolshim_provider(Some("grammar".to_string()));
+        assert_eq!(config.toolshim_provider, Some("grammar".to_string()));
     }
 
     #[test]
@@ -10,6 +10,7 @@ use mcp_core::{tool::Tool, TextContent};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::collections::HashSet;
+use std::path::Path;
 use std::sync::Arc;
 
 /// Creates the tool definition for checking read-only permissions.
@@ -167,6 +168,7 @@ pub async fn check_tool_permissions(
     tools_without_annotation: HashSet<String>,
     permission_manager: &mut PermissionManager,
     provider: Arc<dyn Provider>,
+    working_dir: &Path,
 ) -> (PermissionCheckResult, Vec<String>) {
     let mut approved = vec![];
     let mut needs_approval = vec![];
@@ -178,14 +180,26 @@ pub async fn check_tool_permissions(
         if let Ok(tool_call) = request.tool_call.clone() {
             if mode == "chat" {
                 continue;
-            } else if mode == "auto" {
+            }
+
+            // 1. Check the policy, which applies in every other mode and can't be overridden
+            if let Some(level) = permission_manager.get_policy_permission(&tool_call, working_dir) {
+                match level {
+                    PermissionLevel::AlwaysAllow => approved.push(request.clone()),
+                    PermissionLevel::AskBefore => needs_approval.push(request.clone()),
+                    PermissionLevel::NeverAllow => denied.push(request.clone()),
+                }
+                continue;
+            }
+
+            if mode == "auto" {
                 approved.push(request.clone());
             } else {
                 if tool_call.name == PLATFORM_MANAGE_EXTENSIONS_TOOL_NAME {
                     extension_request_ids.push(request.id.clone());
                 }
 
-                // 1. Check user-defined permission
+                // 2. Check user-defined permission
                 if let Some(level) = permission_manager.get_user_permission(&tool_call.name) {
                     match level {
                         PermissionLevel::AlwaysAllow => approved.push(request.clone()),
@@ -195,7 +209,7 @@ pub async fn check_tool_permissions(
                     continue;
                 }
 
-                // 2. Fallback based on mode
+                // 3. Fallback based on mode
                 match mode {
                     "approve" => {
                         needs_approval.push(request.clone());
@@ -228,7 +242,7 @@ pub async fn check_tool_permissions(
         }
     }
 
-    // 3. LLM detect
+    // 4. LLM detect
     if !llm_detect_candidates.is_empty() && mode == "smart_approve" {
         let detected_readonly_tools =
             detect_read_only_tools(provider, llm_detect_candidates.iter().collect()).await;
@@ -448,6 +462,7 @@ mod tests {
             tools_without_annotation,
             &mut permission_manager,
             provider,
+            Path::new("/"),
         )
         .await;
 
@@ -507,6 +522,7 @@ mod tests {
             tools_without_annotation,
             &mut permission_manager,
             provider,
+            Path::new("/"),
         )
         .await;
 
@@ -515,4 +531,63 @@ mod tests {
         assert_eq!(result.needs_approval.len(), 0); // data_fetcher should need approval
         assert_eq!(result.denied.len(), 0); // No tool should be denied in this test
     }
+
+    #[tokio::test]
+    async fn test_check_tool_permissions_policy_takes_precedence() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let mut permission_manager = PermissionManager::new(temp_file.path());
+        permission_manager.update_user_permission("developer__shell", PermissionLevel::AlwaysAllow);
+        permission_manager.set_policy(
+            crate::permission::ToolPolicy::from_yaml(
+                r#"
+rules:
+  - tool: developer__shell
+    args:
+      - pointer: /command
+        regex: "rm\\s+-rf"
+    action: deny
+  - tool: developer__shell
+    args:
+      - pointer: /command
+        regex: "^git push"
+    action: ask
+"#,
+            )
+            .unwrap(),
+        );
+
+        let shell = |id: &str, command: &str| ToolRequest {
+            id: id.to_string(),
+            tool_call: ToolResult::Ok(ToolCall {
+                name: "developer__shell".to_string(),
+                arguments: serde_json::json!({ "command": command }),
+            }),
+        };
+        let candidate_requests = vec![
+            shell("tool_1", "ls"),
+            shell("tool_2", "rm -rf /"),
+            shell("tool_3", "git push --force"),
+        ];
+
+        // The policy applies even in auto mode, and overrides the user's permission
+        for mode in ["auto", "approve"] {
+            let (result, _) = check_tool_permissions(
+                &candidate_requests,
+                mode,
+                HashSet::new(),
+                HashSet::new(),
+                &mut permission_manager,
+                create_mock_provider(),
+                Path::new("/home/user/project"),
+            )
+            .await;
+
+            assert_eq!(result.approved.len(), 1);
+            assert_eq!(result.approved[0].id, "tool_1");
+            assert_eq!(result.denied.len(), 1);
+            assert_eq!(result.denied[0].id, "tool_2");
+            assert_eq!(result.needs_approval.len(), 1);
+            assert_eq!(result.needs_approval[0].id, "tool_3");
+        }
+    }
 }
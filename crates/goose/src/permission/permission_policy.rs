@@ -0,0 +1,408 @@
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+use mcp_core::ToolCall;
+use regex::Regex;
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::config::permission::PermissionLevel;
+
+/// What a policy rule does with the tool calls it matches, from least to most restrictive
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PolicyAction {
+    Allow,
+    Ask,
+    Deny,
+}
+
+impl From<PolicyAction> for PermissionLevel {
+    fn from(action: PolicyAction) -> Self {
+        match action {
+            PolicyAction::Allow => PermissionLevel::AlwaysAllow,
+            PolicyAction::Ask => PermissionLevel::AskBefore,
+            PolicyAction::Deny => PermissionLevel::NeverAllow,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PolicyFile {
+    #[serde(default)]
+    rules: Vec<RuleConfig>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RuleConfig {
+    /// Glob matched against the whole tool name
+    tool: Option<String>,
+    /// Regex matched against the tool name, for names a glob can't describe
+    tool_regex: Option<String>,
+    #[serde(default)]
+    args: Vec<ArgumentConfig>,
+    action: PolicyAction,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ArgumentConfig {
+    pointer: String,
+    regex: Option<String>,
+    glob: Option<String>,
+    within_working_dir: Option<bool>,
+}
+
+#[derive(Debug)]
+enum ValueMatcher {
+    Pattern(Regex),
+    WithinWorkingDir(bool),
+}
+
+#[derive(Debug)]
+struct ArgumentMatcher {
+    /// JSON pointer to the argument, such as `/command`
+    pointer: String,
+    matcher: ValueMatcher,
+}
+
+#[derive(Debug)]
+struct PolicyRule {
+    tool: Regex,
+    args: Vec<ArgumentMatcher>,
+    action: PolicyAction,
+}
+
+/// Declarative allow, ask and deny rules for tool calls, matched on the tool name and the
+/// values of its arguments.
+///
+/// ```yaml
+/// rules:
+///   - tool: developer__shell
+///     args:
+///       - pointer: /command
+///         regex: "^(cargo|git status|rg) "
+///     action: allow
+///   - tool: developer__shell
+///     args:
+///       - pointer: /command
+///         regex: "rm\\s+-rf"
+///     action: deny
+///   - tool: developer__text_editor
+///     args:
+///       - pointer: /path
+///         within_working_dir: false
+///     action: deny
+///   - tool_regex: "^(memory|todo)__(remember|write)_.*$"
+///     action: ask
+/// ```
+///
+/// A rule matches when its `tool` glob matches the whole tool name, or its `tool_regex` matches
+/// it, and every one of its `args` matches. When several rules match a call the most restrictive action wins, so deny beats ask
+/// and ask beats allow.
+#[derive(Debug, Default)]
+pub struct ToolPolicy {
+    rules: Vec<PolicyRule>,
+}
+
+impl ToolPolicy {
+    pub fn from_yaml(yaml: &str) -> Result<Self> {
+        let file: PolicyFile = serde_yaml::from_str(yaml)?;
+        let rules = file
+            .rules
+            .into_iter()
+            .enumerate()
+            .map(|(index, rule)| {
+                PolicyRule::compile(rule).with_context(|| format!("Invalid rule {}", index + 1))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { rules })
+    }
+
+    /// Load the policy at `path`, which is empty if there is no file there
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let yaml = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        Self::from_yaml(&yaml).with_context(|| format!("Failed to parse {}", path.display()))
+    }
+
+    /// A policy that asks before every tool call, used in place of one that failed to load
+    pub fn ask_always() -> Self {
+        Self {
+            rules: vec![PolicyRule {
+                tool: glob_to_regex("*").expect("valid glob"),
+                args: vec![],
+                action: PolicyAction::Ask,
+            }],
+        }
+    }
+
+    /// The most restrictive action of the rules matching `tool_call`, if any do
+    pub fn evaluate(&self, tool_call: &ToolCall, working_dir: &Path) -> Option<PolicyAction> {
+        self.rules
+            .iter()
+            .filter(|rule| rule.matches(tool_call, working_dir))
+            .map(|rule| rule.action)
+            .max()
+    }
+}
+
+impl PolicyRule {
+    fn compile(rule: RuleConfig) -> Result<Self> {
+        let args = rule
+            .args
+            .into_iter()
+            .map(ArgumentMatcher::compile)
+            .collect::<Result<_>>()?;
+        let tool = match (rule.tool, rule.tool_regex) {
+            (Some(glob), None) => glob_to_regex(&glob)?,
+            (None, Some(regex)) => Regex::new(&regex)?,
+            _ => return Err(anyhow!("A rule needs exactly one of tool or tool_regex")),
+        };
+        Ok(Self {
+            tool,
+            args,
+            action: rule.action,
+        })
+    }
+
+    fn matches(&self, tool_call: &ToolCall, working_dir: &Path) -> bool {
+        self.tool.is_match(&tool_call.name)
+            && self
+                .args
+                .iter()
+                .all(|arg| arg.matches(&tool_call.arguments, working_dir))
+    }
+}
+
+impl ArgumentMatcher {
+    fn compile(arg: ArgumentConfig) -> Result<Self> {
+        if !arg.pointer.is_empty() && !arg.pointer.starts_with('/') {
+            return Err(anyhow!(
+                "`{}` is not a JSON pointer, it should start with /",
+                arg.pointer
+            ));
+        }
+        let matcher = match (arg.regex, arg.glob, arg.within_working_dir) {
+            (Some(regex), None, None) => ValueMatcher::Pattern(Regex::new(&regex)?),
+            (None, Some(glob), None) => ValueMatcher::Pattern(glob_to_regex(&glob)?),
+            (None, None, Some(within)) => ValueMatcher::WithinWorkingDir(within),
+            _ => {
+                return Err(anyhow!(
+                    "Argument `{}` needs exactly one of regex, glob or within_working_dir",
+                    arg.pointer
+                ))
+            }
+        };
+        Ok(Self {
+            pointer: arg.pointer,
+            matcher,
+        })
+    }
+
+    /// Arguments that are missing never match
+    fn matches(&self, arguments: &Value, working_dir: &Path) -> bool {
+        let value = match arguments.pointer(&self.pointer) {
+            Some(Value::String(value)) => value.clone(),
+            Some(Value::Null) | None => return false,
+            Some(value) => value.to_string(),
+        };
+        match &self.matcher {
+            ValueMatcher::Pattern(regex) => regex.is_match(&value),
+            ValueMatcher::WithinWorkingDir(within) => {
+                is_within(Path::new(&value), working_dir) == *within
+            }
+        }
+    }
+}
+
+/// Translate a glob where `*` matches any run of characters and `?` any single one into a
+/// regex matching the whole string
+fn glob_to_regex(glob: &str) -> Result<Regex> {
+    let pattern = regex::escape(glob).replace(r"\*", ".*").replace(r"\?", ".");
+    Ok(Regex::new(&format!("^{}$", pattern))?)
+}
+
+/// Whether `path`, relative to `working_dir` unless absolute, stays inside `working_dir`.
+/// Paths are compared as written, without resolving symlinks.
+fn is_within(path: &Path, working_dir: &Path) -> bool {
+    normalize(&working_dir.join(path)).starts_with(normalize(working_dir))
+}
+
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const POLICY: &str = r#"
+rules:
+  - tool: developer__shell
+    args:
+      - pointer: /command
+        regex: "^(cargo|git status|rg) "
+    action: allow
+  - tool: developer__shell
+    args:
+      - pointer: /command
+        regex: "rm\\s+-rf"
+    action: deny
+  - tool: developer__text_editor
+    args:
+      - pointer: /path
+        within_working_dir: false
+    action: deny
+  - tool: "memory__*"
+    action: ask
+  - tool_regex: "^(todo|tasks)__(add|remove)$"
+    action: deny
+"#;
+
+    fn call(name: &str, arguments: Value) -> ToolCall {
+        ToolCall::new(name, arguments)
+    }
+
+    #[test]
+    fn test_argument_rules() {
+        let policy = ToolPolicy::from_yaml(POLICY).unwrap();
+        let working_dir = Path::new("/home/user/project");
+        let shell = |command: &str| call("developer__shell", json!({ "command": command }));
+
+        assert_eq!(
+            policy.evaluate(&shell("cargo test"), working_dir),
+            Some(PolicyAction::Allow)
+        );
+        assert_eq!(policy.evaluate(&shell("ls -la"), working_dir), None);
+        // Deny wins over the allow that also matches
+        assert_eq!(
+            policy.evaluate(&shell("cargo clean && rm -rf /"), working_dir),
+            Some(PolicyAction::Deny)
+        );
+        assert_eq!(
+            policy.evaluate(&call("memory__remember", json!({})), working_dir),
+            Some(PolicyAction::Ask)
+        );
+    }
+
+    #[test]
+    fn test_tool_regex() {
+        let policy = ToolPolicy::from_yaml(POLICY).unwrap();
+        let working_dir = Path::new("/home/user/project");
+
+        assert_eq!(
+            policy.evaluate(&call("tasks__remove", json!({})), working_dir),
+            Some(PolicyAction::Deny)
+        );
+        assert_eq!(
+            policy.evaluate(&call("todo__list", json!({})), working_dir),
+            None
+        );
+    }
+
+    #[test]
+    fn test_within_working_dir() {
+        let policy = ToolPolicy::from_yaml(POLICY).unwrap();
+        let working_dir = Path::new("/home/user/project");
+        let edit = |path: &str| {
+            call(
+                "developer__text_editor",
+                json!({ "command": "write", "path": path }),
+            )
+        };
+
+        assert_eq!(
+            policy.evaluate(&edit("/home/user/project/src/main.rs"), working_dir),
+            None
+        );
+        assert_eq!(policy.evaluate(&edit("src/main.rs"), working_dir), None);
+        assert_eq!(
+            policy.evaluate(&edit("/home/user/project/../.ssh/config"), working_dir),
+            Some(PolicyAction::Deny)
+        );
+        assert_eq!(
+            policy.evaluate(&edit("/etc/passwd"), working_dir),
+            Some(PolicyAction::Deny)
+        );
+        // Without a path there is nothing to match
+        assert_eq!(
+            policy.evaluate(
+                &call("developer__text_editor", json!({ "command": "view" })),
+                working_dir
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_invalid_policies_are_rejected() {
+        let both = r#"
+rules:
+  - tool: developer__shell
+    args:
+      - pointer: /command
+        regex: "^ls"
+        glob: "ls*"
+    action: allow
+"#;
+        assert!(ToolPolicy::from_yaml(both).is_err());
+
+        let not_a_pointer = r#"
+rules:
+  - tool: developer__shell
+    args:
+      - pointer: command
+        glob: "ls*"
+    action: allow
+"#;
+        assert!(ToolPolicy::from_yaml(not_a_pointer).is_err());
+
+        let unknown_action = r#"
+rules:
+  - tool: developer__shell
+    action: maybe
+"#;
+        assert!(ToolPolicy::from_yaml(unknown_action).is_err());
+
+        let glob_and_regex = r#"
+rules:
+  - tool: developer__shell
+    tool_regex: "^developer__"
+    action: ask
+"#;
+        assert!(ToolPolicy::from_yaml(glob_and_regex).is_err());
+
+        let no_tool = r#"
+rules:
+  - action: ask
+"#;
+        assert!(ToolPolicy::from_yaml(no_tool).is_err());
+    }
+
+    #[test]
+    fn test_ask_always() {
+        let policy = ToolPolicy::ask_always();
+        assert_eq!(
+            policy.evaluate(&call("anything__at_all", json!({})), Path::new("/")),
+            Some(PolicyAction::Ask)
+        );
+    }
+}
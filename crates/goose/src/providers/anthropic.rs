@@ -1,14 +1,20 @@
 use anyhow::Result;
+use async_stream::try_stream;
 use async_trait::async_trait;
 use axum::http::HeaderMap;
-use reqwest::{Client, StatusCode};
-use serde_json::Value;
+use futures::StreamExt;
+use reqwest::{Client, Response, StatusCode};
+use serde_json::{json, Value};
 use std::time::Duration;
 
-use super::base::{ConfigKey, Provider, ProviderMetadata, ProviderUsage};
+use super::base::{
+    ConfigKey, MessageStream, Provider, ProviderMetadata, ProviderStreamItem, ProviderUsage,
+};
 use super::errors::ProviderError;
-use super::formats::anthropic::{create_request, get_usage, response_to_message};
-use super::utils::{emit_debug_trace, get_model};
+use super::formats::anthropic::{
+    create_request, get_usage, response_to_message, AnthropicStreamCollector,
+};
+use super::utils::{emit_debug_trace, get_model, instrument_stream, retry_after, sse_data_stream};
 use crate::message::Message;
 use crate::model::ModelConfig;
 use mcp_core::tool::Tool;
@@ -61,7 +67,38 @@ impl AnthropicProvider {
         })
     }
 
+    fn request_headers(&self) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-api-key", self.api_key.parse().unwrap());
+        headers.insert("anthropic-version", ANTHROPIC_API_VERSION.parse().unwrap());
+
+        let is_thinking_enabled = std::env::var("CLAUDE_THINKING_ENABLED").is_ok();
+        if self.model.model_name.starts_with("claude-3-7-sonnet-") && is_thinking_enabled {
+            // https://docs.anthropic.com/en/docs/build-with-claude/extended-thinking#extended-output-capabilities-beta
+            headers.insert("anthropic-beta", "output-128k-2025-02-19".parse().unwrap());
+        }
+
+        if self.model.model_name.starts_with("claude-3-7-sonnet-") {
+            // https://docs.anthropic.com/en/docs/build-with-claude/tool-use/token-efficient-tool-use
+            headers.insert(
+                "anthropic-beta",
+                "token-efficient-tools-2025-02-19".parse().unwrap(),
+            );
+        }
+
+        headers
+    }
+
     async fn post(&self, headers: HeaderMap, payload: Value) -> Result<Value, ProviderError> {
+        let response = self.send(headers, &payload).await?;
+        response.json().await.map_err(|_| {
+            ProviderError::RequestFailed("Response body is not valid JSON".to_string())
+        })
+    }
+
+    /// Send a request and return the successful response with its body unread, so it can be
+    /// parsed as JSON or consumed as an event stream
+    async fn send(&self, headers: HeaderMap, payload: &Value) -> Result<Response, ProviderError> {
         let base_url = url::Url::parse(&self.host)
             .map_err(|e| ProviderError::RequestFailed(format!("Invalid base URL: {e}")))?;
         let url = base_url.join("v1/messages").map_err(|e| {
@@ -72,16 +109,19 @@ impl AnthropicProvider {
             .client
             .post(url)
             .headers(headers)
-            .json(&payload)
+            .json(payload)
             .send()
             .await?;
 
         let status = response.status();
+        if status == StatusCode::OK {
+            return Ok(response);
+        }
+        let retry_delay = retry_after(response.headers());
         let payload: Option<Value> = response.json().await.ok();
 
         // https://docs.anthropic.com/en/api/errors
         match status {
-            StatusCode::OK => payload.ok_or_else( || ProviderError::RequestFailed("Response body is not valid JSON".to_string()) ),
             StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => {
                 Err(ProviderError::Authentication(format!("Authentication failed. Please ensure your API keys are valid and have the required permissions. \
                     Status: {}. Response: {:?}", status, payload)))
@@ -102,11 +142,15 @@ impl AnthropicProvider {
                 Err(ProviderError::RequestFailed(format!("Request failed with status: {}. Message: {}", status, error_msg)))
             }
             StatusCode::TOO_MANY_REQUESTS => {
-                Err(ProviderError::RateLimitExceeded(format!("{:?}", payload)))
+                Err(ProviderError::RateLimitExceeded { details: format!("{:?}", payload), retry_delay })
             }
             StatusCode::INTERNAL_SERVER_ERROR | StatusCode::SERVICE_UNAVAILABLE => {
                 Err(ProviderError::ServerError(format!("{:?}", payload)))
             }
+            // 529 is returned while the API is temporarily overloaded
+            _ if status.as_u16() == 529 => {
+                Err(ProviderError::ServerError(format!("{:?}", payload)))
+            }
             _ => {
                 tracing::debug!(
                     "{}", format!("Provider request failed with status: {}. Payload: {:?}", status, payload)
@@ -117,6 +161,20 @@ impl AnthropicProvider {
     }
 }
 
+/// Convert a (possibly reassembled) messages response into a message and its usage
+fn completion_from_response(
+    model_config: &ModelConfig,
+    payload: &Value,
+    response: Value,
+) -> Result<(Message, ProviderUsage), ProviderError> {
+    let message = response_to_message(response.clone())?;
+    let usage = get_usage(&response)?;
+
+    let model = get_model(&response);
+    emit_debug_trace(model_config, payload, &response, &usage);
+    Ok((message, ProviderUsage::new(model, usage)))
+}
+
 #[async_trait]
 impl Provider for AnthropicProvider {
     fn metadata() -> ProviderMetadata {
@@ -155,34 +213,59 @@ impl Provider for AnthropicProvider {
     ) -> Result<(Message, ProviderUsage), ProviderError> {
         let payload = create_request(&self.model, system, messages, tools)?;
 
-        let mut headers = reqwest::header::HeaderMap::new();
-        headers.insert("x-api-key", self.api_key.parse().unwrap());
-        headers.insert("anthropic-version", ANTHROPIC_API_VERSION.parse().unwrap());
+        // Make request
+        let response = self.post(self.request_headers(), payload.clone()).await?;
 
-        let is_thinking_enabled = std::env::var("CLAUDE_THINKING_ENABLED").is_ok();
-        if self.model.model_name.starts_with("claude-3-7-sonnet-") && is_thinking_enabled {
-            // https://docs.anthropic.com/en/docs/build-with-claude/extended-thinking#extended-output-capabilities-beta
-            headers.insert("anthropic-beta", "output-128k-2025-02-19".parse().unwrap());
-        }
+        // Parse response
+        completion_from_response(&self.model, &payload, response)
+    }
 
-        if self.model.model_name.starts_with("claude-3-7-sonnet-") {
-            // https://docs.anthropic.com/en/docs/build-with-claude/tool-use/token-efficient-tool-use
-            headers.insert(
-                "anthropic-beta",
-                "token-efficient-tools-2025-02-19".parse().unwrap(),
-            );
-        }
+    #[tracing::instrument(
+        skip(self, system, messages, tools),
+        fields(model_config, input, output, input_tokens, output_tokens, total_tokens)
+    )]
+    async fn stream(
+        &self,
+        system: &str,
+        messages: &[Message],
+        tools: &[Tool],
+    ) -> Result<MessageStream, ProviderError> {
+        let mut payload = create_request(&self.model, system, messages, tools)?;
+        payload
+            .as_object_mut()
+            .ok_or_else(|| {
+                ProviderError::RequestFailed("Request payload is not a JSON object".to_string())
+            })?
+            .insert("stream".to_string(), json!(true));
 
-        // Make request
-        let response = self.post(headers, payload.clone()).await?;
+        let response = self.send(self.request_headers(), &payload).await?;
 
-        // Parse response
-        let message = response_to_message(response.clone())?;
-        let usage = get_usage(&response)?;
+        let model_config = self.model.clone();
+        let stream = Box::pin(try_stream! {
+            let mut events = Box::pin(sse_data_stream(response));
+            let mut collector = AnthropicStreamCollector::new();
+
+            while let Some(event) = events.next().await {
+                let event: Value = serde_json::from_str(&event?)
+                    .map_err(|e| ProviderError::RequestFailed(format!("Invalid stream event: {}", e)))?;
+                if let Some(delta) = collector.add_event(&event)? {
+                    yield ProviderStreamItem::Delta(delta);
+                }
+            }
+
+            let (message, usage) =
+                completion_from_response(&model_config, &payload, collector.build_response())?;
+            yield ProviderStreamItem::Complete(message, usage);
+        });
+        Ok(instrument_stream(
+            stream,
+            &self.model,
+            tracing::Span::current(),
+        ))
+    }
 
-        let model = get_model(&response);
-        emit_debug_trace(&self.model, &payload, &response, &usage);
-        Ok((message, ProviderUsage::new(model, usage)))
+    fn supports_streaming(&self) -> bool {
+        true
     }
 
     /// Fetch supported models from Anthropic; returns Err on failure, Ok(None) if not present
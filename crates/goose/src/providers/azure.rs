@@ -118,7 +118,7 @@ impl AzureProvider {
                     DEFAULT_MAX_RETRIES
                 );
                 tracing::error!("{}", error_msg);
-                return Err(last_error.unwrap_or(ProviderError::RateLimitExceeded(error_msg)));
+                return Err(last_error.unwrap_or(ProviderError::rate_limit_exceeded(error_msg)));
             }
 
             // Get a fresh auth token for each attempt
@@ -148,9 +148,9 @@ impl AzureProvider {
                     Ok(result) => {
                         return Ok(result);
                     }
-                    Err(ProviderError::RateLimitExceeded(msg)) => {
+                    Err(ProviderError::RateLimitExceeded { details: msg, .. }) => {
                         attempts += 1;
-                        last_error = Some(ProviderError::RateLimitExceeded(msg.clone()));
+                        last_error = Some(ProviderError::rate_limit_exceeded(msg.clone()));
 
                         let retry_after =
                             if let Some(secs) = msg.to_lowercase().find("try again in ") {
@@ -1,8 +1,9 @@
 use anyhow::Result;
+use futures::stream::BoxStream;
 use serde::{Deserialize, Serialize};
 
 use super::errors::ProviderError;
-use crate::message::Message;
+use crate::message::{Message, MessageDelta};
 use crate::model::ModelConfig;
 use mcp_core::tool::Tool;
 use utoipa::ToSchema;
@@ -117,11 +118,24 @@ impl ConfigKey {
 pub struct ProviderUsage {
     pub model: String,
     pub usage: Usage,
+    /// Name of the provider that served the call, such as "openai"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
 }
 
 impl ProviderUsage {
     pub fn new(model: String, usage: Usage) -> Self {
-        Self { model, usage }
+        Self {
+            model,
+            usage,
+            provider: None,
+        }
+    }
+
+    /// Record the provider that served the call, unless a provider it delegated to already did
+    pub fn with_provider(mut self, provider: &str) -> Self {
+        self.provider.get_or_insert_with(|| provider.to_string());
+        self
     }
 }
 
@@ -130,6 +144,15 @@ pub struct Usage {
     pub input_tokens: Option<i32>,
     pub output_tokens: Option<i32>,
     pub total_tokens: Option<i32>,
+    /// Input tokens read from the provider's prompt cache, included in `input_tokens`
+    #[serde(default)]
+    pub cache_read_input_tokens: Option<i32>,
+    /// Input tokens written to the provider's prompt cache, included in `input_tokens`
+    #[serde(default)]
+    pub cache_write_input_tokens: Option<i32>,
+    /// Output tokens spent on reasoning, included in `output_tokens`
+    #[serde(default)]
+    pub reasoning_tokens: Option<i32>,
 }
 
 impl Usage {
@@ -142,10 +165,23 @@ impl Usage {
             input_tokens,
             output_tokens,
             total_tokens,
+            ..Default::default()
         }
     }
 }
 
+/// An item produced while streaming a completion from a provider
+#[derive(Debug, Clone)]
+pub enum ProviderStreamItem {
+    /// Partial text or thinking, for display while the response is in flight
+    Delta(MessageDelta),
+    /// The fully assembled response, always the last item of a successful stream
+    Complete(Message, ProviderUsage),
+}
+
+/// The stream returned by `Provider::stream`
+pub type MessageStream = BoxStream<'static, Result<ProviderStreamItem, ProviderError>>;
+
 use async_trait::async_trait;
 
 /// Trait for LeadWorkerProvider-specific functionality
@@ -182,6 +218,28 @@ pub trait Provider: Send + Sync {
         tools: &[Tool],
     ) -> Result<(Message, ProviderUsage), ProviderError>;
 
+    /// Generate the next message as a stream, yielding deltas as the model produces them
+    ///
+    /// The stream ends with a `ProviderStreamItem::Complete` carrying the same message and
+    /// usage `complete` would have returned. Providers without native streaming fall back to
+    /// `complete` and yield only that final item.
+    async fn stream(
+        &self,
+        system: &str,
+        messages: &[Message],
+        tools: &[Tool],
+    ) -> Result<MessageStream, ProviderError> {
+        let (message, usage) = self.complete(system, messages, tools).await?;
+        Ok(Box::pin(futures::stream::once(async move {
+            Ok(ProviderStreamItem::Complete(message, usage))
+        })))
+    }
+
+    /// Check if this provider streams deltas natively rather than falling back to `complete`
+    fn supports_streaming(&self) -> bool {
+        false
+    }
+
     /// Get the model config from the provider
     fn get_model_config(&self) -> ModelConfig;
 
@@ -177,7 +177,7 @@ impl Provider for BedrockProvider {
                                     "Failed after {MAX_RETRIES} retries: {:?}",
                                     throttle_err
                                 );
-                                return Err(ProviderError::RateLimitExceeded(format!(
+                                return Err(ProviderError::rate_limit_exceeded(format!(
                                     "Failed to call Bedrock after {MAX_RETRIES} retries: {:?}",
                                     throttle_err
                                 )));
@@ -1,9 +1,10 @@
-use super::base::{ConfigKey, Provider, ProviderMetadata, ProviderUsage, Usage};
+use super::base::{ConfigKey, MessageStream, Provider, ProviderMetadata, ProviderUsage, Usage};
 use super::embedding::EmbeddingCapable;
 use super::errors::ProviderError;
 use super::formats::databricks::{create_request, get_usage, response_to_message};
 use super::oauth;
-use super::utils::{get_model, ImageFormat};
+use super::utils::{get_model, sse_data_stream, ImageFormat};
+use super::utils_universal_openai_stream::stream_chat_completion;
 use crate::config::ConfigError;
 use crate::message::Message;
 use crate::model::ModelConfig;
@@ -13,7 +14,7 @@ use url::Url;
 
 use anyhow::Result;
 use async_trait::async_trait;
-use reqwest::{Client, StatusCode};
+use reqwest::{Client, Response, StatusCode};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::time::Duration;
@@ -266,6 +267,15 @@ impl DatabricksProvider {
     }
 
     async fn post(&self, payload: Value) -> Result<Value, ProviderError> {
+        let response = self.send(&payload).await?;
+        response.json().await.map_err(|_| {
+            ProviderError::RequestFailed("Response body is not valid JSON".to_string())
+        })
+    }
+
+    /// Send a request, retrying on rate limits and server errors, and return the successful
+    /// response with its body unread so it can be parsed or streamed by the caller
+    async fn send(&self, payload: &Value) -> Result<Response, ProviderError> {
         let base_url = Url::parse(&self.host)
             .map_err(|e| ProviderError::RequestFailed(format!("Invalid base URL: {e}")))?;
 
@@ -295,7 +305,7 @@ impl DatabricksProvider {
                     self.retry_config.max_retries
                 );
                 tracing::error!("{}", error_msg);
-                return Err(last_error.unwrap_or(ProviderError::RateLimitExceeded(error_msg)));
+                return Err(last_error.unwrap_or(ProviderError::rate_limit_exceeded(error_msg)));
             }
 
             let auth_header = self.ensure_auth_header().await?;
@@ -303,19 +313,17 @@ impl DatabricksProvider {
                 .client
                 .post(url.clone())
                 .header("Authorization", auth_header)
-                .json(&payload)
+                .json(payload)
                 .send()
                 .await?;
 
             let status = response.status();
+            if status == StatusCode::OK {
+                return Ok(response);
+            }
             let payload: Option<Value> = response.json().await.ok();
 
             match status {
-                StatusCode::OK => {
-                    return payload.ok_or_else(|| {
-                        ProviderError::RequestFailed("Response body is not valid JSON".to_string())
-                    });
-                }
                 StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => {
                     return Err(ProviderError::Authentication(format!(
                         "Authentication failed. Please ensure your API keys are valid and have the required permissions. \
@@ -383,7 +391,7 @@ impl DatabricksProvider {
                     tracing::warn!("{}. Retrying after backoff...", error_msg);
 
                     // Store the error in case we need to return it after max retries
-                    last_error = Some(ProviderError::RateLimitExceeded(error_msg));
+                    last_error = Some(ProviderError::rate_limit_exceeded(error_msg));
 
                     // Calculate and apply the backoff delay
                     let delay = self.retry_config.delay_for_attempt(attempts);
@@ -430,6 +438,27 @@ impl DatabricksProvider {
     }
 }
 
+/// Convert a (possibly reassembled) serving endpoint response into a message and its usage
+fn completion_from_response(
+    model_config: &ModelConfig,
+    payload: &Value,
+    response: Value,
+) -> Result<(Message, ProviderUsage), ProviderError> {
+    let message = response_to_message(response.clone())?;
+    let usage = match get_usage(&response) {
+        Ok(usage) => usage,
+        Err(ProviderError::UsageError(e)) => {
+            tracing::debug!("Failed to get usage data: {}", e);
+            Usage::default()
+        }
+        Err(e) => return Err(e),
+    };
+    let model = get_model(&response);
+    super::utils::emit_debug_trace(model_config, payload, &response, &usage);
+
+    Ok((message, ProviderUsage::new(model, usage)))
+}
+
 #[async_trait]
 impl Provider for DatabricksProvider {
     fn metadata() -> ProviderMetadata {
@@ -471,19 +500,42 @@ impl Provider for DatabricksProvider {
         let response = self.post(payload.clone()).await?;
 
         // Parse response
-        let message = response_to_message(response.clone())?;
-        let usage = match get_usage(&response) {
-            Ok(usage) => usage,
-            Err(ProviderError::UsageError(e)) => {
-                tracing::debug!("Failed to get usage data: {}", e);
-                Usage::default()
-            }
-            Err(e) => return Err(e),
-        };
-        let model = get_model(&response);
-        super::utils::emit_debug_trace(&self.model, &payload, &response, &usage);
+        completion_from_response(&self.model, &payload, response)
+    }
 
-        Ok((message, ProviderUsage::new(model, usage)))
+    #[tracing::instrument(
+        skip(self, system, messages, tools),
+        fields(model_config, input, output, input_tokens, output_tokens, total_tokens)
+    )]
+    async fn stream(
+        &self,
+        system: &str,
+        messages: &[Message],
+        tools: &[Tool],
+    ) -> Result<MessageStream, ProviderError> {
+        let mut payload = create_request(&self.model, system, messages, tools, &self.image_format)?;
+        let request = payload
+            .as_object_mut()
+            .expect("payload should have model key");
+        // Remove the model key which is part of the url with databricks
+        request.remove("model");
+        request.insert("stream".to_string(), json!(true));
+
+        let response = self.send(&payload).await?;
+
+        let model_config = self.model.clone();
+        let stream = stream_chat_completion(sse_data_stream(response), move |response| {
+            completion_from_response(&model_config, &payload, response)
+        });
+        Ok(super::utils::instrument_stream(
+            stream,
+            &self.model,
+            tracing::Span::current(),
+        ))
+    }
+
+    fn supports_streaming(&self) -> bool {
+        true
     }
 
     fn supports_embeddings(&self) -> bool {
@@ -1,4 +1,5 @@
 use reqwest::StatusCode;
+use std::time::Duration;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -9,8 +10,12 @@ pub enum ProviderError {
     #[error("Context length exceeded: {0}")]
     ContextLengthExceeded(String),
 
-    #[error("Rate limit exceeded: {0}")]
-    RateLimitExceeded(String),
+    #[error("Rate limit exceeded: {details}")]
+    RateLimitExceeded {
+        details: String,
+        /// How long the provider asked us to wait before retrying, from its Retry-After header
+        retry_delay: Option<Duration>,
+    },
 
     #[error("Server error: {0}")]
     ServerError(String),
@@ -25,6 +30,30 @@ pub enum ProviderError {
     UsageError(String),
 }
 
+impl ProviderError {
+    pub fn rate_limit_exceeded(details: impl Into<String>) -> Self {
+        ProviderError::RateLimitExceeded {
+            details: details.into(),
+            retry_delay: None,
+        }
+    }
+
+    /// Whether the request may succeed if retried later or sent to another provider
+    pub fn is_transient(&self) -> bool {
+        matches!(
+            self,
+            ProviderError::RateLimitExceeded { .. } | ProviderError::ServerError(_)
+        )
+    }
+
+    pub fn retry_delay(&self) -> Option<Duration> {
+        match self {
+            ProviderError::RateLimitExceeded { retry_delay, .. } => *retry_delay,
+            _ => None,
+        }
+    }
+}
+
 impl From<anyhow::Error> for ProviderError {
     fn from(error: anyhow::Error) -> Self {
         ProviderError::ExecutionError(error.to_string())
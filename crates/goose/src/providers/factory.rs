@@ -1,11 +1,16 @@
 use std::sync::Arc;
 
+use super::errors::ProviderError;
 use super::{
     anthropic::AnthropicProvider,
     azure::AzureProvider,
-    base::{Provider, ProviderMetadata},
+    base::{
+        LeadWorkerProviderTrait, MessageStream, Provider, ProviderMetadata, ProviderStreamItem,
+        ProviderUsage,
+    },
     bedrock::BedrockProvider,
     databricks::DatabricksProvider,
+    fallback::{FallbackProvider, FallbackTarget, RetryPolicy},
     gcpvertexai::GcpVertexAIProvider,
     githubcopilot::GithubCopilotProvider,
     google::GoogleProvider,
@@ -14,15 +19,15 @@ use super::{
     ollama::OllamaProvider,
     openai::OpenAiProvider,
     openrouter::OpenRouterProvider,
+    replay::ReplayProvider,
     snowflake::SnowflakeProvider,
     venice::VeniceProvider,
 };
+use crate::message::Message;
 use crate::model::ModelConfig;
 use anyhow::Result;
-
-#[cfg(test)]
-use super::errors::ProviderError;
-#[cfg(test)]
+use async_trait::async_trait;
+use futures::TryStreamExt;
 use mcp_core::tool::Tool;
 
 fn default_lead_turns() -> usize {
@@ -55,16 +60,45 @@ pub fn providers() -> Vec<ProviderMetadata> {
 
 pub fn create(name: &str, model: ModelConfig) -> Result<Arc<dyn Provider>> {
     let config = crate::config::Config::global();
+    let model_name = model.model_name.clone();
 
     // Check for lead model environment variables
-    if let Ok(lead_model_name) = config.get_param::<String>("GOOSE_LEAD_MODEL") {
+    let provider = if let Ok(lead_model_name) = config.get_param::<String>("GOOSE_LEAD_MODEL") {
         tracing::info!("Creating lead/worker provider from environment variables");
 
-        return create_lead_worker_from_env(name, &model, &lead_model_name);
+        create_lead_worker_from_env(name, &model, &lead_model_name)?
+    } else {
+        // Default: create regular provider
+        create_provider(name, model)?
+    };
+
+    // Fall back to other providers when this one is rate limited or down
+    match config.get_param::<Vec<FallbackTarget>>("GOOSE_FALLBACK_PROVIDERS") {
+        Ok(targets) if !targets.is_empty() => {
+            tracing::info!("Creating fallback chain from GOOSE_FALLBACK_PROVIDERS");
+            create_fallback_chain(name, &model_name, provider, targets)
+        }
+        _ => Ok(provider),
     }
+}
 
-    // Default: create regular provider
-    create_provider(name, model)
+/// Chain `primary` with the fallback providers, tried in order
+fn create_fallback_chain(
+    primary_name: &str,
+    primary_model: &str,
+    primary: Arc<dyn Provider>,
+    targets: Vec<FallbackTarget>,
+) -> Result<Arc<dyn Provider>> {
+    let mut providers = vec![(format!("{}/{}", primary_name, primary_model), primary)];
+    for target in targets {
+        let provider = create_provider(&target.provider, ModelConfig::new(target.model.clone()))?;
+        providers.push((format!("{}/{}", target.provider, target.model), provider));
+    }
+
+    Ok(Arc::new(FallbackProvider::new(
+        providers,
+        RetryPolicy::from_config(crate::config::Config::global()),
+    )))
 }
 
 /// Create a lead/worker provider from environment variables
@@ -111,29 +145,98 @@ fn create_lead_worker_from_env(
 
 fn create_provider(name: &str, model: ModelConfig) -> Result<Arc<dyn Provider>> {
     // We use Arc instead of Box to be able to clone for multiple async tasks
-    match name {
-        "openai" => Ok(Arc::new(OpenAiProvider::from_env(model)?)),
-        "anthropic" => Ok(Arc::new(AnthropicProvider::from_env(model)?)),
-        "azure_openai" => Ok(Arc::new(AzureProvider::from_env(model)?)),
-        "aws_bedrock" => Ok(Arc::new(BedrockProvider::from_env(model)?)),
-        "databricks" => Ok(Arc::new(DatabricksProvider::from_env(model)?)),
-        "groq" => Ok(Arc::new(GroqProvider::from_env(model)?)),
-        "ollama" => Ok(Arc::new(OllamaProvider::from_env(model)?)),
-        "openrouter" => Ok(Arc::new(OpenRouterProvider::from_env(model)?)),
-        "gcp_vertex_ai" => Ok(Arc::new(GcpVertexAIProvider::from_env(model)?)),
-        "google" => Ok(Arc::new(GoogleProvider::from_env(model)?)),
-        "venice" => Ok(Arc::new(VeniceProvider::from_env(model)?)),
-        "snowflake" => Ok(Arc::new(SnowflakeProvider::from_env(model)?)),
-        "github_copilot" => Ok(Arc::new(GithubCopilotProvider::from_env(model)?)),
-        _ => Err(anyhow::anyhow!("Unknown provider: {}", name)),
+    let provider: Arc<dyn Provider> = match name {
+        "openai" => Arc::new(OpenAiProvider::from_env(model)?),
+        "anthropic" => Arc::new(AnthropicProvider::from_env(model)?),
+        "azure_openai" => Arc::new(AzureProvider::from_env(model)?),
+        "aws_bedrock" => Arc::new(BedrockProvider::from_env(model)?),
+        "databricks" => Arc::new(DatabricksProvider::from_env(model)?),
+        "groq" => Arc::new(GroqProvider::from_env(model)?),
+        "ollama" => Arc::new(OllamaProvider::from_env(model)?),
+        "openrouter" => Arc::new(OpenRouterProvider::from_env(model)?),
+        "gcp_vertex_ai" => Arc::new(GcpVertexAIProvider::from_env(model)?),
+        "google" => Arc::new(GoogleProvider::from_env(model)?),
+        "venice" => Arc::new(VeniceProvider::from_env(model)?),
+        "snowflake" => Arc::new(SnowflakeProvider::from_env(model)?),
+        "github_copilot" => Arc::new(GithubCopilotProvider::from_env(model)?),
+        "replay" => Arc::new(ReplayProvider::from_env(model)?),
+        _ => return Err(anyhow::anyhow!("Unknown provider: {}", name)),
+    };
+    Ok(Arc::new(NamedProvider {
+        name: name.to_string(),
+        inner: provider,
+    }))
+}
+
+/// Records its name in the usage of every call, so calls are priced for the provider that
+/// served them when providers are chained for fallback or lead/worker
+struct NamedProvider {
+    name: String,
+    inner: Arc<dyn Provider>,
+}
+
+#[async_trait]
+impl Provider for NamedProvider {
+    fn metadata() -> ProviderMetadata {
+        ProviderMetadata::empty()
+    }
+
+    async fn complete(
+        &self,
+        system: &str,
+        messages: &[Message],
+        tools: &[Tool],
+    ) -> Result<(Message, ProviderUsage), ProviderError> {
+        let (message, usage) = self.inner.complete(system, messages, tools).await?;
+        Ok((message, usage.with_provider(&self.name)))
+    }
+
+    async fn stream(
+        &self,
+        system: &str,
+        messages: &[Message],
+        tools: &[Tool],
+    ) -> Result<MessageStream, ProviderError> {
+        let name = self.name.clone();
+        let stream = self.inner.stream(system, messages, tools).await?;
+        Ok(Box::pin(stream.map_ok(move |item| match item {
+            ProviderStreamItem::Complete(message, usage) => {
+                ProviderStreamItem::Complete(message, usage.with_provider(&name))
+            }
+            delta => delta,
+        })))
+    }
+
+    fn supports_streaming(&self) -> bool {
+        self.inner.supports_streaming()
+    }
+
+    fn get_model_config(&self) -> ModelConfig {
+        self.inner.get_model_config()
+    }
+
+    async fn fetch_supported_models_async(&self) -> Result<Option<Vec<String>>, ProviderError> {
+        self.inner.fetch_supported_models_async().await
+    }
+
+    fn supports_embeddings(&self) -> bool {
+        self.inner.supports_embeddings()
+    }
+
+    async fn create_embeddings(&self, texts: Vec<String>) -> Result<Vec<Vec<f32>>, ProviderError> {
+        self.inner.create_embeddings(texts).await
+    }
+
+    fn as_lead_worker(&self) -> Option<&dyn LeadWorkerProviderTrait> {
+        self.inner.as_lead_worker()
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::message::{Message, MessageContent};
-    use crate::providers::base::{ProviderMetadata, ProviderUsage, Usage};
+    use crate::message::MessageContent;
+    use crate::providers::base::Usage;
     use chrono::Utc;
     use mcp_core::{content::TextContent, Role};
     use std::env;
@@ -339,4 +442,26 @@ mod tests {
             env::set_var("GOOSE_LEAD_FALLBACK_TURNS", val);
         }
     }
+
+    #[tokio::test]
+    async fn test_usage_names_the_serving_provider() {
+        let mock = MockTestProvider {
+            name: "mock".to_string(),
+            model_config: ModelConfig::new("mock-model".to_string()),
+        };
+        let named = NamedProvider {
+            name: "ollama".to_string(),
+            inner: Arc::new(mock),
+        };
+        let (_, usage) = named.complete("", &[], &[]).await.unwrap();
+        assert_eq!(usage.provider.as_deref(), Some("ollama"));
+
+        // A wrapper keeps the name of the provider it delegated to
+        let wrapped = NamedProvider {
+            name: "fallback".to_string(),
+            inner: Arc::new(named),
+        };
+        let (_, usage) = wrapped.complete("", &[], &[]).await.unwrap();
+        assert_eq!(usage.provider.as_deref(), Some("ollama"));
+    }
 }
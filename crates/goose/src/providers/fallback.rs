@@ -0,0 +1,434 @@
+use async_trait::async_trait;
+use futures::future::BoxFuture;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+use super::base::{
+    LeadWorkerProviderTrait, MessageStream, Provider, ProviderMetadata, ProviderUsage,
+};
+use super::errors::ProviderError;
+use crate::config::Config;
+use crate::message::Message;
+use crate::model::ModelConfig;
+use mcp_core::tool::Tool;
+
+/// One entry of the GOOSE_FALLBACK_PROVIDERS list
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FallbackTarget {
+    pub provider: String,
+    pub model: String,
+}
+
+/// How calls to each provider of the chain are retried before failing over to the next one
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    /// Retries after the first attempt
+    pub max_retries: u32,
+    pub initial_delay: Duration,
+    pub max_delay: Duration,
+    pub multiplier: f64,
+    /// How long a provider that used up its retries is skipped
+    pub cooldown: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 2,
+            initial_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(30),
+            multiplier: 2.0,
+            cooldown: Duration::from_secs(60),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Read GOOSE_FALLBACK_MAX_RETRIES, GOOSE_FALLBACK_INITIAL_DELAY_MS,
+    /// GOOSE_FALLBACK_MAX_DELAY_MS and GOOSE_FALLBACK_COOLDOWN_SECONDS
+    pub fn from_config(config: &Config) -> Self {
+        let default = Self::default();
+        Self {
+            max_retries: config
+                .get_param("GOOSE_FALLBACK_MAX_RETRIES")
+                .unwrap_or(default.max_retries),
+            initial_delay: config
+                .get_param("GOOSE_FALLBACK_INITIAL_DELAY_MS")
+                .map(Duration::from_millis)
+                .unwrap_or(default.initial_delay),
+            max_delay: config
+                .get_param("GOOSE_FALLBACK_MAX_DELAY_MS")
+                .map(Duration::from_millis)
+                .unwrap_or(default.max_delay),
+            multiplier: default.multiplier,
+            cooldown: config
+                .get_param("GOOSE_FALLBACK_COOLDOWN_SECONDS")
+                .map(Duration::from_secs)
+                .unwrap_or(default.cooldown),
+        }
+    }
+
+    /// Exponential backoff with full jitter for the retry after `attempt` failed attempts
+    fn backoff(&self, attempt: u32) -> Duration {
+        let exponential = self.initial_delay.as_secs_f64() * self.multiplier.powi(attempt as i32);
+        let capped = exponential.min(self.max_delay.as_secs_f64());
+        Duration::from_secs_f64(rand::thread_rng().gen_range(0.0..=capped))
+    }
+}
+
+struct Member {
+    name: String,
+    provider: Arc<dyn Provider>,
+    /// Set while the provider is skipped after failing
+    open_until: Mutex<Option<Instant>>,
+}
+
+impl Member {
+    async fn is_open(&self) -> bool {
+        matches!(*self.open_until.lock().await, Some(until) if until > Instant::now())
+    }
+}
+
+/// A provider that tries an ordered chain of providers, retrying rate limits and server
+/// errors with backoff and failing over to the next provider once retries run out. A provider
+/// that failed is skipped for a cooldown period, like an open circuit breaker.
+pub struct FallbackProvider {
+    members: Vec<Member>,
+    retry: RetryPolicy,
+}
+
+impl FallbackProvider {
+    /// Chain `providers`, named for logging, in the order they are tried. The first one is the
+    /// primary, whose model config the chain reports.
+    pub fn new(providers: Vec<(String, Arc<dyn Provider>)>, retry: RetryPolicy) -> Self {
+        Self {
+            members: providers
+                .into_iter()
+                .map(|(name, provider)| Member {
+                    name,
+                    provider,
+                    open_until: Mutex::new(None),
+                })
+                .collect(),
+            retry,
+        }
+    }
+
+    /// Run `call` against the chain. Members with an open circuit are skipped, unless every
+    /// member's is open, in which case all of them are tried rather than failing outright.
+    async fn call_with_failover<'a, T>(
+        &'a self,
+        call: impl Fn(Arc<dyn Provider>) -> BoxFuture<'a, Result<T, ProviderError>>,
+    ) -> Result<T, ProviderError> {
+        let mut available = Vec::new();
+        for member in &self.members {
+            if member.is_open().await {
+                tracing::debug!("Skipping provider {} while it cools down", member.name);
+            } else {
+                available.push(member);
+            }
+        }
+        if available.is_empty() {
+            available = self.members.iter().collect();
+        }
+
+        let mut last_error = None;
+        for member in available {
+            match self.call_member(member, &call).await {
+                Ok(result) => return Ok(result),
+                Err(e) if e.is_transient() => {
+                    let cooldown = e
+                        .retry_delay()
+                        .map_or(self.retry.cooldown, |delay| delay.max(self.retry.cooldown));
+                    tracing::warn!(
+                        "Provider {} failed, skipping it for {:?}: {}",
+                        member.name,
+                        cooldown,
+                        e
+                    );
+                    *member.open_until.lock().await = Some(Instant::now() + cooldown);
+                    last_error = Some(e);
+                }
+                // Other errors such as an exceeded context length would fail the same way on
+                // every provider, and the agent needs to see them to handle them
+                Err(e) => return Err(e),
+            }
+        }
+        Err(last_error.unwrap_or_else(|| {
+            ProviderError::ExecutionError("No providers configured in the fallback chain".into())
+        }))
+    }
+
+    /// Call one member, retrying transient errors with backoff
+    async fn call_member<'a, T>(
+        &'a self,
+        member: &Member,
+        call: &impl Fn(Arc<dyn Provider>) -> BoxFuture<'a, Result<T, ProviderError>>,
+    ) -> Result<T, ProviderError> {
+        let mut attempt = 0;
+        loop {
+            match call(Arc::clone(&member.provider)).await {
+                Ok(result) => {
+                    *member.open_until.lock().await = None;
+                    return Ok(result);
+                }
+                Err(e) if e.is_transient() && attempt < self.retry.max_retries => {
+                    let delay = match e.retry_delay() {
+                        // Waiting longer than the backoff allows is left to the next provider
+                        Some(delay) if delay > self.retry.max_delay => return Err(e),
+                        Some(delay) => delay,
+                        None => self.retry.backoff(attempt),
+                    };
+                    attempt += 1;
+                    tracing::info!(
+                        "Provider {} failed ({}), retry {}/{} in {:?}",
+                        member.name,
+                        e,
+                        attempt,
+                        self.retry.max_retries,
+                        delay
+                    );
+                    tokio::time::sleep(delay).await;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl Provider for FallbackProvider {
+    fn metadata() -> ProviderMetadata {
+        // This is a wrapper provider, configured through the providers it chains
+        ProviderMetadata::new(
+            "fallback",
+            "Fallback Chain",
+            "Tries an ordered list of providers, failing over when one is rate limited or down",
+            "",
+            vec![],
+            "",
+            vec![],
+        )
+    }
+
+    fn get_model_config(&self) -> ModelConfig {
+        self.members[0].provider.get_model_config()
+    }
+
+    async fn complete(
+        &self,
+        system: &str,
+        messages: &[Message],
+        tools: &[Tool],
+    ) -> Result<(Message, ProviderUsage), ProviderError> {
+        self.call_with_failover(|provider| {
+            Box::pin(async move { provider.complete(system, messages, tools).await })
+        })
+        .await
+    }
+
+    /// Fails over only while the stream is being opened, errors once it has started are returned
+    async fn stream(
+        &self,
+        system: &str,
+        messages: &[Message],
+        tools: &[Tool],
+    ) -> Result<MessageStream, ProviderError> {
+        self.call_with_failover(|provider| {
+            Box::pin(async move { provider.stream(system, messages, tools).await })
+        })
+        .await
+    }
+
+    fn supports_streaming(&self) -> bool {
+        self.members[0].provider.supports_streaming()
+    }
+
+    fn as_lead_worker(&self) -> Option<&dyn LeadWorkerProviderTrait> {
+        self.members[0].provider.as_lead_worker()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::base::Usage;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Fails with the queued errors, then answers with its name
+    struct ScriptedProvider {
+        name: &'static str,
+        errors: std::sync::Mutex<Vec<ProviderError>>,
+        calls: AtomicUsize,
+    }
+
+    impl ScriptedProvider {
+        fn new(name: &'static str, errors: Vec<ProviderError>) -> Arc<Self> {
+            Arc::new(Self {
+                name,
+                errors: std::sync::Mutex::new(errors),
+                calls: AtomicUsize::new(0),
+            })
+        }
+
+        fn calls(&self) -> usize {
+            self.calls.load(Ordering::SeqCst)
+        }
+    }
+
+    #[async_trait]
+    impl Provider for ScriptedProvider {
+        fn metadata() -> ProviderMetadata {
+            ProviderMetadata::empty()
+        }
+
+        fn get_model_config(&self) -> ModelConfig {
+            ModelConfig::new(self.name.to_string())
+        }
+
+        async fn complete(
+            &self,
+            _system: &str,
+            _messages: &[Message],
+            _tools: &[Tool],
+        ) -> Result<(Message, ProviderUsage), ProviderError> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            let mut errors = self.errors.lock().unwrap();
+            if !errors.is_empty() {
+                return Err(errors.remove(0));
+            }
+            Ok((
+                Message::assistant().with_text(self.name),
+                ProviderUsage::new(self.name.to_string(), Usage::default()),
+            ))
+        }
+    }
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy {
+            max_retries: 2,
+            initial_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(10),
+            multiplier: 2.0,
+            cooldown: Duration::from_secs(60),
+        }
+    }
+
+    fn chain(providers: &[&Arc<ScriptedProvider>], retry: RetryPolicy) -> FallbackProvider {
+        FallbackProvider::new(
+            providers
+                .iter()
+                .map(|provider| {
+                    (
+                        provider.name.to_string(),
+                        Arc::clone(*provider) as Arc<dyn Provider>,
+                    )
+                })
+                .collect(),
+            retry,
+        )
+    }
+
+    async fn answer(provider: &FallbackProvider) -> Result<String, ProviderError> {
+        provider
+            .complete("", &[Message::user().with_text("hi")], &[])
+            .await
+            .map(|(message, _)| message.as_concat_text())
+    }
+
+    #[tokio::test]
+    async fn test_retries_transient_errors_before_failing_over() {
+        let primary = ScriptedProvider::new(
+            "primary",
+            vec![
+                ProviderError::ServerError("overloaded".into()),
+                ProviderError::rate_limit_exceeded("slow down"),
+            ],
+        );
+        let backup = ScriptedProvider::new("backup", vec![]);
+        let provider = chain(&[&primary, &backup], policy());
+
+        assert_eq!(answer(&provider).await.unwrap(), "primary");
+        assert_eq!(primary.calls(), 3);
+        assert_eq!(backup.calls(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_fails_over_and_skips_failed_provider_during_cooldown() {
+        let errors = (0..3)
+            .map(|_| ProviderError::ServerError("down".into()))
+            .collect();
+        let primary = ScriptedProvider::new("primary", errors);
+        let backup = ScriptedProvider::new("backup", vec![]);
+        let provider = chain(&[&primary, &backup], policy());
+
+        assert_eq!(answer(&provider).await.unwrap(), "backup");
+        assert_eq!(primary.calls(), 3);
+
+        // The primary's circuit is open, so the next call goes straight to the backup
+        assert_eq!(answer(&provider).await.unwrap(), "backup");
+        assert_eq!(primary.calls(), 3);
+        assert_eq!(backup.calls(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_long_retry_after_fails_over_immediately() {
+        let primary = ScriptedProvider::new(
+            "primary",
+            vec![ProviderError::RateLimitExceeded {
+                details: "quota".into(),
+                retry_delay: Some(Duration::from_secs(3600)),
+            }],
+        );
+        let backup = ScriptedProvider::new("backup", vec![]);
+        let provider = chain(&[&primary, &backup], policy());
+
+        assert_eq!(answer(&provider).await.unwrap(), "backup");
+        assert_eq!(primary.calls(), 1);
+        let open_until = provider.members[0].open_until.lock().await.unwrap();
+        assert!(open_until > Instant::now() + Duration::from_secs(3000));
+    }
+
+    #[tokio::test]
+    async fn test_non_transient_errors_are_returned_without_failover() {
+        let primary = ScriptedProvider::new(
+            "primary",
+            vec![ProviderError::ContextLengthExceeded("too long".into())],
+        );
+        let backup = ScriptedProvider::new("backup", vec![]);
+        let provider = chain(&[&primary, &backup], policy());
+
+        assert!(matches!(
+            answer(&provider).await,
+            Err(ProviderError::ContextLengthExceeded(_))
+        ));
+        assert_eq!(backup.calls(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_all_open_circuits_are_still_tried() {
+        let primary =
+            ScriptedProvider::new("primary", vec![ProviderError::ServerError("down".into())]);
+        let provider = chain(
+            &[&primary],
+            RetryPolicy {
+                max_retries: 0,
+                ..policy()
+            },
+        );
+
+        assert!(answer(&provider).await.is_err());
+        assert_eq!(answer(&provider).await.unwrap(), "primary");
+    }
+
+    #[test]
+    fn test_backoff_is_capped() {
+        let retry = policy();
+        for attempt in 0..10 {
+            assert!(retry.backoff(attempt) <= retry.max_delay);
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use crate::message::{Message, MessageContent};
+use crate::message::{Message, MessageContent, MessageDelta};
 use crate::model::ModelConfig;
 use crate::providers::base::Usage;
 use crate::providers::errors::ProviderError;
@@ -7,7 +7,7 @@ use mcp_core::content::Content;
 use mcp_core::role::Role;
 use mcp_core::tool::{Tool, ToolCall};
 use serde_json::{json, Value};
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 
 /// Convert internal Message format to Anthropic's API message specification
 pub fn format_messages(messages: &[Message]) -> Vec<Value> {
@@ -66,6 +66,9 @@ pub fn format_messages(messages: &[Message]) -> Vec<Value> {
                 MessageContent::SummarizationRequested(_) => {
                     // Skip
                 }
+                MessageContent::BudgetExceeded(_) => {
+                    // Skip
+                }
                 MessageContent::Thinking(thinking) => {
                     content.push(json!({
                         "type": "thinking",
@@ -117,6 +120,7 @@ pub fn format_messages(messages: &[Message]) -> Vec<Value> {
     // During each turn, we mark the final message with cache_control so the conversation can be
     // incrementally cached. The second-to-last user message is also marked for caching with the
     // cache_control parameter, so that this checkpoint can read from the previous cache.
+    // Together with the system prompt and tools, this uses all four breakpoints the API allows.
     let mut user_count = 0;
     for message in anthropic_messages.iter_mut().rev() {
         if message.get("role") == Some(&json!("user")) {
@@ -237,22 +241,15 @@ pub fn response_to_message(response: Value) -> Result<Message> {
 pub fn get_usage(data: &Value) -> Result<Usage> {
     // Extract usage data if available
     if let Some(usage) = data.get("usage") {
+        let count = |key: &str| usage.get(key).and_then(|v| v.as_u64()).unwrap_or(0);
+
         // Sum up all input token types:
         // - input_tokens (fresh/uncached)
         // - cache_creation_input_tokens (being written to cache)
         // - cache_read_input_tokens (read from cache)
-        let total_input_tokens = usage
-            .get("input_tokens")
-            .and_then(|v| v.as_u64())
-            .unwrap_or(0)
-            + usage
-                .get("cache_creation_input_tokens")
-                .and_then(|v| v.as_u64())
-                .unwrap_or(0)
-            + usage
-                .get("cache_read_input_tokens")
-                .and_then(|v| v.as_u64())
-                .unwrap_or(0);
+        let cache_write_tokens = count("cache_creation_input_tokens");
+        let cache_read_tokens = count("cache_read_input_tokens");
+        let total_input_tokens = count("input_tokens") + cache_write_tokens + cache_read_tokens;
 
         let input_tokens = Some(total_input_tokens as i32);
 
@@ -263,7 +260,11 @@ pub fn get_usage(data: &Value) -> Result<Usage> {
 
         let total_tokens = output_tokens.map(|o| total_input_tokens as i32 + o);
 
-        Ok(Usage::new(input_tokens, output_tokens, total_tokens))
+        Ok(Usage {
+            cache_read_input_tokens: Some(cache_read_tokens as i32),
+            cache_write_input_tokens: Some(cache_write_tokens as i32),
+            ..Usage::new(input_tokens, output_tokens, total_tokens)
+        })
     } else {
         tracing::debug!(
             "Failed to get usage data: {}",
@@ -352,6 +353,141 @@ pub fn create_request(
     Ok(payload)
 }
 
+/// Reassembles the events of a streamed `v1/messages` call into the same JSON a
+/// non-streaming call returns, so `response_to_message` and `get_usage` can be reused
+///
+/// https://docs.anthropic.com/en/api/messages-streaming
+#[derive(Debug, Default)]
+pub struct AnthropicStreamCollector {
+    message: Value,
+    blocks: BTreeMap<usize, Value>,
+    partial_json: BTreeMap<usize, String>,
+}
+
+impl AnthropicStreamCollector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Apply a single stream event, returning the text or thinking delta it carried, if any
+    pub fn add_event(&mut self, event: &Value) -> Result<Option<MessageDelta>, ProviderError> {
+        let index = event
+            .get("index")
+            .and_then(|i| i.as_u64())
+            .map(|i| i as usize);
+
+        match event.get("type").and_then(|t| t.as_str()) {
+            Some("message_start") => {
+                if let Some(message) = event.get("message") {
+                    self.message = message.clone();
+                }
+            }
+            Some("content_block_start") => {
+                if let (Some(index), Some(block)) = (index, event.get("content_block")) {
+                    self.blocks.insert(index, block.clone());
+                }
+            }
+            Some("content_block_delta") => {
+                let (index, delta) = match (index, event.get("delta")) {
+                    (Some(index), Some(delta)) => (index, delta),
+                    _ => return Ok(None),
+                };
+                let block = match self.blocks.get_mut(&index) {
+                    Some(block) => block,
+                    None => return Ok(None),
+                };
+
+                match delta.get("type").and_then(|t| t.as_str()) {
+                    Some("text_delta") => {
+                        let text = delta_str(delta, "text");
+                        append_str(block, "text", text);
+                        return Ok(Some(MessageDelta::text(text)));
+                    }
+                    Some("thinking_delta") => {
+                        let thinking = delta_str(delta, "thinking");
+                        append_str(block, "thinking", thinking);
+                        return Ok(Some(MessageDelta::thinking(thinking)));
+                    }
+                    Some("signature_delta") => {
+                        append_str(block, "signature", delta_str(delta, "signature"))
+                    }
+                    Some("input_json_delta") => self
+                        .partial_json
+                        .entry(index)
+                        .or_default()
+                        .push_str(delta_str(delta, "partial_json")),
+                    _ => {}
+                }
+            }
+            Some("content_block_stop") => {
+                let json = index.and_then(|index| self.partial_json.remove(&index));
+                let block = index.and_then(|index| self.blocks.get_mut(&index));
+                if let (Some(json), Some(block)) = (json, block) {
+                    // A tool called without arguments streams no input at all
+                    let input = if json.trim().is_empty() {
+                        json!({})
+                    } else {
+                        serde_json::from_str(&json).map_err(|e| {
+                            ProviderError::RequestFailed(format!(
+                                "Invalid tool input in stream: {}",
+                                e
+                            ))
+                        })?
+                    };
+                    block["input"] = input;
+                }
+            }
+            Some("message_delta") => {
+                if let Some(stop_reason) = event.get("delta").and_then(|d| d.get("stop_reason")) {
+                    self.message["stop_reason"] = stop_reason.clone();
+                }
+                if let Some(Value::Object(usage)) = event.get("usage") {
+                    for (key, value) in usage {
+                        self.message["usage"][key] = value.clone();
+                    }
+                }
+            }
+            Some("error") => {
+                let error = event.get("error").cloned().unwrap_or(Value::Null);
+                let message = error
+                    .get("message")
+                    .and_then(|m| m.as_str())
+                    .unwrap_or("Unknown error")
+                    .to_string();
+                return Err(match error.get("type").and_then(|t| t.as_str()) {
+                    Some("overloaded_error") | Some("api_error") => {
+                        ProviderError::ServerError(message)
+                    }
+                    Some("rate_limit_error") => ProviderError::rate_limit_exceeded(message),
+                    _ => ProviderError::RequestFailed(message),
+                });
+            }
+            // ping and message_stop carry nothing we need
+            _ => {}
+        }
+
+        Ok(None)
+    }
+
+    /// Build the equivalent non-streaming response from everything collected so far
+    pub fn build_response(mut self) -> Value {
+        if !self.message.is_object() {
+            self.message = json!({});
+        }
+        self.message["content"] = Value::Array(self.blocks.into_values().collect());
+        self.message
+    }
+}
+
+fn delta_str<'a>(delta: &'a Value, key: &str) -> &'a str {
+    delta.get(key).and_then(|v| v.as_str()).unwrap_or("")
+}
+
+fn append_str(block: &mut Value, key: &str, fragment: &str) {
+    let current = block.get(key).and_then(|v| v.as_str()).unwrap_or("");
+    block[key] = Value::String(format!("{}{}", current, fragment));
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -390,6 +526,8 @@ mod tests {
         assert_eq!(usage.input_tokens, Some(24)); // 12 + 12 + 0
         assert_eq!(usage.output_tokens, Some(15));
         assert_eq!(usage.total_tokens, Some(39)); // 24 + 15
+        assert_eq!(usage.cache_write_input_tokens, Some(12));
+        assert_eq!(usage.cache_read_input_tokens, Some(0));
 
         Ok(())
     }
@@ -576,6 +714,24 @@ mod tests {
         assert!(spec[1].get("cache_control").is_some());
     }
 
+    #[test]
+    fn test_cache_breakpoints_on_last_two_user_messages() {
+        let messages = vec![
+            Message::user().with_text("first"),
+            Message::assistant().with_text("one"),
+            Message::user().with_text("second"),
+            Message::assistant().with_text("two"),
+            Message::user().with_text("third"),
+        ];
+        let spec = format_messages(&messages);
+
+        let cached: Vec<bool> = spec
+            .iter()
+            .map(|message| message["content"][0].get("cache_control").is_some())
+            .collect();
+        assert_eq!(cached, vec![false, false, true, false, true]);
+    }
+
     #[test]
     fn test_system_to_anthropic_spec() {
         let system = "You are a helpful assistant.";
@@ -631,4 +787,78 @@ mod tests {
         // Return the test result
         result
     }
+
+    #[test]
+    fn test_stream_collector_rebuilds_response() -> Result<()> {
+        let events = vec![
+            json!({"type": "message_start", "message": {"id": "msg_1", "type": "message", "role": "assistant", "model": "claude-3-7-sonnet-20250219", "content": [], "usage": {"input_tokens": 25, "cache_read_input_tokens": 100, "output_tokens": 1}}}),
+            json!({"type": "content_block_start", "index": 0, "content_block": {"type": "thinking", "thinking": ""}}),
+            json!({"type": "content_block_delta", "index": 0, "delta": {"type": "thinking_delta", "thinking": "Let me "}}),
+            json!({"type": "content_block_delta", "index": 0, "delta": {"type": "thinking_delta", "thinking": "check."}}),
+            json!({"type": "content_block_delta", "index": 0, "delta": {"type": "signature_delta", "signature": "sig"}}),
+            json!({"type": "content_block_stop", "index": 0}),
+            json!({"type": "content_block_start", "index": 1, "content_block": {"type": "text", "text": ""}}),
+            json!({"type": "content_block_delta", "index": 1, "delta": {"type": "text_delta", "text": "Checking the "}}),
+            json!({"type": "content_block_delta", "index": 1, "delta": {"type": "text_delta", "text": "weather."}}),
+            json!({"type": "content_block_stop", "index": 1}),
+            json!({"type": "content_block_start", "index": 2, "content_block": {"type": "tool_use", "id": "tool_1", "name": "get_weather", "input": {}}}),
+            json!({"type": "content_block_delta", "index": 2, "delta": {"type": "input_json_delta", "partial_json": "{\"location\": "}}),
+            json!({"type": "content_block_delta", "index": 2, "delta": {"type": "input_json_delta", "partial_json": "\"Paris\"}"}}),
+            json!({"type": "content_block_stop", "index": 2}),
+            json!({"type": "message_delta", "delta": {"stop_reason": "tool_use"}, "usage": {"output_tokens": 42}}),
+            json!({"type": "message_stop"}),
+        ];
+
+        let mut collector = AnthropicStreamCollector::new();
+        let mut deltas = Vec::new();
+        for event in &events {
+            if let Some(delta) = collector.add_event(event)? {
+                deltas.push(delta);
+            }
+        }
+
+        assert_eq!(
+            deltas,
+            vec![
+                MessageDelta::thinking("Let me "),
+                MessageDelta::thinking("check."),
+                MessageDelta::text("Checking the "),
+                MessageDelta::text("weather."),
+            ]
+        );
+
+        let response = collector.build_response();
+        let message = response_to_message(response.clone())?;
+        assert_eq!(message.content.len(), 3);
+        let thinking = message.content[0].as_thinking().unwrap();
+        assert_eq!(thinking.thinking, "Let me check.");
+        assert_eq!(thinking.signature, "sig");
+        assert_eq!(message.content[1].as_text(), Some("Checking the weather."));
+        let tool_call = message.content[2]
+            .as_tool_request()
+            .unwrap()
+            .tool_call
+            .as_ref()
+            .unwrap();
+        assert_eq!(tool_call.name, "get_weather");
+        assert_eq!(tool_call.arguments, json!({"location": "Paris"}));
+
+        let usage = get_usage(&response)?;
+        assert_eq!(usage.input_tokens, Some(125));
+        assert_eq!(usage.output_tokens, Some(42));
+        assert_eq!(usage.cache_read_input_tokens, Some(100));
+        assert_eq!(response["model"], "claude-3-7-sonnet-20250219");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_stream_collector_maps_error_events() {
+        let mut collector = AnthropicStreamCollector::new();
+        let result = collector.add_event(&json!({
+            "type": "error",
+            "error": {"type": "overloaded_error", "message": "Overloaded"}
+        }));
+        assert!(matches!(result, Err(ProviderError::ServerError(msg)) if msg == "Overloaded"));
+    }
 }
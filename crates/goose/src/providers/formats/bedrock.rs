@@ -48,6 +48,9 @@ pub fn to_bedrock_message_content(content: &MessageContent) -> Result<bedrock::C
         MessageContent::SummarizationRequested(_) => {
             bail!("SummarizationRequested should not get passed to the provider")
         }
+        MessageContent::BudgetExceeded(_) => {
+            bail!("BudgetExceeded should not get passed to the provider")
+        }
         MessageContent::ToolRequest(tool_req) => {
             let tool_use_id = tool_req.id.to_string();
             let tool_use = if let Ok(call) = tool_req.tool_call.as_ref() {
@@ -286,10 +289,16 @@ pub fn from_bedrock_role(role: &bedrock::ConversationRole) -> Result<Role> {
 }
 
 pub fn from_bedrock_usage(usage: &bedrock::TokenUsage) -> Usage {
+    // Bedrock reports cached tokens separately from input_tokens, while Usage counts them as input
+    let cache_read = usage.cache_read_input_tokens.unwrap_or(0);
+    let cache_write = usage.cache_write_input_tokens.unwrap_or(0);
     Usage {
-        input_tokens: Some(usage.input_tokens),
+        input_tokens: Some(usage.input_tokens + cache_read + cache_write),
         output_tokens: Some(usage.output_tokens),
         total_tokens: Some(usage.total_tokens),
+        cache_read_input_tokens: usage.cache_read_input_tokens,
+        cache_write_input_tokens: usage.cache_write_input_tokens,
+        ..Default::default()
     }
 }
 
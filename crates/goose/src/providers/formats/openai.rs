@@ -58,6 +58,9 @@ pub fn format_messages(messages: &[Message], image_format: &ImageFormat) -> Vec<
                 MessageContent::SummarizationRequested(_) => {
                     continue;
                 }
+                MessageContent::BudgetExceeded(_) => {
+                    continue;
+                }
                 MessageContent::ToolRequest(request) => match &request.tool_call {
                     Ok(tool_call) => {
                         let sanitized_name = sanitize_function_name(&tool_call.name);
@@ -305,7 +308,24 @@ pub fn get_usage(data: &Value) -> Result<Usage, ProviderError> {
             _ => None,
         });
 
-    Ok(Usage::new(input_tokens, output_tokens, total_tokens))
+    let reasoning_tokens = usage
+        .get("completion_tokens_details")
+        .and_then(|details| details.get("reasoning_tokens"))
+        .and_then(|v| v.as_i64())
+        .map(|v| v as i32);
+
+    // Cached prompt tokens are already counted in prompt_tokens
+    let cache_read_input_tokens = usage
+        .get("prompt_tokens_details")
+        .and_then(|details| details.get("cached_tokens"))
+        .and_then(|v| v.as_i64())
+        .map(|v| v as i32);
+
+    Ok(Usage {
+        cache_read_input_tokens,
+        reasoning_tokens,
+        ..Usage::new(input_tokens, output_tokens, total_tokens)
+    })
 }
 
 /// Validates and fixes tool schemas to ensure they have proper parameter structure.
@@ -768,6 +788,29 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_get_usage_with_cached_and_reasoning_tokens() -> anyhow::Result<()> {
+        let response = json!({
+            "usage": {
+                "prompt_tokens": 2000,
+                "completion_tokens": 300,
+                "total_tokens": 2300,
+                "prompt_tokens_details": {"cached_tokens": 1536},
+                "completion_tokens_details": {"reasoning_tokens": 256}
+            }
+        });
+
+        let usage = get_usage(&response)?;
+        assert_eq!(usage.input_tokens, Some(2000));
+        assert_eq!(usage.output_tokens, Some(300));
+        assert_eq!(usage.total_tokens, Some(2300));
+        assert_eq!(usage.cache_read_input_tokens, Some(1536));
+        assert_eq!(usage.cache_write_input_tokens, None);
+        assert_eq!(usage.reasoning_tokens, Some(256));
+
+        Ok(())
+    }
+
     #[test]
     fn test_response_to_message_valid_toolrequest() -> anyhow::Result<()> {
         let response: Value = serde_json::from_str(OPENAI_TOOL_USE_RESPONSE)?;
@@ -859,6 +902,7 @@ mod tests {
             max_tokens: Some(1024),
             toolshim: false,
             toolshim_model: None,
+            toolshim_provider: None,
         };
         let request = create_request(&model_config, "system", &[], &[], &ImageFormat::OpenAi)?;
         let obj = request.as_object().unwrap();
@@ -891,6 +935,7 @@ mod tests {
             max_tokens: Some(1024),
             toolshim: false,
             toolshim_model: None,
+            toolshim_provider: None,
         };
         let request = create_request(&model_config, "system", &[], &[], &ImageFormat::OpenAi)?;
         let obj = request.as_object().unwrap();
@@ -924,6 +969,7 @@ mod tests {
             max_tokens: Some(1024),
             toolshim: false,
             toolshim_model: None,
+            toolshim_provider: None,
         };
         let request = create_request(&model_config, "system", &[], &[], &ImageFormat::OpenAi)?;
         let obj = request.as_object().unwrap();
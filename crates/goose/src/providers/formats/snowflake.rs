@@ -63,6 +63,9 @@ pub fn format_messages(messages: &[Message]) -> Vec<Value> {
                 MessageContent::SummarizationRequested(_) => {
                     // Skip
                 }
+                MessageContent::BudgetExceeded(_) => {
+                    // Skip
+                }
                 MessageContent::Thinking(_thinking) => {
                     // Skip thinking for now
                 }
@@ -93,10 +93,10 @@ impl GoogleProvider {
                 Ok(res) => {
                     match handle_response_google_compat(res).await {
                         Ok(result) => return Ok(result),
-                        Err(ProviderError::RateLimitExceeded(_)) => {
+                        Err(ProviderError::RateLimitExceeded { .. }) => {
                             retries += 1;
                             if retries > max_retries {
-                                return Err(ProviderError::RateLimitExceeded(
+                                return Err(ProviderError::rate_limit_exceeded(
                                     "Max retries exceeded for rate limit error".to_string(),
                                 ));
                             }
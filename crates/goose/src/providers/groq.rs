@@ -3,7 +3,7 @@ use crate::message::Message;
 use crate::model::ModelConfig;
 use crate::providers::base::{ConfigKey, Provider, ProviderMetadata, ProviderUsage, Usage};
 use crate::providers::formats::openai::{create_request, get_usage, response_to_message};
-use crate::providers::utils::get_model;
+use crate::providers::utils::{get_model, retry_after};
 use anyhow::Result;
 use async_trait::async_trait;
 use mcp_core::Tool;
@@ -70,6 +70,7 @@ impl GroqProvider {
             .await?;
 
         let status = response.status();
+        let retry_delay = retry_after(response.headers());
         let payload: Option<Value> = response.json().await.ok();
 
         match status {
@@ -82,7 +83,7 @@ impl GroqProvider {
                 Err(ProviderError::ContextLengthExceeded(format!("{:?}", payload)))
             }
             StatusCode::TOO_MANY_REQUESTS => {
-                Err(ProviderError::RateLimitExceeded(format!("{:?}", payload)))
+                Err(ProviderError::RateLimitExceeded { details: format!("{:?}", payload), retry_delay })
             }
             StatusCode::INTERNAL_SERVER_ERROR | StatusCode::SERVICE_UNAVAILABLE => {
                 Err(ProviderError::ServerError(format!("{:?}", payload)))
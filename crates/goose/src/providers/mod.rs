@@ -7,6 +7,7 @@ pub mod databricks;
 pub mod embedding;
 pub mod errors;
 mod factory;
+pub mod fallback;
 pub mod formats;
 mod gcpauth;
 pub mod gcpvertexai;
@@ -18,6 +19,8 @@ pub mod oauth;
 pub mod ollama;
 pub mod openai;
 pub mod openrouter;
+pub mod pricing;
+pub mod replay;
 pub mod snowflake;
 pub mod toolshim;
 pub mod utils;
@@ -1,14 +1,18 @@
-use super::base::{ConfigKey, Provider, ProviderMetadata, ProviderUsage, Usage};
+use super::base::{ConfigKey, MessageStream, Provider, ProviderMetadata, ProviderUsage, Usage};
 use super::errors::ProviderError;
-use super::utils::{get_model, handle_response_openai_compat};
+use super::utils::{
+    check_streaming_response_openai_compat, get_model, handle_response_openai_compat,
+    sse_data_stream,
+};
+use super::utils_universal_openai_stream::stream_chat_completion;
 use crate::message::Message;
 use crate::model::ModelConfig;
 use crate::providers::formats::openai::{create_request, get_usage, response_to_message};
 use anyhow::Result;
 use async_trait::async_trait;
 use mcp_core::tool::Tool;
-use reqwest::Client;
-use serde_json::Value;
+use reqwest::{Client, Response};
+use serde_json::{json, Value};
 use std::time::Duration;
 use url::Url;
 
@@ -80,7 +84,7 @@ impl OllamaProvider {
         Ok(base_url)
     }
 
-    async fn post(&self, payload: Value) -> Result<Value, ProviderError> {
+    async fn send(&self, payload: &Value) -> Result<Response, ProviderError> {
         // TODO: remove this later when the UI handles provider config refresh
         let base_url = self.get_base_url()?;
 
@@ -88,12 +92,37 @@ impl OllamaProvider {
             ProviderError::RequestFailed(format!("Failed to construct endpoint URL: {e}"))
         })?;
 
-        let response = self.client.post(url).json(&payload).send().await?;
+        Ok(self.client.post(url).json(payload).send().await?)
+    }
+
+    async fn post(&self, payload: Value) -> Result<Value, ProviderError> {
+        let response = self.send(&payload).await?;
 
         handle_response_openai_compat(response).await
     }
 }
 
+/// Convert a (possibly reassembled) chat completion response into a message and its usage
+fn completion_from_response(
+    model_config: &ModelConfig,
+    payload: &Value,
+    response: Value,
+) -> Result<(Message, ProviderUsage), ProviderError> {
+    let message = response_to_message(response.clone())?;
+
+    let usage = match get_usage(&response) {
+        Ok(usage) => usage,
+        Err(ProviderError::UsageError(e)) => {
+            tracing::debug!("Failed to get usage data: {}", e);
+            Usage::default()
+        }
+        Err(e) => return Err(e),
+    };
+    let model = get_model(&response);
+    super::utils::emit_debug_trace(model_config, payload, &response, &usage);
+    Ok((message, ProviderUsage::new(model, usage)))
+}
+
 #[async_trait]
 impl Provider for OllamaProvider {
     fn metadata() -> ProviderMetadata {
@@ -136,18 +165,50 @@ impl Provider for OllamaProvider {
         )?;
 
         let response = self.post(payload.clone()).await?;
-        let message = response_to_message(response.clone())?;
-
-        let usage = match get_usage(&response) {
-            Ok(usage) => usage,
-            Err(ProviderError::UsageError(e)) => {
-                tracing::debug!("Failed to get usage data: {}", e);
-                Usage::default()
-            }
-            Err(e) => return Err(e),
-        };
-        let model = get_model(&response);
-        super::utils::emit_debug_trace(&self.model, &payload, &response, &usage);
-        Ok((message, ProviderUsage::new(model, usage)))
+        completion_from_response(&self.model, &payload, response)
+    }
+
+    #[tracing::instrument(
+        skip(self, system, messages, tools),
+        fields(model_config, input, output, input_tokens, output_tokens, total_tokens)
+    )]
+    async fn stream(
+        &self,
+        system: &str,
+        messages: &[Message],
+        tools: &[Tool],
+    ) -> Result<MessageStream, ProviderError> {
+        let mut payload = create_request(
+            &self.model,
+            system,
+            messages,
+            tools,
+            &super::utils::ImageFormat::OpenAi,
+        )?;
+        let request = payload.as_object_mut().ok_or_else(|| {
+            ProviderError::RequestFailed("Request payload is not a JSON object".to_string())
+        })?;
+        request.insert("stream".to_string(), json!(true));
+        request.insert(
+            "stream_options".to_string(),
+            json!({ "include_usage": true }),
+        );
+
+        let response = self.send(&payload).await?;
+        let response = check_streaming_response_openai_compat(response).await?;
+
+        let model_config = self.model.clone();
+        let stream = stream_chat_completion(sse_data_stream(response), move |response| {
+            completion_from_response(&model_config, &payload, response)
+        });
+        Ok(super::utils::instrument_stream(
+            stream,
+            &self.model,
+            tracing::Span::current(),
+        ))
+    }
+
+    fn supports_streaming(&self) -> bool {
+        true
     }
 }
@@ -1,15 +1,19 @@
 use anyhow::Result;
 use async_trait::async_trait;
-use reqwest::Client;
-use serde_json::Value;
+use reqwest::{Client, Response};
+use serde_json::{json, Value};
 use std::collections::HashMap;
 use std::time::Duration;
 
-use super::base::{ConfigKey, Provider, ProviderMetadata, ProviderUsage, Usage};
+use super::base::{ConfigKey, MessageStream, Provider, ProviderMetadata, ProviderUsage, Usage};
 use super::embedding::{EmbeddingCapable, EmbeddingRequest, EmbeddingResponse};
 use super::errors::ProviderError;
 use super::formats::openai::{create_request, get_usage, response_to_message};
-use super::utils::{emit_debug_trace, get_model, handle_response_openai_compat, ImageFormat};
+use super::utils::{
+    check_streaming_response_openai_compat, emit_debug_trace, get_model,
+    handle_response_openai_compat, instrument_stream, sse_data_stream, ImageFormat,
+};
+use super::utils_universal_openai_stream::stream_chat_completion;
 use crate::message::Message;
 use crate::model::ModelConfig;
 use mcp_core::tool::Tool;
@@ -103,7 +107,7 @@ impl OpenAiProvider {
         request
     }
 
-    async fn post(&self, payload: Value) -> Result<Value, ProviderError> {
+    async fn send(&self, payload: &Value) -> Result<Response, ProviderError> {
         let base_url = url::Url::parse(&self.host)
             .map_err(|e| ProviderError::RequestFailed(format!("Invalid base URL: {e}")))?;
         let url = base_url.join(&self.base_path).map_err(|e| {
@@ -117,12 +121,36 @@ impl OpenAiProvider {
 
         let request = self.add_headers(request);
 
-        let response = request.json(&payload).send().await?;
+        Ok(request.json(payload).send().await?)
+    }
+
+    async fn post(&self, payload: Value) -> Result<Value, ProviderError> {
+        let response = self.send(&payload).await?;
 
         handle_response_openai_compat(response).await
     }
 }
 
+/// Convert a (possibly reassembled) chat completion response into a message and its usage
+fn completion_from_response(
+    model_config: &ModelConfig,
+    payload: &Value,
+    response: Value,
+) -> Result<(Message, ProviderUsage), ProviderError> {
+    let message = response_to_message(response.clone())?;
+    let usage = match get_usage(&response) {
+        Ok(usage) => usage,
+        Err(ProviderError::UsageError(e)) => {
+            tracing::debug!("Failed to get usage data: {}", e);
+            Usage::default()
+        }
+        Err(e) => return Err(e),
+    };
+    let model = get_model(&response);
+    emit_debug_trace(model_config, payload, &response, &usage);
+    Ok((message, ProviderUsage::new(model, usage)))
+}
+
 #[async_trait]
 impl Provider for OpenAiProvider {
     fn metadata() -> ProviderMetadata {
@@ -165,18 +193,47 @@ impl Provider for OpenAiProvider {
         let response = self.post(payload.clone()).await?;
 
         // Parse response
-        let message = response_to_message(response.clone())?;
-        let usage = match get_usage(&response) {
-            Ok(usage) => usage,
-            Err(ProviderError::UsageError(e)) => {
-                tracing::debug!("Failed to get usage data: {}", e);
-                Usage::default()
-            }
-            Err(e) => return Err(e),
-        };
-        let model = get_model(&response);
-        emit_debug_trace(&self.model, &payload, &response, &usage);
-        Ok((message, ProviderUsage::new(model, usage)))
+        completion_from_response(&self.model, &payload, response)
+    }
+
+    #[tracing::instrument(
+        skip(self, system, messages, tools),
+        fields(model_config, input, output, input_tokens, output_tokens, total_tokens)
+    )]
+    async fn stream(
+        &self,
+        system: &str,
+        messages: &[Message],
+        tools: &[Tool],
+    ) -> Result<MessageStream, ProviderError> {
+        let mut payload =
+            create_request(&self.model, system, messages, tools, &ImageFormat::OpenAi)?;
+        let request = payload.as_object_mut().ok_or_else(|| {
+            ProviderError::RequestFailed("Request payload is not a JSON object".to_string())
+        })?;
+        request.insert("stream".to_string(), json!(true));
+        // Usage is only reported on the final chunk when explicitly requested
+        request.insert(
+            "stream_options".to_string(),
+            json!({ "include_usage": true }),
+        );
+
+        let response = self.send(&payload).await?;
+        let response = check_streaming_response_openai_compat(response).await?;
+
+        let model_config = self.model.clone();
+        let stream = stream_chat_completion(sse_data_stream(response), move |response| {
+            completion_from_response(&model_config, &payload, response)
+        });
+        Ok(instrument_stream(
+            stream,
+            &self.model,
+            tracing::Span::current(),
+        ))
+    }
+
+    fn supports_streaming(&self) -> bool {
+        true
     }
 
     /// Fetch supported models from OpenAI; returns Err on any failure, Ok(None) if no data
@@ -0,0 +1,347 @@
+use std::sync::LazyLock;
+
+use serde::{Deserialize, Serialize};
+
+use super::base::{ProviderUsage, Usage};
+use crate::config::Config;
+
+/// The configured prices, read once since they are needed for every provider call
+static PRICING: LazyLock<PricingRegistry> =
+    LazyLock::new(|| PricingRegistry::from_config(Config::global()));
+
+/// Prices of a model, in USD per million tokens
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ModelPricing {
+    pub input: f64,
+    pub output: f64,
+    /// Price of input tokens read from the prompt cache, the input price if not set
+    #[serde(default)]
+    pub cache_read: Option<f64>,
+    /// Price of input tokens written to the prompt cache, the input price if not set
+    #[serde(default)]
+    pub cache_write: Option<f64>,
+}
+
+impl ModelPricing {
+    pub const fn new(input: f64, output: f64) -> Self {
+        Self {
+            input,
+            output,
+            cache_read: None,
+            cache_write: None,
+        }
+    }
+
+    pub const fn with_cache(self, cache_read: f64, cache_write: f64) -> Self {
+        Self {
+            cache_read: Some(cache_read),
+            cache_write: Some(cache_write),
+            ..self
+        }
+    }
+
+    /// The cost of `usage` in USD. Cached input tokens are counted in the input tokens and
+    /// reasoning tokens in the output tokens, as providers report them.
+    pub fn cost(&self, usage: &Usage) -> f64 {
+        let price = |tokens: i32, per_million: f64| tokens.max(0) as f64 * per_million / 1e6;
+        let cache_read = usage.cache_read_input_tokens.unwrap_or(0);
+        let cache_write = usage.cache_write_input_tokens.unwrap_or(0);
+        let uncached = usage.input_tokens.unwrap_or(0) - cache_read - cache_write;
+
+        price(uncached, self.input)
+            + price(cache_read, self.cache_read.unwrap_or(self.input))
+            + price(cache_write, self.cache_write.unwrap_or(self.input))
+            + price(usage.output_tokens.unwrap_or(0), self.output)
+    }
+}
+
+/// The prices of the models matching `model`, optionally only when used through `provider`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PricingEntry {
+    /// Matches model names containing it as a whole part, so "gpt-4o" matches
+    /// "gpt-4o-2024-08-06" and "openai/gpt-4o" but not "gpt-4o-mini" when that has its own entry.
+    /// An empty model matches every model of the provider.
+    pub model: String,
+    #[serde(default)]
+    pub provider: Option<String>,
+    #[serde(flatten)]
+    pub pricing: ModelPricing,
+}
+
+impl PricingEntry {
+    fn builtin(model: &str, pricing: ModelPricing) -> Self {
+        Self {
+            model: model.to_string(),
+            provider: None,
+            pricing,
+        }
+    }
+}
+
+/// Pricing of the well known models, which can be out of date and is overridden by the
+/// GOOSE_PRICING config
+fn builtin_entries() -> Vec<PricingEntry> {
+    vec![
+        // Anthropic, also served through Bedrock, Vertex AI, Databricks and OpenRouter
+        PricingEntry::builtin(
+            "claude-opus-4",
+            ModelPricing::new(15.0, 75.0).with_cache(1.5, 18.75),
+        ),
+        PricingEntry::builtin(
+            "claude-4-opus",
+            ModelPricing::new(15.0, 75.0).with_cache(1.5, 18.75),
+        ),
+        PricingEntry::builtin(
+            "claude-sonnet-4",
+            ModelPricing::new(3.0, 15.0).with_cache(0.3, 3.75),
+        ),
+        PricingEntry::builtin(
+            "claude-4-sonnet",
+            ModelPricing::new(3.0, 15.0).with_cache(0.3, 3.75),
+        ),
+        PricingEntry::builtin(
+            "claude-3-7-sonnet",
+            ModelPricing::new(3.0, 15.0).with_cache(0.3, 3.75),
+        ),
+        PricingEntry::builtin(
+            "claude-3-5-sonnet",
+            ModelPricing::new(3.0, 15.0).with_cache(0.3, 3.75),
+        ),
+        PricingEntry::builtin(
+            "claude-3-5-haiku",
+            ModelPricing::new(0.8, 4.0).with_cache(0.08, 1.0),
+        ),
+        PricingEntry::builtin(
+            "claude-3-opus",
+            ModelPricing::new(15.0, 75.0).with_cache(1.5, 18.75),
+        ),
+        PricingEntry::builtin(
+            "claude-3-haiku",
+            ModelPricing::new(0.25, 1.25).with_cache(0.03, 0.3),
+        ),
+        // OpenAI, cached input is read at a discount and written at no extra cost
+        PricingEntry::builtin("gpt-4o", ModelPricing::new(2.5, 10.0).with_cache(1.25, 2.5)),
+        PricingEntry::builtin(
+            "gpt-4o-mini",
+            ModelPricing::new(0.15, 0.6).with_cache(0.075, 0.15),
+        ),
+        PricingEntry::builtin("gpt-4.1", ModelPricing::new(2.0, 8.0).with_cache(0.5, 2.0)),
+        PricingEntry::builtin(
+            "gpt-4.1-mini",
+            ModelPricing::new(0.4, 1.6).with_cache(0.1, 0.4),
+        ),
+        PricingEntry::builtin(
+            "gpt-4.1-nano",
+            ModelPricing::new(0.1, 0.4).with_cache(0.025, 0.1),
+        ),
+        PricingEntry::builtin("gpt-4-turbo", ModelPricing::new(10.0, 30.0)),
+        PricingEntry::builtin("o1", ModelPricing::new(15.0, 60.0).with_cache(7.5, 15.0)),
+        PricingEntry::builtin("o1-mini", ModelPricing::new(1.1, 4.4).with_cache(0.55, 1.1)),
+        PricingEntry::builtin("o3", ModelPricing::new(2.0, 8.0).with_cache(0.5, 2.0)),
+        PricingEntry::builtin("o3-mini", ModelPricing::new(1.1, 4.4).with_cache(0.55, 1.1)),
+        PricingEntry::builtin(
+            "o4-mini",
+            ModelPricing::new(1.1, 4.4).with_cache(0.275, 1.1),
+        ),
+        // Google
+        PricingEntry::builtin("gemini-2.5-pro", ModelPricing::new(1.25, 10.0)),
+        PricingEntry::builtin("gemini-2.5-flash", ModelPricing::new(0.3, 2.5)),
+        PricingEntry::builtin("gemini-2.0-flash", ModelPricing::new(0.1, 0.4)),
+        PricingEntry::builtin("gemini-1.5-pro", ModelPricing::new(1.25, 5.0)),
+        PricingEntry::builtin("gemini-1.5-flash", ModelPricing::new(0.075, 0.3)),
+        // Groq
+        PricingEntry::builtin("llama-3.3-70b-versatile", ModelPricing::new(0.59, 0.79)),
+        // Local models cost nothing
+        PricingEntry {
+            model: String::new(),
+            provider: Some("ollama".to_string()),
+            pricing: ModelPricing::new(0.0, 0.0),
+        },
+    ]
+}
+
+/// Lowercase with dots as dashes, so "claude-3.5-sonnet" and "claude-3-5-sonnet" are the same
+fn normalize(model: &str) -> String {
+    model.to_lowercase().replace('.', "-")
+}
+
+/// Whether `key` appears in `model` as whole parts separated by non-alphanumeric characters
+fn contains_part(model: &str, key: &str) -> bool {
+    if key.is_empty() {
+        return true;
+    }
+    let is_boundary = |c: Option<char>| c.is_none_or(|c| !c.is_ascii_alphanumeric());
+    model.match_indices(key).any(|(start, _)| {
+        is_boundary(model[..start].chars().next_back())
+            && is_boundary(model[start + key.len()..].chars().next())
+    })
+}
+
+/// The entry for the provider or else any provider with the longest name matching `model`
+fn best_entry<'a>(
+    entries: &'a [PricingEntry],
+    provider: Option<&str>,
+    model: &str,
+) -> Option<&'a PricingEntry> {
+    entries
+        .iter()
+        .filter(|entry| match &entry.provider {
+            Some(entry_provider) => Some(entry_provider.as_str()) == provider,
+            None => true,
+        })
+        .filter(|entry| contains_part(model, &normalize(&entry.model)))
+        .max_by_key(|entry| (entry.provider.is_some(), entry.model.len()))
+}
+
+/// Model prices, the built-in ones overridden by the GOOSE_PRICING config
+#[derive(Debug, Clone)]
+pub struct PricingRegistry {
+    overrides: Vec<PricingEntry>,
+    builtin: Vec<PricingEntry>,
+}
+
+impl Default for PricingRegistry {
+    fn default() -> Self {
+        Self::new(Vec::new())
+    }
+}
+
+impl PricingRegistry {
+    pub fn new(overrides: Vec<PricingEntry>) -> Self {
+        Self {
+            overrides,
+            builtin: builtin_entries(),
+        }
+    }
+
+    /// Read price overrides from GOOSE_PRICING, a list of entries in the config file such as
+    /// `{model: my-model, provider: openai, input: 1.0, output: 2.0}`
+    pub fn from_config(config: &Config) -> Self {
+        let overrides = match config.get_param::<Vec<PricingEntry>>("GOOSE_PRICING") {
+            Ok(overrides) => overrides,
+            Err(crate::config::ConfigError::NotFound(_)) => Vec::new(),
+            Err(e) => {
+                tracing::warn!("Ignoring invalid GOOSE_PRICING: {}", e);
+                Vec::new()
+            }
+        };
+        Self::new(overrides)
+    }
+
+    /// The pricing of `model`. Overrides win over built-in prices, then entries for the
+    /// provider over ones for any provider, then the longest matching model name.
+    pub fn get(&self, provider: Option<&str>, model: &str) -> Option<&ModelPricing> {
+        let model = normalize(model);
+        best_entry(&self.overrides, provider, &model)
+            .or_else(|| best_entry(&self.builtin, provider, &model))
+            .map(|entry| &entry.pricing)
+    }
+
+    /// The cost of a provider call in USD, if the model's price is known
+    pub fn cost(&self, provider: Option<&str>, usage: &ProviderUsage) -> Option<f64> {
+        self.get(provider, &usage.model)
+            .map(|pricing| pricing.cost(&usage.usage))
+    }
+}
+
+/// The cost of a provider call in USD with the configured prices, for the provider that
+/// served it
+pub fn cost_of(usage: &ProviderUsage) -> Option<f64> {
+    PRICING.cost(usage.provider.as_deref(), usage)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usage(model: &str, input: i32, output: i32) -> ProviderUsage {
+        ProviderUsage::new(
+            model.to_string(),
+            Usage::new(Some(input), Some(output), Some(input + output)),
+        )
+    }
+
+    #[test]
+    fn test_builtin_lookup_matches_model_variants() {
+        let registry = PricingRegistry::default();
+        let sonnet = registry.get(None, "claude-3-5-sonnet").unwrap();
+        for model in [
+            "claude-3-5-sonnet-20241022",
+            "anthropic/claude-3.5-sonnet",
+            "anthropic.claude-3-5-sonnet-20241022-v2:0",
+        ] {
+            assert_eq!(registry.get(None, model), Some(sonnet), "{}", model);
+        }
+
+        // The most specific name wins
+        assert_eq!(
+            registry.get(None, "gpt-4o-mini-2024-07-18").unwrap().input,
+            0.15
+        );
+        assert_eq!(registry.get(None, "gpt-4o-2024-08-06").unwrap().input, 2.5);
+        assert_eq!(registry.get(None, "o3-mini").unwrap().input, 1.1);
+        assert!(registry.get(None, "llama3.2").is_none());
+        // Only a whole part matches
+        assert!(registry.get(None, "gpt-4o3").is_none());
+
+        assert_eq!(
+            registry.get(Some("ollama"), "llama3.2"),
+            Some(&ModelPricing::new(0.0, 0.0))
+        );
+    }
+
+    #[test]
+    fn test_overrides_take_precedence() {
+        let registry = PricingRegistry::new(vec![
+            PricingEntry {
+                model: "gpt-4o".to_string(),
+                provider: None,
+                pricing: ModelPricing::new(1.0, 2.0),
+            },
+            PricingEntry {
+                model: "gpt-4o".to_string(),
+                provider: Some("azure_openai".to_string()),
+                pricing: ModelPricing::new(3.0, 4.0),
+            },
+        ]);
+        assert_eq!(registry.get(None, "gpt-4o").unwrap().input, 1.0);
+        assert_eq!(registry.get(Some("openai"), "gpt-4o").unwrap().input, 1.0);
+        assert_eq!(
+            registry.get(Some("azure_openai"), "gpt-4o").unwrap().input,
+            3.0
+        );
+        assert_eq!(registry.get(None, "gpt-4o-mini").unwrap().input, 0.15);
+
+        let entries: Vec<PricingEntry> = serde_yaml::from_str(
+            "- model: my-model\n  provider: openai\n  input: 1.5\n  output: 6\n  cache_read: 0.5\n",
+        )
+        .unwrap();
+        assert_eq!(
+            entries[0].pricing,
+            ModelPricing {
+                input: 1.5,
+                output: 6.0,
+                cache_read: Some(0.5),
+                cache_write: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_cost_prices_cached_tokens_separately() {
+        let registry = PricingRegistry::default();
+        let cost = registry
+            .cost(None, &usage("claude-sonnet-4-20250514", 1_000_000, 100_000))
+            .unwrap();
+        assert!((cost - 4.5).abs() < 1e-9);
+
+        let mut cached = usage("claude-sonnet-4-20250514", 1_000_000, 100_000);
+        cached.usage.cache_read_input_tokens = Some(800_000);
+        cached.usage.cache_write_input_tokens = Some(100_000);
+        // 100k uncached at $3, 800k read at $0.30, 100k written at $3.75, 100k output at $15
+        let cost = registry.cost(None, &cached).unwrap();
+        assert!((cost - (0.3 + 0.24 + 0.375 + 1.5)).abs() < 1e-9);
+
+        assert!(registry.cost(None, &usage("unknown", 10, 10)).is_none());
+    }
+}
@@ -0,0 +1,462 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use sha2::Digest;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+use super::base::{ConfigKey, Provider, ProviderMetadata, ProviderUsage};
+use super::errors::ProviderError;
+use crate::message::Message;
+use crate::model::ModelConfig;
+use mcp_core::{tool::Tool, Role};
+
+pub const REPLAY_DOC_URL: &str =
+    "https://block.github.io/goose/docs/guides/environment-variables#record-and-replay";
+
+const FIXTURE_VERSION: u32 = 1;
+
+/// How strictly a request has to match a recorded one to be served its response
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReplayMatch {
+    /// The system prompt, the messages and the tools must all be the same
+    Strict,
+    /// The messages and the tools must be the same, the system prompt is ignored since it
+    /// contains the current date
+    #[default]
+    Messages,
+    /// Responses are served in the order they were recorded, whatever the request
+    Sequential,
+}
+
+impl std::str::FromStr for ReplayMatch {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "strict" => Ok(ReplayMatch::Strict),
+            "messages" => Ok(ReplayMatch::Messages),
+            "sequential" => Ok(ReplayMatch::Sequential),
+            _ => Err(anyhow::anyhow!(
+                "Unknown replay match '{}', expected strict, messages or sequential",
+                s
+            )),
+        }
+    }
+}
+
+/// A recorded request and the response the provider gave to it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Exchange {
+    pub system: String,
+    pub messages: Vec<Message>,
+    pub tools_hash: String,
+    pub response: Message,
+    pub usage: ProviderUsage,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayFixture {
+    pub version: u32,
+    pub exchanges: Vec<Exchange>,
+}
+
+impl Default for ReplayFixture {
+    fn default() -> Self {
+        Self {
+            version: FIXTURE_VERSION,
+            exchanges: Vec::new(),
+        }
+    }
+}
+
+impl ReplayFixture {
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("Failed to read replay file {}: {}", path.display(), e))?;
+        let fixture: ReplayFixture = serde_json::from_str(&contents).map_err(|e| {
+            anyhow::anyhow!("Failed to parse replay file {}: {}", path.display(), e)
+        })?;
+        if fixture.version != FIXTURE_VERSION {
+            return Err(anyhow::anyhow!(
+                "Unsupported replay file version {} in {}",
+                fixture.version,
+                path.display()
+            ));
+        }
+        Ok(fixture)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+/// A hash of the tool definitions, independent of the order extensions list them in
+pub fn tools_hash(tools: &[Tool]) -> String {
+    let mut definitions: Vec<String> = tools
+        .iter()
+        .map(|tool| serde_json::to_string(tool).unwrap_or_default())
+        .collect();
+    definitions.sort();
+    let mut hasher = sha2::Sha256::new();
+    for definition in definitions {
+        hasher.update(definition.as_bytes());
+        hasher.update(b"\n");
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+/// Messages are compared on their role and content, not on when they were created
+fn same_messages(recorded: &[Message], messages: &[Message]) -> bool {
+    recorded.len() == messages.len()
+        && recorded
+            .iter()
+            .zip(messages)
+            .all(|(a, b)| a.role == b.role && a.content == b.content)
+}
+
+enum Mode {
+    Record(Arc<dyn Provider>),
+    Replay,
+}
+
+struct ReplayState {
+    fixture: ReplayFixture,
+    /// Which recorded exchanges have been served, used to replay repeated requests in order
+    used: Vec<bool>,
+}
+
+/// A provider that records the requests and responses of another provider to a file, or serves
+/// the recorded responses without calling any model, for deterministic tests and offline runs
+pub struct ReplayProvider {
+    path: PathBuf,
+    mode: Mode,
+    matching: ReplayMatch,
+    model: ModelConfig,
+    state: Mutex<ReplayState>,
+}
+
+impl ReplayProvider {
+    /// Serve the responses recorded in `path`
+    pub fn replay(path: impl Into<PathBuf>, matching: ReplayMatch) -> Result<Self> {
+        let path = path.into();
+        let fixture = ReplayFixture::load(&path)?;
+        let model = fixture
+            .exchanges
+            .first()
+            .map(|exchange| exchange.usage.model.clone())
+            .unwrap_or_else(|| "replay".to_string());
+        let used = vec![false; fixture.exchanges.len()];
+        Ok(Self {
+            path,
+            mode: Mode::Replay,
+            matching,
+            model: ModelConfig::new(model),
+            state: Mutex::new(ReplayState { fixture, used }),
+        })
+    }
+
+    /// Call `inner` and record every exchange to `path`, replacing what it held before
+    pub fn record(
+        path: impl Into<PathBuf>,
+        inner: Arc<dyn Provider>,
+        matching: ReplayMatch,
+    ) -> Self {
+        Self {
+            path: path.into(),
+            model: inner.get_model_config(),
+            mode: Mode::Record(inner),
+            matching,
+            state: Mutex::new(ReplayState {
+                fixture: ReplayFixture::default(),
+                used: Vec::new(),
+            }),
+        }
+    }
+
+    pub fn from_env(model: ModelConfig) -> Result<Self> {
+        let config = crate::config::Config::global();
+        let path: String = config.get_param("GOOSE_REPLAY_FILE")?;
+        let matching = match config.get_param::<String>("GOOSE_REPLAY_MATCH") {
+            Ok(matching) => matching.parse()?,
+            Err(_) => ReplayMatch::default(),
+        };
+        let mode = config
+            .get_param::<String>("GOOSE_REPLAY_MODE")
+            .unwrap_or_else(|_| "replay".to_string());
+
+        match mode.to_lowercase().as_str() {
+            "replay" => Self::replay(path, matching),
+            "record" => {
+                let inner_name: String =
+                    config.get_param("GOOSE_REPLAY_PROVIDER").map_err(|_| {
+                        anyhow::anyhow!("GOOSE_REPLAY_PROVIDER must name the provider to record")
+                    })?;
+                if inner_name == "replay" {
+                    return Err(anyhow::anyhow!(
+                        "GOOSE_REPLAY_PROVIDER cannot be the replay provider itself"
+                    ));
+                }
+                let inner = super::create(&inner_name, model)?;
+                Ok(Self::record(path, inner, matching))
+            }
+            _ => Err(anyhow::anyhow!(
+                "Unknown GOOSE_REPLAY_MODE '{}', expected replay or record",
+                mode
+            )),
+        }
+    }
+
+    fn find_exchange(
+        &self,
+        state: &ReplayState,
+        system: &str,
+        messages: &[Message],
+        tools_hash: &str,
+    ) -> Option<usize> {
+        let exchanges = &state.fixture.exchanges;
+        if self.matching == ReplayMatch::Sequential {
+            return state.used.iter().position(|used| !used);
+        }
+
+        let matches: Vec<usize> = exchanges
+            .iter()
+            .enumerate()
+            .filter(|(_, exchange)| {
+                (self.matching != ReplayMatch::Strict || exchange.system == system)
+                    && exchange.tools_hash == tools_hash
+                    && same_messages(&exchange.messages, messages)
+            })
+            .map(|(index, _)| index)
+            .collect();
+        // The same request can be recorded several times, serve those in order and repeat the last
+        matches
+            .iter()
+            .copied()
+            .find(|&index| !state.used[index])
+            .or_else(|| matches.last().copied())
+    }
+}
+
+#[async_trait]
+impl Provider for ReplayProvider {
+    fn metadata() -> ProviderMetadata {
+        ProviderMetadata::new(
+            "replay",
+            "Record/Replay",
+            "Records another provider's responses to a file and replays them offline",
+            "replay",
+            vec![],
+            REPLAY_DOC_URL,
+            vec![
+                ConfigKey::new("GOOSE_REPLAY_FILE", true, false, None),
+                ConfigKey::new("GOOSE_REPLAY_MODE", false, false, Some("replay")),
+                ConfigKey::new("GOOSE_REPLAY_PROVIDER", false, false, None),
+                ConfigKey::new("GOOSE_REPLAY_MATCH", false, false, Some("messages")),
+            ],
+        )
+    }
+
+    fn get_model_config(&self) -> ModelConfig {
+        self.model.clone()
+    }
+
+    async fn complete(
+        &self,
+        system: &str,
+        messages: &[Message],
+        tools: &[Tool],
+    ) -> Result<(Message, ProviderUsage), ProviderError> {
+        let tools_hash = tools_hash(tools);
+
+        match &self.mode {
+            Mode::Record(inner) => {
+                let (response, usage) = inner.complete(system, messages, tools).await?;
+                let mut state = self.state.lock().await;
+                state.fixture.exchanges.push(Exchange {
+                    system: system.to_string(),
+                    messages: messages.to_vec(),
+                    tools_hash,
+                    response: response.clone(),
+                    usage: usage.clone(),
+                });
+                state.used.push(true);
+                // Written after every exchange so an interrupted run still leaves a usable file
+                state.fixture.save(&self.path).map_err(|e| {
+                    ProviderError::ExecutionError(format!(
+                        "Failed to write replay file {}: {}",
+                        self.path.display(),
+                        e
+                    ))
+                })?;
+                Ok((response, usage))
+            }
+            Mode::Replay => {
+                let mut state = self.state.lock().await;
+                let index = self
+                    .find_exchange(&state, system, messages, &tools_hash)
+                    .ok_or_else(|| {
+                        let last_user = messages
+                            .iter()
+                            .rev()
+                            .find(|message| message.role == Role::User)
+                            .map(|message| message.as_concat_text())
+                            .unwrap_or_default();
+                        ProviderError::ExecutionError(format!(
+                            "No recorded response in {} matches this request ({} messages, last user message: {:?})",
+                            self.path.display(),
+                            messages.len(),
+                            last_user
+                        ))
+                    })?;
+                state.used[index] = true;
+                let exchange = &state.fixture.exchanges[index];
+                let mut response = exchange.response.clone();
+                response.created = chrono::Utc::now().timestamp();
+                Ok((response, exchange.usage.clone()))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::base::Usage;
+    use serde_json::json;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Answers each request with the number of calls made so far
+    struct CountingProvider {
+        calls: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl Provider for CountingProvider {
+        fn metadata() -> ProviderMetadata {
+            ProviderMetadata::empty()
+        }
+
+        fn get_model_config(&self) -> ModelConfig {
+            ModelConfig::new("counting-model".to_string())
+        }
+
+        async fn complete(
+            &self,
+            _system: &str,
+            _messages: &[Message],
+            _tools: &[Tool],
+        ) -> Result<(Message, ProviderUsage), ProviderError> {
+            let call = self.calls.fetch_add(1, Ordering::SeqCst);
+            Ok((
+                Message::assistant().with_text(format!("response {}", call)),
+                ProviderUsage::new(
+                    "counting-model".to_string(),
+                    Usage::new(Some(10), Some(5), Some(15)),
+                ),
+            ))
+        }
+    }
+
+    fn tool(name: &str) -> Tool {
+        Tool::new(name, "A test tool", json!({"type": "object"}), None)
+    }
+
+    async fn record_fixture(path: &Path) {
+        let inner = Arc::new(CountingProvider {
+            calls: AtomicUsize::new(0),
+        });
+        let recorder = ReplayProvider::record(path, inner, ReplayMatch::Messages);
+        let tools = vec![tool("a"), tool("b")];
+        recorder
+            .complete("system 1", &[Message::user().with_text("hello")], &tools)
+            .await
+            .unwrap();
+        recorder
+            .complete("system 1", &[Message::user().with_text("again")], &tools)
+            .await
+            .unwrap();
+    }
+
+    fn text(message: &Message) -> String {
+        message.as_concat_text()
+    }
+
+    #[tokio::test]
+    async fn test_record_then_replay() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("fixture.json");
+        record_fixture(&path).await;
+
+        let replay = ReplayProvider::replay(&path, ReplayMatch::Messages).unwrap();
+        assert_eq!(replay.get_model_config().model_name, "counting-model");
+
+        // Tool order and the system prompt don't matter when matching on messages
+        let tools = vec![tool("b"), tool("a")];
+        let (response, usage) = replay
+            .complete("system 2", &[Message::user().with_text("again")], &tools)
+            .await
+            .unwrap();
+        assert_eq!(text(&response), "response 1");
+        assert_eq!(usage.usage.total_tokens, Some(15));
+
+        let (response, _) = replay
+            .complete("system 2", &[Message::user().with_text("hello")], &tools)
+            .await
+            .unwrap();
+        assert_eq!(text(&response), "response 0");
+    }
+
+    #[tokio::test]
+    async fn test_match_strictness() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("fixture.json");
+        record_fixture(&path).await;
+        let tools = vec![tool("a"), tool("b")];
+        let hello = [Message::user().with_text("hello")];
+
+        let strict = ReplayProvider::replay(&path, ReplayMatch::Strict).unwrap();
+        assert!(strict.complete("system 2", &hello, &tools).await.is_err());
+        assert!(strict.complete("system 1", &hello, &tools).await.is_ok());
+
+        let messages = ReplayProvider::replay(&path, ReplayMatch::Messages).unwrap();
+        assert!(messages
+            .complete("system 1", &hello, &[tool("a")])
+            .await
+            .is_err());
+
+        let sequential = ReplayProvider::replay(&path, ReplayMatch::Sequential).unwrap();
+        let other = [Message::user().with_text("something else")];
+        let (first, _) = sequential.complete("", &other, &[]).await.unwrap();
+        let (second, _) = sequential.complete("", &other, &[]).await.unwrap();
+        assert_eq!(text(&first), "response 0");
+        assert_eq!(text(&second), "response 1");
+        assert!(sequential.complete("", &other, &[]).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_unmatched_request_names_the_fixture() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("fixture.json");
+        record_fixture(&path).await;
+
+        let replay = ReplayProvider::replay(&path, ReplayMatch::Messages).unwrap();
+        let error = replay
+            .complete("", &[Message::user().with_text("unknown")], &[])
+            .await
+            .unwrap_err();
+        let message = error.to_string();
+        assert!(message.contains("fixture.json"));
+        assert!(message.contains("unknown"));
+
+        assert!(
+            ReplayProvider::replay(dir.path().join("missing.json"), ReplayMatch::Strict).is_err()
+        );
+    }
+}
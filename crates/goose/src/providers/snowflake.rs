@@ -369,7 +369,7 @@ impl SnowflakeProvider {
                     error_msg
                 )))
             }
-            StatusCode::TOO_MANY_REQUESTS => Err(ProviderError::RateLimitExceeded(
+            StatusCode::TOO_MANY_REQUESTS => Err(ProviderError::rate_limit_exceeded(
                 "Rate limit exceeded. Please try again later.".to_string(),
             )),
             StatusCode::INTERNAL_SERVER_ERROR | StatusCode::SERVICE_UNAVAILABLE => {
@@ -21,13 +21,17 @@
 //!
 //! ### Implementations
 //!
-//! The module provides an implementation for Ollama:
+//! The module provides these implementations, selected by `ModelConfig::toolshim_provider`:
 //!
-//! - `OllamaInterpreter`: Uses Ollama's structured output API to interpret tool calls
+//! - `OllamaInterpreter` (`ollama`, the default): Uses Ollama's structured output API to interpret tool calls
+//! - `OpenAiCompatibleInterpreter` (`openai`): Uses an OpenAI-compatible chat API with a JSON schema response format
+//! - `OpenAiCompatibleInterpreter` (`grammar`): Constrains the reply with a GBNF grammar, as llama.cpp's server supports
+//! - `ExtractInterpreter` (`extract`): Parses JSON tool calls out of the text itself, without a second model call
 //!
 //! ### Helper Functions
 //!
-//! - `augment_message_with_tool_calls`: A utility function that takes any message, extracts text content, sends it to an interpreter, and adds any detected tool calls back to the message.
+//! - `create_interpreter`: Creates the interpreter a model config selects.
+//! - `augment_message_with_tool_calls`: A utility function that takes any message, extracts text content, sends it to an interpreter, and adds any detected tool calls back to the message. If the interpreter fails, tool calls are extracted from the text instead.
 //!
 
 use super::errors::ProviderError;
@@ -47,12 +51,50 @@ use uuid::Uuid;
 /// Default model to use for tool interpretation
 pub const DEFAULT_INTERPRETER_MODEL_OLLAMA: &str = "mistral-nemo";
 
+/// Keys of the tool interpreter backends, for `ModelConfig::toolshim_provider`
+pub const TOOLSHIM_PROVIDER_OLLAMA: &str = "ollama";
+pub const TOOLSHIM_PROVIDER_OPENAI: &str = "openai";
+pub const TOOLSHIM_PROVIDER_GRAMMAR: &str = "grammar";
+pub const TOOLSHIM_PROVIDER_EXTRACT: &str = "extract";
+
+/// Default server for the OpenAI-compatible interpreters, where llama.cpp's server listens
+pub const DEFAULT_TOOLSHIM_HOST: &str = "http://localhost:8080";
+
+/// Name the interpreters use when the text asks for no tool call
+const NOOP_TOOL_NAME: &str = "noop";
+
+const INTERPRETER_SYSTEM_PROMPT: &str = "If there is detectable JSON-formatted tool requests, write them into valid JSON tool calls in the following format:
+{{
+  \"tool_calls\": [
+    {{
+      \"name\": \"tool_name\",
+      \"arguments\": {{
+        \"param1\": \"value1\",
+        \"param2\": \"value2\"
+      }}
+    }}
+  ]
+}}
+
+Otherwise, if no JSON tool requests are provided, use the no-op tool:
+{{
+  \"tool_calls\": [
+    {{
+    \"name\": \"noop\",
+      \"arguments\": {{
+      }}
+    }}]
+}}
+";
+
 /// Environment variables that affect behavior:
 /// - GOOSE_TOOLSHIM: When set to "true" or "1", enables using the tool shim in the standard OllamaProvider (default: false)
-/// - GOOSE_TOOLSHIM_OLLAMA_MODEL: Ollama model to use as the tool interpreter (default: DEFAULT_INTERPRETER_MODEL)
+/// - GOOSE_TOOLSHIM_PROVIDER: Tool interpreter backend, one of ollama, openai, grammar or extract (default: ollama)
+/// - GOOSE_TOOLSHIM_MODEL or GOOSE_TOOLSHIM_OLLAMA_MODEL: Model to use as the tool interpreter (default: DEFAULT_INTERPRETER_MODEL_OLLAMA for Ollama, the main model otherwise)
+/// - GOOSE_TOOLSHIM_HOST and GOOSE_TOOLSHIM_API_KEY: Server of the OpenAI-compatible interpreters (default: DEFAULT_TOOLSHIM_HOST)
 /// A trait for models that can interpret text into structured tool call JSON format
 #[async_trait::async_trait]
-pub trait ToolInterpreter {
+pub trait ToolInterpreter: Send + Sync {
     /// Interpret potential tool calls from text and convert them to proper tool call JSON format
     async fn interpret_to_tool_calls(
         &self,
@@ -65,6 +107,7 @@ pub trait ToolInterpreter {
 pub struct OllamaInterpreter {
     client: Client,
     base_url: String,
+    model: String,
 }
 
 impl OllamaInterpreter {
@@ -76,7 +119,21 @@ impl OllamaInterpreter {
 
         let base_url = Self::get_ollama_base_url()?;
 
-        Ok(Self { client, base_url })
+        // Determine which model to use for interpretation (from env var or default)
+        let model = std::env::var("GOOSE_TOOLSHIM_OLLAMA_MODEL")
+            .unwrap_or_else(|_| DEFAULT_INTERPRETER_MODEL_OLLAMA.to_string());
+
+        Ok(Self {
+            client,
+            base_url,
+            model,
+        })
+    }
+
+    /// Set the Ollama model used to interpret tool calls
+    pub fn with_model(mut self, model: String) -> Self {
+        self.model = model;
+        self
     }
 
     /// Get the Ollama base URL from existing config or use default values
@@ -196,40 +253,13 @@ impl OllamaInterpreter {
     }
 
     fn process_interpreter_response(response: &Value) -> Result<Vec<ToolCall>, ProviderError> {
-        let mut tool_calls = Vec::new();
         tracing::info!(
             "Tool interpreter response is {}",
             serde_json::to_string_pretty(&response).unwrap_or_default()
         );
         // Extract tool_calls array from the response
-        if response.get("message").is_some() && response["message"].get("content").is_some() {
-            let content = response["message"]["content"].as_str().unwrap_or_default();
-
-            // Try to parse the content as JSON
-            if let Ok(content_json) = serde_json::from_str::<Value>(content) {
-                // Check for the format with tool_calls array inside an object
-                if content_json.is_object() && content_json.get("tool_calls").is_some() {
-                    // Process each tool call in the array
-                    if let Some(tool_calls_array) = content_json["tool_calls"].as_array() {
-                        for item in tool_calls_array {
-                            if item.is_object()
-                                && item.get("name").is_some()
-                                && item.get("arguments").is_some()
-                            {
-                                // Create ToolCall directly from the JSON data
-                                let name = item["name"].as_str().unwrap_or_default().to_string();
-                                let arguments = item["arguments"].clone();
-
-                                // Add the tool call to our result vector
-                                tool_calls.push(ToolCall::new(name, arguments));
-                            }
-                        }
-                    }
-                }
-            }
-        }
-
-        Ok(tool_calls)
+        let content = response["message"]["content"].as_str().unwrap_or_default();
+        Ok(parse_tool_calls(content))
     }
 }
 
@@ -244,44 +274,15 @@ impl ToolInterpreter for OllamaInterpreter {
             return Ok(vec![]);
         }
 
-        // Create the system prompt
-        let system_prompt = "If there is detectable JSON-formatted tool requests, write them into valid JSON tool calls in the following format:
-{{
-  \"tool_calls\": [
-    {{
-      \"name\": \"tool_name\",
-      \"arguments\": {{
-        \"param1\": \"value1\",
-        \"param2\": \"value2\"
-      }}
-    }}
-  ]
-}}
-
-Otherwise, if no JSON tool requests are provided, use the no-op tool:
-{{
-  \"tool_calls\": [
-    {{
-    \"name\": \"noop\",
-      \"arguments\": {{
-      }}
-    }}]
-}}
-";
-
         // Create enhanced content with instruction to output tool calls as JSON
-        let format_instruction = format!("{}\nRequest: {}\n\n", system_prompt, last_assistant_msg);
+        let format_instruction = format_instruction(last_assistant_msg);
 
         // Define the JSON schema for tool call format
         let format_schema = OllamaInterpreter::tool_structured_ouput_format_schema();
 
-        // Determine which model to use for interpretation (from env var or default)
-        let interpreter_model = std::env::var("GOOSE_TOOLSHIM_OLLAMA_MODEL")
-            .unwrap_or_else(|_| DEFAULT_INTERPRETER_MODEL_OLLAMA.to_string());
-
         // Make a call to ollama with structured output
         let interpreter_response = self
-            .post_structured("", &format_instruction, format_schema, &interpreter_model)
+            .post_structured("", &format_instruction, format_schema, &self.model)
             .await?;
 
         // Process the interpreter response to get tool calls directly
@@ -291,6 +292,333 @@ Otherwise, if no JSON tool requests are provided, use the no-op tool:
     }
 }
 
+/// The request an interpreter model is asked to turn into tool calls
+fn format_instruction(last_assistant_msg: &str) -> String {
+    format!(
+        "{}\nRequest: {}\n\n",
+        INTERPRETER_SYSTEM_PROMPT, last_assistant_msg
+    )
+}
+
+/// Read the `tool_calls` array out of an interpreter's JSON reply
+fn parse_tool_calls(content: &str) -> Vec<ToolCall> {
+    let content_json = match serde_json::from_str::<Value>(content) {
+        Ok(content_json) => content_json,
+        Err(_) => return Vec::new(),
+    };
+
+    content_json["tool_calls"]
+        .as_array()
+        .map(|calls| {
+            calls
+                .iter()
+                .filter(|item| item.get("name").is_some() && item.get("arguments").is_some())
+                .map(|item| {
+                    ToolCall::new(
+                        item["name"].as_str().unwrap_or_default(),
+                        item["arguments"].clone(),
+                    )
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Names an interpreter may pick from: the available tools, and the no-op tool
+fn tool_names(tools: &[Tool]) -> Vec<String> {
+    tools
+        .iter()
+        .map(|tool| tool.name.clone())
+        .chain(std::iter::once(NOOP_TOOL_NAME.to_string()))
+        .collect()
+}
+
+/// JSON schema of the interpreter reply, limiting tool names to the available tools
+fn tool_calls_schema(tools: &[Tool]) -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "tool_calls": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "name": {
+                            "type": "string",
+                            "enum": tool_names(tools),
+                            "description": "The name of the tool to call"
+                        },
+                        "arguments": {
+                            "type": "object",
+                            "description": "The arguments to pass to the tool"
+                        }
+                    },
+                    "required": ["name", "arguments"]
+                }
+            }
+        },
+        "required": ["tool_calls"]
+    })
+}
+
+/// GBNF grammar of the interpreter reply, limiting tool names to the available tools and
+/// arguments to a JSON object
+fn tool_calls_grammar(tools: &[Tool]) -> String {
+    let names = tool_names(tools)
+        .iter()
+        .map(|name| {
+            // A GBNF literal holding the JSON string of the name
+            let json_name = Value::String(name.clone()).to_string();
+            format!(
+                "\"{}\"",
+                json_name.replace('\\', "\\\\").replace('"', "\\\"")
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(" | ");
+
+    format!(
+        r#"root ::= "{{" ws "\"tool_calls\"" ws ":" ws "[" ws (call (ws "," ws call)*)? ws "]" ws "}}"
+call ::= "{{" ws "\"name\"" ws ":" ws name ws "," ws "\"arguments\"" ws ":" ws object ws "}}"
+name ::= {}
+object ::= "{{" ws (string ws ":" ws value (ws "," ws string ws ":" ws value)*)? ws "}}"
+array ::= "[" ws (value (ws "," ws value)*)? ws "]"
+value ::= object | array | string | number | "true" | "false" | "null"
+string ::= "\"" ([^"\\\x7F\x00-\x1F] | "\\" (["\\/bfnrt] | "u" [0-9a-fA-F] [0-9a-fA-F] [0-9a-fA-F] [0-9a-fA-F]))* "\""
+number ::= "-"? ([0-9] | [1-9] [0-9]*) ("." [0-9]+)? ([eE] [-+]? [0-9]+)?
+ws ::= [ \t\n]*
+"#,
+        names
+    )
+}
+
+/// How the OpenAI-compatible interpreter constrains the reply to tool call JSON
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OutputConstraint {
+    /// A JSON schema `response_format`, supported by OpenAI and most compatible servers
+    JsonSchema,
+    /// A GBNF grammar in the `grammar` field, as llama.cpp's server accepts
+    Grammar,
+}
+
+/// Implementation of the ToolInterpreter trait for OpenAI-compatible chat completion APIs,
+/// such as llama.cpp's server, vLLM or OpenAI itself
+pub struct OpenAiCompatibleInterpreter {
+    client: Client,
+    host: String,
+    api_key: Option<String>,
+    model: String,
+    constraint: OutputConstraint,
+}
+
+impl OpenAiCompatibleInterpreter {
+    pub fn new(model: String, constraint: OutputConstraint) -> Result<Self, ProviderError> {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(600))
+            .build()
+            .expect("Failed to create HTTP client");
+
+        let config = crate::config::Config::global();
+        let host: String = config
+            .get_param("GOOSE_TOOLSHIM_HOST")
+            .unwrap_or_else(|_| DEFAULT_TOOLSHIM_HOST.to_string());
+        url::Url::parse(&host).map_err(|e| {
+            ProviderError::RequestFailed(format!("Invalid GOOSE_TOOLSHIM_HOST {}: {e}", host))
+        })?;
+        let api_key: Option<String> = config.get_secret("GOOSE_TOOLSHIM_API_KEY").ok();
+
+        Ok(Self {
+            client,
+            host,
+            api_key,
+            model,
+            constraint,
+        })
+    }
+
+    async fn post(&self, payload: &Value) -> Result<Value, ProviderError> {
+        let url = format!("{}/v1/chat/completions", self.host.trim_end_matches('/'));
+
+        tracing::info!(
+            "Tool interpreter payload: {}",
+            serde_json::to_string_pretty(&payload).unwrap_or_default()
+        );
+
+        let mut request = self.client.post(&url).json(payload);
+        if let Some(api_key) = &self.api_key {
+            request = request.bearer_auth(api_key);
+        }
+        let response = request.send().await?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+
+            let error_text = match response.text().await {
+                Ok(text) => text,
+                Err(_) => "Could not read error response".to_string(),
+            };
+
+            return Err(ProviderError::RequestFailed(format!(
+                "Tool interpreter API returned error status {}: {}",
+                status, error_text
+            )));
+        }
+
+        response.json().await.map_err(|e| {
+            ProviderError::RequestFailed(format!("Failed to parse tool interpreter response: {e}"))
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl ToolInterpreter for OpenAiCompatibleInterpreter {
+    async fn interpret_to_tool_calls(
+        &self,
+        last_assistant_msg: &str,
+        tools: &[Tool],
+    ) -> Result<Vec<ToolCall>, ProviderError> {
+        if tools.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let messages = vec![Message::user().with_text(format_instruction(last_assistant_msg))];
+        let mut payload = create_request(
+            &ModelConfig::new(self.model.clone()),
+            "",
+            &messages,
+            &[], // No tools
+            &super::utils::ImageFormat::OpenAi,
+        )?;
+
+        match self.constraint {
+            OutputConstraint::JsonSchema => {
+                payload["response_format"] = json!({
+                    "type": "json_schema",
+                    "json_schema": {
+                        "name": "tool_calls",
+                        "schema": tool_calls_schema(tools)
+                    }
+                });
+            }
+            OutputConstraint::Grammar => {
+                payload["grammar"] = json!(tool_calls_grammar(tools));
+            }
+        }
+
+        let response = self.post(&payload).await?;
+        tracing::info!(
+            "Tool interpreter response is {}",
+            serde_json::to_string_pretty(&response).unwrap_or_default()
+        );
+
+        let content = response["choices"][0]["message"]["content"]
+            .as_str()
+            .unwrap_or_default();
+        Ok(parse_tool_calls(content))
+    }
+}
+
+/// Implementation of the ToolInterpreter trait that reads the JSON tool calls the model was
+/// prompted to write straight out of its text, without asking another model
+pub struct ExtractInterpreter;
+
+#[async_trait::async_trait]
+impl ToolInterpreter for ExtractInterpreter {
+    async fn interpret_to_tool_calls(
+        &self,
+        content: &str,
+        tools: &[Tool],
+    ) -> Result<Vec<ToolCall>, ProviderError> {
+        Ok(extract_tool_calls(content, tools))
+    }
+}
+
+/// Find tool calls written as JSON objects in the text, inline or in code blocks, in the
+/// `{"name": ..., "arguments": ...}` form the toolshim system prompt asks for or wrapped in a
+/// `tool_calls` array. Objects naming a tool that isn't available are ignored.
+pub fn extract_tool_calls(content: &str, tools: &[Tool]) -> Vec<ToolCall> {
+    let mut tool_calls = Vec::new();
+    let mut rest = content;
+    while let Some(start) = rest.find('{') {
+        let candidate = &rest[start..];
+        let mut values = serde_json::Deserializer::from_str(candidate).into_iter::<Value>();
+        match values.next() {
+            Some(Ok(value)) => {
+                collect_tool_calls(&value, tools, &mut tool_calls);
+                rest = &candidate[values.byte_offset()..];
+            }
+            _ => rest = &candidate[1..],
+        }
+    }
+    tool_calls
+}
+
+fn collect_tool_calls(value: &Value, tools: &[Tool], tool_calls: &mut Vec<ToolCall>) {
+    if let Some(calls) = value.get("tool_calls").and_then(Value::as_array) {
+        for call in calls {
+            collect_tool_calls(call, tools, tool_calls);
+        }
+        return;
+    }
+
+    let name = match value.get("name").and_then(Value::as_str) {
+        Some(name) if tools.iter().any(|tool| tool.name == name) => name,
+        _ => return,
+    };
+    let arguments = match value.get("arguments") {
+        Some(arguments) if arguments.is_object() => arguments.clone(),
+        // Some models write the arguments as a JSON string
+        Some(Value::String(arguments)) => match serde_json::from_str::<Value>(arguments) {
+            Ok(arguments) if arguments.is_object() => arguments,
+            _ => return,
+        },
+        None => json!({}),
+        _ => return,
+    };
+    tool_calls.push(ToolCall::new(name, arguments));
+}
+
+/// Create the tool interpreter the model config selects with its toolshim provider, Ollama by
+/// default. The interpreter model defaults to DEFAULT_INTERPRETER_MODEL_OLLAMA for Ollama and
+/// to the main model for OpenAI-compatible servers.
+pub fn create_interpreter(config: &ModelConfig) -> Result<Box<dyn ToolInterpreter>, ProviderError> {
+    let provider = config
+        .toolshim_provider
+        .as_deref()
+        .unwrap_or(TOOLSHIM_PROVIDER_OLLAMA);
+    let model = config
+        .toolshim_model
+        .clone()
+        .unwrap_or_else(|| config.model_name.clone());
+
+    match provider {
+        TOOLSHIM_PROVIDER_OLLAMA => {
+            let interpreter = OllamaInterpreter::new()?;
+            Ok(Box::new(match &config.toolshim_model {
+                Some(model) => interpreter.with_model(model.clone()),
+                None => interpreter,
+            }))
+        }
+        TOOLSHIM_PROVIDER_OPENAI => Ok(Box::new(OpenAiCompatibleInterpreter::new(
+            model,
+            OutputConstraint::JsonSchema,
+        )?)),
+        TOOLSHIM_PROVIDER_GRAMMAR => Ok(Box::new(OpenAiCompatibleInterpreter::new(
+            model,
+            OutputConstraint::Grammar,
+        )?)),
+        TOOLSHIM_PROVIDER_EXTRACT => Ok(Box::new(ExtractInterpreter)),
+        other => Err(ProviderError::ExecutionError(format!(
+            "Unknown toolshim provider '{}', expected one of {}, {}, {} or {}",
+            other,
+            TOOLSHIM_PROVIDER_OLLAMA,
+            TOOLSHIM_PROVIDER_OPENAI,
+            TOOLSHIM_PROVIDER_GRAMMAR,
+            TOOLSHIM_PROVIDER_EXTRACT
+        ))),
+    }
+}
+
 /// Creates a string containing formatted tool information
 pub fn format_tool_info(tools: &[Tool]) -> String {
     let mut tool_info = String::new();
@@ -383,7 +711,7 @@ pub fn modify_system_prompt_for_tool_json(system_prompt: &str, tools: &[Tool]) -
 }
 
 /// Helper function to augment a message with tool calls if any are detected
-pub async fn augment_message_with_tool_calls<T: ToolInterpreter>(
+pub async fn augment_message_with_tool_calls<T: ToolInterpreter + ?Sized>(
     interpreter: &T,
     message: Message,
     tools: &[Tool],
@@ -417,8 +745,18 @@ pub async fn augment_message_with_tool_calls<T: ToolInterpreter>(
         return Ok(message);
     }
 
-    // Use the interpreter to convert the content to tool calls
-    let tool_calls = interpreter.interpret_to_tool_calls(content, tools).await?;
+    // Use the interpreter to convert the content to tool calls, or read them from the text
+    // without a model if the interpreter is unavailable
+    let tool_calls = match interpreter.interpret_to_tool_calls(content, tools).await {
+        Ok(tool_calls) => tool_calls,
+        Err(e) => {
+            tracing::warn!(
+                "Tool interpreter failed, extracting tool calls from the text instead: {}",
+                e
+            );
+            extract_tool_calls(content, tools)
+        }
+    };
 
     // If no tool calls were detected, return the original message
     if tool_calls.is_empty() {
@@ -428,7 +766,7 @@ pub async fn augment_message_with_tool_calls<T: ToolInterpreter>(
     // Add each tool call to the message
     let mut final_message = message;
     for tool_call in tool_calls {
-        if tool_call.name != "noop" {
+        if tool_call.name != NOOP_TOOL_NAME {
             // do not actually execute noop tool
             let id = Uuid::new_v4().to_string();
             final_message = final_message.with_tool_request(id, Ok(tool_call));
@@ -437,3 +775,114 @@ pub async fn augment_message_with_tool_calls<T: ToolInterpreter>(
 
     Ok(final_message)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tools() -> Vec<Tool> {
+        ["developer__shell", "developer__text_editor"]
+            .iter()
+            .map(|name| Tool::new(*name, "A tool", json!({"type": "object"}), None))
+            .collect()
+    }
+
+    #[test]
+    fn test_extract_tool_calls() {
+        let content = r#"I'll list the files first.
+```json
+{
+  "name": "developer__shell",
+  "arguments": {"command": "echo '{not json}'"}
+}
+```
+Then {"name": "unknown__tool", "arguments": {}} is skipped, and
+{"tool_calls": [{"name": "developer__text_editor", "arguments": "{\"command\": \"view\", \"path\": \"a.rs\"}"}]}"#;
+
+        let tool_calls = extract_tool_calls(content, &tools());
+        assert_eq!(
+            tool_calls,
+            vec![
+                ToolCall::new("developer__shell", json!({"command": "echo '{not json}'"})),
+                ToolCall::new(
+                    "developer__text_editor",
+                    json!({"command": "view", "path": "a.rs"})
+                ),
+            ]
+        );
+
+        assert!(extract_tool_calls("No tools needed {here}", &tools()).is_empty());
+    }
+
+    #[test]
+    fn test_parse_tool_calls() {
+        let content = r#"{"tool_calls": [{"name": "developer__shell", "arguments": {"command": "ls"}}, {"name": "missing_arguments"}]}"#;
+        assert_eq!(
+            parse_tool_calls(content),
+            vec![ToolCall::new("developer__shell", json!({"command": "ls"}))]
+        );
+        assert!(parse_tool_calls("not json").is_empty());
+    }
+
+    #[test]
+    fn test_constraints_limit_tool_names() {
+        let grammar = tool_calls_grammar(&tools());
+        assert!(grammar.contains(
+            r#"name ::= "\"developer__shell\"" | "\"developer__text_editor\"" | "\"noop\"""#
+        ));
+        assert!(grammar.starts_with(r#"root ::= "{" ws "\"tool_calls\"""#));
+
+        let schema = tool_calls_schema(&tools());
+        assert_eq!(
+            schema["properties"]["tool_calls"]["items"]["properties"]["name"]["enum"],
+            json!(["developer__shell", "developer__text_editor", "noop"])
+        );
+    }
+
+    #[test]
+    fn test_create_interpreter() {
+        let config = ModelConfig::new("test-model".to_string())
+            .with_toolshim_provider(Some(TOOLSHIM_PROVIDER_EXTRACT.to_string()));
+        assert!(create_interpreter(&config).is_ok());
+
+        let config = config.with_toolshim_provider(Some("unknown".to_string()));
+        assert!(create_interpreter(&config).is_err());
+    }
+
+    struct FailingInterpreter;
+
+    #[async_trait::async_trait]
+    impl ToolInterpreter for FailingInterpreter {
+        async fn interpret_to_tool_calls(
+            &self,
+            _content: &str,
+            _tools: &[Tool],
+        ) -> Result<Vec<ToolCall>, ProviderError> {
+            Err(ProviderError::RequestFailed(
+                "connection refused".to_string(),
+            ))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_augment_falls_back_to_extraction() {
+        let message = Message::assistant()
+            .with_text(r#"{"name": "developer__shell", "arguments": {"command": "ls"}}"#);
+
+        let augmented = augment_message_with_tool_calls(&FailingInterpreter, message, &tools())
+            .await
+            .unwrap();
+        let tool_calls: Vec<ToolCall> = augmented
+            .content
+            .iter()
+            .filter_map(|content| match content {
+                MessageContent::ToolRequest(req) => req.tool_call.clone().ok(),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            tool_calls,
+            vec![ToolCall::new("developer__shell", json!({"command": "ls"}))]
+        );
+    }
+}
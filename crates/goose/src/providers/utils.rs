@@ -1,14 +1,19 @@
-use super::base::Usage;
+use super::base::{MessageStream, ProviderStreamItem, Usage};
 use super::errors::GoogleErrorCode;
 use crate::model::ModelConfig;
 use anyhow::Result;
+use async_stream::try_stream;
 use base64::Engine;
+use futures::{Stream, StreamExt};
 use regex::Regex;
+use reqwest::header::HeaderMap;
 use reqwest::{Response, StatusCode};
 use serde::{Deserialize, Serialize};
 use serde_json::{from_value, json, Map, Value};
 use std::io::Read;
 use std::path::Path;
+use std::time::Duration;
+use tracing::Instrument;
 
 use crate::providers::errors::{OpenAIError, ProviderError};
 use mcp_core::content::ImageContent;
@@ -44,11 +49,33 @@ pub fn convert_image(image: &ImageContent, image_format: &ImageFormat) -> Value
     }
 }
 
+/// The delay a provider asked for before retrying, from a Retry-After header in seconds or as
+/// an HTTP date, or the retry-after-ms header some OpenAI compatible endpoints send
+pub fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let header = |name: &str| headers.get(name).and_then(|value| value.to_str().ok());
+
+    if let Some(ms) = header("retry-after-ms").and_then(|ms| ms.trim().parse::<f64>().ok()) {
+        return Some(Duration::from_secs_f64(ms.max(0.0) / 1000.0));
+    }
+    let value = header("retry-after")?.trim();
+    if let Ok(seconds) = value.parse::<f64>() {
+        return Some(Duration::from_secs_f64(seconds.max(0.0)));
+    }
+    chrono::DateTime::parse_from_rfc2822(value)
+        .ok()
+        .map(|date| {
+            (date.with_timezone(&chrono::Utc) - chrono::Utc::now())
+                .to_std()
+                .unwrap_or_default()
+        })
+}
+
 /// Handle response from OpenAI compatible endpoints
 /// Error codes: https://platform.openai.com/docs/guides/error-codes
 /// Context window exceeded: https://community.openai.com/t/help-needed-tackling-context-length-limits-in-openai-models/617543
 pub async fn handle_response_openai_compat(response: Response) -> Result<Value, ProviderError> {
     let status = response.status();
+    let retry_delay = retry_after(response.headers());
     // Try to parse the response body as JSON (if applicable)
     let payload = match response.json::<Value>().await {
         Ok(json) => json,
@@ -75,7 +102,7 @@ pub async fn handle_response_openai_compat(response: Response) -> Result<Value,
             Err(ProviderError::RequestFailed(format!("Unknown error (status {})", status)))
         }
         StatusCode::TOO_MANY_REQUESTS => {
-            Err(ProviderError::RateLimitExceeded(format!("{:?}", payload)))
+            Err(ProviderError::RateLimitExceeded { details: format!("{:?}", payload), retry_delay })
         }
         StatusCode::INTERNAL_SERVER_ERROR | StatusCode::SERVICE_UNAVAILABLE => {
             Err(ProviderError::ServerError(format!("{:?}", payload)))
@@ -89,6 +116,58 @@ pub async fn handle_response_openai_compat(response: Response) -> Result<Value,
     }
 }
 
+/// Check the status of a streaming response from an OpenAI compatible endpoint
+///
+/// Successful responses are returned untouched so their body can be streamed, failures are
+/// mapped the same way as `handle_response_openai_compat` maps them for regular requests.
+pub async fn check_streaming_response_openai_compat(
+    response: Response,
+) -> Result<Response, ProviderError> {
+    if response.status().is_success() {
+        return Ok(response);
+    }
+    match handle_response_openai_compat(response).await {
+        Err(e) => Err(e),
+        Ok(payload) => Err(ProviderError::RequestFailed(format!(
+            "Unexpected response: {}",
+            payload
+        ))),
+    }
+}
+
+/// Split a streaming response body into the payloads of its server-sent event `data:` lines
+///
+/// Lines are buffered across network chunks so an event split between two reads is
+/// reassembled before being yielded. The stream ends at the OpenAI-style `[DONE]` marker
+/// or when the body is exhausted.
+pub fn sse_data_stream(
+    response: Response,
+) -> impl Stream<Item = Result<String, ProviderError>> + Send + 'static {
+    try_stream! {
+        let mut body = response.bytes_stream();
+        let mut buffer: Vec<u8> = Vec::new();
+
+        'read: while let Some(chunk) = body.next().await {
+            let chunk = chunk.map_err(|e| ProviderError::RequestFailed(e.to_string()))?;
+            buffer.extend_from_slice(&chunk);
+
+            while let Some(pos) = buffer.iter().position(|b| *b == b'\n') {
+                let line: Vec<u8> = buffer.drain(..=pos).collect();
+                let line = String::from_utf8_lossy(&line);
+                if let Some(data) = line.trim().strip_prefix("data:") {
+                    let data = data.trim();
+                    if data == "[DONE]" {
+                        break 'read;
+                    }
+                    if !data.is_empty() {
+                        yield data.to_string();
+                    }
+                }
+            }
+        }
+    }
+}
+
 /// Check if the model is a Google model based on the "model" field in the payload.
 ///
 /// ### Arguments
@@ -140,6 +219,7 @@ fn get_google_final_status(status: StatusCode, payload: Option<&Value>) -> Statu
 /// - `Err(ProviderError)`: Describes the failure reason.
 pub async fn handle_response_google_compat(response: Response) -> Result<Value, ProviderError> {
     let status = response.status();
+    let retry_delay = retry_after(response.headers());
     let payload: Option<Value> = response.json().await.ok();
     let final_status = get_google_final_status(status, payload.as_ref());
 
@@ -166,7 +246,7 @@ pub async fn handle_response_google_compat(response: Response) -> Result<Value,
             Err(ProviderError::RequestFailed(format!("Request failed with status: {}. Message: {}", final_status, error_msg)))
         }
         StatusCode::TOO_MANY_REQUESTS => {
-            Err(ProviderError::RateLimitExceeded(format!("{:?}", payload)))
+            Err(ProviderError::RateLimitExceeded { details: format!("{:?}", payload), retry_delay })
         }
         StatusCode::INTERNAL_SERVER_ERROR | StatusCode::SERVICE_UNAVAILABLE => {
             Err(ProviderError::ServerError(format!("{:?}", payload)))
@@ -335,6 +415,35 @@ pub fn emit_debug_trace(
     );
 }
 
+/// Keep a provider stream in the span of the `stream` call that created it, since that call has
+/// returned by the time the stream is polled. The model and usage are recorded on the span like
+/// `emit_debug_trace` does for `complete`, the usage once the completion arrives.
+pub fn instrument_stream(
+    stream: MessageStream,
+    model_config: &ModelConfig,
+    span: tracing::Span,
+) -> MessageStream {
+    span.record(
+        "model_config",
+        serde_json::to_string_pretty(model_config)
+            .unwrap_or_default()
+            .as_str(),
+    );
+    Box::pin(futures::stream::unfold(
+        (stream, span),
+        |(mut stream, span)| async move {
+            let item = stream.next().instrument(span.clone()).await?;
+            if let Ok(ProviderStreamItem::Complete(_, usage)) = &item {
+                let usage = &usage.usage;
+                span.record("input_tokens", usage.input_tokens.unwrap_or_default());
+                span.record("output_tokens", usage.output_tokens.unwrap_or_default());
+                span.record("total_tokens", usage.total_tokens.unwrap_or_default());
+            }
+            Some((item, (stream, span)))
+        },
+    ))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,6 +1,13 @@
+use async_stream::try_stream;
+use futures::{Stream, StreamExt};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::collections::{BTreeMap, HashMap};
 
+use super::base::{MessageStream, ProviderStreamItem, ProviderUsage};
+use super::errors::ProviderError;
+use crate::message::{Message, MessageDelta};
+
 #[derive(Clone, Debug, Serialize, Deserialize, Default)]
 pub struct OAIUsage {
     pub prompt_tokens: Option<usize>,
@@ -136,6 +143,30 @@ impl OAIStreamCollector {
     }
 
     pub fn add_chunk(&mut self, chunk: &OAIStreamChunk) {
+        // Top-level fields repeat on every chunk; keep the first non-empty value seen,
+        // except usage which is only sent (and final) on the last chunk
+        if self.id.is_none() {
+            self.id = chunk.id.clone().filter(|id| !id.is_empty());
+        }
+        if self.object.is_none() {
+            self.object = chunk.object.clone();
+        }
+        if self.created.is_none() {
+            self.created = chunk.created.filter(|created| *created != 0);
+        }
+        if self.model.is_none() {
+            self.model = chunk.model.clone();
+        }
+        if self.system_fingerprint.is_none() {
+            self.system_fingerprint = chunk.system_fingerprint.clone();
+        }
+        if self.prompt_filter_results.is_none() {
+            self.prompt_filter_results = chunk.prompt_filter_results.clone();
+        }
+        if chunk.usage.is_some() {
+            self.usage = chunk.usage.clone();
+        }
+
         for ch in chunk.choices.iter() {
             // Always ensure choice exists, even if all fields are absent!
             let idx = ch.index;
@@ -241,6 +272,46 @@ impl OAIStreamCollector {
         }
     }
 }
+/// Turn the `data:` payloads of an OpenAI compatible chat completion stream into a
+/// provider stream
+///
+/// Content deltas are forwarded as they arrive. Once the stream finishes, the collected
+/// chunks are rebuilt into a regular chat completion response and handed to `finish`,
+/// which converts it into the final message and usage with the provider's own format.
+pub fn stream_chat_completion<S, F>(data: S, finish: F) -> MessageStream
+where
+    S: Stream<Item = Result<String, ProviderError>> + Send + 'static,
+    F: FnOnce(Value) -> Result<(Message, ProviderUsage), ProviderError> + Send + 'static,
+{
+    Box::pin(try_stream! {
+        let mut data = Box::pin(data);
+        let mut collector = OAIStreamCollector::new();
+
+        while let Some(payload) = data.next().await {
+            let payload = payload?;
+            let chunk = match serde_json::from_str::<OAIStreamChunk>(&payload) {
+                Ok(chunk) => chunk,
+                Err(e) => {
+                    tracing::debug!("Skipping unparseable stream chunk: {} | payload: {}", e, payload);
+                    continue;
+                }
+            };
+
+            for choice in &chunk.choices {
+                if let Some(text) = choice.delta.content.as_deref().filter(|t| !t.is_empty()) {
+                    yield ProviderStreamItem::Delta(MessageDelta::text(text));
+                }
+            }
+            collector.add_chunk(&chunk);
+        }
+
+        let response = serde_json::to_value(collector.build_response())
+            .map_err(|e| ProviderError::RequestFailed(e.to_string()))?;
+        let (message, usage) = finish(response)?;
+        yield ProviderStreamItem::Complete(message, usage);
+    })
+}
+
 fn null_to_empty_string<'de, D>(deserializer: D) -> Result<String, D::Error>
 where
     D: serde::Deserializer<'de>,
@@ -347,6 +418,44 @@ data: [DONE]
             "Hello! How can I assist you today? 🌍"
         );
         assert_eq!(choice.finish_reason, "stop");
+        assert_eq!(resp.id, "chatcmpl-BYcvCkaKJjQIM7e2j6vg08RIcY8qp");
+        assert_eq!(resp.model, "gpt-4o-2024-11-20");
+        assert_eq!(resp.usage.and_then(|u| u.total_tokens), Some(1688));
+    }
+
+    #[tokio::test]
+    async fn test_stream_chat_completion_yields_deltas_then_message() {
+        use crate::providers::formats::openai::{get_usage, response_to_message};
+        use futures::StreamExt;
+
+        let payloads: Vec<Result<String, ProviderError>> = TEXT_STREAM
+            .lines()
+            .filter_map(|line| line.trim().strip_prefix("data: "))
+            .filter(|payload| *payload != "[DONE]")
+            .map(|payload| Ok(payload.to_string()))
+            .collect();
+
+        let mut stream = stream_chat_completion(futures::stream::iter(payloads), |response| {
+            let message = response_to_message(response.clone())?;
+            let usage = get_usage(&response)?;
+            Ok((message, ProviderUsage::new("gpt-4o".to_string(), usage)))
+        });
+
+        let mut streamed = String::new();
+        let mut complete = None;
+        while let Some(item) = stream.next().await {
+            match item.unwrap() {
+                ProviderStreamItem::Delta(MessageDelta::Text { text }) => streamed.push_str(&text),
+                ProviderStreamItem::Delta(_) => panic!("unexpected thinking delta"),
+                ProviderStreamItem::Complete(message, usage) => complete = Some((message, usage)),
+            }
+        }
+
+        let (message, usage) = complete.expect("stream should end with a complete message");
+        assert_eq!(streamed, "Hello! How can I assist you today? 🌍");
+        assert_eq!(message.as_concat_text(), streamed);
+        assert_eq!(usage.usage.input_tokens, Some(1675));
+        assert_eq!(usage.usage.output_tokens, Some(13));
     }
     const CLAUDE_STREAM: &str = r#"
 data: {"choices":[{"index":0,"delta":{"content":"I","role":"assistant"}}],"created":1747613682,"id":"938bb8e2-6276-4a58-bca3-c675cfe7f2f5","model":"claude-3.5-sonnet"}
@@ -1,7 +1,13 @@
+use std::collections::HashMap;
 use std::fmt;
 
 use crate::agents::extension::ExtensionConfig;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+mod sub_recipe;
+
+pub use sub_recipe::SubRecipeTemplate;
 
 fn default_version() -> String {
     "1.0.0".to_string()
@@ -25,6 +31,9 @@ fn default_version() -> String {
 /// * `activities` - Activity labels that appear when loading the Recipe
 /// * `author` - Information about the Recipe's creator and metadata
 /// * `parameters` - Additional parameters for the Recipe
+/// * `response` - The structured result the agent must report when it finishes
+/// * `sub_recipes` - Other recipes the agent can run as tools
+/// * `retry` - Checks that decide whether a headless run succeeded, and how to retry if not
 ///
 /// # Example
 ///
@@ -51,6 +60,9 @@ fn default_version() -> String {
 ///     activities: None,
 ///     author: None,
 ///     parameters: None,
+///     response: None,
+///     sub_recipes: None,
+///     retry: None,
 /// };
 ///
 #[derive(Serialize, Deserialize, Debug)]
@@ -85,6 +97,15 @@ pub struct Recipe {
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub parameters: Option<Vec<RecipeParameter>>, // any additional parameters for the recipe
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response: Option<Response>, // the structured output the recipe produces
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sub_recipes: Option<Vec<SubRecipe>>, // recipes the agent can run as tools
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry: Option<RetryConfig>, // success checks and how to retry when they fail
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -97,6 +118,47 @@ pub struct Author {
 }
 
 #[derive(Serialize, Deserialize, Debug)]
+pub struct Response {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub json_schema: Option<Value>, // JSON schema the final output is validated against
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SubRecipe {
+    pub name: String, // tool name suffix, letters, digits, '_' and '-' only
+
+    pub path: String, // recipe file, relative to the recipe declaring it
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>, // when to use it, defaults to the sub-recipe's description
+
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub values: HashMap<String, String>, // parameters fixed by the parent instead of the agent
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RetryConfig {
+    pub max_retries: u32, // attempts after the first one
+
+    pub checks: Vec<SuccessCheck>, // all must pass for an attempt to succeed
+
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub reset_files: bool, // undo the agent's file edits before retrying
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub on_failure: Option<String>, // shell command to clean up before retrying
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout_seconds: Option<u64>, // limit for each check and cleanup command
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SuccessCheck {
+    Shell { command: String }, // passes when the command exits with status 0
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "snake_case")]
 pub enum RecipeParameterRequirement {
     Required,
@@ -114,7 +176,7 @@ impl fmt::Display for RecipeParameterRequirement {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "snake_case")]
 pub enum RecipeParameterInputType {
     String,
@@ -134,7 +196,7 @@ impl fmt::Display for RecipeParameterInputType {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct RecipeParameter {
     pub key: String,
     pub input_type: RecipeParameterInputType,
@@ -159,6 +221,9 @@ pub struct RecipeBuilder {
     activities: Option<Vec<String>>,
     author: Option<Author>,
     parameters: Option<Vec<RecipeParameter>>,
+    response: Option<Response>,
+    sub_recipes: Option<Vec<SubRecipe>>,
+    retry: Option<RetryConfig>,
 }
 
 impl Recipe {
@@ -188,6 +253,9 @@ impl Recipe {
             activities: None,
             author: None,
             parameters: None,
+            response: None,
+            sub_recipes: None,
+            retry: None,
         }
     }
 }
@@ -252,6 +320,24 @@ impl RecipeBuilder {
         self
     }
 
+    /// Sets the structured response the Recipe produces
+    pub fn response(mut self, response: Response) -> Self {
+        self.response = Some(response);
+        self
+    }
+
+    /// Sets the sub-recipes the agent can run as tools
+    pub fn sub_recipes(mut self, sub_recipes: Vec<SubRecipe>) -> Self {
+        self.sub_recipes = Some(sub_recipes);
+        self
+    }
+
+    /// Sets the success checks and retry policy for headless runs
+    pub fn retry(mut self, retry: RetryConfig) -> Self {
+        self.retry = Some(retry);
+        self
+    }
+
     /// Builds the Recipe instance
     ///
     /// Returns an error if any required fields are missing
@@ -274,6 +360,9 @@ impl RecipeBuilder {
             activities: self.activities,
             author: self.author,
             parameters: self.parameters,
+            response: self.response,
+            sub_recipes: self.sub_recipes,
+            retry: self.retry,
         })
     }
 }
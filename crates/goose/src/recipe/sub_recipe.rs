@@ -0,0 +1,241 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Result};
+use minijinja::{Environment, UndefinedBehavior};
+use serde_json::{json, Map, Value};
+
+use super::{Recipe, RecipeParameter, RecipeParameterInputType};
+
+/// Template variable every recipe can use for the directory it was loaded from
+const RECIPE_DIR_PARAM: &str = "recipe_dir";
+
+/// A sub-recipe resolved by the recipe loader. It stays unrendered until the agent runs it,
+/// since the parameters the parent does not fix are only known then.
+#[derive(Clone, Debug)]
+pub struct SubRecipeTemplate {
+    pub name: String,
+    pub description: String,
+    /// The recipe file as written, with its template variables
+    pub content: String,
+    pub recipe_dir: PathBuf,
+    pub parameters: Vec<RecipeParameter>,
+    /// Parameter values fixed by the parent recipe
+    pub values: HashMap<String, String>,
+    /// The sub-recipes this one declares in turn
+    pub sub_recipes: Vec<SubRecipeTemplate>,
+}
+
+impl SubRecipeTemplate {
+    /// JSON schema of the parameters the agent has to provide
+    pub fn input_schema(&self) -> Value {
+        let mut properties = Map::new();
+        let mut required = Vec::new();
+        for parameter in self.open_parameters() {
+            let mut property = match parameter.input_type {
+                RecipeParameterInputType::Number => json!({"type": "number"}),
+                RecipeParameterInputType::Boolean => json!({"type": "boolean"}),
+                RecipeParameterInputType::Date => json!({"type": "string", "format": "date"}),
+                RecipeParameterInputType::String | RecipeParameterInputType::File => {
+                    json!({"type": "string"})
+                }
+            };
+            property["description"] = Value::String(parameter.description.clone());
+            if let Some(default) = &parameter.default {
+                property["default"] = default_to_value(&parameter.input_type, default);
+            } else {
+                required.push(parameter.key.clone());
+            }
+            properties.insert(parameter.key.clone(), property);
+        }
+
+        json!({
+            "type": "object",
+            "required": required,
+            "properties": properties,
+        })
+    }
+
+    /// Render the recipe with the parent's values and the agent's `arguments`, checking each
+    /// argument against the type of its parameter
+    pub fn render(&self, arguments: &Value) -> Result<Recipe> {
+        let arguments = match arguments {
+            Value::Object(arguments) => arguments.clone(),
+            Value::Null => Map::new(),
+            _ => return Err(anyhow!("Arguments must be an object")),
+        };
+
+        let mut params = self.values.clone();
+        for (key, value) in &arguments {
+            let parameter = self
+                .open_parameters()
+                .find(|parameter| &parameter.key == key)
+                .ok_or_else(|| anyhow!("Unknown parameter '{}'", key))?;
+            params.insert(key.clone(), argument_to_string(parameter, value)?);
+        }
+
+        let mut missing = Vec::new();
+        for parameter in &self.parameters {
+            if params.contains_key(&parameter.key) {
+                continue;
+            }
+            match &parameter.default {
+                Some(default) => {
+                    params.insert(parameter.key.clone(), default.clone());
+                }
+                None => missing.push(parameter.key.clone()),
+            }
+        }
+        if !missing.is_empty() {
+            return Err(anyhow!("Missing parameters: {}", missing.join(", ")));
+        }
+        params.insert(
+            RECIPE_DIR_PARAM.to_string(),
+            self.recipe_dir.to_string_lossy().to_string(),
+        );
+
+        let mut env = Environment::new();
+        env.set_undefined_behavior(UndefinedBehavior::Strict);
+        let rendered = env
+            .render_str(&self.content, &params)
+            .map_err(|e| anyhow!("Failed to render sub-recipe '{}': {}", self.name, e))?;
+
+        if serde_json::from_str::<Value>(&rendered).is_ok() {
+            Ok(serde_json::from_str(&rendered)?)
+        } else {
+            Ok(serde_yaml::from_str(&rendered)?)
+        }
+    }
+
+    /// Parameters the parent left for the agent to fill in
+    fn open_parameters(&self) -> impl Iterator<Item = &RecipeParameter> {
+        self.parameters
+            .iter()
+            .filter(|parameter| !self.values.contains_key(&parameter.key))
+    }
+}
+
+/// A parameter default as the JSON value its type calls for, falling back to the string as
+/// written when it doesn't parse
+fn default_to_value(input_type: &RecipeParameterInputType, default: &str) -> Value {
+    let parsed = match input_type {
+        RecipeParameterInputType::Number => serde_json::from_str::<serde_json::Number>(default)
+            .ok()
+            .map(Value::Number),
+        RecipeParameterInputType::Boolean => default.parse::<bool>().ok().map(Value::Bool),
+        RecipeParameterInputType::String
+        | RecipeParameterInputType::Date
+        | RecipeParameterInputType::File => None,
+    };
+    parsed.unwrap_or_else(|| Value::String(default.to_string()))
+}
+
+fn argument_to_string(parameter: &RecipeParameter, value: &Value) -> Result<String> {
+    match (&parameter.input_type, value) {
+        (RecipeParameterInputType::Number, Value::Number(number)) => Ok(number.to_string()),
+        (RecipeParameterInputType::Boolean, Value::Bool(boolean)) => Ok(boolean.to_string()),
+        (
+            RecipeParameterInputType::String
+            | RecipeParameterInputType::Date
+            | RecipeParameterInputType::File,
+            Value::String(text),
+        ) => Ok(text.clone()),
+        (input_type, _) => Err(anyhow!(
+            "Parameter '{}' must be a {}, got {}",
+            parameter.key,
+            input_type,
+            value
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recipe::RecipeParameterRequirement;
+
+    fn template() -> SubRecipeTemplate {
+        SubRecipeTemplate {
+            name: "write_tests".to_string(),
+            description: "Write tests for a module".to_string(),
+            content: r#"{
+                "title": "Write tests",
+                "description": "Tests for {{ module }}",
+                "prompt": "Write {{ count }} tests for {{ module }} in {{ repo }}, strict: {{ strict }}",
+                "parameters": []
+            }"#
+            .to_string(),
+            recipe_dir: PathBuf::from("/recipes"),
+            parameters: vec![
+                parameter("repo", RecipeParameterInputType::String, None),
+                parameter("module", RecipeParameterInputType::String, None),
+                parameter("count", RecipeParameterInputType::Number, Some("3")),
+                parameter("strict", RecipeParameterInputType::Boolean, Some("false")),
+            ],
+            values: HashMap::from([("repo".to_string(), "goose".to_string())]),
+            sub_recipes: vec![],
+        }
+    }
+
+    fn parameter(
+        key: &str,
+        input_type: RecipeParameterInputType,
+        default: Option<&str>,
+    ) -> RecipeParameter {
+        RecipeParameter {
+            key: key.to_string(),
+            input_type,
+            requirement: if default.is_some() {
+                RecipeParameterRequirement::Optional
+            } else {
+                RecipeParameterRequirement::Required
+            },
+            description: format!("The {}", key),
+            default: default.map(String::from),
+        }
+    }
+
+    #[test]
+    fn test_input_schema_leaves_out_fixed_values() {
+        let schema = template().input_schema();
+        assert_eq!(schema["required"], json!(["module"]));
+        assert!(schema["properties"].get("repo").is_none());
+        assert_eq!(schema["properties"]["count"]["type"], "number");
+        assert_eq!(schema["properties"]["count"]["default"], json!(3));
+        assert_eq!(schema["properties"]["strict"]["default"], json!(false));
+    }
+
+    #[test]
+    fn test_render_with_typed_arguments() {
+        let recipe = template()
+            .render(&json!({"module": "agents", "count": 5, "strict": true}))
+            .unwrap();
+        assert_eq!(
+            recipe.prompt.unwrap(),
+            "Write 5 tests for agents in goose, strict: true"
+        );
+
+        let recipe = template().render(&json!({"module": "agents"})).unwrap();
+        assert_eq!(
+            recipe.prompt.unwrap(),
+            "Write 3 tests for agents in goose, strict: false"
+        );
+    }
+
+    #[test]
+    fn test_render_rejects_bad_arguments() {
+        let err = template().render(&json!({})).unwrap_err();
+        assert!(err.to_string().contains("Missing parameters: module"));
+
+        let err = template()
+            .render(&json!({"module": "agents", "count": "five"}))
+            .unwrap_err();
+        assert!(err.to_string().contains("'count' must be a number"));
+
+        // Values fixed by the parent cannot be overridden
+        let err = template()
+            .render(&json!({"module": "agents", "repo": "other"}))
+            .unwrap_err();
+        assert!(err.to_string().contains("Unknown parameter 'repo'"));
+    }
+}
@@ -12,7 +12,7 @@ use tokio::sync::Mutex;
 use tokio_cron_scheduler::{job::JobId, Job, JobScheduler as TokioJobScheduler};
 
 use crate::agents::AgentEvent;
-use crate::agents::{Agent, SessionConfig};
+use crate::agents::{Agent, ReplyBudget, SessionConfig};
 use crate::config::{self, Config};
 use crate::message::Message;
 use crate::providers::base::Provider as GooseProvider; // Alias to avoid conflict in test section
@@ -192,6 +192,8 @@ impl Scheduler {
             )));
         }
 
+        load_scheduled_recipe(original_recipe_path).map_err(SchedulerError::RecipeLoadError)?;
+
         let scheduled_recipes_dir = get_default_scheduled_recipes_dir()?;
         let original_extension = original_recipe_path
             .extension()
@@ -956,6 +958,45 @@ impl Scheduler {
     }
 }
 
+/// Load a scheduled recipe file. Recipes with sub-recipes are refused, since the scheduler
+/// runs its own copy of the file and doesn't load the sub-recipes it points to.
+fn load_scheduled_recipe(recipe_path: &Path) -> std::result::Result<Recipe, String> {
+    let source = recipe_path.display();
+    let recipe_content = fs::read_to_string(recipe_path)
+        .map_err(|e| format!("Failed to load recipe file '{}': {}", source, e))?;
+
+    let extension = recipe_path
+        .extension()
+        .and_then(|os_str| os_str.to_str())
+        .unwrap_or("yaml")
+        .to_lowercase();
+
+    let recipe: Recipe = match extension.as_str() {
+        "json" | "jsonl" => serde_json::from_str(&recipe_content)
+            .map_err(|e| format!("Failed to parse JSON recipe '{}': {}", source, e))?,
+        "yaml" | "yml" => serde_yaml::from_str(&recipe_content)
+            .map_err(|e| format!("Failed to parse YAML recipe '{}': {}", source, e))?,
+        _ => {
+            return Err(format!(
+                "Unsupported recipe file extension '{}' for: {}",
+                extension, source
+            ))
+        }
+    };
+
+    if recipe
+        .sub_recipes
+        .as_ref()
+        .is_some_and(|sub_recipes| !sub_recipes.is_empty())
+    {
+        return Err(format!(
+            "Recipe '{}' declares sub-recipes, which scheduled runs don't support",
+            source
+        ));
+    }
+    Ok(recipe)
+}
+
 #[derive(Debug)]
 struct JobExecutionError {
     job_id: String,
@@ -972,45 +1013,10 @@ async fn run_scheduled_job_internal(
 
     let recipe_path = Path::new(&job.source);
 
-    let recipe_content = match fs::read_to_string(recipe_path) {
-        Ok(content) => content,
-        Err(e) => {
-            return Err(JobExecutionError {
-                job_id: job.id.clone(),
-                error: format!("Failed to load recipe file '{}': {}", job.source, e),
-            });
-        }
-    };
-
-    let recipe: Recipe = {
-        let extension = recipe_path
-            .extension()
-            .and_then(|os_str| os_str.to_str())
-            .unwrap_or("yaml")
-            .to_lowercase();
-
-        match extension.as_str() {
-            "json" | "jsonl" => {
-                serde_json::from_str::<Recipe>(&recipe_content).map_err(|e| JobExecutionError {
-                    job_id: job.id.clone(),
-                    error: format!("Failed to parse JSON recipe '{}': {}", job.source, e),
-                })
-            }
-            "yaml" | "yml" => {
-                serde_yaml::from_str::<Recipe>(&recipe_content).map_err(|e| JobExecutionError {
-                    job_id: job.id.clone(),
-                    error: format!("Failed to parse YAML recipe '{}': {}", job.source, e),
-                })
-            }
-            _ => Err(JobExecutionError {
-                job_id: job.id.clone(),
-                error: format!(
-                    "Unsupported recipe file extension '{}' for: {}",
-                    extension, job.source
-                ),
-            }),
-        }
-    }?;
+    let recipe = load_scheduled_recipe(recipe_path).map_err(|error| JobExecutionError {
+        job_id: job.id.clone(),
+        error,
+    })?;
 
     let agent: Agent = Agent::new();
 
@@ -1057,6 +1063,15 @@ async fn run_scheduled_job_internal(
     }
     tracing::info!("Agent configured with provider for job '{}'", job.id);
 
+    if let Some(schema) = recipe.response.and_then(|response| response.json_schema) {
+        if let Err(e) = agent.set_final_output_schema(schema).await {
+            return Err(JobExecutionError {
+                job_id: job.id.clone(),
+                error: format!("Failed to set the recipe's response schema: {}", e),
+            });
+        }
+    }
+
     let session_id_for_return = session::generate_session_id();
 
     // Update the job with the session ID if we have access to the jobs arc
@@ -1089,6 +1104,7 @@ async fn run_scheduled_job_internal(
             id: crate::session::storage::Identifier::Name(session_id_for_return.clone()),
             working_dir: current_dir.clone(),
             schedule_id: Some(job.id.clone()),
+            budget: ReplyBudget::default(),
         };
 
         match agent
@@ -1109,6 +1125,18 @@ async fn run_scheduled_job_internal(
                             }
                             all_session_messages.push(msg);
                         }
+                        Ok(AgentEvent::MessageDelta(_)) => {
+                            // Deltas are only for display, the full message follows
+                        }
+                        Ok(AgentEvent::HistoryReplaced(messages)) => {
+                            all_session_messages = messages;
+                        }
+                        Ok(AgentEvent::Subagent(_)) => {
+                            // Sub-agents report back through the delegate tool's result
+                        }
+                        Ok(AgentEvent::ToolProgress(_)) => {
+                            // Nobody is watching scheduled jobs run
+                        }
                         Ok(AgentEvent::McpNotification(_)) => {
                             // Handle notifications if needed
                         }
@@ -1155,6 +1183,10 @@ async fn run_scheduled_job_internal(
                             accumulated_total_tokens: None,
                             accumulated_input_tokens: None,
                             accumulated_output_tokens: None,
+                            accumulated_cost: None,
+                            parent_session: None,
+                            fork_point: None,
+                            retry_attempts: None,
                         };
                         if let Err(e_fb) = crate::session::storage::save_messages_with_metadata(
                             &session_file_path,
@@ -1298,6 +1330,9 @@ mod tests {
             activities: None,
             author: None,
             parameters: None,
+            response: None,
+            sub_recipes: None,
+            retry: None,
         };
         let mut recipe_file = File::create(&recipe_filename)?;
         writeln!(
@@ -1370,4 +1405,33 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_recipes_with_sub_recipes_are_refused() {
+        let temp_dir = tempdir().unwrap();
+        let recipe_path = temp_dir.path().join("parent.yaml");
+        fs::write(
+            &recipe_path,
+            r#"
+version: 1.0.0
+title: Parent
+description: Delegates to a sub-recipe
+prompt: Fix the bug
+sub_recipes:
+  - name: fix
+    path: fix.yaml
+"#,
+        )
+        .unwrap();
+
+        let err = load_scheduled_recipe(&recipe_path).unwrap_err();
+        assert!(err.contains("declares sub-recipes"));
+
+        fs::write(
+            &recipe_path,
+            "version: 1.0.0\ntitle: Plain\ndescription: No sub-recipes\nprompt: Fix the bug\n",
+        )
+        .unwrap();
+        assert!(load_scheduled_recipe(&recipe_path).is_ok());
+    }
 }
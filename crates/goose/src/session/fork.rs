@@ -0,0 +1,257 @@
+use super::storage::{
+    generate_session_id, get_path, read_messages, read_metadata, save_messages_with_metadata,
+    session_exists, Identifier,
+};
+use crate::message::{Message, MessageContent};
+use anyhow::Result;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// Keep the first `count` messages, dropping tool requests whose response was cut off and any
+/// message left empty as a result, so the history can be sent to a provider again
+pub fn truncate_history(messages: &[Message], count: usize) -> Vec<Message> {
+    let kept = &messages[..count.min(messages.len())];
+    let request_ids: HashSet<String> = kept
+        .iter()
+        .flat_map(|m| m.get_tool_request_ids())
+        .map(String::from)
+        .collect();
+    let response_ids: HashSet<String> = kept
+        .iter()
+        .flat_map(|m| m.get_tool_response_ids())
+        .map(String::from)
+        .collect();
+
+    kept.iter()
+        .filter_map(|message| {
+            let mut message = message.clone();
+            message.content.retain(|content| match content {
+                MessageContent::ToolRequest(req) => response_ids.contains(&req.id),
+                MessageContent::FrontendToolRequest(req) => response_ids.contains(&req.id),
+                MessageContent::ToolConfirmationRequest(req) => response_ids.contains(&req.id),
+                MessageContent::ToolResponse(res) => request_ids.contains(&res.id),
+                _ => true,
+            });
+            (!message.content.is_empty()).then_some(message)
+        })
+        .collect()
+}
+
+/// Files changed by successful `text_editor` calls after the first `count` messages, with how
+/// many edits to undo for each, in the order they were first edited
+pub fn edits_after(messages: &[Message], count: usize) -> Vec<(PathBuf, usize)> {
+    let later = &messages[count.min(messages.len())..];
+    let succeeded: HashSet<&str> = later
+        .iter()
+        .flat_map(|m| &m.content)
+        .filter_map(|content| match content {
+            MessageContent::ToolResponse(res) if res.tool_result.is_ok() => Some(res.id.as_str()),
+            _ => None,
+        })
+        .collect();
+
+    let mut edits: Vec<(PathBuf, usize)> = Vec::new();
+    for content in later.iter().flat_map(|m| &m.content) {
+        let tool_call = match content {
+            MessageContent::ToolRequest(req) if succeeded.contains(req.id.as_str()) => {
+                match &req.tool_call {
+                    Ok(tool_call) if tool_call.name.ends_with("__text_editor") => tool_call,
+                    _ => continue,
+                }
+            }
+            _ => continue,
+        };
+        let path = match tool_call.arguments.get("path").and_then(|p| p.as_str()) {
+            Some(path) => PathBuf::from(path),
+            None => continue,
+        };
+        let command = tool_call.arguments.get("command").and_then(|c| c.as_str());
+
+        let index = match edits.iter().position(|(p, _)| p == &path) {
+            Some(index) => index,
+            None => {
+                edits.push((path, 0));
+                edits.len() - 1
+            }
+        };
+        match command {
+            Some("write") | Some("str_replace") => edits[index].1 += 1,
+            // An undo after the fork point already reverted one of the edits
+            Some("undo_edit") => edits[index].1 = edits[index].1.saturating_sub(1),
+            _ => {}
+        }
+    }
+
+    edits.retain(|(_, undos)| *undos > 0);
+    edits
+}
+
+/// Copy the first `count` messages of a session into a new session, recording where it was
+/// forked from. The new session is named `target`, or gets a generated name.
+pub fn fork_session(source: &Path, count: usize, target: Option<Identifier>) -> Result<PathBuf> {
+    if !session_exists(source) {
+        return Err(anyhow::anyhow!("Session not found: {}", source.display()));
+    }
+
+    let messages = read_messages(source)?;
+    if count > messages.len() {
+        return Err(anyhow::anyhow!(
+            "The session only has {} messages",
+            messages.len()
+        ));
+    }
+
+    let target = match target {
+        Some(identifier) => get_path(identifier),
+        None => unused_session_path(),
+    };
+    if session_exists(&target) {
+        return Err(anyhow::anyhow!(
+            "Session already exists: {}",
+            target.display()
+        ));
+    }
+
+    let history = truncate_history(&messages, count);
+    let mut metadata = read_metadata(source)?;
+    metadata.message_count = history.len();
+    // The last usage describes the parent's longer history
+    metadata.total_tokens = None;
+    metadata.input_tokens = None;
+    metadata.output_tokens = None;
+    metadata.parent_session = source
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string());
+    metadata.fork_point = Some(count);
+
+    save_messages_with_metadata(&target, &metadata, &history)?;
+    Ok(target)
+}
+
+/// Session IDs are timestamps, so a fork made in the same second as its parent needs a suffix
+fn unused_session_path() -> PathBuf {
+    let id = generate_session_id();
+    let mut path = get_path(Identifier::Name(id.clone()));
+    let mut suffix = 1;
+    while session_exists(&path) {
+        path = get_path(Identifier::Name(format!("{}_{}", id, suffix)));
+        suffix += 1;
+    }
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mcp_core::tool::ToolCall;
+    use mcp_core::ToolError;
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    fn edit(id: &str, command: &str, path: &str) -> Message {
+        Message::assistant().with_tool_request(
+            id,
+            Ok(ToolCall::new(
+                "developer__text_editor",
+                json!({"command": command, "path": path}),
+            )),
+        )
+    }
+
+    fn history() -> Vec<Message> {
+        vec![
+            Message::user().with_text("Fix the bug"),
+            edit("1", "str_replace", "/repo/a.rs"),
+            Message::user().with_tool_response("1", Ok(vec![])),
+            edit("2", "write", "/repo/b.rs"),
+            Message::user().with_tool_response("2", Ok(vec![])),
+            edit("3", "str_replace", "/repo/a.rs"),
+            Message::user().with_tool_response("3", Ok(vec![])),
+            edit("4", "str_replace", "/repo/c.rs"),
+            Message::user()
+                .with_tool_response("4", Err(ToolError::ExecutionError("no match".into()))),
+            edit("5", "undo_edit", "/repo/b.rs"),
+            Message::user().with_tool_response("5", Ok(vec![])),
+            Message::assistant().with_text("Done"),
+        ]
+    }
+
+    #[test]
+    fn test_truncate_history_drops_unanswered_tool_requests() {
+        let messages = vec![
+            Message::user().with_text("Fix the bug"),
+            Message::assistant()
+                .with_text("Let me look")
+                .with_tool_request("1", Ok(ToolCall::new("developer__shell", json!({})))),
+            Message::user().with_tool_response("1", Ok(vec![])),
+        ];
+
+        let truncated = truncate_history(&messages, 2);
+        assert_eq!(truncated.len(), 2);
+        assert_eq!(truncated[1].content.len(), 1);
+        assert!(!truncated[1].is_tool_call());
+
+        // A message holding only the unanswered request is dropped entirely
+        let truncated = truncate_history(&history(), 2);
+        assert_eq!(truncated.len(), 1);
+
+        assert_eq!(truncate_history(&messages, 10), messages);
+    }
+
+    #[test]
+    fn test_edits_after() {
+        let edits = edits_after(&history(), 3);
+        // b.rs was written then undone, and the edit to c.rs failed
+        assert_eq!(edits, vec![(PathBuf::from("/repo/a.rs"), 1)]);
+
+        let edits = edits_after(&history(), 0);
+        assert_eq!(edits, vec![(PathBuf::from("/repo/a.rs"), 2)]);
+        assert!(edits_after(&history(), 11).is_empty());
+    }
+
+    #[test]
+    fn test_edits_after_counts_writes() {
+        // Writes are undone like replacements, including the one that created the file
+        let messages = vec![
+            Message::user().with_text("Add a module"),
+            edit("1", "write", "/repo/new.rs"),
+            Message::user().with_tool_response("1", Ok(vec![])),
+            edit("2", "str_replace", "/repo/new.rs"),
+            Message::user().with_tool_response("2", Ok(vec![])),
+            edit("3", "write", "/repo/new.rs"),
+            Message::user().with_tool_response("3", Ok(vec![])),
+        ];
+        assert_eq!(
+            edits_after(&messages, 1),
+            vec![(PathBuf::from("/repo/new.rs"), 3)]
+        );
+        assert_eq!(
+            edits_after(&messages, 5),
+            vec![(PathBuf::from("/repo/new.rs"), 1)]
+        );
+    }
+
+    #[test]
+    fn test_fork_session() -> Result<()> {
+        let dir = tempdir()?;
+        let source = dir.path().join("parent.jsonl");
+        let target = dir.path().join("child.jsonl");
+        let mut metadata = crate::session::SessionMetadata::default();
+        metadata.description = "Parent".to_string();
+        save_messages_with_metadata(&source, &metadata, &history())?;
+
+        let forked = fork_session(&source, 3, Some(Identifier::Path(target.clone())))?;
+        assert_eq!(forked, target);
+        assert_eq!(read_messages(&target)?, history()[..3].to_vec());
+
+        let metadata = read_metadata(&target)?;
+        assert_eq!(metadata.parent_session.as_deref(), Some("parent"));
+        assert_eq!(metadata.fork_point, Some(3));
+        assert_eq!(metadata.message_count, 3);
+        assert_eq!(metadata.description, "Parent");
+
+        assert!(fork_session(&source, 3, Some(Identifier::Path(target))).is_err());
+        assert!(fork_session(&source, 100, None).is_err());
+        Ok(())
+    }
+}
@@ -1,11 +1,17 @@
+pub mod fork;
 pub mod info;
+pub mod sqlite;
 pub mod storage;
+pub mod store;
 
 // Re-export common session types and functions
 pub use storage::{
-    ensure_session_dir, generate_description, generate_session_id, get_most_recent_session,
-    get_path, list_sessions, persist_messages, read_messages, read_metadata, update_metadata,
-    Identifier, SessionMetadata,
+    delete_session, ensure_session_dir, generate_description, generate_session_id,
+    get_most_recent_session, get_path, list_sessions, persist_messages, read_messages,
+    read_metadata, search_sessions, session_exists, update_metadata, Identifier, RetryAttempt,
+    SessionMetadata,
 };
+pub use store::{SessionQuery, SessionSearchHit, SessionStore};
 
-pub use info::{get_session_info, SessionInfo};
+pub use fork::fork_session;
+pub use info::{get_session_info, query_sessions, SessionInfo};
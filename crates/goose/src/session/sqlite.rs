@@ -0,0 +1,452 @@
+use super::info::{SessionInfo, SortOrder};
+use super::storage::{ensure_session_dir, SessionMetadata};
+use super::store::{format_modified, JsonlStore, SessionQuery, SessionSearchHit, SessionStore};
+use crate::message::Message;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use rusqlite::types::Value as SqlValue;
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+// Metadata columns are copied out of the JSON so session listings can use indexes
+const SCHEMA: &str = r#"
+PRAGMA journal_mode = WAL;
+
+CREATE TABLE IF NOT EXISTS sessions (
+    path TEXT PRIMARY KEY,
+    id TEXT NOT NULL,
+    modified INTEGER NOT NULL,
+    working_dir TEXT NOT NULL,
+    schedule_id TEXT,
+    total_tokens INTEGER,
+    metadata TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS sessions_modified ON sessions (modified);
+CREATE INDEX IF NOT EXISTS sessions_working_dir ON sessions (working_dir, modified);
+CREATE INDEX IF NOT EXISTS sessions_schedule_id ON sessions (schedule_id, modified);
+CREATE INDEX IF NOT EXISTS sessions_total_tokens ON sessions (total_tokens);
+
+CREATE TABLE IF NOT EXISTS messages (
+    session_path TEXT NOT NULL,
+    idx INTEGER NOT NULL,
+    message TEXT NOT NULL,
+    PRIMARY KEY (session_path, idx)
+);
+
+CREATE VIRTUAL TABLE IF NOT EXISTS messages_fts USING fts5 (
+    text,
+    session_path UNINDEXED,
+    idx UNINDEXED
+);
+"#;
+
+/// Path of the session database in the session directory
+pub fn database_path() -> Result<PathBuf> {
+    Ok(ensure_session_dir()?.join("sessions.db"))
+}
+
+/// Sessions saved in a SQLite database, with their message text indexed for full-text search
+pub struct SqliteStore {
+    conn: Mutex<Connection>,
+}
+
+impl SqliteStore {
+    pub fn open(path: &Path) -> Result<Self> {
+        let conn = Connection::open(path)?;
+        // The CLI, server and scheduler may all write to the same database
+        conn.busy_timeout(Duration::from_secs(5))?;
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    pub fn open_default() -> Result<Self> {
+        Self::open(&database_path()?)
+    }
+
+    /// Copy the JSONL session files in `dir` into the database, replacing sessions that were
+    /// already imported. Returns the number of sessions imported.
+    pub fn import_jsonl(&self, dir: &Path) -> Result<usize> {
+        let mut imported = 0;
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension().is_none_or(|ext| ext != "jsonl") {
+                continue;
+            }
+
+            let metadata = JsonlStore.read_metadata(&path)?;
+            let messages = JsonlStore.read_messages(&path)?;
+            let modified = path
+                .metadata()
+                .and_then(|m| m.modified())
+                .map(DateTime::<Utc>::from)
+                .unwrap_or_else(|_| Utc::now());
+            self.write(&path, &metadata, &messages, modified)?;
+            imported += 1;
+        }
+        Ok(imported)
+    }
+
+    fn write(
+        &self,
+        session_file: &Path,
+        metadata: &SessionMetadata,
+        messages: &[Message],
+        modified: DateTime<Utc>,
+    ) -> Result<()> {
+        let key = session_key(session_file);
+        let id = session_file
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_else(|| key.clone());
+
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        tx.execute(
+            "INSERT OR REPLACE INTO sessions
+                (path, id, modified, working_dir, schedule_id, total_tokens, metadata)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                key,
+                id,
+                modified.timestamp(),
+                metadata.working_dir.to_string_lossy().to_string(),
+                metadata.schedule_id,
+                metadata.accumulated_total_tokens.or(metadata.total_tokens),
+                serde_json::to_string(metadata)?,
+            ],
+        )?;
+        tx.execute("DELETE FROM messages WHERE session_path = ?1", params![key])?;
+        tx.execute(
+            "DELETE FROM messages_fts WHERE session_path = ?1",
+            params![key],
+        )?;
+        {
+            let mut insert_message = tx
+                .prepare("INSERT INTO messages (session_path, idx, message) VALUES (?1, ?2, ?3)")?;
+            let mut insert_text = tx.prepare(
+                "INSERT INTO messages_fts (text, session_path, idx) VALUES (?1, ?2, ?3)",
+            )?;
+            for (idx, message) in messages.iter().enumerate() {
+                insert_message.execute(params![
+                    key,
+                    idx as i64,
+                    serde_json::to_string(message)?
+                ])?;
+                let text = message.as_concat_text();
+                if !text.trim().is_empty() {
+                    insert_text.execute(params![text, key, idx as i64])?;
+                }
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+}
+
+fn session_key(session_file: &Path) -> String {
+    session_file.to_string_lossy().to_string()
+}
+
+/// Quote every word so punctuation in the search text isn't read as FTS5 query syntax
+fn fts_query(text: &str) -> String {
+    text.split_whitespace()
+        .map(|word| format!("\"{}\"", word.replace('"', "\"\"")))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+impl SessionStore for SqliteStore {
+    fn read_messages(&self, session_file: &Path) -> Result<Vec<Message>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt =
+            conn.prepare("SELECT message FROM messages WHERE session_path = ?1 ORDER BY idx")?;
+        let rows = stmt
+            .query_map(params![session_key(session_file)], |row| {
+                row.get::<_, String>(0)
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        rows.iter()
+            .map(|json| Ok(serde_json::from_str::<Message>(json)?))
+            .collect()
+    }
+
+    fn read_metadata(&self, session_file: &Path) -> Result<SessionMetadata> {
+        let conn = self.conn.lock().unwrap();
+        let metadata = conn
+            .query_row(
+                "SELECT metadata FROM sessions WHERE path = ?1",
+                params![session_key(session_file)],
+                |row| row.get::<_, String>(0),
+            )
+            .optional()?;
+
+        match metadata {
+            Some(json) => Ok(serde_json::from_str(&json)?),
+            None => Ok(SessionMetadata::default()),
+        }
+    }
+
+    fn save(
+        &self,
+        session_file: &Path,
+        metadata: &SessionMetadata,
+        messages: &[Message],
+    ) -> Result<()> {
+        self.write(session_file, metadata, messages, Utc::now())
+    }
+
+    fn exists(&self, session_file: &Path) -> bool {
+        let conn = self.conn.lock().unwrap();
+        conn.query_row(
+            "SELECT 1 FROM sessions WHERE path = ?1",
+            params![session_key(session_file)],
+            |_| Ok(()),
+        )
+        .optional()
+        .is_ok_and(|row| row.is_some())
+    }
+
+    fn delete(&self, session_file: &Path) -> Result<()> {
+        let key = session_key(session_file);
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let deleted = tx.execute("DELETE FROM sessions WHERE path = ?1", params![key])?;
+        tx.execute("DELETE FROM messages WHERE session_path = ?1", params![key])?;
+        tx.execute(
+            "DELETE FROM messages_fts WHERE session_path = ?1",
+            params![key],
+        )?;
+        tx.commit()?;
+
+        if deleted == 0 {
+            return Err(anyhow::anyhow!("Session '{}' not found", key));
+        }
+        Ok(())
+    }
+
+    fn list(&self) -> Result<Vec<(String, PathBuf)>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT id, path FROM sessions")?;
+        let sessions = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    PathBuf::from(row.get::<_, String>(1)?),
+                ))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(sessions)
+    }
+
+    fn query(&self, query: &SessionQuery) -> Result<Vec<SessionInfo>> {
+        let mut sql = "SELECT id, path, modified, metadata FROM sessions WHERE 1 = 1".to_string();
+        let mut values = Vec::new();
+        if let Some(since) = query.since {
+            sql.push_str(" AND modified >= ?");
+            values.push(SqlValue::Integer(since.timestamp()));
+        }
+        if let Some(until) = query.until {
+            sql.push_str(" AND modified < ?");
+            values.push(SqlValue::Integer(until.timestamp()));
+        }
+        if let Some(working_dir) = &query.working_dir {
+            sql.push_str(" AND working_dir = ?");
+            values.push(SqlValue::Text(working_dir.to_string_lossy().to_string()));
+        }
+        if let Some(schedule_id) = &query.schedule_id {
+            sql.push_str(" AND schedule_id = ?");
+            values.push(SqlValue::Text(schedule_id.clone()));
+        }
+        if let Some(min_total_tokens) = query.min_total_tokens {
+            sql.push_str(" AND total_tokens >= ?");
+            values.push(SqlValue::Integer(min_total_tokens as i64));
+        }
+        sql.push_str(match query.order {
+            SortOrder::Ascending => " ORDER BY modified ASC",
+            SortOrder::Descending => " ORDER BY modified DESC",
+        });
+        if let Some(limit) = query.limit {
+            sql.push_str(" LIMIT ?");
+            values.push(SqlValue::Integer(limit as i64));
+        }
+
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt
+            .query_map(params_from_iter(values), |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, i64>(2)?,
+                    row.get::<_, String>(3)?,
+                ))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        rows.into_iter()
+            .map(|(id, path, modified, metadata)| {
+                Ok(SessionInfo {
+                    id,
+                    path,
+                    modified: DateTime::from_timestamp(modified, 0)
+                        .map(format_modified)
+                        .unwrap_or_else(|| "Unknown".to_string()),
+                    metadata: serde_json::from_str(&metadata)?,
+                })
+            })
+            .collect()
+    }
+
+    fn search(&self, text: &str, limit: usize) -> Result<Vec<SessionSearchHit>> {
+        let query = fts_query(text);
+        if query.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT sessions.id, sessions.path, sessions.metadata, messages_fts.idx,
+                    snippet(messages_fts, 0, '', '', '…', 16)
+             FROM messages_fts JOIN sessions ON sessions.path = messages_fts.session_path
+             WHERE messages_fts MATCH ?1
+             ORDER BY sessions.modified DESC, messages_fts.idx
+             LIMIT ?2",
+        )?;
+        let rows = stmt
+            .query_map(params![query, limit as i64], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, i64>(3)?,
+                    row.get::<_, String>(4)?,
+                ))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+
+        rows.into_iter()
+            .map(|(id, path, metadata, idx, snippet)| {
+                let metadata: SessionMetadata = serde_json::from_str(&metadata)?;
+                Ok(SessionSearchHit {
+                    id,
+                    path,
+                    description: metadata.description,
+                    message_index: idx as usize,
+                    snippet,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn metadata(schedule_id: Option<&str>, total_tokens: i32) -> SessionMetadata {
+        let mut metadata = SessionMetadata::default();
+        metadata.description = "Test session".to_string();
+        metadata.schedule_id = schedule_id.map(String::from);
+        metadata.accumulated_total_tokens = Some(total_tokens);
+        metadata
+    }
+
+    #[test]
+    fn test_save_read_query_and_delete() -> Result<()> {
+        let dir = tempdir()?;
+        let store = SqliteStore::open(&dir.path().join("sessions.db"))?;
+        let first = dir.path().join("first.jsonl");
+        let second = dir.path().join("second.jsonl");
+
+        let messages = vec![
+            Message::user().with_text("Hello"),
+            Message::assistant().with_text("Hi there"),
+        ];
+        store.save(&first, &metadata(Some("nightly"), 500), &messages)?;
+        store.save(&second, &metadata(None, 5000), &messages[..1])?;
+
+        assert!(store.exists(&first));
+        assert_eq!(store.read_messages(&first)?, messages);
+        assert_eq!(
+            store.read_metadata(&first)?.schedule_id.as_deref(),
+            Some("nightly")
+        );
+        assert!(store
+            .read_messages(&dir.path().join("missing.jsonl"))?
+            .is_empty());
+
+        let nightly = store.query(&SessionQuery {
+            schedule_id: Some("nightly".to_string()),
+            ..Default::default()
+        })?;
+        assert_eq!(nightly.len(), 1);
+        assert_eq!(nightly[0].id, "first");
+
+        let large = store.query(&SessionQuery {
+            min_total_tokens: Some(1000),
+            ..Default::default()
+        })?;
+        assert_eq!(large.len(), 1);
+        assert_eq!(large[0].id, "second");
+
+        store.delete(&first)?;
+        assert!(!store.exists(&first));
+        assert!(store.read_messages(&first)?.is_empty());
+        assert_eq!(store.list()?.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_full_text_search() -> Result<()> {
+        let dir = tempdir()?;
+        let store = SqliteStore::open(&dir.path().join("sessions.db"))?;
+        let messages = vec![
+            Message::user().with_text("Why does the parser reject trailing commas?"),
+            Message::assistant().with_text("The tokenizer treats a comma before `]` as an error."),
+            Message::user().with_text("Fix the \"parser\" then"),
+        ];
+        store.save(
+            &dir.path().join("parser.jsonl"),
+            &metadata(None, 100),
+            &messages,
+        )?;
+
+        let hits = store.search("parser", 10)?;
+        assert_eq!(
+            hits.iter().map(|hit| hit.message_index).collect::<Vec<_>>(),
+            vec![0, 2]
+        );
+        assert_eq!(hits[0].id, "parser");
+
+        // Every word has to match, and punctuation isn't read as query syntax
+        assert_eq!(store.search("trailing commas?", 10)?.len(), 1);
+        assert_eq!(store.search("parser tokenizer", 10)?.len(), 0);
+        assert!(store.search("   ", 10)?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_import_jsonl() -> Result<()> {
+        let dir = tempdir()?;
+        let session_file = dir.path().join("imported.jsonl");
+        let messages = vec![Message::user().with_text("Imported message")];
+        JsonlStore.save(&session_file, &metadata(Some("weekly"), 42), &messages)?;
+
+        let store = SqliteStore::open(&dir.path().join("sessions.db"))?;
+        assert_eq!(store.import_jsonl(dir.path())?, 1);
+        // Importing again replaces the session rather than duplicating it
+        assert_eq!(store.import_jsonl(dir.path())?, 1);
+
+        assert_eq!(store.read_messages(&session_file)?, messages);
+        assert_eq!(store.list()?.len(), 1);
+        assert_eq!(store.search("imported", 10)?.len(), 1);
+        Ok(())
+    }
+}
@@ -1,11 +1,11 @@
+use super::store::{store, SessionQuery, SessionSearchHit};
 use crate::message::Message;
 use crate::providers::base::Provider;
 use anyhow::Result;
 use chrono::Local;
 use etcetera::{choose_app_strategy, AppStrategy, AppStrategyArgs};
 use serde::{Deserialize, Serialize};
-use std::fs::{self, File};
-use std::io::{self, BufRead, Write};
+use std::fs;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use utoipa::ToSchema;
@@ -41,6 +41,27 @@ pub struct SessionMetadata {
     pub accumulated_input_tokens: Option<i32>,
     /// The number of output tokens used in the session. Accumulated across all messages.
     pub accumulated_output_tokens: Option<i32>,
+    /// The estimated cost of the session in USD, accumulated across all provider calls whose
+    /// model has a known price
+    pub accumulated_cost: Option<f64>,
+    /// ID of the session this one was forked from, if any
+    pub parent_session: Option<String>,
+    /// Number of messages kept from the parent session when forking
+    pub fork_point: Option<usize>,
+    /// Attempts of a recipe run with success checks, in order
+    pub retry_attempts: Option<Vec<RetryAttempt>>,
+}
+
+/// One attempt of a recipe run with success checks
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct RetryAttempt {
+    /// Attempt number, starting at 1
+    pub attempt: u32,
+    /// Index of the first message of the attempt in the session
+    pub message_index: usize,
+    pub passed: bool,
+    /// Output of the first check that failed
+    pub failure: Option<String>,
 }
 
 // Custom deserializer to handle old sessions without working_dir
@@ -60,6 +81,10 @@ impl<'de> Deserialize<'de> for SessionMetadata {
             accumulated_total_tokens: Option<i32>,
             accumulated_input_tokens: Option<i32>,
             accumulated_output_tokens: Option<i32>,
+            accumulated_cost: Option<f64>,
+            parent_session: Option<String>,
+            fork_point: Option<usize>,
+            retry_attempts: Option<Vec<RetryAttempt>>,
             working_dir: Option<PathBuf>,
         }
 
@@ -81,6 +106,10 @@ impl<'de> Deserialize<'de> for SessionMetadata {
             accumulated_total_tokens: helper.accumulated_total_tokens,
             accumulated_input_tokens: helper.accumulated_input_tokens,
             accumulated_output_tokens: helper.accumulated_output_tokens,
+            accumulated_cost: helper.accumulated_cost,
+            parent_session: helper.parent_session,
+            fork_point: helper.fork_point,
+            retry_attempts: helper.retry_attempts,
             working_dir,
         })
     }
@@ -106,6 +135,10 @@ impl SessionMetadata {
             accumulated_total_tokens: None,
             accumulated_input_tokens: None,
             accumulated_output_tokens: None,
+            accumulated_cost: None,
+            parent_session: None,
+            fork_point: None,
+            retry_attempts: None,
         }
     }
 }
@@ -155,51 +188,38 @@ pub fn ensure_session_dir() -> Result<PathBuf> {
     Ok(data_dir)
 }
 
-/// Get the path to the most recently modified session file
+/// Get the path to the most recently modified session
 pub fn get_most_recent_session() -> Result<PathBuf> {
-    let session_dir = ensure_session_dir()?;
-    let mut entries = fs::read_dir(&session_dir)?
-        .filter_map(|entry| entry.ok())
-        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "jsonl"))
-        .collect::<Vec<_>>();
-
-    if entries.is_empty() {
-        return Err(anyhow::anyhow!("No session files found"));
-    }
-
-    // Sort by modification time, most recent first
-    entries.sort_by(|a, b| {
-        b.metadata()
-            .and_then(|m| m.modified())
-            .unwrap_or(std::time::SystemTime::UNIX_EPOCH)
-            .cmp(
-                &a.metadata()
-                    .and_then(|m| m.modified())
-                    .unwrap_or(std::time::SystemTime::UNIX_EPOCH),
-            )
-    });
-
-    Ok(entries[0].path())
+    let query = SessionQuery {
+        limit: Some(1),
+        ..Default::default()
+    };
+    store()
+        .query(&query)?
+        .into_iter()
+        .next()
+        .map(|info| PathBuf::from(info.path))
+        .ok_or_else(|| anyhow::anyhow!("No session files found"))
 }
 
-/// List all available session files
+/// List all available sessions
 pub fn list_sessions() -> Result<Vec<(String, PathBuf)>> {
-    let session_dir = ensure_session_dir()?;
-    let entries = fs::read_dir(&session_dir)?
-        .filter_map(|entry| {
-            let entry = entry.ok()?;
-            let path = entry.path();
-
-            if path.extension().is_some_and(|ext| ext == "jsonl") {
-                let name = path.file_stem()?.to_string_lossy().to_string();
-                Some((name, path))
-            } else {
-                None
-            }
-        })
-        .collect::<Vec<_>>();
+    store().list()
+}
+
+/// Check whether a session has been saved
+pub fn session_exists(session_file: &Path) -> bool {
+    store().exists(session_file)
+}
 
-    Ok(entries)
+/// Delete a session and its messages
+pub fn delete_session(session_file: &Path) -> Result<()> {
+    store().delete(session_file)
+}
+
+/// Search the text of all session messages
+pub fn search_sessions(text: &str, limit: usize) -> Result<Vec<SessionSearchHit>> {
+    store().search(text, limit)
 }
 
 /// Generate a session ID using timestamp format (yyyymmdd_hhmmss)
@@ -212,63 +232,14 @@ pub fn generate_session_id() -> String {
 /// Creates the file if it doesn't exist, reads and deserializes all messages if it does.
 /// The first line of the file is expected to be metadata, and the rest are messages.
 pub fn read_messages(session_file: &Path) -> Result<Vec<Message>> {
-    let file = fs::OpenOptions::new()
-        .read(true)
-        .write(true)
-        .create(true)
-        .truncate(false)
-        .open(session_file)?;
-
-    let reader = io::BufReader::new(file);
-    let mut lines = reader.lines();
-    let mut messages = Vec::new();
-
-    // Read the first line as metadata or create default if empty/missing
-    if let Some(line) = lines.next() {
-        let line = line?;
-        // Try to parse as metadata, but if it fails, treat it as a message
-        if let Ok(_metadata) = serde_json::from_str::<SessionMetadata>(&line) {
-            // Metadata successfully parsed, continue with the rest of the lines as messages
-        } else {
-            // This is not metadata, it's a message
-            messages.push(serde_json::from_str::<Message>(&line)?);
-        }
-    }
-
-    // Read the rest of the lines as messages
-    for line in lines {
-        messages.push(serde_json::from_str::<Message>(&line?)?);
-    }
-
-    Ok(messages)
+    store().read_messages(session_file)
 }
 
 /// Read session metadata from a session file
 ///
 /// Returns default empty metadata if the file doesn't exist or has no metadata.
 pub fn read_metadata(session_file: &Path) -> Result<SessionMetadata> {
-    if !session_file.exists() {
-        return Ok(SessionMetadata::default());
-    }
-
-    let file = fs::File::open(session_file)?;
-    let mut reader = io::BufReader::new(file);
-    let mut first_line = String::new();
-
-    // Read just the first line
-    if reader.read_line(&mut first_line)? > 0 {
-        // Try to parse as metadata
-        match serde_json::from_str::<SessionMetadata>(&first_line) {
-            Ok(metadata) => Ok(metadata),
-            Err(_) => {
-                // If the first line isn't metadata, return default
-                Ok(SessionMetadata::default())
-            }
-        }
-    } else {
-        // Empty file, return default
-        Ok(SessionMetadata::default())
-    }
+    store().read_metadata(session_file)
 }
 
 /// Write messages to a session file with metadata
@@ -309,21 +280,7 @@ pub fn save_messages_with_metadata(
     metadata: &SessionMetadata,
     messages: &[Message],
 ) -> Result<()> {
-    let file = File::create(session_file).expect("The path specified does not exist");
-    let mut writer = io::BufWriter::new(file);
-
-    // Write metadata as the first line
-    serde_json::to_writer(&mut writer, &metadata)?;
-    writeln!(writer)?;
-
-    // Write all messages
-    for message in messages {
-        serde_json::to_writer(&mut writer, &message)?;
-        writeln!(writer)?;
-    }
-
-    writer.flush()?;
-    Ok(())
+    store().save(session_file, metadata, messages)
 }
 
 /// Generate a description for the session using the provider
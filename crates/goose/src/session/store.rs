@@ -0,0 +1,419 @@
+use super::info::{SessionInfo, SortOrder};
+use super::sqlite::SqliteStore;
+use super::storage::{ensure_session_dir, SessionMetadata};
+use crate::config::Config;
+use crate::message::Message;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::fs::{self, File};
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+use utoipa::ToSchema;
+
+/// Where sessions are saved. Sessions are always addressed by the path `get_path` returns,
+/// whether or not the store keeps them in that file.
+pub trait SessionStore: Send + Sync {
+    /// Read all messages of a session, empty if it doesn't exist
+    fn read_messages(&self, session_file: &Path) -> Result<Vec<Message>>;
+
+    /// Read the metadata of a session, default metadata if it doesn't exist
+    fn read_metadata(&self, session_file: &Path) -> Result<SessionMetadata>;
+
+    /// Replace the metadata and messages of a session
+    fn save(
+        &self,
+        session_file: &Path,
+        metadata: &SessionMetadata,
+        messages: &[Message],
+    ) -> Result<()>;
+
+    fn exists(&self, session_file: &Path) -> bool;
+
+    fn delete(&self, session_file: &Path) -> Result<()>;
+
+    /// The ID and path of every session, in no particular order
+    fn list(&self) -> Result<Vec<(String, PathBuf)>>;
+
+    /// Sessions matching the query, ordered by when they were last modified
+    fn query(&self, query: &SessionQuery) -> Result<Vec<SessionInfo>>;
+
+    /// Messages containing every word of `text`, most recent sessions first
+    fn search(&self, text: &str, limit: usize) -> Result<Vec<SessionSearchHit>>;
+}
+
+/// Filters for listing sessions. Unset fields match every session.
+#[derive(Debug, Clone, Default)]
+pub struct SessionQuery {
+    /// Only sessions modified at or after this time
+    pub since: Option<DateTime<Utc>>,
+    /// Only sessions modified before this time
+    pub until: Option<DateTime<Utc>>,
+    pub working_dir: Option<PathBuf>,
+    pub schedule_id: Option<String>,
+    /// Only sessions that used at least this many tokens in total
+    pub min_total_tokens: Option<i32>,
+    pub order: SortOrder,
+    pub limit: Option<usize>,
+}
+
+impl SessionQuery {
+    fn matches(&self, modified: Option<DateTime<Utc>>, metadata: &SessionMetadata) -> bool {
+        let in_range = match modified {
+            Some(modified) => {
+                self.since.is_none_or(|since| modified >= since)
+                    && self.until.is_none_or(|until| modified < until)
+            }
+            None => self.since.is_none() && self.until.is_none(),
+        };
+        in_range
+            && self
+                .working_dir
+                .as_ref()
+                .is_none_or(|dir| dir == &metadata.working_dir)
+            && self
+                .schedule_id
+                .as_ref()
+                .is_none_or(|id| metadata.schedule_id.as_ref() == Some(id))
+            && self.min_total_tokens.is_none_or(|min| {
+                metadata
+                    .accumulated_total_tokens
+                    .or(metadata.total_tokens)
+                    .is_some_and(|total| total >= min)
+            })
+    }
+}
+
+/// A message that matched a session search
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct SessionSearchHit {
+    /// ID of the session containing the message
+    pub id: String,
+    pub path: String,
+    /// Description of the session
+    pub description: String,
+    /// Position of the message in the session
+    pub message_index: usize,
+    /// The matching part of the message text
+    pub snippet: String,
+}
+
+static STORE: Lazy<Box<dyn SessionStore>> = Lazy::new(|| {
+    let backend = Config::global()
+        .get_param::<String>("GOOSE_SESSION_STORE")
+        .unwrap_or_else(|_| "jsonl".to_string());
+    match backend.as_str() {
+        "sqlite" => match SqliteStore::open_default() {
+            Ok(store) => Box::new(store),
+            Err(e) => {
+                tracing::error!(
+                    "Failed to open the session database, using JSONL files: {}",
+                    e
+                );
+                Box::new(JsonlStore)
+            }
+        },
+        "jsonl" => Box::new(JsonlStore),
+        other => {
+            tracing::warn!("Unknown GOOSE_SESSION_STORE '{}', using JSONL files", other);
+            Box::new(JsonlStore)
+        }
+    }
+});
+
+/// The session store selected by GOOSE_SESSION_STORE
+pub fn store() -> &'static dyn SessionStore {
+    STORE.as_ref()
+}
+
+pub(crate) fn format_modified(time: DateTime<Utc>) -> String {
+    time.format("%Y-%m-%d %H:%M:%S UTC").to_string()
+}
+
+/// Sessions saved as JSONL files in the session directory, with the metadata on the first line
+/// and a message on each following line
+pub struct JsonlStore;
+
+impl JsonlStore {
+    fn modified(path: &Path) -> Option<DateTime<Utc>> {
+        path.metadata()
+            .and_then(|m| m.modified())
+            .ok()
+            .map(DateTime::<Utc>::from)
+    }
+}
+
+impl SessionStore for JsonlStore {
+    fn read_messages(&self, session_file: &Path) -> Result<Vec<Message>> {
+        let file = fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(session_file)?;
+
+        let reader = io::BufReader::new(file);
+        let mut lines = reader.lines();
+        let mut messages = Vec::new();
+
+        // Read the first line as metadata or create default if empty/missing
+        if let Some(line) = lines.next() {
+            let line = line?;
+            // Try to parse as metadata, but if it fails, treat it as a message
+            if let Ok(_metadata) = serde_json::from_str::<SessionMetadata>(&line) {
+                // Metadata successfully parsed, continue with the rest of the lines as messages
+            } else {
+                // This is not metadata, it's a message
+                messages.push(serde_json::from_str::<Message>(&line)?);
+            }
+        }
+
+        // Read the rest of the lines as messages
+        for line in lines {
+            messages.push(serde_json::from_str::<Message>(&line?)?);
+        }
+
+        Ok(messages)
+    }
+
+    fn read_metadata(&self, session_file: &Path) -> Result<SessionMetadata> {
+        if !session_file.exists() {
+            return Ok(SessionMetadata::default());
+        }
+
+        let file = fs::File::open(session_file)?;
+        let mut reader = io::BufReader::new(file);
+        let mut first_line = String::new();
+
+        // Read just the first line
+        if reader.read_line(&mut first_line)? > 0 {
+            // Try to parse as metadata
+            match serde_json::from_str::<SessionMetadata>(&first_line) {
+                Ok(metadata) => Ok(metadata),
+                Err(_) => {
+                    // If the first line isn't metadata, return default
+                    Ok(SessionMetadata::default())
+                }
+            }
+        } else {
+            // Empty file, return default
+            Ok(SessionMetadata::default())
+        }
+    }
+
+    fn save(
+        &self,
+        session_file: &Path,
+        metadata: &SessionMetadata,
+        messages: &[Message],
+    ) -> Result<()> {
+        let file = File::create(session_file).expect("The path specified does not exist");
+        let mut writer = io::BufWriter::new(file);
+
+        // Write metadata as the first line
+        serde_json::to_writer(&mut writer, &metadata)?;
+        writeln!(writer)?;
+
+        // Write all messages
+        for message in messages {
+            serde_json::to_writer(&mut writer, &message)?;
+            writeln!(writer)?;
+        }
+
+        writer.flush()?;
+        Ok(())
+    }
+
+    fn exists(&self, session_file: &Path) -> bool {
+        session_file.exists()
+    }
+
+    fn delete(&self, session_file: &Path) -> Result<()> {
+        fs::remove_file(session_file)?;
+        Ok(())
+    }
+
+    fn list(&self) -> Result<Vec<(String, PathBuf)>> {
+        let session_dir = ensure_session_dir()?;
+        let entries = fs::read_dir(&session_dir)?
+            .filter_map(|entry| {
+                let entry = entry.ok()?;
+                let path = entry.path();
+
+                if path.extension().is_some_and(|ext| ext == "jsonl") {
+                    let name = path.file_stem()?.to_string_lossy().to_string();
+                    Some((name, path))
+                } else {
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
+
+        Ok(entries)
+    }
+
+    fn query(&self, query: &SessionQuery) -> Result<Vec<SessionInfo>> {
+        // Every file has to be read, so filter before sorting and truncating
+        let mut sessions = self
+            .list()?
+            .into_iter()
+            .filter_map(|(id, path)| {
+                let metadata = match self.read_metadata(&path) {
+                    Ok(metadata) => metadata,
+                    Err(e) => {
+                        tracing::warn!("Skipping session {}: {}", id, e);
+                        return None;
+                    }
+                };
+                let modified = Self::modified(&path);
+                query
+                    .matches(modified, &metadata)
+                    .then_some((modified, id, path, metadata))
+            })
+            .collect::<Vec<_>>();
+
+        // Unknown dates go last
+        sessions.sort_by(|a, b| match (a.0, b.0) {
+            (Some(a), Some(b)) => match query.order {
+                SortOrder::Ascending => a.cmp(&b),
+                SortOrder::Descending => b.cmp(&a),
+            },
+            (a, b) => b.is_some().cmp(&a.is_some()),
+        });
+        if let Some(limit) = query.limit {
+            sessions.truncate(limit);
+        }
+
+        Ok(sessions
+            .into_iter()
+            .map(|(modified, id, path, metadata)| SessionInfo {
+                id,
+                path: path.to_string_lossy().to_string(),
+                modified: modified
+                    .map(format_modified)
+                    .unwrap_or_else(|| "Unknown".to_string()),
+                metadata,
+            })
+            .collect())
+    }
+
+    fn search(&self, text: &str, limit: usize) -> Result<Vec<SessionSearchHit>> {
+        let terms = text
+            .split_whitespace()
+            .map(str::to_lowercase)
+            .collect::<Vec<_>>();
+        if terms.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut hits = Vec::new();
+        for session in self.query(&SessionQuery::default())? {
+            let messages = match self.read_messages(Path::new(&session.path)) {
+                Ok(messages) => messages,
+                Err(e) => {
+                    tracing::warn!("Skipping session {}: {}", session.id, e);
+                    continue;
+                }
+            };
+            for (index, message) in messages.iter().enumerate() {
+                let text = message.as_concat_text();
+                let lowercase = text.to_lowercase();
+                if !terms.iter().all(|term| lowercase.contains(term)) {
+                    continue;
+                }
+                hits.push(SessionSearchHit {
+                    id: session.id.clone(),
+                    path: session.path.clone(),
+                    description: session.metadata.description.clone(),
+                    message_index: index,
+                    snippet: snippet(&text, &lowercase, &terms[0]),
+                });
+                if hits.len() >= limit {
+                    return Ok(hits);
+                }
+            }
+        }
+        Ok(hits)
+    }
+}
+
+/// Up to `SNIPPET_CONTEXT` characters either side of the first occurrence of `term`
+fn snippet(text: &str, lowercase: &str, term: &str) -> String {
+    const SNIPPET_CONTEXT: usize = 60;
+
+    // Lowercasing can change byte offsets, so locate the match by characters
+    let chars = text.chars().collect::<Vec<_>>();
+    let position = lowercase
+        .find(term)
+        .map(|byte| lowercase[..byte].chars().count())
+        .unwrap_or(0)
+        .min(chars.len());
+    let start = position.saturating_sub(SNIPPET_CONTEXT);
+    let end = (position + term.chars().count() + SNIPPET_CONTEXT).min(chars.len());
+
+    let mut snippet = chars[start..end].iter().collect::<String>();
+    snippet = snippet.split_whitespace().collect::<Vec<_>>().join(" ");
+    if start > 0 {
+        snippet.insert(0, '…');
+    }
+    if end < chars.len() {
+        snippet.push('…');
+    }
+    snippet
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_query_matches_metadata() {
+        let mut metadata = SessionMetadata::default();
+        metadata.schedule_id = Some("nightly".to_string());
+        metadata.accumulated_total_tokens = Some(5000);
+        let modified = DateTime::parse_from_rfc3339("2025-06-01T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        let query = SessionQuery {
+            schedule_id: Some("nightly".to_string()),
+            min_total_tokens: Some(1000),
+            since: Some(modified - chrono::Duration::days(1)),
+            ..Default::default()
+        };
+        assert!(query.matches(Some(modified), &metadata));
+        assert!(!query.matches(None, &metadata));
+
+        let query = SessionQuery {
+            until: Some(modified),
+            ..Default::default()
+        };
+        assert!(!query.matches(Some(modified), &metadata));
+
+        let query = SessionQuery {
+            min_total_tokens: Some(10_000),
+            ..Default::default()
+        };
+        assert!(!query.matches(Some(modified), &metadata));
+    }
+
+    #[test]
+    fn test_snippet() {
+        let text = format!("{} needle {}", "a ".repeat(100), "b ".repeat(100));
+        let lowercase = text.to_lowercase();
+        let snippet = snippet(&text, &lowercase, "needle");
+        assert!(snippet.starts_with('…'));
+        assert!(snippet.ends_with('…'));
+        assert!(snippet.contains("needle"));
+
+        assert_eq!(
+            snippet_of("Short NEEDLE text", "needle"),
+            "Short NEEDLE text"
+        );
+    }
+
+    fn snippet_of(text: &str, term: &str) -> String {
+        snippet(text, &text.to_lowercase(), term)
+    }
+}
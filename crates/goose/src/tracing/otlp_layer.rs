@@ -0,0 +1,932 @@
+use crate::tracing::observation_layer::{BatchManager, ObservationLayer, SpanTracker};
+use chrono::DateTime;
+use opentelemetry_proto::tonic::collector::trace::v1::trace_service_client::TraceServiceClient;
+use opentelemetry_proto::tonic::collector::trace::v1::ExportTraceServiceRequest;
+use opentelemetry_proto::tonic::common::v1::{any_value, AnyValue, InstrumentationScope, KeyValue};
+use opentelemetry_proto::tonic::resource::v1::Resource;
+use opentelemetry_proto::tonic::trace::v1::span::SpanKind;
+use opentelemetry_proto::tonic::trace::v1::status::StatusCode;
+use opentelemetry_proto::tonic::trace::v1::{ResourceSpans, ScopeSpans, Span, Status};
+use prost::Message;
+use reqwest::Client;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::env;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tonic::metadata::{MetadataKey, MetadataValue};
+use tonic::transport::{Channel, ClientTlsConfig, Endpoint};
+use uuid::Uuid;
+
+const DEFAULT_HTTP_ENDPOINT: &str = "http://localhost:4318";
+const DEFAULT_GRPC_ENDPOINT: &str = "http://localhost:4317";
+const DEFAULT_TIMEOUT_MS: u64 = 10_000;
+const DEFAULT_SERVICE_NAME: &str = "goose";
+const SCOPE_NAME: &str = "goose";
+
+/// Finished spans kept while the collector is unreachable, the oldest are dropped beyond this
+const MAX_QUEUED_SPANS: usize = 2048;
+
+/// Span fields holding prompts, completions and tool arguments, only exported when
+/// OTEL_INSTRUMENTATION_GENAI_CAPTURE_MESSAGE_CONTENT is enabled
+const CONTENT_FIELDS: [&str; 3] = ["input", "output", "user_message"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OtlpProtocol {
+    HttpProtobuf,
+    Grpc,
+}
+
+/// Exporter settings, read from the standard `OTEL_*` environment variables
+#[derive(Debug, Clone, PartialEq)]
+pub struct OtlpConfig {
+    pub endpoint: String,
+    pub protocol: OtlpProtocol,
+    pub headers: Vec<(String, String)>,
+    pub timeout: Duration,
+    pub resource_attributes: Vec<(String, String)>,
+    pub capture_content: bool,
+}
+
+/// The traces specific variable if set, otherwise the general one
+fn exporter_var(name: &str) -> Option<String> {
+    [
+        format!("OTEL_EXPORTER_OTLP_TRACES_{}", name),
+        format!("OTEL_EXPORTER_OTLP_{}", name),
+    ]
+    .iter()
+    .find_map(|var| env::var(var).ok().filter(|value| !value.trim().is_empty()))
+}
+
+fn env_flag(name: &str) -> bool {
+    env::var(name)
+        .map(|value| value.trim().eq_ignore_ascii_case("true"))
+        .unwrap_or(false)
+}
+
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+/// Parse a `key1=value1,key2=value2` list with percent-encoded values, as used by
+/// OTEL_EXPORTER_OTLP_HEADERS and OTEL_RESOURCE_ATTRIBUTES
+fn parse_key_values(list: &str) -> Vec<(String, String)> {
+    list.split(',')
+        .filter_map(|pair| pair.split_once('='))
+        .map(|(key, value)| (key.trim().to_string(), percent_decode(value.trim())))
+        .filter(|(key, _)| !key.is_empty())
+        .collect()
+}
+
+impl OtlpConfig {
+    /// The exporter configuration, or None unless an OTLP endpoint is configured
+    pub fn from_env() -> Option<Self> {
+        if env_flag("OTEL_SDK_DISABLED") {
+            return None;
+        }
+
+        let protocol = match exporter_var("PROTOCOL").as_deref().map(str::trim) {
+            Some("grpc") => OtlpProtocol::Grpc,
+            _ => OtlpProtocol::HttpProtobuf,
+        };
+
+        // A traces endpoint is used as is, the general endpoint is a base URL for HTTP
+        let endpoint = match env::var("OTEL_EXPORTER_OTLP_TRACES_ENDPOINT") {
+            Ok(endpoint) if !endpoint.trim().is_empty() => endpoint.trim().to_string(),
+            _ => {
+                let base = env::var("OTEL_EXPORTER_OTLP_ENDPOINT").ok()?;
+                let base = base.trim().trim_end_matches('/');
+                match protocol {
+                    OtlpProtocol::HttpProtobuf => format!("{}/v1/traces", base),
+                    OtlpProtocol::Grpc => base.to_string(),
+                }
+            }
+        };
+
+        let timeout = exporter_var("TIMEOUT")
+            .and_then(|timeout| timeout.trim().parse().ok())
+            .unwrap_or(DEFAULT_TIMEOUT_MS);
+
+        let mut resource_attributes = env::var("OTEL_RESOURCE_ATTRIBUTES")
+            .map(|attributes| parse_key_values(&attributes))
+            .unwrap_or_default();
+        let service_name = env::var("OTEL_SERVICE_NAME").ok().or_else(|| {
+            resource_attributes
+                .iter()
+                .find(|(key, _)| key == "service.name")
+                .map(|(_, value)| value.clone())
+        });
+        resource_attributes.retain(|(key, _)| key != "service.name");
+        resource_attributes.push((
+            "service.name".to_string(),
+            service_name.unwrap_or_else(|| DEFAULT_SERVICE_NAME.to_string()),
+        ));
+        if !resource_attributes
+            .iter()
+            .any(|(key, _)| key == "service.version")
+        {
+            resource_attributes.push((
+                "service.version".to_string(),
+                env!("CARGO_PKG_VERSION").to_string(),
+            ));
+        }
+
+        Some(Self {
+            endpoint,
+            protocol,
+            headers: exporter_var("HEADERS")
+                .map(|headers| parse_key_values(&headers))
+                .unwrap_or_default(),
+            timeout: Duration::from_millis(timeout),
+            resource_attributes,
+            capture_content: env_flag("OTEL_INSTRUMENTATION_GENAI_CAPTURE_MESSAGE_CONTENT"),
+        })
+    }
+
+    /// The configuration for `protocol` with the default local collector endpoint
+    pub fn local(protocol: OtlpProtocol) -> Self {
+        let endpoint = match protocol {
+            OtlpProtocol::HttpProtobuf => format!("{}/v1/traces", DEFAULT_HTTP_ENDPOINT),
+            OtlpProtocol::Grpc => DEFAULT_GRPC_ENDPOINT.to_string(),
+        };
+        Self {
+            endpoint,
+            protocol,
+            headers: Vec::new(),
+            timeout: Duration::from_millis(DEFAULT_TIMEOUT_MS),
+            resource_attributes: vec![(
+                "service.name".to_string(),
+                DEFAULT_SERVICE_NAME.to_string(),
+            )],
+            capture_content: false,
+        }
+    }
+}
+
+/// A span that has started but not yet closed
+#[derive(Debug, Clone)]
+struct OpenSpan {
+    trace_id: Vec<u8>,
+    span_id: Vec<u8>,
+    parent_span_id: Vec<u8>,
+    name: String,
+    start_time_unix_nano: u64,
+    level: String,
+    fields: serde_json::Map<String, Value>,
+}
+
+/// Exports the spans recorded by the `ObservationLayer` to an OpenTelemetry collector over
+/// OTLP, naming and annotating provider calls and tool calls with the GenAI semantic
+/// conventions.
+pub struct OtlpBatchManager {
+    pub config: OtlpConfig,
+    pub finished: Vec<Span>,
+    open: HashMap<String, OpenSpan>,
+    client: Client,
+    grpc_client: Option<TraceServiceClient<Channel>>,
+}
+
+fn trace_id(uuid: &str) -> Vec<u8> {
+    Uuid::parse_str(uuid)
+        .map(|uuid| uuid.as_bytes().to_vec())
+        .unwrap_or_default()
+}
+
+fn span_id(uuid: &str) -> Vec<u8> {
+    let mut id = trace_id(uuid);
+    id.truncate(8);
+    id
+}
+
+fn unix_nanos(timestamp: Option<&Value>) -> u64 {
+    timestamp
+        .and_then(Value::as_str)
+        .and_then(|timestamp| DateTime::parse_from_rfc3339(timestamp).ok())
+        .and_then(|time| time.timestamp_nanos_opt())
+        .unwrap_or_default() as u64
+}
+
+fn key_value(key: &str, value: any_value::Value) -> KeyValue {
+    KeyValue {
+        key: key.to_string(),
+        value: Some(AnyValue { value: Some(value) }),
+    }
+}
+
+fn string_attribute(key: &str, value: impl Into<String>) -> KeyValue {
+    key_value(key, any_value::Value::StringValue(value.into()))
+}
+
+fn json_attribute(key: &str, value: &Value) -> KeyValue {
+    let value = match value {
+        Value::String(s) => any_value::Value::StringValue(s.clone()),
+        Value::Bool(b) => any_value::Value::BoolValue(*b),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => any_value::Value::IntValue(i),
+            None => any_value::Value::DoubleValue(n.as_f64().unwrap_or_default()),
+        },
+        other => any_value::Value::StringValue(other.to_string()),
+    };
+    key_value(key, value)
+}
+
+/// Token counts are recorded with their Debug representation, so they arrive as strings
+fn as_int(value: &Value) -> Option<i64> {
+    match value {
+        Value::Number(n) => n.as_i64(),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+fn as_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+impl OpenSpan {
+    /// Convert to an OTLP span, mapping the spans goose instruments onto the GenAI
+    /// semantic conventions and any other fields onto `goose.*` attributes
+    fn finish(mut self, end_time_unix_nano: u64, capture_content: bool) -> Span {
+        let mut attributes = Vec::new();
+        let mut kind = SpanKind::Internal;
+        let mut name = self.name.clone();
+
+        match self.name.as_str() {
+            "complete" | "stream" => {
+                kind = SpanKind::Client;
+                attributes.push(string_attribute("gen_ai.operation.name", "chat"));
+                let model = self
+                    .fields
+                    .remove("model_config")
+                    .map(|config| match config {
+                        Value::String(s) => serde_json::from_str(&s).unwrap_or(Value::Null),
+                        other => other,
+                    })
+                    .and_then(|config| config.get("model_name").cloned())
+                    .and_then(|model| model.as_str().map(str::to_string));
+                if let Some(model) = model {
+                    name = format!("chat {}", model);
+                    attributes.push(string_attribute("gen_ai.request.model", model));
+                }
+                for (field, key) in [
+                    ("input_tokens", "gen_ai.usage.input_tokens"),
+                    ("output_tokens", "gen_ai.usage.output_tokens"),
+                ] {
+                    if let Some(tokens) = self.fields.remove(field).as_ref().and_then(as_int) {
+                        attributes.push(key_value(key, any_value::Value::IntValue(tokens)));
+                    }
+                }
+                self.fields.remove("total_tokens");
+                if capture_content {
+                    for (field, key) in [
+                        ("input", "gen_ai.input.messages"),
+                        ("output", "gen_ai.output.messages"),
+                    ] {
+                        if let Some(value) = self.fields.remove(field) {
+                            attributes.push(string_attribute(key, as_text(&value)));
+                        }
+                    }
+                }
+            }
+            "dispatch_tool_call" => {
+                attributes.push(string_attribute("gen_ai.operation.name", "execute_tool"));
+                if let Some(tool) = self.fields.remove("tool_name") {
+                    let tool = as_text(&tool);
+                    name = format!("execute_tool {}", tool);
+                    attributes.push(string_attribute("gen_ai.tool.name", tool));
+                }
+                if let Some(id) = self.fields.remove("tool_call_id") {
+                    attributes.push(string_attribute("gen_ai.tool.call.id", as_text(&id)));
+                }
+                if capture_content {
+                    for (field, key) in [
+                        ("input", "gen_ai.tool.call.arguments"),
+                        ("output", "gen_ai.tool.call.result"),
+                    ] {
+                        if let Some(value) = self.fields.remove(field) {
+                            attributes.push(string_attribute(key, as_text(&value)));
+                        }
+                    }
+                }
+            }
+            "add_extension" => {
+                if let Some(extension) = self.fields.remove("extension") {
+                    name = format!("add_extension {}", as_text(&extension));
+                    attributes.push(string_attribute(
+                        "goose.extension.name",
+                        as_text(&extension),
+                    ));
+                }
+            }
+            _ => {}
+        }
+
+        for (field, value) in &self.fields {
+            if !capture_content && CONTENT_FIELDS.contains(&field.as_str()) {
+                continue;
+            }
+            attributes.push(json_attribute(&format!("goose.{}", field), value));
+        }
+
+        let status = (self.level == "ERROR").then(|| Status {
+            code: StatusCode::Error as i32,
+            ..Default::default()
+        });
+
+        Span {
+            trace_id: self.trace_id,
+            span_id: self.span_id,
+            parent_span_id: self.parent_span_id,
+            name,
+            kind: kind as i32,
+            start_time_unix_nano: self.start_time_unix_nano,
+            end_time_unix_nano,
+            attributes,
+            status,
+            ..Default::default()
+        }
+    }
+}
+
+impl OtlpBatchManager {
+    pub fn new(config: OtlpConfig) -> Self {
+        Self {
+            client: Client::builder()
+                .timeout(config.timeout)
+                .build()
+                .expect("Failed to create HTTP client"),
+            config,
+            finished: Vec::new(),
+            open: HashMap::new(),
+            grpc_client: None,
+        }
+    }
+
+    pub fn spawn_sender(manager: Arc<Mutex<Self>>) {
+        const BATCH_INTERVAL: Duration = Duration::from_secs(5);
+
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(BATCH_INTERVAL).await;
+                if let Err(e) = manager.lock().await.send() {
+                    tracing::error!(
+                        error.msg = %e,
+                        error.type = %std::any::type_name_of_val(&e),
+                        "Failed to export spans over OTLP"
+                    );
+                }
+            }
+        });
+    }
+
+    fn export_request(&self) -> ExportTraceServiceRequest {
+        let resource = Resource {
+            attributes: self
+                .config
+                .resource_attributes
+                .iter()
+                .map(|(key, value)| string_attribute(key, value.clone()))
+                .collect(),
+            ..Default::default()
+        };
+        ExportTraceServiceRequest {
+            resource_spans: vec![ResourceSpans {
+                resource: Some(resource),
+                scope_spans: vec![ScopeSpans {
+                    scope: Some(InstrumentationScope {
+                        name: SCOPE_NAME.to_string(),
+                        version: env!("CARGO_PKG_VERSION").to_string(),
+                        ..Default::default()
+                    }),
+                    spans: self.finished.clone(),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }],
+        }
+    }
+
+    async fn export_http(
+        &self,
+        request: ExportTraceServiceRequest,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        let mut builder = self
+            .client
+            .post(&self.config.endpoint)
+            .header("Content-Type", "application/x-protobuf")
+            .body(request.encode_to_vec());
+        for (key, value) in &self.config.headers {
+            builder = builder.header(key, value);
+        }
+
+        let response = builder.send().await?;
+        let status = response.status();
+        if status.is_success() {
+            Ok(())
+        } else {
+            let err_text = response.text().await.unwrap_or_default();
+            Err(format!("OTLP collector error: {}: {}", status, err_text).into())
+        }
+    }
+
+    async fn export_grpc(
+        &mut self,
+        request: ExportTraceServiceRequest,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        // The channel connects on first use and is reused for later exports
+        let mut client = match &self.grpc_client {
+            Some(client) => client.clone(),
+            None => {
+                let mut endpoint = Endpoint::from_shared(self.config.endpoint.clone())?
+                    .timeout(self.config.timeout);
+                if self.config.endpoint.starts_with("https://") {
+                    endpoint = endpoint.tls_config(ClientTlsConfig::new().with_native_roots())?;
+                }
+                let client = TraceServiceClient::new(endpoint.connect_lazy());
+                self.grpc_client = Some(client.clone());
+                client
+            }
+        };
+
+        let mut request = tonic::Request::new(request);
+        for (key, value) in &self.config.headers {
+            request.metadata_mut().insert(
+                MetadataKey::from_bytes(key.to_lowercase().as_bytes())?,
+                MetadataValue::try_from(value.as_str())?,
+            );
+        }
+        client.export(request).await?;
+        Ok(())
+    }
+
+    pub async fn send_async(&mut self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        if self.finished.is_empty() {
+            return Ok(());
+        }
+
+        let request = self.export_request();
+        let result = match self.config.protocol {
+            OtlpProtocol::HttpProtobuf => self.export_http(request).await,
+            OtlpProtocol::Grpc => self.export_grpc(request).await,
+        };
+
+        match result {
+            Ok(()) => {
+                self.finished.clear();
+                Ok(())
+            }
+            Err(e) => {
+                // Keep the spans for the next attempt, within limits
+                let excess = self.finished.len().saturating_sub(MAX_QUEUED_SPANS);
+                self.finished.drain(..excess);
+                Err(e)
+            }
+        }
+    }
+}
+
+impl BatchManager for OtlpBatchManager {
+    fn add_event(&mut self, event_type: &str, body: Value) {
+        let id = match body.get("id").and_then(Value::as_str) {
+            Some(id) => id.to_string(),
+            None => return,
+        };
+
+        match event_type {
+            "observation-create" => {
+                let fields = body
+                    .get("metadata")
+                    .and_then(Value::as_object)
+                    .cloned()
+                    .unwrap_or_default();
+                self.open.insert(
+                    id.clone(),
+                    OpenSpan {
+                        trace_id: trace_id(body["traceId"].as_str().unwrap_or_default()),
+                        span_id: span_id(&id),
+                        parent_span_id: body
+                            .get("parentObservationId")
+                            .and_then(Value::as_str)
+                            .map(span_id)
+                            .unwrap_or_default(),
+                        name: body["name"].as_str().unwrap_or_default().to_string(),
+                        start_time_unix_nano: unix_nanos(body.get("startTime")),
+                        level: body["level"].as_str().unwrap_or_default().to_string(),
+                        fields,
+                    },
+                );
+            }
+            "span-update" => {
+                if let Some(span) = self.open.get_mut(&id) {
+                    for field in ["input", "output"] {
+                        if let Some(value) = body.get(field) {
+                            span.fields.insert(field.to_string(), value.clone());
+                        }
+                    }
+                    if let Some(metadata) = body.get("metadata").and_then(Value::as_object) {
+                        span.fields
+                            .extend(metadata.iter().map(|(k, v)| (k.clone(), v.clone())));
+                    }
+                }
+            }
+            "observation-update" => {
+                if body.get("endTime").is_some() {
+                    if let Some(span) = self.open.remove(&id) {
+                        let end_time = unix_nanos(body.get("endTime"));
+                        self.finished
+                            .push(span.finish(end_time, self.config.capture_content));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn send(&mut self) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        tokio::task::block_in_place(|| {
+            tokio::runtime::Handle::current().block_on(self.send_async())
+        })
+    }
+
+    fn is_empty(&self) -> bool {
+        self.finished.is_empty()
+    }
+}
+
+pub fn create_otlp_observer() -> Option<ObservationLayer> {
+    let config = OtlpConfig::from_env()?;
+    let batch_manager = Arc::new(Mutex::new(OtlpBatchManager::new(config)));
+
+    if !cfg!(test) {
+        OtlpBatchManager::spawn_sender(batch_manager.clone());
+    }
+
+    Some(ObservationLayer {
+        batch_manager,
+        span_tracker: Arc::new(Mutex::new(SpanTracker::new())),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use opentelemetry_proto::tonic::collector::trace::v1::trace_service_server::{
+        TraceService, TraceServiceServer,
+    };
+    use opentelemetry_proto::tonic::collector::trace::v1::ExportTraceServiceResponse;
+    use serde_json::json;
+    use tokio_stream::wrappers::TcpListenerStream;
+    use wiremock::matchers::{header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    const TRACE_ID: &str = "5b8aa5a2-d2c5-4d8b-9c4e-4d5a8c3b2a10";
+    const REPLY_ID: &str = "0f8fad5b-d9cb-469f-a165-70867728950e";
+    const COMPLETE_ID: &str = "7c9e6679-7425-40de-944b-e07fc1f90ae7";
+    const TOOL_ID: &str = "e4eaaaf2-d142-11e1-b3e4-080027620cdd";
+
+    /// Record a reply span with a provider call and a tool call inside it
+    fn record_spans(manager: &mut OtlpBatchManager) {
+        let now = Utc::now().to_rfc3339();
+        let create = |id: &str, parent: Option<&str>, name: &str, metadata: Value| {
+            json!({
+                "id": id,
+                "traceId": TRACE_ID,
+                "type": "SPAN",
+                "name": name,
+                "startTime": now,
+                "parentObservationId": parent,
+                "metadata": metadata,
+                "level": "DEFAULT"
+            })
+        };
+        let end =
+            |id: &str| json!({ "id": id, "type": "SPAN", "endTime": Utc::now().to_rfc3339() });
+
+        manager.add_event(
+            "observation-create",
+            create(REPLY_ID, None, "reply", json!({ "user_message": "hi" })),
+        );
+        manager.add_event(
+            "observation-create",
+            create(COMPLETE_ID, Some(REPLY_ID), "complete", json!({})),
+        );
+        manager.add_event(
+            "span-update",
+            json!({
+                "id": COMPLETE_ID,
+                "input": "{\"messages\": []}",
+                "output": "{\"content\": \"hello\"}",
+                "metadata": {
+                    "model_config": "{\"model_name\": \"gpt-4o\"}",
+                    "input_tokens": "12",
+                    "output_tokens": "34",
+                    "total_tokens": "46"
+                }
+            }),
+        );
+        manager.add_event("observation-update", end(COMPLETE_ID));
+        manager.add_event(
+            "observation-create",
+            create(
+                TOOL_ID,
+                Some(REPLY_ID),
+                "dispatch_tool_call",
+                json!({ "tool_name": "developer__shell", "tool_call_id": "call_1" }),
+            ),
+        );
+        manager.add_event("observation-update", end(TOOL_ID));
+        manager.add_event("observation-update", end(REPLY_ID));
+    }
+
+    fn attribute<'a>(span: &'a Span, key: &str) -> Option<&'a any_value::Value> {
+        span.attributes
+            .iter()
+            .find(|kv| kv.key == key)
+            .and_then(|kv| kv.value.as_ref())
+            .and_then(|value| value.value.as_ref())
+    }
+
+    fn string(value: &str) -> any_value::Value {
+        any_value::Value::StringValue(value.to_string())
+    }
+
+    fn assert_exported(request: &ExportTraceServiceRequest) {
+        let resource_spans = &request.resource_spans[0];
+        let resource = resource_spans.resource.as_ref().unwrap();
+        assert!(resource
+            .attributes
+            .iter()
+            .any(|kv| kv.key == "service.name"));
+
+        let spans = &resource_spans.scope_spans[0].spans;
+        assert_eq!(spans.len(), 3);
+        let chat = spans.iter().find(|s| s.name == "chat gpt-4o").unwrap();
+        let tool = spans
+            .iter()
+            .find(|s| s.name == "execute_tool developer__shell")
+            .unwrap();
+        let reply = spans.iter().find(|s| s.name == "reply").unwrap();
+
+        assert_eq!(chat.kind, SpanKind::Client as i32);
+        assert_eq!(
+            attribute(chat, "gen_ai.request.model"),
+            Some(&string("gpt-4o"))
+        );
+        assert_eq!(
+            attribute(chat, "gen_ai.usage.input_tokens"),
+            Some(&any_value::Value::IntValue(12))
+        );
+        assert_eq!(
+            attribute(chat, "gen_ai.usage.output_tokens"),
+            Some(&any_value::Value::IntValue(34))
+        );
+        assert_eq!(
+            attribute(tool, "gen_ai.tool.name"),
+            Some(&string("developer__shell"))
+        );
+        assert_eq!(
+            attribute(tool, "gen_ai.tool.call.id"),
+            Some(&string("call_1"))
+        );
+
+        // Prompts and completions are not exported unless content capture is enabled
+        assert!(attribute(chat, "gen_ai.input.messages").is_none());
+        assert!(attribute(chat, "goose.input").is_none());
+        assert!(attribute(reply, "goose.user_message").is_none());
+
+        assert_eq!(chat.trace_id, trace_id(TRACE_ID));
+        assert_eq!(chat.trace_id.len(), 16);
+        assert_eq!(chat.parent_span_id, reply.span_id);
+        assert_eq!(tool.parent_span_id, reply.span_id);
+        assert!(reply.parent_span_id.is_empty());
+        assert!(chat.end_time_unix_nano >= chat.start_time_unix_nano);
+    }
+
+    #[test]
+    fn test_spans_map_to_gen_ai_conventions() {
+        let mut manager = OtlpBatchManager::new(OtlpConfig::local(OtlpProtocol::HttpProtobuf));
+        record_spans(&mut manager);
+        assert_exported(&manager.export_request());
+    }
+
+    #[test]
+    fn test_content_capture() {
+        let mut manager = OtlpBatchManager::new(OtlpConfig {
+            capture_content: true,
+            ..OtlpConfig::local(OtlpProtocol::HttpProtobuf)
+        });
+        record_spans(&mut manager);
+
+        let chat = manager
+            .finished
+            .iter()
+            .find(|s| s.name == "chat gpt-4o")
+            .unwrap();
+        assert_eq!(
+            attribute(chat, "gen_ai.output.messages"),
+            Some(&string("{\"content\": \"hello\"}"))
+        );
+    }
+
+    #[test]
+    fn test_parse_key_values() {
+        assert_eq!(
+            parse_key_values("api-key=secret%20value, x-team = goose,invalid"),
+            vec![
+                ("api-key".to_string(), "secret value".to_string()),
+                ("x-team".to_string(), "goose".to_string()),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_http_export_to_collector() {
+        let collector = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/traces"))
+            .and(header("content-type", "application/x-protobuf"))
+            .and(header("x-api-key", "secret"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&collector)
+            .await;
+
+        let mut manager = OtlpBatchManager::new(OtlpConfig {
+            endpoint: format!("{}/v1/traces", collector.uri()),
+            headers: vec![("x-api-key".to_string(), "secret".to_string())],
+            ..OtlpConfig::local(OtlpProtocol::HttpProtobuf)
+        });
+        record_spans(&mut manager);
+        manager.send_async().await.unwrap();
+        assert!(manager.is_empty());
+
+        let received = collector.received_requests().await.unwrap();
+        let request = ExportTraceServiceRequest::decode(received[0].body.as_slice()).unwrap();
+        assert_exported(&request);
+    }
+
+    #[tokio::test]
+    async fn test_http_export_failure_keeps_spans() {
+        let collector = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(503))
+            .mount(&collector)
+            .await;
+
+        let mut manager = OtlpBatchManager::new(OtlpConfig {
+            endpoint: format!("{}/v1/traces", collector.uri()),
+            ..OtlpConfig::local(OtlpProtocol::HttpProtobuf)
+        });
+        record_spans(&mut manager);
+        assert!(manager.send_async().await.is_err());
+        assert_eq!(manager.finished.len(), 3);
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn test_provider_stream_is_exported() {
+        use crate::message::Message;
+        use crate::model::ModelConfig;
+        use crate::providers::base::{Provider, ProviderStreamItem};
+        use crate::providers::openai::OpenAiProvider;
+        use futures::StreamExt;
+        use tracing_subscriber::layer::SubscriberExt;
+
+        let server = MockServer::start().await;
+        let chunks = [
+            json!({ "choices": [{ "index": 0, "delta": { "role": "assistant", "content": "Hel" } }] }),
+            json!({ "choices": [{ "index": 0, "delta": { "content": "lo" }, "finish_reason": "stop" }] }),
+            json!({
+                "choices": [],
+                "usage": { "prompt_tokens": 12, "completion_tokens": 34, "total_tokens": 46 }
+            }),
+        ];
+        let body = chunks
+            .iter()
+            .map(|chunk| format!("data: {}\n\n", chunk))
+            .chain(std::iter::once("data: [DONE]\n\n".to_string()))
+            .collect::<String>();
+        Mock::given(method("POST"))
+            .and(path("/v1/chat/completions"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("content-type", "text/event-stream")
+                    .set_body_string(body),
+            )
+            .mount(&server)
+            .await;
+
+        std::env::set_var("OPENAI_API_KEY", "test-key");
+        std::env::set_var("OPENAI_HOST", server.uri());
+        let provider = OpenAiProvider::from_env(ModelConfig::new("gpt-4o".to_string()));
+        std::env::remove_var("OPENAI_API_KEY");
+        std::env::remove_var("OPENAI_HOST");
+        let provider = provider.unwrap();
+
+        let manager = Arc::new(Mutex::new(OtlpBatchManager::new(OtlpConfig::local(
+            OtlpProtocol::HttpProtobuf,
+        ))));
+        let layer = ObservationLayer {
+            batch_manager: manager.clone(),
+            span_tracker: Arc::new(Mutex::new(SpanTracker::new())),
+        };
+        let _guard = tracing::subscriber::set_default(tracing_subscriber::registry().with(layer));
+
+        let mut stream = provider
+            .stream(
+                "You are a helpful assistant.",
+                &[Message::user().with_text("hi")],
+                &[],
+            )
+            .await
+            .unwrap();
+        while let Some(item) = stream.next().await {
+            if let ProviderStreamItem::Complete(..) = item.unwrap() {
+                break;
+            }
+        }
+        drop(stream);
+        // The layer hands spans to the batch manager from spawned tasks
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        let manager = manager.lock().await;
+        let chat = manager
+            .finished
+            .iter()
+            .find(|s| s.name == "chat gpt-4o")
+            .unwrap();
+        assert_eq!(chat.kind, SpanKind::Client as i32);
+        assert_eq!(
+            attribute(chat, "gen_ai.usage.input_tokens"),
+            Some(&any_value::Value::IntValue(12))
+        );
+        assert_eq!(
+            attribute(chat, "gen_ai.usage.output_tokens"),
+            Some(&any_value::Value::IntValue(34))
+        );
+    }
+
+    #[derive(Default)]
+    struct Collector {
+        requests: Arc<Mutex<Vec<ExportTraceServiceRequest>>>,
+    }
+
+    #[tonic::async_trait]
+    impl TraceService for Collector {
+        async fn export(
+            &self,
+            request: tonic::Request<ExportTraceServiceRequest>,
+        ) -> Result<tonic::Response<ExportTraceServiceResponse>, tonic::Status> {
+            self.requests.lock().await.push(request.into_inner());
+            Ok(tonic::Response::new(ExportTraceServiceResponse::default()))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_grpc_export_to_collector() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let collector = Collector::default();
+        let requests = collector.requests.clone();
+        tokio::spawn(
+            tonic::transport::Server::builder()
+                .add_service(TraceServiceServer::new(collector))
+                .serve_with_incoming(TcpListenerStream::new(listener)),
+        );
+
+        let mut manager = OtlpBatchManager::new(OtlpConfig {
+            endpoint: format!("http://{}", address),
+            ..OtlpConfig::local(OtlpProtocol::Grpc)
+        });
+        record_spans(&mut manager);
+        manager.send_async().await.unwrap();
+
+        let requests = requests.lock().await;
+        assert_eq!(requests.len(), 1);
+        assert_exported(&requests[0]);
+    }
+}
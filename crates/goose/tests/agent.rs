@@ -133,6 +133,12 @@ async fn run_truncate_test(
     while let Some(response_result) = reply_stream.next().await {
         match response_result {
             Ok(AgentEvent::Message(response)) => responses.push(response),
+            Ok(AgentEvent::MessageDelta(_)) => {}
+            Ok(AgentEvent::HistoryReplaced(_)) => {}
+            Ok(AgentEvent::Subagent(_)) => {}
+            Ok(AgentEvent::ToolProgress(progress)) => {
+                println!("Tool progress: {progress:?}");
+            }
             Ok(AgentEvent::McpNotification(n)) => {
                 println!("MCP Notification: {n:?}");
             }
@@ -1,7 +1,9 @@
 use mcp_core::protocol::{
-    CallToolResult, GetPromptResult, Implementation, InitializeResult, JsonRpcError,
-    JsonRpcMessage, JsonRpcNotification, JsonRpcRequest, JsonRpcResponse, ListPromptsResult,
-    ListResourcesResult, ListToolsResult, ReadResourceResult, ServerCapabilities, METHOD_NOT_FOUND,
+    CallToolResult, CreateMessageParams, CreateMessageResult, ErrorData, GetPromptResult,
+    Implementation, InitializeResult, JsonRpcError, JsonRpcMessage, JsonRpcNotification,
+    JsonRpcRequest, JsonRpcResponse, ListPromptsResult, ListResourcesResult, ListRootsResult,
+    ListToolsResult, ReadResourceResult, Root, ServerCapabilities, INVALID_PARAMS,
+    METHOD_NOT_FOUND,
 };
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
@@ -10,7 +12,8 @@ use std::sync::{
     Arc,
 };
 use thiserror::Error;
-use tokio::sync::{mpsc, Mutex};
+use tokio::sync::{mpsc, Mutex, RwLock};
+use tokio::task::JoinHandle;
 use tower::{timeout::TimeoutLayer, Layer, Service, ServiceExt};
 
 use crate::{McpService, TransportHandle};
@@ -68,7 +71,29 @@ pub struct ClientInfo {
 
 #[derive(Serialize, Deserialize, Default)]
 pub struct ClientCapabilities {
-    // Add fields as needed. For now, empty capabilities are fine.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub roots: Option<RootsCapability>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sampling: Option<SamplingCapability>,
+}
+
+#[derive(Serialize, Deserialize, Default, Clone)]
+pub struct RootsCapability {
+    /// Whether the client sends `notifications/roots/list_changed`
+    #[serde(rename = "listChanged", skip_serializing_if = "Option::is_none")]
+    pub list_changed: Option<bool>,
+}
+
+#[derive(Serialize, Deserialize, Default, Clone)]
+pub struct SamplingCapability {}
+
+/// Answers `sampling/createMessage` requests that a server sends to the client
+#[async_trait::async_trait]
+pub trait SamplingHandler: Send + Sync {
+    async fn create_message(
+        &self,
+        params: CreateMessageParams,
+    ) -> Result<CreateMessageResult, ErrorData>;
 }
 
 #[derive(Serialize, Deserialize)]
@@ -99,11 +124,24 @@ pub trait McpClientTrait: Send + Sync {
 
     async fn call_tool(&self, name: &str, arguments: Value) -> Result<CallToolResult, Error>;
 
+    /// Call a tool, tagging the server's `notifications/progress` for it with `progress_token`
+    async fn call_tool_with_progress(
+        &self,
+        name: &str,
+        arguments: Value,
+        _progress_token: &str,
+    ) -> Result<CallToolResult, Error> {
+        self.call_tool(name, arguments).await
+    }
+
     async fn list_prompts(&self, next_cursor: Option<String>) -> Result<ListPromptsResult, Error>;
 
     async fn get_prompt(&self, name: &str, arguments: Value) -> Result<GetPromptResult, Error>;
 
     async fn subscribe(&self) -> mpsc::Receiver<JsonRpcMessage>;
+
+    /// Replace the roots answered to `roots/list`, notifying the server once initialized
+    async fn set_roots(&self, roots: Vec<Root>) -> Result<(), Error>;
 }
 
 /// The MCP client is the interface for MCP operations.
@@ -116,6 +154,69 @@ where
     server_capabilities: Option<ServerCapabilities>,
     server_info: Option<Implementation>,
     notification_subscribers: Arc<Mutex<Vec<mpsc::Sender<JsonRpcMessage>>>>,
+    roots: Arc<RwLock<Vec<Root>>>,
+    transport: T,
+    /// Reads incoming messages; it holds its own transport handle, so it is stopped on drop
+    /// to let the transport shut down once the client is gone
+    receive_task: JoinHandle<()>,
+}
+
+impl<T> Drop for McpClient<T>
+where
+    T: TransportHandle + Send + Sync + 'static,
+{
+    fn drop(&mut self) {
+        self.receive_task.abort();
+    }
+}
+
+/// Sends `notifications/cancelled` for a request if it is dropped before its response arrives,
+/// e.g. when the caller's future is dropped or the request times out
+struct CancelOnDrop<T>
+where
+    T: TransportHandle + Send + Sync + 'static,
+{
+    transport: Option<T>,
+    request_id: u64,
+}
+
+impl<T> CancelOnDrop<T>
+where
+    T: TransportHandle + Send + Sync + 'static,
+{
+    fn disarm(&mut self) {
+        self.transport = None;
+    }
+}
+
+impl<T> Drop for CancelOnDrop<T>
+where
+    T: TransportHandle + Send + Sync + 'static,
+{
+    fn drop(&mut self) {
+        let transport = match self.transport.take() {
+            Some(transport) => transport,
+            None => return,
+        };
+        let handle = match tokio::runtime::Handle::try_current() {
+            Ok(handle) => handle,
+            Err(_) => return,
+        };
+
+        let notification = JsonRpcMessage::Notification(JsonRpcNotification {
+            jsonrpc: "2.0".to_string(),
+            method: "notifications/cancelled".to_string(),
+            params: Some(json!({
+                "requestId": self.request_id,
+                "reason": "Request cancelled by the client",
+            })),
+        });
+        handle.spawn(async move {
+            if let Err(e) = transport.send(notification).await {
+                tracing::debug!("Failed to send cancellation: {:?}", e);
+            }
+        });
+    }
 }
 
 impl<T> McpClient<T>
@@ -123,13 +224,26 @@ where
     T: TransportHandle + Send + Sync + 'static,
 {
     pub async fn connect(transport: T, timeout: std::time::Duration) -> Result<Self, Error> {
+        Self::connect_with_sampling(transport, timeout, None).await
+    }
+
+    /// Connect, answering the server's sampling requests with `sampling_handler` if one is given.
+    /// Pass `ClientCapabilities { sampling: Some(..) }` to `initialize` to advertise it.
+    pub async fn connect_with_sampling(
+        transport: T,
+        timeout: std::time::Duration,
+        sampling_handler: Option<Arc<dyn SamplingHandler>>,
+    ) -> Result<Self, Error> {
         let service = McpService::new(transport.clone());
+        let transport_ptr = transport.clone();
         let service_ptr = service.clone();
         let notification_subscribers =
             Arc::new(Mutex::new(Vec::<mpsc::Sender<JsonRpcMessage>>::new()));
         let subscribers_ptr = notification_subscribers.clone();
+        let roots = Arc::new(RwLock::new(Vec::new()));
+        let roots_ptr = roots.clone();
 
-        tokio::spawn(async move {
+        let receive_task = tokio::spawn(async move {
             loop {
                 match transport.receive().await {
                     Ok(message) => {
@@ -138,6 +252,16 @@ where
                             JsonRpcMessage::Response(JsonRpcResponse { id: Some(id), .. }) => {
                                 service_ptr.respond(&id.to_string(), Ok(message)).await;
                             }
+                            JsonRpcMessage::Request(request) => {
+                                // Requests from the server are answered off the receive loop,
+                                // sampling in particular can take a while
+                                tokio::spawn(Self::handle_server_request(
+                                    transport.clone(),
+                                    sampling_handler.clone(),
+                                    roots_ptr.clone(),
+                                    request,
+                                ));
+                            }
                             _ => {
                                 let mut subs = subscribers_ptr.lock().await;
                                 subs.retain(|sub| sub.try_send(message.clone()).is_ok());
@@ -162,21 +286,94 @@ where
             server_capabilities: None,
             server_info: None,
             notification_subscribers,
+            roots,
+            transport: transport_ptr,
+            receive_task,
         })
     }
 
+    /// Answer a request the server sent to us, replying over the same transport
+    async fn handle_server_request(
+        transport: T,
+        sampling_handler: Option<Arc<dyn SamplingHandler>>,
+        roots: Arc<RwLock<Vec<Root>>>,
+        request: JsonRpcRequest,
+    ) {
+        let result = match (request.method.as_str(), sampling_handler) {
+            ("ping", _) => Ok(json!({})),
+            ("roots/list", _) => Ok(json!(ListRootsResult {
+                roots: roots.read().await.clone(),
+            })),
+            ("sampling/createMessage", Some(handler)) => {
+                match serde_json::from_value::<CreateMessageParams>(
+                    request.params.unwrap_or(Value::Null),
+                ) {
+                    Ok(params) => handler.create_message(params).await.and_then(|result| {
+                        serde_json::to_value(result).map_err(|e| ErrorData {
+                            code: INVALID_PARAMS,
+                            message: e.to_string(),
+                            data: None,
+                        })
+                    }),
+                    Err(e) => Err(ErrorData {
+                        code: INVALID_PARAMS,
+                        message: format!("Invalid sampling request: {}", e),
+                        data: None,
+                    }),
+                }
+            }
+            (method, _) => Err(ErrorData {
+                code: METHOD_NOT_FOUND,
+                message: format!("Client does not support '{}'", method),
+                data: None,
+            }),
+        };
+
+        let reply = match result {
+            Ok(result) => JsonRpcMessage::Response(JsonRpcResponse {
+                jsonrpc: "2.0".to_string(),
+                id: request.id,
+                result: Some(result),
+                error: None,
+            }),
+            Err(error) => JsonRpcMessage::Error(JsonRpcError {
+                jsonrpc: "2.0".to_string(),
+                id: request.id,
+                error,
+            }),
+        };
+
+        if let Err(e) = transport.send(reply).await {
+            tracing::error!("Failed to reply to server request: {:?}", e);
+        }
+    }
+
     /// Send a JSON-RPC request and check we don't get an error response.
     async fn send_request<R>(&self, method: &str, params: Value) -> Result<R, Error>
     where
         R: for<'de> Deserialize<'de>,
     {
-        let mut service = self.service.lock().await;
-        service.ready().await.map_err(|_| Error::NotReady)?;
+        self.send_request_with_progress(method, params, None).await
+    }
+
+    /// Send a JSON-RPC request with the given progress token, defaulting to one derived from the id.
+    /// If the returned future is dropped before the response arrives the server is told to cancel.
+    async fn send_request_with_progress<R>(
+        &self,
+        method: &str,
+        params: Value,
+        progress_token: Option<&str>,
+    ) -> Result<R, Error>
+    where
+        R: for<'de> Deserialize<'de>,
+    {
         let id = self.next_id.fetch_add(1, Ordering::SeqCst);
 
         let mut params = params.clone();
         params["_meta"] = json!({
-            "progressToken": format!("prog-{}", id),
+            "progressToken": progress_token
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("prog-{}", id)),
         });
 
         let request = JsonRpcMessage::Request(JsonRpcRequest {
@@ -186,26 +383,39 @@ where
             params: Some(params),
         });
 
-        let response_msg = service
-            .call(request)
-            .await
-            .map_err(|e| Error::McpServerError {
-                server: self
-                    .server_info
-                    .as_ref()
-                    .map(|s| s.name.clone())
-                    .unwrap_or("".to_string()),
-                method: method.to_string(),
-                // we don't need include params because it can be really large
-                source: Box::<Error>::new(e.into()),
-            })?;
+        // The service is only locked to send the request, so other requests can be sent while
+        // this one waits for its response
+        let response = {
+            let mut service = self.service.lock().await;
+            service.ready().await.map_err(|_| Error::NotReady)?;
+            service.call(request)
+        };
+
+        let mut cancel_guard = CancelOnDrop {
+            transport: Some(self.transport.clone()),
+            request_id: id,
+        };
+        let response_msg = response.await.map_err(|e| Error::McpServerError {
+            server: self
+                .server_info
+                .as_ref()
+                .map(|s| s.name.clone())
+                .unwrap_or("".to_string()),
+            method: method.to_string(),
+            // we don't need include params because it can be really large
+            source: Box::<Error>::new(e.into()),
+        })?;
+        cancel_guard.disarm();
 
         match response_msg {
             JsonRpcMessage::Response(JsonRpcResponse {
-                id, result, error, ..
+                id: response_id,
+                result,
+                error,
+                ..
             }) => {
                 // Verify id matches
-                if id != Some(self.next_id.load(Ordering::SeqCst) - 1) {
+                if response_id != Some(id) {
                     return Err(Error::UnexpectedResponse(
                         "id mismatch for JsonRpcResponse".to_string(),
                     ));
@@ -221,8 +431,12 @@ where
                     Err(Error::UnexpectedResponse("missing result".to_string()))
                 }
             }
-            JsonRpcMessage::Error(JsonRpcError { id, error, .. }) => {
-                if id != Some(self.next_id.load(Ordering::SeqCst) - 1) {
+            JsonRpcMessage::Error(JsonRpcError {
+                id: response_id,
+                error,
+                ..
+            }) => {
+                if response_id != Some(id) {
                     return Err(Error::UnexpectedResponse(
                         "id mismatch for JsonRpcError".to_string(),
                     ));
@@ -273,6 +487,31 @@ where
     fn completed_initialization(&self) -> bool {
         self.server_capabilities.is_some()
     }
+
+    async fn call_tool_inner(
+        &self,
+        name: &str,
+        arguments: Value,
+        progress_token: Option<&str>,
+    ) -> Result<CallToolResult, Error> {
+        if !self.completed_initialization() {
+            return Err(Error::NotInitialized);
+        }
+        // If tools is not supported, return an error
+        if self.server_capabilities.as_ref().unwrap().tools.is_none() {
+            return Err(Error::RpcError {
+                code: METHOD_NOT_FOUND,
+                message: "Server does not support 'tools' capability".to_string(),
+            });
+        }
+
+        let params = serde_json::json!({ "name": name, "arguments": arguments });
+
+        // TODO ERROR: check that if there is an error, we send back is_error: true with msg
+        // https://modelcontextprotocol.io/docs/concepts/tools#error-handling-2
+        self.send_request_with_progress("tools/call", params, progress_token)
+            .await
+    }
 }
 
 #[async_trait::async_trait]
@@ -374,22 +613,17 @@ where
     }
 
     async fn call_tool(&self, name: &str, arguments: Value) -> Result<CallToolResult, Error> {
-        if !self.completed_initialization() {
-            return Err(Error::NotInitialized);
-        }
-        // If tools is not supported, return an error
-        if self.server_capabilities.as_ref().unwrap().tools.is_none() {
-            return Err(Error::RpcError {
-                code: METHOD_NOT_FOUND,
-                message: "Server does not support 'tools' capability".to_string(),
-            });
-        }
-
-        let params = serde_json::json!({ "name": name, "arguments": arguments });
+        self.call_tool_inner(name, arguments, None).await
+    }
 
-        // TODO ERROR: check that if there is an error, we send back is_error: true with msg
-        // https://modelcontextprotocol.io/docs/concepts/tools#error-handling-2
-        self.send_request("tools/call", params).await
+    async fn call_tool_with_progress(
+        &self,
+        name: &str,
+        arguments: Value,
+        progress_token: &str,
+    ) -> Result<CallToolResult, Error> {
+        self.call_tool_inner(name, arguments, Some(progress_token))
+            .await
     }
 
     async fn list_prompts(&self, next_cursor: Option<String>) -> Result<ListPromptsResult, Error> {
@@ -435,4 +669,21 @@ where
         self.notification_subscribers.lock().await.push(tx);
         rx
     }
+
+    async fn set_roots(&self, roots: Vec<Root>) -> Result<(), Error> {
+        {
+            let mut current = self.roots.write().await;
+            if *current == roots {
+                return Ok(());
+            }
+            *current = roots;
+        }
+
+        // Before initialization the server reads the roots when it first asks for them
+        if !self.completed_initialization() {
+            return Ok(());
+        }
+        self.send_notification("notifications/roots/list_changed", json!({}))
+            .await
+    }
 }
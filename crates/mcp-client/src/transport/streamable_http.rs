@@ -0,0 +1,590 @@
+use crate::transport::Error;
+use async_trait::async_trait;
+use futures::StreamExt;
+use mcp_core::protocol::{ErrorData, JsonRpcMessage, JsonRpcResponse};
+use reqwest::{Client as HttpClient, RequestBuilder, Response, StatusCode};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::{mpsc, Mutex, RwLock};
+use tokio::task::JoinHandle;
+use tokio::time::Duration;
+use tracing::warn;
+
+use super::{serialize_and_send, Transport, TransportHandle};
+
+const SESSION_ID_HEADER: &str = "Mcp-Session-Id";
+const LAST_EVENT_ID_HEADER: &str = "Last-Event-ID";
+
+// How many times we try to resume an interrupted event stream before giving up
+const MAX_RESUME_ATTEMPTS: usize = 3;
+const RESUME_DELAY_MS: u64 = 500;
+
+// JSON-RPC error code used when the transport itself fails a request
+const TRANSPORT_ERROR_CODE: i32 = -32000;
+
+/// A single event parsed from a `text/event-stream` body
+#[derive(Debug, Default, Clone, PartialEq)]
+struct SseEvent {
+    id: Option<String>,
+    event: Option<String>,
+    data: String,
+}
+
+/// Incrementally parses server-sent events out of arbitrary byte chunks
+#[derive(Default)]
+struct SseParser {
+    buffer: Vec<u8>,
+    current: SseEvent,
+    has_data: bool,
+}
+
+impl SseParser {
+    /// Feed a chunk of bytes, returning every event completed by it
+    fn push(&mut self, chunk: &[u8]) -> Vec<SseEvent> {
+        self.buffer.extend_from_slice(chunk);
+        let mut events = Vec::new();
+
+        while let Some(pos) = self.buffer.iter().position(|b| *b == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=pos).collect();
+            let line = String::from_utf8_lossy(&line);
+            let line = line.trim_end_matches(['\n', '\r']);
+
+            if line.is_empty() {
+                // A blank line dispatches the event
+                if self.has_data {
+                    events.push(std::mem::take(&mut self.current));
+                } else {
+                    self.current = SseEvent::default();
+                }
+                self.has_data = false;
+                continue;
+            }
+
+            if line.starts_with(':') {
+                continue;
+            }
+
+            let (field, value) = match line.split_once(':') {
+                Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+                None => (line, ""),
+            };
+
+            match field {
+                "data" => {
+                    if self.has_data {
+                        self.current.data.push('\n');
+                    }
+                    self.current.data.push_str(value);
+                    self.has_data = true;
+                }
+                "event" => self.current.event = Some(value.to_string()),
+                "id" => self.current.id = Some(value.to_string()),
+                _ => {}
+            }
+        }
+
+        events
+    }
+}
+
+/// Build a JSON-RPC error response for a request the transport could not deliver
+fn transport_error_response(message_str: &str, error: &Error) -> Option<JsonRpcMessage> {
+    let id = serde_json::from_str::<Value>(message_str)
+        .ok()?
+        .get("id")?
+        .as_u64()?;
+    Some(JsonRpcMessage::Response(JsonRpcResponse {
+        jsonrpc: "2.0".to_string(),
+        id: Some(id),
+        result: None,
+        error: Some(ErrorData {
+            code: TRANSPORT_ERROR_CODE,
+            message: error.to_string(),
+            data: None,
+        }),
+    }))
+}
+
+fn is_initialized_notification(message_str: &str) -> bool {
+    serde_json::from_str::<Value>(message_str)
+        .ok()
+        .and_then(|v| v.get("method").and_then(|m| m.as_str()).map(str::to_string))
+        .is_some_and(|method| method == "notifications/initialized")
+}
+
+/// Shared state for talking to a single Streamable HTTP endpoint
+#[derive(Clone)]
+struct StreamableHttpConnection {
+    mcp_url: String,
+    http_client: HttpClient,
+    headers: HashMap<String, String>,
+    /// The session id assigned by the server on initialization, if any
+    session_id: Arc<RwLock<Option<String>>>,
+}
+
+impl StreamableHttpConnection {
+    async fn request(&self, mut builder: RequestBuilder) -> RequestBuilder {
+        for (key, value) in &self.headers {
+            builder = builder.header(key, value);
+        }
+        if let Some(session_id) = self.session_id.read().await.as_ref() {
+            builder = builder.header(SESSION_ID_HEADER, session_id);
+        }
+        builder
+    }
+
+    /// Open a GET stream, either to listen for server messages or to resume an interrupted one
+    async fn get_stream(&self, last_event_id: Option<&str>) -> Result<Response, Error> {
+        let mut builder = self
+            .request(
+                self.http_client
+                    .get(&self.mcp_url)
+                    .header("Accept", "text/event-stream"),
+            )
+            .await;
+        if let Some(id) = last_event_id {
+            builder = builder.header(LAST_EVENT_ID_HEADER, id);
+        }
+        builder.send().await.map_err(|e| Error::HttpError {
+            status: 0,
+            message: e.to_string(),
+        })
+    }
+
+    /// Forward every JSON-RPC message in an event stream to `sender`.
+    /// Returns true if a response was among them.
+    async fn consume_stream(
+        response: Response,
+        sender: &mpsc::Sender<JsonRpcMessage>,
+        last_event_id: &mut Option<String>,
+    ) -> bool {
+        let mut parser = SseParser::default();
+        let mut stream = response.bytes_stream();
+        let mut saw_response = false;
+
+        while let Some(chunk) = stream.next().await {
+            let chunk = match chunk {
+                Ok(chunk) => chunk,
+                Err(e) => {
+                    warn!("Error reading Streamable HTTP event stream: {e}");
+                    break;
+                }
+            };
+
+            for event in parser.push(&chunk) {
+                if event.id.is_some() {
+                    *last_event_id = event.id.clone();
+                }
+                if event.data.is_empty() || event.event.as_deref().is_some_and(|e| e != "message") {
+                    continue;
+                }
+                match serde_json::from_str::<JsonRpcMessage>(&event.data) {
+                    Ok(message) => {
+                        if matches!(
+                            message,
+                            JsonRpcMessage::Response(_) | JsonRpcMessage::Error(_)
+                        ) {
+                            saw_response = true;
+                        }
+                        let _ = sender.send(message).await;
+                    }
+                    Err(err) => warn!("Failed to parse Streamable HTTP message: {err}"),
+                }
+            }
+        }
+
+        saw_response
+    }
+
+    /// Forward the JSON body of a response, which may be a single message or a batch
+    async fn consume_json(response: Response, sender: &mpsc::Sender<JsonRpcMessage>) {
+        let body = match response.json::<Value>().await {
+            Ok(body) => body,
+            Err(e) => {
+                warn!("Failed to read Streamable HTTP response body: {e}");
+                return;
+            }
+        };
+
+        let messages = match body {
+            Value::Array(items) => items,
+            other => vec![other],
+        };
+        for message in messages {
+            match serde_json::from_value::<JsonRpcMessage>(message) {
+                Ok(message) => {
+                    let _ = sender.send(message).await;
+                }
+                Err(err) => warn!("Failed to parse Streamable HTTP message: {err}"),
+            }
+        }
+    }
+
+    /// POST a single outgoing message, returning the server's reply once its headers arrive
+    async fn post(&self, message_str: &str) -> Result<Response, Error> {
+        let had_session = self.session_id.read().await.is_some();
+        let response = self
+            .request(
+                self.http_client
+                    .post(&self.mcp_url)
+                    .header("Content-Type", "application/json")
+                    .header("Accept", "application/json, text/event-stream"),
+            )
+            .await
+            .body(message_str.to_string())
+            .send()
+            .await
+            .map_err(|e| Error::HttpError {
+                status: 0,
+                message: e.to_string(),
+            })?;
+
+        if let Some(session_id) = response
+            .headers()
+            .get(SESSION_ID_HEADER)
+            .and_then(|v| v.to_str().ok())
+        {
+            *self.session_id.write().await = Some(session_id.to_string());
+        }
+
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response);
+        }
+
+        if status == StatusCode::NOT_FOUND && had_session {
+            // The server no longer knows our session, a new one has to be initialized
+            *self.session_id.write().await = None;
+            return Err(Error::HttpError {
+                status: status.as_u16(),
+                message: "MCP session expired".to_string(),
+            });
+        }
+        Err(Error::HttpError {
+            status: status.as_u16(),
+            message: status.to_string(),
+        })
+    }
+
+    /// Forward whatever the server replied to a POST with
+    async fn forward_reply(
+        &self,
+        message_str: String,
+        response: Response,
+        sender: mpsc::Sender<JsonRpcMessage>,
+    ) {
+        let status = response.status();
+
+        // 202 Accepted is the reply to notifications and responses, there is no body
+        if status == StatusCode::ACCEPTED {
+            return;
+        }
+
+        let is_event_stream = response
+            .headers()
+            .get("Content-Type")
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.starts_with("text/event-stream"));
+
+        if !is_event_stream {
+            Self::consume_json(response, &sender).await;
+            return;
+        }
+
+        let mut last_event_id = None;
+        if Self::consume_stream(response, &sender, &mut last_event_id).await {
+            return;
+        }
+
+        // The stream closed before the response arrived, resume it from the last event we saw
+        for _ in 0..MAX_RESUME_ATTEMPTS {
+            let id = match last_event_id.clone() {
+                Some(id) => id,
+                None => break,
+            };
+            tokio::time::sleep(Duration::from_millis(RESUME_DELAY_MS)).await;
+            match self.get_stream(Some(&id)).await {
+                Ok(response) if response.status().is_success() => {
+                    if Self::consume_stream(response, &sender, &mut last_event_id).await {
+                        return;
+                    }
+                }
+                Ok(response) => {
+                    warn!("Failed to resume event stream: {}", response.status());
+                    break;
+                }
+                Err(e) => warn!("Failed to resume event stream: {e}"),
+            }
+        }
+
+        let err = Error::HttpError {
+            status: status.as_u16(),
+            message: "Event stream closed before a response was received".to_string(),
+        };
+        if let Some(reply) = transport_error_response(&message_str, &err) {
+            let _ = sender.send(reply).await;
+        }
+    }
+
+    /// Listen for server-initiated messages on a GET stream, reconnecting when it drops
+    async fn listen(&self, sender: mpsc::Sender<JsonRpcMessage>) {
+        let mut last_event_id: Option<String> = None;
+        let mut failures = 0;
+
+        while failures < MAX_RESUME_ATTEMPTS {
+            match self.get_stream(last_event_id.as_deref()).await {
+                Ok(response) if response.status() == StatusCode::METHOD_NOT_ALLOWED => {
+                    // The server does not offer a standalone stream
+                    tracing::debug!("Server does not support a Streamable HTTP GET stream");
+                    return;
+                }
+                Ok(response) if response.status().is_success() => {
+                    failures = 0;
+                    Self::consume_stream(response, &sender, &mut last_event_id).await;
+                }
+                Ok(response) => {
+                    warn!("Streamable HTTP GET stream returned {}", response.status());
+                    failures += 1;
+                }
+                Err(e) => {
+                    warn!("Streamable HTTP GET stream failed: {e}");
+                    failures += 1;
+                }
+            }
+
+            if self.session_id.read().await.is_none() || sender.is_closed() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(RESUME_DELAY_MS)).await;
+        }
+
+        tracing::info!("Streamable HTTP GET stream ended.");
+    }
+
+    /// Ask the server to release the session, if one was established
+    async fn terminate(&self) -> Result<(), Error> {
+        let session_id = match self.session_id.write().await.take() {
+            Some(id) => id,
+            None => return Ok(()),
+        };
+
+        let mut builder = self
+            .http_client
+            .delete(&self.mcp_url)
+            .header(SESSION_ID_HEADER, session_id);
+        for (key, value) in &self.headers {
+            builder = builder.header(key, value);
+        }
+
+        match builder.send().await {
+            // Servers may refuse explicit termination
+            Ok(resp)
+                if resp.status().is_success()
+                    || resp.status() == StatusCode::METHOD_NOT_ALLOWED =>
+            {
+                Ok(())
+            }
+            Ok(resp) => Err(Error::HttpError {
+                status: resp.status().as_u16(),
+                message: resp.status().to_string(),
+            }),
+            Err(e) => Err(Error::HttpError {
+                status: 0,
+                message: e.to_string(),
+            }),
+        }
+    }
+}
+
+/// The Streamable HTTP actor that:
+/// - Sends outgoing messages as HTTP POSTs to the MCP endpoint, one at a time and in order.
+/// - Forwards JSON or event stream replies back to the handle.
+/// - Opens a GET stream for server-initiated messages once the session is initialized.
+/// - Terminates the session once every handle has been dropped.
+pub struct StreamableHttpActor {
+    /// Receives messages (requests/notifications) from the handle
+    receiver: mpsc::Receiver<String>,
+    /// Sends messages (responses) back to the handle
+    sender: mpsc::Sender<JsonRpcMessage>,
+    connection: StreamableHttpConnection,
+}
+
+impl StreamableHttpActor {
+    pub async fn run(mut self) {
+        let mut listener: Option<JoinHandle<()>> = None;
+
+        while let Some(message_str) = self.receiver.recv().await {
+            // Waiting for the reply headers before sending the next message keeps JSON-RPC
+            // messages in order, the body is read in its own task so a long-running stream
+            // does not block other requests
+            let response = match self.connection.post(&message_str).await {
+                Ok(response) => response,
+                Err(err) => {
+                    warn!("HTTP POST failed: {err}");
+                    if let Some(reply) = transport_error_response(&message_str, &err) {
+                        let _ = self.sender.send(reply).await;
+                    }
+                    continue;
+                }
+            };
+
+            let start_listening = is_initialized_notification(&message_str);
+            let connection = self.connection.clone();
+            let sender = self.sender.clone();
+            tokio::spawn(async move {
+                connection
+                    .forward_reply(message_str, response, sender)
+                    .await;
+            });
+
+            if start_listening && listener.is_none() {
+                let connection = self.connection.clone();
+                let sender = self.sender.clone();
+                listener = Some(tokio::spawn(async move { connection.listen(sender).await }));
+            }
+        }
+
+        if let Some(listener) = listener {
+            listener.abort();
+        }
+        if let Err(e) = self.connection.terminate().await {
+            warn!("Failed to terminate Streamable HTTP session: {e}");
+        }
+        tracing::info!("StreamableHttpActor shut down.");
+    }
+}
+
+#[derive(Clone)]
+pub struct StreamableHttpTransportHandle {
+    sender: mpsc::Sender<String>,
+    receiver: Arc<Mutex<mpsc::Receiver<JsonRpcMessage>>>,
+}
+
+#[async_trait::async_trait]
+impl TransportHandle for StreamableHttpTransportHandle {
+    async fn send(&self, message: JsonRpcMessage) -> Result<(), Error> {
+        serialize_and_send(&self.sender, message).await
+    }
+
+    async fn receive(&self) -> Result<JsonRpcMessage, Error> {
+        let mut receiver = self.receiver.lock().await;
+        receiver.recv().await.ok_or(Error::ChannelClosed)
+    }
+}
+
+#[derive(Clone)]
+pub struct StreamableHttpTransport {
+    env: HashMap<String, String>,
+    connection: StreamableHttpConnection,
+}
+
+/// The Streamable HTTP transport spawns a `StreamableHttpActor` on `start()`.
+impl StreamableHttpTransport {
+    pub fn new<S: Into<String>>(
+        mcp_url: S,
+        env: HashMap<String, String>,
+        headers: HashMap<String, String>,
+    ) -> Self {
+        Self {
+            env,
+            connection: StreamableHttpConnection {
+                mcp_url: mcp_url.into(),
+                http_client: HttpClient::new(),
+                headers,
+                session_id: Arc::new(RwLock::new(None)),
+            },
+        }
+    }
+}
+
+#[async_trait]
+impl Transport for StreamableHttpTransport {
+    type Handle = StreamableHttpTransportHandle;
+
+    async fn start(&self) -> Result<Self::Handle, Error> {
+        // Set environment variables
+        for (key, value) in &self.env {
+            std::env::set_var(key, value);
+        }
+
+        let (tx, rx) = mpsc::channel(32);
+        let (otx, orx) = mpsc::channel(32);
+
+        let actor = StreamableHttpActor {
+            receiver: rx,
+            sender: otx,
+            connection: self.connection.clone(),
+        };
+
+        tokio::spawn(actor.run());
+
+        Ok(StreamableHttpTransportHandle {
+            sender: tx,
+            receiver: Arc::new(Mutex::new(orx)),
+        })
+    }
+
+    async fn close(&self) -> Result<(), Error> {
+        // Let the server know it can release the session
+        self.connection.terminate().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sse_parser_splits_events_across_chunks() {
+        let mut parser = SseParser::default();
+
+        assert!(parser.push(b"id: 1\nevent: mess").is_empty());
+        let events =
+            parser.push(b"age\ndata: {\"a\":\ndata: 1}\n\n: keep-alive\n\ndata: x\r\n\r\n");
+
+        assert_eq!(
+            events,
+            vec![
+                SseEvent {
+                    id: Some("1".to_string()),
+                    event: Some("message".to_string()),
+                    data: "{\"a\":\n1}".to_string(),
+                },
+                SseEvent {
+                    id: None,
+                    event: None,
+                    data: "x".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_transport_error_response_only_for_requests() {
+        let err = Error::HttpError {
+            status: 404,
+            message: "MCP session expired".to_string(),
+        };
+
+        let reply =
+            transport_error_response(r#"{"jsonrpc":"2.0","id":7,"method":"tools/list"}"#, &err);
+        match reply {
+            Some(JsonRpcMessage::Response(JsonRpcResponse {
+                id: Some(7),
+                error: Some(error),
+                ..
+            })) => assert!(error.message.contains("session expired")),
+            other => panic!("unexpected reply: {:?}", other),
+        }
+
+        assert!(transport_error_response(
+            r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#,
+            &err
+        )
+        .is_none());
+        assert!(is_initialized_notification(
+            r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#
+        ));
+    }
+}
@@ -4,6 +4,7 @@ use crate::{
     prompt::{Prompt, PromptMessage},
     resource::Resource,
     resource::ResourceContents,
+    role::Role,
     tool::Tool,
 };
 use serde::{Deserialize, Serialize};
@@ -237,6 +238,57 @@ pub struct GetPromptResult {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct EmptyResult {}
 
+/// A directory or file the client exposes to servers, identified by a `file://` URI
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Root {
+    pub uri: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ListRootsResult {
+    pub roots: Vec<Root>,
+}
+
+/// A message in a `sampling/createMessage` request from a server
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct SamplingMessage {
+    pub role: Role,
+    pub content: Content,
+}
+
+/// Parameters of a `sampling/createMessage` request, asking the client for a completion
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateMessageParams {
+    pub messages: Vec<SamplingMessage>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model_preferences: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub system_prompt: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include_context: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f32>,
+    pub max_tokens: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop_sequences: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<Value>,
+}
+
+/// The client's reply to a `sampling/createMessage` request
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateMessageResult {
+    pub role: Role,
+    pub content: Content,
+    pub model: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop_reason: Option<String>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -264,6 +316,40 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_create_message_params_deserialization() {
+        let params: CreateMessageParams = serde_json::from_value(json!({
+            "messages": [
+                {"role": "user", "content": {"type": "text", "text": "Summarize this"}}
+            ],
+            "systemPrompt": "Be brief",
+            "maxTokens": 100
+        }))
+        .unwrap();
+
+        assert_eq!(params.messages.len(), 1);
+        assert_eq!(params.messages[0].role, Role::User);
+        assert_eq!(params.messages[0].content, Content::text("Summarize this"));
+        assert_eq!(params.system_prompt.as_deref(), Some("Be brief"));
+        assert_eq!(params.max_tokens, 100);
+
+        let result = CreateMessageResult {
+            role: Role::Assistant,
+            content: Content::text("Done"),
+            model: "gpt-4o".to_string(),
+            stop_reason: Some("endTurn".to_string()),
+        };
+        assert_eq!(
+            serde_json::to_value(result).unwrap(),
+            json!({
+                "role": "assistant",
+                "content": {"type": "text", "text": "Done"},
+                "model": "gpt-4o",
+                "stopReason": "endTurn"
+            })
+        );
+    }
+
     #[test]
     fn test_request_conversion() {
         let raw = JsonRpcRaw {
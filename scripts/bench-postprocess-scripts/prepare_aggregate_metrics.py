@@ -138,6 +138,8 @@ def extract_metrics_from_eval_file(eval_file, provider, model_name, session_file
                                 metrics[metric_name] = float(metric_value['Float'])
                             elif 'Bool' in metric_value:
                                 metrics[metric_name] = 1 if metric_value['Bool'] else 0
+                            elif 'LlmJudge' in metric_value:
+                                metrics[metric_name] = float(metric_value['LlmJudge']['score'])
                             # Skip string values for aggregation
                         elif isinstance(metric_value, (int, float)) and not isinstance(metric_value, bool):
                             metrics[metric_name] = metric_value
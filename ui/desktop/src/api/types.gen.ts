@@ -6,6 +6,27 @@ export type Annotations = {
     timestamp?: string;
 };
 
+/**
+ * Ends a reply that used up one of its budgets
+ */
+export type BudgetExceeded = {
+    budget: BudgetKind;
+    /**
+     * The configured spending limit in USD, for the session cost budget
+     */
+    cost_limit?: number | null;
+    /**
+     * The configured limit, in turns, tokens or seconds
+     */
+    limit?: number | null;
+    msg: string;
+};
+
+/**
+ * A per-reply limit on how much work the agent may do
+ */
+export type BudgetKind = 'turns' | 'inputTokens' | 'outputTokens' | 'time' | 'sessionCost';
+
 export type ConfigKey = {
     default?: string | null;
     name: string;
@@ -92,9 +113,38 @@ export type ExtensionConfig = {
      * The name used to identify this extension
      */
     name: string;
+    /**
+     * Extra directories exposed to the extension as MCP roots, besides the working directory
+     */
+    roots?: Array<string>;
     timeout?: number | null;
     type: 'sse';
     uri: string;
+} | {
+    /**
+     * Whether this extension is bundled with Goose
+     */
+    bundled?: boolean | null;
+    description?: string | null;
+    env_keys?: Array<string>;
+    envs?: Envs;
+    /**
+     * Extra HTTP headers sent with every request, e.g. for authentication
+     */
+    headers?: {
+        [key: string]: string;
+    };
+    /**
+     * The name used to identify this extension
+     */
+    name: string;
+    /**
+     * Extra directories exposed to the extension as MCP roots, besides the working directory
+     */
+    roots?: Array<string>;
+    timeout?: number | null;
+    type: 'streamable_http';
+    uri: string;
 } | {
     args: Array<string>;
     /**
@@ -109,6 +159,10 @@ export type ExtensionConfig = {
      * The name used to identify this extension
      */
     name: string;
+    /**
+     * Extra directories exposed to the extension as MCP roots, besides the working directory
+     */
+    roots?: Array<string>;
     timeout?: number | null;
     type: 'stdio';
 } | {
@@ -218,6 +272,8 @@ export type MessageContent = (TextContent & {
     type: 'contextLengthExceeded';
 }) | (SummarizationRequested & {
     type: 'summarizationRequested';
+}) | (BudgetExceeded & {
+    type: 'budgetExceeded';
 });
 
 /**
@@ -306,6 +362,25 @@ export type ResourceContents = {
     uri: string;
 };
 
+/**
+ * One attempt of a recipe run with success checks
+ */
+export type RetryAttempt = {
+    /**
+     * Attempt number, starting at 1
+     */
+    attempt: number;
+    /**
+     * Output of the first check that failed
+     */
+    failure?: string | null;
+    /**
+     * Index of the first message of the attempt in the session
+     */
+    message_index: number;
+    passed: boolean;
+};
+
 export type Role = 'user' | 'assistant';
 
 export type RunNowResponse = {
@@ -368,6 +443,11 @@ export type SessionListResponse = {
  * Metadata for a session, stored as the first line in the session file
  */
 export type SessionMetadata = {
+    /**
+     * The estimated cost of the session in USD, accumulated across all provider calls whose
+     * model has a known price
+     */
+    accumulated_cost?: number | null;
     /**
      * The number of input tokens used in the session. Accumulated across all messages.
      */
@@ -384,6 +464,10 @@ export type SessionMetadata = {
      * A short description of the session, typically 3 words or less
      */
     description: string;
+    /**
+     * Number of messages kept from the parent session when forking
+     */
+    fork_point?: number | null;
     /**
      * The number of input tokens used in the session. Retrieved from the provider's last usage.
      */
@@ -396,6 +480,14 @@ export type SessionMetadata = {
      * The number of output tokens used in the session. Retrieved from the provider's last usage.
      */
     output_tokens?: number | null;
+    /**
+     * ID of the session this one was forked from, if any
+     */
+    parent_session?: string | null;
+    /**
+     * Attempts of a recipe run with success checks, in order
+     */
+    retry_attempts?: Array<RetryAttempt> | null;
     /**
      * ID of the schedule that triggered this session, if any
      */
@@ -410,6 +502,36 @@ export type SessionMetadata = {
     working_dir: string;
 };
 
+/**
+ * A message that matched a session search
+ */
+export type SessionSearchHit = {
+    /**
+     * Description of the session
+     */
+    description: string;
+    /**
+     * ID of the session containing the message
+     */
+    id: string;
+    /**
+     * Position of the message in the session
+     */
+    message_index: number;
+    path: string;
+    /**
+     * The matching part of the message text
+     */
+    snippet: string;
+};
+
+export type SessionSearchResponse = {
+    /**
+     * Matching messages, most recent sessions first
+     */
+    results: Array<SessionSearchHit>;
+};
+
 export type SessionsQuery = {
     limit?: number;
 };
@@ -1221,6 +1343,42 @@ export type ListSessionsResponses = {
 
 export type ListSessionsResponse = ListSessionsResponses[keyof ListSessionsResponses];
 
+export type SearchSessionsData = {
+    body?: never;
+    path?: never;
+    query: {
+        /**
+         * Words that must all appear in a message
+         */
+        query: string;
+        /**
+         * Maximum number of messages to return
+         */
+        limit?: number;
+    };
+    url: '/sessions/search';
+};
+
+export type SearchSessionsErrors = {
+    /**
+     * Unauthorized - Invalid or missing API key
+     */
+    401: unknown;
+    /**
+     * Internal server error
+     */
+    500: unknown;
+};
+
+export type SearchSessionsResponses = {
+    /**
+     * Messages matching the search
+     */
+    200: SessionSearchResponse;
+};
+
+export type SearchSessionsResponse = SearchSessionsResponses[keyof SearchSessionsResponses];
+
 export type GetSessionHistoryData = {
     body?: never;
     path: {
@@ -121,6 +121,13 @@ function mapApiContentToFrontendMessageContent(
       type: 'summarizationRequested',
       msg: apiContent.msg,
     };
+  } else if (apiContent.type === 'budgetExceeded') {
+    return {
+      type: 'budgetExceeded',
+      budget: apiContent.budget,
+      limit: apiContent.limit,
+      msg: apiContent.msg,
+    };
   }
 
   // For types that exist in API but not in frontend, either skip or convert
@@ -19,12 +19,22 @@ export interface NotificationEvent {
   };
 }
 
+export interface ToolProgressEvent {
+  type: 'ToolProgress';
+  request_id: string;
+  progress: number;
+  total?: number | null;
+  message?: string | null;
+}
+
 // Event types for SSE stream
 type MessageEvent =
   | { type: 'Message'; message: Message }
   | { type: 'Error'; error: string }
   | { type: 'Finish'; reason: string }
-  | NotificationEvent;
+  | { type: 'HistoryReplaced'; messages: Message[] }
+  | NotificationEvent
+  | ToolProgressEvent;
 
 export interface UseMessageStreamOptions {
   /**
@@ -265,6 +275,22 @@ export function useMessageStream({
                     break;
                   }
 
+                  case 'HistoryReplaced': {
+                    // The conversation was compacted. Keep showing what was already there but
+                    // send the compacted history to the model from now on
+                    const compacted = parsedEvent.messages.map((message) => ({
+                      ...message,
+                      display: false,
+                      sendToLLM: true,
+                    }));
+                    currentMessages = [
+                      ...currentMessages.map((message) => ({ ...message, sendToLLM: false })),
+                      ...compacted,
+                    ];
+                    mutate(currentMessages, false);
+                    break;
+                  }
+
                   case 'Notification': {
                     const newNotification = {
                       ...parsedEvent,
@@ -273,6 +299,26 @@ export function useMessageStream({
                     break;
                   }
 
+                  case 'ToolProgress': {
+                    // Progress arrives parsed and scoped to its tool call, keep it with the
+                    // call's other notifications so it renders the same way
+                    const progressNotification: NotificationEvent = {
+                      type: 'Notification',
+                      request_id: parsedEvent.request_id,
+                      message: {
+                        method: 'notifications/progress',
+                        params: {
+                          progressToken: parsedEvent.request_id,
+                          progress: parsedEvent.progress,
+                          total: parsedEvent.total ?? null,
+                          message: parsedEvent.message ?? null,
+                        },
+                      },
+                    };
+                    setNotifications((prev) => [...prev, progressNotification]);
+                    break;
+                  }
+
                   case 'Error':
                     throw new Error(parsedEvent.error);
 
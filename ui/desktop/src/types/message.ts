@@ -83,6 +83,14 @@ export interface SummarizationRequestedContent {
   msg: string;
 }
 
+export interface BudgetExceededContent {
+  type: 'budgetExceeded';
+  budget: 'turns' | 'inputTokens' | 'outputTokens' | 'time' | 'sessionCost';
+  limit?: number;
+  cost_limit?: number;
+  msg: string;
+}
+
 export type MessageContent =
   | TextContent
   | ImageContent
@@ -90,7 +98,8 @@ export type MessageContent =
   | ToolResponseMessageContent
   | ToolConfirmationRequestMessageContent
   | ContextLengthExceededContent
-  | SummarizationRequestedContent;
+  | SummarizationRequestedContent
+  | BudgetExceededContent;
 
 export interface Message {
   id?: string;
@@ -190,13 +199,15 @@ function generateId(): string {
 export function getTextContent(message: Message): string {
   return message.content
     .filter(
-      (content): content is TextContent | ContextLengthExceededContent =>
-        content.type === 'text' || content.type === 'contextLengthExceeded'
+      (content): content is TextContent | ContextLengthExceededContent | BudgetExceededContent =>
+        content.type === 'text' ||
+        content.type === 'contextLengthExceeded' ||
+        content.type === 'budgetExceeded'
     )
     .map((content) => {
       if (content.type === 'text') {
         return content.text;
-      } else if (content.type === 'contextLengthExceeded') {
+      } else if (content.type === 'contextLengthExceeded' || content.type === 'budgetExceeded') {
         return content.msg;
       }
       return '';